@@ -0,0 +1,101 @@
+use crate::outline::language_for_path;
+use crate::tokens::estimate_tokens;
+use crate::FileEntry;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use tree_sitter::{Node, Parser};
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Chunk {
+    pub path: String,
+    /// 1-based, inclusive.
+    pub start_line: usize,
+    pub end_line: usize,
+    pub tokens: usize,
+    pub content: String,
+}
+
+/// Comments and attributes stay with the declaration that follows them.
+fn is_leading_trivia(kind: &str) -> bool {
+    kind.contains("comment") || matches!(kind, "attribute_item" | "decorator")
+}
+
+/// Adds the 0-based lines where `node`'s children start. Children too big for one chunk
+/// are descended into, so a large class or impl is split between its members.
+fn collect_boundaries(node: &Node, max_tokens: usize, lines: &mut BTreeSet<usize>) {
+    let mut cursor = node.walk();
+    let mut trivia_start = None;
+    for child in node.named_children(&mut cursor) {
+        if is_leading_trivia(child.kind()) {
+            trivia_start.get_or_insert(child.start_position().row);
+            continue;
+        }
+        lines.insert(trivia_start.take().unwrap_or(child.start_position().row));
+        if (child.end_byte() - child.start_byte()) / 4 > max_tokens {
+            collect_boundaries(&child, max_tokens, lines);
+        }
+    }
+}
+
+/// Lines where declarations start, when a grammar is bundled for the file type.
+fn syntax_boundaries(path: &str, source: &str, max_tokens: usize) -> Option<BTreeSet<usize>> {
+    let mut parser = Parser::new();
+    parser.set_language(&language_for_path(path)?).ok()?;
+    let tree = parser.parse(source, None)?;
+    let mut lines = BTreeSet::new();
+    collect_boundaries(&tree.root_node(), max_tokens, &mut lines);
+    Some(lines)
+}
+
+/// Splits a file into chunks of at most `max_tokens` (a single longer line is kept
+/// whole), repeating up to `overlap` tokens of trailing lines at the start of the next
+/// chunk. Chunks end before a function/class/declaration when the file's grammar is
+/// bundled and such a boundary falls in the second half of the chunk; otherwise they
+/// end at the line that fills the budget.
+pub fn chunk_file(file: &FileEntry, max_tokens: usize, overlap: usize) -> Vec<Chunk> {
+    let lines: Vec<&str> = file.content.lines().collect();
+    let max_tokens = max_tokens.max(16);
+    let overlap = overlap.min(max_tokens / 2);
+    let line_tokens: Vec<usize> = lines.iter().map(|l| estimate_tokens(l) + 1).collect();
+    let boundaries = if line_tokens.iter().sum::<usize>() > max_tokens {
+        syntax_boundaries(&file.path, &file.content, max_tokens).unwrap_or_default()
+    } else {
+        BTreeSet::new()
+    };
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < lines.len() {
+        let mut end = start;
+        let mut tokens = 0;
+        let mut last_boundary = None;
+        while end < lines.len() && (end == start || tokens + line_tokens[end] <= max_tokens) {
+            tokens += line_tokens[end];
+            end += 1;
+            if boundaries.contains(&end) && tokens >= max_tokens / 2 {
+                last_boundary = Some(end);
+            }
+        }
+        if end < lines.len() {
+            if let Some(b) = last_boundary {
+                end = b;
+            }
+        }
+        let content = lines[start..end].join("\n");
+        if !content.trim().is_empty() {
+            chunks.push(Chunk { path: file.path.clone(), start_line: start + 1, end_line: end, tokens: estimate_tokens(&content), content });
+        }
+        if end == lines.len() {
+            break;
+        }
+        let mut next = end;
+        let mut repeated = 0;
+        while next > start + 1 && repeated + line_tokens[next - 1] <= overlap {
+            next -= 1;
+            repeated += line_tokens[next];
+        }
+        start = next;
+    }
+    chunks
+}
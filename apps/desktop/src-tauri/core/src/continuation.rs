@@ -0,0 +1,23 @@
+/// Continuation requests made for one answer unless the caller asks otherwise.
+pub const DEFAULT_MAX_CONTINUATIONS: u32 = 3;
+
+/// User turn asking the model to carry on after a reply cut off at the token limit.
+pub const CONTINUE_PROMPT: &str =
+    "Your previous reply was cut off. Continue exactly where it stopped, without repeating anything and without an introduction.";
+
+/// Longest overlap looked for between the end of one segment and the start of the next.
+const MAX_OVERLAP_CHARS: usize = 400;
+
+/// Appends a continuation to `text`, dropping a repeat of `text`'s tail at its start
+/// (models often restate the last few words before carrying on).
+pub fn stitch(text: &mut String, next: &str) {
+    let tail_start = text.char_indices().rev().nth(MAX_OVERLAP_CHARS - 1).map(|(i, _)| i).unwrap_or(0);
+    let tail = &text[tail_start..];
+    let overlap = tail
+        .char_indices()
+        .map(|(i, _)| &tail[i..])
+        .find(|suffix| suffix.chars().count() >= 8 && next.starts_with(suffix))
+        .map(str::len)
+        .unwrap_or(0);
+    text.push_str(&next[overlap..]);
+}
@@ -0,0 +1,122 @@
+use crate::FileEntry;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Files shorter than this (once normalized) are never collapsed: empty `__init__.py`
+/// files and one-line re-exports are identical by nature, not duplicated code.
+const MIN_DEDUP_BYTES: usize = 64;
+/// Directory names that hold third-party or build output when checked in.
+const VENDORED_DIRS: &[&str] = &["vendor", "vendors", "third_party", "third-party", "thirdparty", "bower_components", "dist", "out", "Pods", "external"];
+/// File name endings of generated code.
+const GENERATED_SUFFIXES: &[&str] = &[
+    ".min.js", ".min.css", ".min.mjs", ".bundle.js", ".chunk.js", ".pb.go", ".pb.cc", ".pb.h", "_pb2.py", "_pb2_grpc.py", ".g.dart", ".freezed.dart", ".generated.ts", ".generated.cs", ".designer.cs",
+    ".js.map", ".css.map",
+];
+/// Markers tools put in the first lines of files they generate.
+const GENERATED_MARKERS: &[&str] = &["code generated", "do not edit", "@generated", "auto-generated", "autogenerated"];
+/// Lines longer than this on average mean minified code.
+const MINIFIED_AVG_LINE: usize = 300;
+
+/// Identical files collapsed into one copy.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    /// The copy that stays in the pack.
+    pub kept: String,
+    /// Files with the same content, left out.
+    pub duplicates: Vec<String>,
+}
+
+/// A file that looks vendored or generated, with the reason.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct VendoredFile {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Content with line endings unified and trailing whitespace dropped, so files differing
+/// only in those still count as identical.
+fn normalize(content: &str) -> String {
+    content.lines().map(str::trim_end).collect::<Vec<_>>().join("\n").trim().to_string()
+}
+
+fn content_hash(normalized: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    normalized.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Collapses files with the same normalized content into one: the one with the shortest
+/// path (then the first alphabetically) is kept, in place, and the others are dropped
+/// and listed in the returned groups.
+pub fn dedup(files: Vec<FileEntry>) -> (Vec<FileEntry>, Vec<DuplicateGroup>) {
+    let mut by_hash: HashMap<u64, Vec<usize>> = HashMap::new();
+    let normalized: Vec<String> = files.iter().map(|f| normalize(&f.content)).collect();
+    for (i, content) in normalized.iter().enumerate() {
+        if content.len() >= MIN_DEDUP_BYTES {
+            by_hash.entry(content_hash(content)).or_default().push(i);
+        }
+    }
+    let mut dropped = vec![false; files.len()];
+    let mut groups = Vec::new();
+    for indices in by_hash.into_values().filter(|g| g.len() > 1) {
+        // Equal hashes are confirmed by comparing content.
+        let mut remaining = indices;
+        while let Some(&first) = remaining.first() {
+            let (same, rest): (Vec<usize>, Vec<usize>) = remaining.iter().partition(|&&i| normalized[i] == normalized[first]);
+            remaining = rest;
+            if same.len() < 2 {
+                continue;
+            }
+            let keep = *same.iter().min_by(|&&a, &&b| files[a].path.len().cmp(&files[b].path.len()).then_with(|| files[a].path.cmp(&files[b].path))).unwrap();
+            let mut duplicates: Vec<String> = same.iter().filter(|&&i| i != keep).map(|&i| files[i].path.clone()).collect();
+            duplicates.sort();
+            for &i in same.iter().filter(|&&i| i != keep) {
+                dropped[i] = true;
+            }
+            groups.push(DuplicateGroup { kept: files[keep].path.clone(), duplicates });
+        }
+    }
+    groups.sort_by(|a, b| a.kept.cmp(&b.kept));
+    let kept = files.into_iter().zip(dropped).filter(|(_, dropped)| !dropped).map(|(f, _)| f).collect();
+    (kept, groups)
+}
+
+/// Why `path` looks vendored or generated, or `None` when it looks hand-written: a
+/// vendor or checked-in build directory, a generated file name, a "generated" marker in
+/// the first lines, or minified content.
+pub fn vendored_reason(path: &str, content: &str) -> Option<String> {
+    let dirs: Vec<&str> = path.split('/').collect();
+    if let Some(dir) = dirs[..dirs.len().saturating_sub(1)].iter().find(|d| VENDORED_DIRS.contains(d)) {
+        return Some(format!("inside a {}/ directory", dir));
+    }
+    let lower = path.to_lowercase();
+    if let Some(suffix) = GENERATED_SUFFIXES.iter().find(|s| lower.ends_with(*s)) {
+        return Some(format!("generated file ({})", suffix));
+    }
+    let head: String = content.lines().take(5).collect::<Vec<_>>().join("\n").to_lowercase();
+    if let Some(marker) = GENERATED_MARKERS.iter().find(|m| head.contains(*m)) {
+        return Some(format!("marked \"{}\"", marker));
+    }
+    let lines = content.lines().filter(|l| !l.trim().is_empty()).count();
+    if lines > 0 && content.len() > 2000 && content.len() / lines > MINIFIED_AVG_LINE {
+        return Some("minified (very long lines)".to_string());
+    }
+    None
+}
+
+/// Splits off files that look vendored or generated (see [`vendored_reason`]). Returns
+/// the rest and the flagged files.
+pub fn split_vendored(files: Vec<FileEntry>) -> (Vec<FileEntry>, Vec<VendoredFile>) {
+    let mut kept = Vec::with_capacity(files.len());
+    let mut flagged = Vec::new();
+    for file in files {
+        match vendored_reason(&file.path, &file.content) {
+            Some(reason) => flagged.push(VendoredFile { path: file.path, reason }),
+            None => kept.push(file),
+        }
+    }
+    (kept, flagged)
+}
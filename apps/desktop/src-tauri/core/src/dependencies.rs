@@ -0,0 +1,565 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Manifest file names with a parser; `*.ext` matches any file with that extension.
+pub const MANIFEST_FILES: &[&str] = &[
+    "package.json", "requirements.txt", "pyproject.toml", "setup.cfg", "go.mod", "Cargo.toml", "pom.xml", "build.gradle", "build.gradle.kts", "Gemfile",
+    "composer.json", "mix.exs", "*.csproj", "pubspec.yaml", "CMakeLists.txt",
+];
+/// Lock files, named in the summary but never included: they pin versions, but are
+/// large and say little the manifests don't.
+pub const LOCK_FILES: &[&str] = &[
+    "package-lock.json", "npm-shrinkwrap.json", "yarn.lock", "pnpm-lock.yaml", "bun.lock", "bun.lockb", "Cargo.lock", "poetry.lock", "Pipfile.lock", "uv.lock",
+    "go.sum", "Gemfile.lock", "composer.lock", "mix.lock", "pubspec.lock", "packages.lock.json", "gradle.lockfile",
+];
+/// Directories whose manifests belong to installed or vendored packages, not the project.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "vendor", "third_party", "target", "build", "dist", ".venv", "venv", "site-packages", "deps", "_build", ".dart_tool", "bin", "obj"];
+/// Manifests taken from one repository at most, the shallowest first.
+pub const MAX_MANIFESTS: usize = 40;
+/// Dependencies listed per kind before the rest is summarized as a count.
+const MAX_LISTED: usize = 60;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum DependencyKind {
+    Runtime,
+    Dev,
+    Build,
+    Peer,
+    Optional,
+    /// Go modules required only by other dependencies.
+    Indirect,
+}
+
+impl DependencyKind {
+    fn label(self) -> &'static str {
+        match self {
+            DependencyKind::Runtime => "runtime",
+            DependencyKind::Dev => "dev",
+            DependencyKind::Build => "build",
+            DependencyKind::Peer => "peer",
+            DependencyKind::Optional => "optional",
+            DependencyKind::Indirect => "indirect",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Dependency {
+    pub name: String,
+    /// Version requirement as written (`^1.2`, `>=2,<3`, ...); `None` when unpinned or
+    /// taken from a path or git source.
+    pub version: Option<String>,
+    pub kind: DependencyKind,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    pub path: String,
+    /// `npm`, `cargo`, `pip`, `python`, `go`, `maven`, `gradle`, `rubygems`, `composer`,
+    /// `hex`, `nuget`, `pub` or `cmake`.
+    pub ecosystem: String,
+    pub dependencies: Vec<Dependency>,
+}
+
+fn dep(name: &str, version: Option<&str>, kind: DependencyKind) -> Dependency {
+    let version = version.map(str::trim).filter(|v| !v.is_empty() && *v != "*");
+    Dependency { name: name.trim().to_string(), version: version.map(str::to_string), kind }
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn parse_package_json(content: &str) -> Result<Vec<Dependency>, String> {
+    let json: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let mut deps = Vec::new();
+    for (key, kind) in [
+        ("dependencies", DependencyKind::Runtime),
+        ("devDependencies", DependencyKind::Dev),
+        ("peerDependencies", DependencyKind::Peer),
+        ("optionalDependencies", DependencyKind::Optional),
+    ] {
+        for (name, version) in json[key].as_object().into_iter().flatten() {
+            deps.push(dep(name, version.as_str(), kind));
+        }
+    }
+    Ok(deps)
+}
+
+/// A Cargo dependency's version: `"1.0"` or `{ version = "1.0", ... }`.
+fn cargo_version(spec: &toml::Value) -> Option<&str> {
+    spec.as_str().or_else(|| spec.get("version")?.as_str())
+}
+
+fn cargo_table(table: &toml::Value, deps: &mut Vec<Dependency>) {
+    for (key, kind) in [("dependencies", DependencyKind::Runtime), ("dev-dependencies", DependencyKind::Dev), ("build-dependencies", DependencyKind::Build)] {
+        for (name, spec) in table.get(key).and_then(toml::Value::as_table).into_iter().flatten() {
+            let kind = if spec.get("optional").and_then(toml::Value::as_bool) == Some(true) { DependencyKind::Optional } else { kind };
+            let name = spec.get("package").and_then(toml::Value::as_str).unwrap_or(name);
+            deps.push(dep(name, cargo_version(spec), kind));
+        }
+    }
+}
+
+fn parse_cargo_toml(content: &str) -> Result<Vec<Dependency>, String> {
+    let doc: toml::Value = toml::from_str(content).map_err(|e| e.to_string())?;
+    let mut deps = Vec::new();
+    cargo_table(&doc, &mut deps);
+    // `[target.'cfg(windows)'.dependencies]` and friends.
+    for target in doc.get("target").and_then(toml::Value::as_table).into_iter().flat_map(|t| t.values()) {
+        cargo_table(target, &mut deps);
+    }
+    if let Some(workspace) = doc.get("workspace") {
+        cargo_table(workspace, &mut deps);
+    }
+    Ok(deps)
+}
+
+/// Splits a PEP 508 requirement such as `requests[socks]>=2.31; python_version<"3.12"`
+/// into name and version specifier.
+fn pep508(requirement: &str) -> Option<(String, Option<String>)> {
+    let requirement = requirement.split(';').next()?.split('#').next()?.trim();
+    if requirement.is_empty() {
+        return None;
+    }
+    let end = requirement.find(|c: char| !(c.is_ascii_alphanumeric() || "-_.".contains(c))).unwrap_or(requirement.len());
+    let name = &requirement[..end];
+    if name.is_empty() {
+        return None;
+    }
+    let rest = requirement[end..].trim();
+    // Extras come before the version.
+    let rest = match rest.strip_prefix('[') {
+        Some(r) => r.split_once(']').map_or("", |(_, v)| v).trim(),
+        None => rest,
+    };
+    let version = rest.trim_start_matches('(').trim_end_matches(')').trim();
+    Some((name.to_string(), (!version.is_empty()).then(|| version.to_string())))
+}
+
+fn parse_requirements(content: &str) -> Vec<Dependency> {
+    content
+        .lines()
+        .map(str::trim)
+        // Options (`-r other.txt`, `--index-url`, `-e .`) and URLs aren't packages.
+        .filter(|l| !l.starts_with('-') && !l.starts_with('#') && !l.contains("://"))
+        .filter_map(pep508)
+        .map(|(name, version)| dep(&name, version.as_deref(), DependencyKind::Runtime))
+        .collect()
+}
+
+fn parse_pyproject(content: &str) -> Result<Vec<Dependency>, String> {
+    let doc: toml::Value = toml::from_str(content).map_err(|e| e.to_string())?;
+    let mut deps = Vec::new();
+    let mut requirements = |list: Option<&toml::Value>, kind: DependencyKind| {
+        for requirement in list.and_then(toml::Value::as_array).into_iter().flatten().filter_map(toml::Value::as_str) {
+            if let Some((name, version)) = pep508(requirement) {
+                deps.push(dep(&name, version.as_deref(), kind));
+            }
+        }
+    };
+    let project = doc.get("project");
+    requirements(project.and_then(|p| p.get("dependencies")), DependencyKind::Runtime);
+    for group in project.and_then(|p| p.get("optional-dependencies")).and_then(toml::Value::as_table).into_iter().flat_map(|t| t.values()) {
+        requirements(Some(group), DependencyKind::Optional);
+    }
+    for group in doc.get("dependency-groups").and_then(toml::Value::as_table).into_iter().flat_map(|t| t.values()) {
+        requirements(Some(group), DependencyKind::Dev);
+    }
+    // Poetry: `name = "^1.0"` or `name = { version = "^1.0" }` tables.
+    let poetry = doc.get("tool").and_then(|t| t.get("poetry"));
+    let mut poetry_table = |table: Option<&toml::Value>, kind: DependencyKind| {
+        for (name, spec) in table.and_then(toml::Value::as_table).into_iter().flatten().filter(|(name, _)| *name != "python") {
+            deps.push(dep(name, cargo_version(spec), kind));
+        }
+    };
+    poetry_table(poetry.and_then(|p| p.get("dependencies")), DependencyKind::Runtime);
+    poetry_table(poetry.and_then(|p| p.get("dev-dependencies")), DependencyKind::Dev);
+    for group in poetry.and_then(|p| p.get("group")).and_then(toml::Value::as_table).into_iter().flat_map(|t| t.values()) {
+        poetry_table(group.get("dependencies"), DependencyKind::Dev);
+    }
+    Ok(deps)
+}
+
+fn parse_go_mod(content: &str) -> Vec<Dependency> {
+    let mut deps = Vec::new();
+    let mut in_block = false;
+    for line in content.lines().map(str::trim) {
+        let entry = if in_block {
+            if line.starts_with(')') {
+                in_block = false;
+                continue;
+            }
+            line
+        } else if let Some(rest) = line.strip_prefix("require") {
+            let rest = rest.trim();
+            if rest.starts_with('(') {
+                in_block = true;
+                continue;
+            }
+            rest
+        } else {
+            continue;
+        };
+        let (spec, comment) = entry.split_once("//").unwrap_or((entry, ""));
+        let mut parts = spec.split_whitespace();
+        if let (Some(name), Some(version)) = (parts.next(), parts.next()) {
+            let kind = if comment.trim() == "indirect" { DependencyKind::Indirect } else { DependencyKind::Runtime };
+            deps.push(dep(name, Some(version), kind));
+        }
+    }
+    deps
+}
+
+/// Text of the first `<tag>` element in `xml`.
+fn xml_text<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let start = xml.find(&format!("<{}>", tag))? + tag.len() + 2;
+    let end = xml[start..].find(&format!("</{}>", tag))?;
+    Some(xml[start..start + end].trim())
+}
+
+fn parse_pom(content: &str) -> Vec<Dependency> {
+    let mut deps = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("<dependency>") {
+        let Some(end) = rest[start..].find("</dependency>") else { break };
+        let block = &rest[start..start + end];
+        rest = &rest[start + end..];
+        let (Some(group), Some(artifact)) = (xml_text(block, "groupId"), xml_text(block, "artifactId")) else { continue };
+        let kind = match xml_text(block, "scope") {
+            Some("test") => DependencyKind::Dev,
+            Some("provided") => DependencyKind::Peer,
+            _ if xml_text(block, "optional") == Some("true") => DependencyKind::Optional,
+            _ => DependencyKind::Runtime,
+        };
+        // `${project.version}` and friends say nothing without the properties.
+        let version = xml_text(block, "version").filter(|v| !v.starts_with("${"));
+        deps.push(dep(&format!("{}:{}", group, artifact), version, kind));
+    }
+    deps
+}
+
+fn parse_gradle(content: &str) -> Vec<Dependency> {
+    const CONFIGURATIONS: &[(&str, DependencyKind)] = &[
+        ("implementation", DependencyKind::Runtime),
+        ("api", DependencyKind::Runtime),
+        ("compile", DependencyKind::Runtime),
+        ("runtimeOnly", DependencyKind::Runtime),
+        ("compileOnly", DependencyKind::Peer),
+        ("testImplementation", DependencyKind::Dev),
+        ("testRuntimeOnly", DependencyKind::Dev),
+        ("androidTestImplementation", DependencyKind::Dev),
+        ("kapt", DependencyKind::Build),
+        ("annotationProcessor", DependencyKind::Build),
+    ];
+    let mut deps = Vec::new();
+    for line in content.lines().map(str::trim) {
+        let Some((configuration, rest)) = line.split_once(|c: char| c == '(' || c.is_whitespace()) else { continue };
+        let Some(&(_, kind)) = CONFIGURATIONS.iter().find(|(c, _)| *c == configuration) else { continue };
+        // Only `group:name:version` coordinates; `project(":core")` and catalogs aren't
+        // resolvable here.
+        let Some(quoted) = rest.split(['"', '\'']).nth(1) else { continue };
+        let parts: Vec<&str> = quoted.split(':').collect();
+        if parts.len() >= 2 && !parts[0].is_empty() {
+            deps.push(dep(&format!("{}:{}", parts[0], parts[1]), parts.get(2).copied(), kind));
+        }
+    }
+    deps
+}
+
+/// `install_requires` and `extras_require` of a setuptools `setup.cfg`.
+fn parse_setup_cfg(content: &str) -> Vec<Dependency> {
+    let mut deps = Vec::new();
+    let mut section = "";
+    // The key whose indented continuation lines are being read, with their kind.
+    let mut list: Option<DependencyKind> = None;
+    for raw in content.lines() {
+        let line = raw.trim();
+        if line.starts_with('[') {
+            section = line.trim_matches(|c| c == '[' || c == ']');
+            list = None;
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        let value = if raw.starts_with(char::is_whitespace) {
+            line
+        } else {
+            let (key, value) = line.split_once('=').unwrap_or((line, ""));
+            list = match (section, key.trim()) {
+                ("options", "install_requires") => Some(DependencyKind::Runtime),
+                ("options", "setup_requires") => Some(DependencyKind::Build),
+                ("options", "tests_require") => Some(DependencyKind::Dev),
+                ("options.extras_require", _) => Some(DependencyKind::Optional),
+                _ => None,
+            };
+            value.trim()
+        };
+        if let (Some(kind), Some((name, version))) = (list, pep508(value)) {
+            deps.push(dep(&name, version.as_deref(), kind));
+        }
+    }
+    deps
+}
+
+/// The quoted strings of a line, in order.
+fn quoted(line: &str) -> Vec<&str> {
+    line.split(['"', '\'']).skip(1).step_by(2).collect()
+}
+
+/// `gem` lines of a Gemfile; those in a `group :development`/`:test` block are dev.
+fn parse_gemfile(content: &str) -> Vec<Dependency> {
+    let mut deps = Vec::new();
+    // Open `do` blocks, and whether each is a development or test group.
+    let mut blocks: Vec<bool> = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if line == "end" {
+            blocks.pop();
+        } else if let Some(rest) = line.strip_prefix("gem ") {
+            let strings = quoted(rest);
+            let Some(name) = strings.first() else { continue };
+            let inline_dev = rest.contains(":development") || rest.contains(":test");
+            let kind = if inline_dev || blocks.iter().any(|&dev| dev) { DependencyKind::Dev } else { DependencyKind::Runtime };
+            // Further quoted strings are version constraints until an option like `require:`.
+            let versions: Vec<&str> = strings[1..].iter().copied().take_while(|s| s.starts_with(|c: char| c.is_ascii_digit() || "~<>=!".contains(c))).collect();
+            deps.push(dep(name, (!versions.is_empty()).then(|| versions.join(", ")).as_deref(), kind));
+        } else if line.ends_with(" do") || line.contains(" do |") {
+            blocks.push(line.starts_with("group") && (line.contains(":development") || line.contains(":test")));
+        }
+    }
+    deps
+}
+
+fn parse_composer(content: &str) -> Result<Vec<Dependency>, String> {
+    let json: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let mut deps = Vec::new();
+    for (key, kind) in [("require", DependencyKind::Runtime), ("require-dev", DependencyKind::Dev)] {
+        // `php` and `ext-*` are platform requirements, not packages.
+        for (name, version) in json[key].as_object().into_iter().flatten().filter(|(n, _)| *n != "php" && !n.starts_with("ext-")) {
+            deps.push(dep(name, version.as_str(), kind));
+        }
+    }
+    Ok(deps)
+}
+
+/// `{:name, "~> 1.0", ...}` tuples of a Mix project; `only: :test` or `:dev` ones are dev.
+fn parse_mix(content: &str) -> Vec<Dependency> {
+    let mut deps = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("{:") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find('}') else { break };
+        let tuple = &rest[..end];
+        let Some((name, options)) = tuple.split_once(',') else { continue };
+        if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            continue;
+        }
+        let version = options.trim().strip_prefix('"').and_then(|v| v.split('"').next());
+        let only = options.split_once("only:").map(|(_, o)| o.split(']').next().unwrap_or(o)).unwrap_or("");
+        let kind = if !only.is_empty() && !only.contains(":prod") { DependencyKind::Dev } else { DependencyKind::Runtime };
+        deps.push(dep(name, version, kind));
+    }
+    deps
+}
+
+/// `dependencies` and `dev_dependencies` of a Dart/Flutter `pubspec.yaml`. Entries given
+/// as a nested map (`sdk:`, `git:`, `path:`) have no version.
+fn parse_pubspec(content: &str) -> Vec<Dependency> {
+    let mut deps = Vec::new();
+    let mut kind = None;
+    for line in content.lines() {
+        let trimmed = line.trim_end();
+        if trimmed.trim_start().starts_with('#') || trimmed.trim().is_empty() {
+            continue;
+        }
+        let indent = trimmed.len() - trimmed.trim_start().len();
+        if indent == 0 {
+            kind = match trimmed {
+                "dependencies:" => Some(DependencyKind::Runtime),
+                "dev_dependencies:" => Some(DependencyKind::Dev),
+                _ => None,
+            };
+            continue;
+        }
+        // Only the direct children of the section, not the keys of a nested map.
+        let (Some(kind), 2) = (kind, indent) else { continue };
+        let Some((name, version)) = trimmed.trim().split_once(':') else { continue };
+        let version = version.trim().trim_matches(|c| c == '"' || c == '\'');
+        deps.push(dep(name, (!version.is_empty()).then_some(version), kind));
+    }
+    deps
+}
+
+/// `find_package` and `FetchContent_Declare` calls of a CMake project.
+fn parse_cmake(content: &str) -> Vec<Dependency> {
+    let mut deps = Vec::new();
+    for line in content.lines().map(str::trim) {
+        let lower = line.to_lowercase();
+        let args = match ["find_package(", "fetchcontent_declare(", "cpmaddpackage("].iter().find(|f| lower.starts_with(*f)) {
+            Some(f) => line[f.len()..].trim_end_matches(')'),
+            None => continue,
+        };
+        let mut parts = args.split_whitespace();
+        let Some(name) = parts.next() else { continue };
+        // `CPMAddPackage("gh:fmtlib/fmt#10.2.1")` and `find_package(Boost 1.80 REQUIRED)`.
+        let (name, version) = match name.trim_matches('"').rsplit_once('#') {
+            Some((name, tag)) => (name.trim_start_matches("gh:").to_string(), Some(tag.to_string())),
+            None => (name.trim_matches('"').to_string(), parts.next().filter(|v| v.starts_with(|c: char| c.is_ascii_digit())).map(str::to_string)),
+        };
+        if !name.is_empty() && !name.starts_with('$') {
+            deps.push(dep(&name, version.as_deref(), DependencyKind::Runtime));
+        }
+    }
+    deps
+}
+
+/// Value of `attribute="..."` in an XML tag.
+fn xml_attribute<'a>(tag: &'a str, attribute: &str) -> Option<&'a str> {
+    let start = tag.find(&format!("{}=\"", attribute))? + attribute.len() + 2;
+    tag[start..].split('"').next()
+}
+
+/// `PackageReference` items of an MSBuild project; the version may be an attribute or a
+/// child element.
+fn parse_csproj(content: &str) -> Vec<Dependency> {
+    let mut deps = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("<PackageReference") {
+        rest = &rest[start..];
+        let Some(end) = rest.find('>') else { break };
+        let tag = &rest[..end];
+        let Some(name) = xml_attribute(tag, "Include") else {
+            rest = &rest[end..];
+            continue;
+        };
+        let version = xml_attribute(tag, "Version").or_else(|| {
+            let body = &rest[end..];
+            let close = body.find("</PackageReference>").filter(|_| !tag.ends_with('/'))?;
+            xml_text(&body[..close], "Version")
+        });
+        // Analyzers and build tools are marked as private assets.
+        let kind = if tag.contains("PrivateAssets=\"all\"") || tag.contains("PrivateAssets=\"All\"") { DependencyKind::Build } else { DependencyKind::Runtime };
+        deps.push(dep(name, version, kind));
+        rest = &rest[end..];
+    }
+    deps
+}
+
+/// Whether `path` names a manifest [`parse_manifest`] reads.
+pub fn is_manifest(path: &str) -> bool {
+    matches_any(MANIFEST_FILES, path)
+}
+
+/// Whether `path`'s file name is one of `patterns`: a plain name, or `*.ext`.
+pub fn matches_any<S: AsRef<str>>(patterns: &[S], path: &str) -> bool {
+    let name = file_name(path);
+    patterns.iter().any(|p| match p.as_ref().strip_prefix('*') {
+        Some(suffix) => name.len() > suffix.len() && name.ends_with(suffix),
+        None => p.as_ref() == name,
+    })
+}
+
+pub fn is_lockfile(path: &str) -> bool {
+    matches_any(LOCK_FILES, path)
+}
+
+/// The manifests and lock files among `paths` (a repository tree), including
+/// subdirectories so every package of a monorepo is covered, but not installed or vendored
+/// packages. `extra` adds file names or `*.ext` patterns to treat as manifests. The
+/// shallowest [`MAX_MANIFESTS`] manifests are kept; lock files don't count towards it.
+pub fn find_manifests(paths: &[String], extra: &[String]) -> Vec<String> {
+    let in_skipped_dir = |path: &str| {
+        let dirs: Vec<&str> = path.split('/').collect();
+        dirs[..dirs.len() - 1].iter().any(|d| SKIPPED_DIRS.contains(d) || (d.starts_with('.') && *d != ".github"))
+    };
+    let mut manifests: Vec<&String> = paths.iter().filter(|p| !in_skipped_dir(p) && (is_manifest(p) || matches_any(extra, p))).collect();
+    manifests.sort_by_key(|p| (p.matches('/').count(), p.as_str()));
+    manifests.truncate(MAX_MANIFESTS);
+    let lockfiles = paths.iter().filter(|p| !in_skipped_dir(p) && is_lockfile(p));
+    manifests.into_iter().chain(lockfiles).cloned().collect()
+}
+
+/// Parses a known manifest (by file name) into its dependencies. `None` for files without
+/// a parser; `Some(Err)` when one can't be read, so the caller can fall back to
+/// its raw content.
+pub fn parse_manifest(path: &str, content: &str) -> Option<Result<Manifest, String>> {
+    let (ecosystem, parsed) = match file_name(path) {
+        "package.json" => ("npm", parse_package_json(content)),
+        "Cargo.toml" => ("cargo", parse_cargo_toml(content)),
+        "requirements.txt" => ("pip", Ok(parse_requirements(content))),
+        "pyproject.toml" => ("python", parse_pyproject(content)),
+        "go.mod" => ("go", Ok(parse_go_mod(content))),
+        "pom.xml" => ("maven", Ok(parse_pom(content))),
+        "build.gradle" | "build.gradle.kts" => ("gradle", Ok(parse_gradle(content))),
+        "setup.cfg" => ("python", Ok(parse_setup_cfg(content))),
+        "Gemfile" => ("rubygems", Ok(parse_gemfile(content))),
+        "composer.json" => ("composer", parse_composer(content)),
+        "mix.exs" => ("hex", Ok(parse_mix(content))),
+        "pubspec.yaml" => ("pub", Ok(parse_pubspec(content))),
+        "CMakeLists.txt" => ("cmake", Ok(parse_cmake(content))),
+        name if name.ends_with(".csproj") => ("nuget", Ok(parse_csproj(content))),
+        _ => return None,
+    };
+    Some(parsed.map(|mut dependencies| {
+        dependencies.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())));
+        dependencies.dedup_by(|a, b| a.kind == b.kind && a.name == b.name);
+        Manifest { path: path.to_string(), ecosystem: ecosystem.to_string(), dependencies }
+    }))
+}
+
+/// Compact summary for the prompt: per manifest, one line per kind listing
+/// `name version` pairs.
+pub fn render_summary(manifests: &[Manifest]) -> String {
+    let mut out = String::new();
+    for manifest in manifests {
+        out.push_str(&format!("\n{} ({}, {} dependencies)\n", manifest.path, manifest.ecosystem, manifest.dependencies.len()));
+        let mut kinds: Vec<DependencyKind> = manifest.dependencies.iter().map(|d| d.kind).collect();
+        kinds.dedup();
+        for kind in kinds {
+            let deps: Vec<&Dependency> = manifest.dependencies.iter().filter(|d| d.kind == kind).collect();
+            let mut listed: Vec<String> = deps
+                .iter()
+                .take(MAX_LISTED)
+                .map(|d| match &d.version {
+                    Some(v) => format!("{} {}", d.name, v),
+                    None => d.name.clone(),
+                })
+                .collect();
+            if deps.len() > MAX_LISTED {
+                listed.push(format!("... and {} more", deps.len() - MAX_LISTED));
+            }
+            out.push_str(&format!("  {}: {}\n", kind.label(), listed.join(", ")));
+        }
+    }
+    out
+}
+
+/// The dependency section of a prompt from `(path, content)` manifests: the compact
+/// summary, with manifests that can't be parsed (or have no parser) included raw. With
+/// `raw`, every manifest is included as is, as before the summary existed. Lock files are
+/// only named; their content isn't needed.
+pub fn describe(files: &[(String, String)], raw: bool) -> (String, Vec<Manifest>) {
+    let raw_block = |path: &str, content: &str| format!("\n--- {} ---\n{}\n", path, content);
+    let (lockfiles, files): (Vec<_>, Vec<_>) = files.iter().partition(|(p, _)| is_lockfile(p));
+    let lockfiles = if lockfiles.is_empty() {
+        String::new()
+    } else {
+        format!("\nLock files: {}\n", lockfiles.iter().map(|(p, _)| p.as_str()).collect::<Vec<_>>().join(", "))
+    };
+    if raw {
+        return (files.iter().map(|(p, c)| raw_block(p, c)).collect::<String>() + &lockfiles, Vec::new());
+    }
+    let mut manifests = Vec::new();
+    let mut unparsed = String::new();
+    for (path, content) in files {
+        match parse_manifest(path, content) {
+            Some(Ok(manifest)) => manifests.push(manifest),
+            _ => unparsed.push_str(&raw_block(path, content)),
+        }
+    }
+    (render_summary(&manifests) + &unparsed + &lockfiles, manifests)
+}
@@ -0,0 +1,21 @@
+/// Scales `v` to unit length, so similarity is a plain dot product. Zero vectors are
+/// returned unchanged.
+pub fn normalize(mut v: Vec<f32>) -> Vec<f32> {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+    v
+}
+
+/// Cosine similarity of two [`normalize`]d vectors.
+pub fn similarity(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+/// `items` by similarity of their vector to `query`, best first.
+pub fn rank_by_similarity<'a, T>(query: &[f32], items: &'a [T], vector: impl Fn(&T) -> &[f32]) -> Vec<(f32, &'a T)> {
+    let mut scored: Vec<(f32, &T)> = items.iter().map(|item| (similarity(vector(item), query), item)).collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored
+}
@@ -0,0 +1,310 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::time::Duration;
+
+pub use crate::usage::Usage;
+
+/// Finish reasons meaning the candidate was withheld rather than cut short.
+const BLOCK_REASONS: &[&str] = &["SAFETY", "RECITATION", "BLOCKLIST", "PROHIBITED_CONTENT", "SPII", "IMAGE_SAFETY"];
+
+/// Text of a Gemini reply, extracted from the response body.
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiReply {
+    pub text: String,
+    pub finish_reason: Option<String>,
+    /// The model stopped at the output token limit; the text is incomplete.
+    pub truncated: bool,
+    /// The body wasn't valid JSON and the text was recovered from what could be read.
+    pub recovered: bool,
+    pub usage: Option<Usage>,
+    /// Follow-up requests made because the reply hit the output token limit.
+    pub continuations: u32,
+}
+
+/// Why a Gemini call produced no usable text.
+pub enum GeminiError {
+    /// Non-2xx status, with the message from the error body.
+    Api { status: u16, code: Option<String>, message: String },
+    /// The prompt or the reply was withheld by safety or recitation filters.
+    /// `prompt` tells whether the prompt itself was rejected.
+    Blocked { prompt: bool, reason: String, categories: Vec<String> },
+    /// A candidate came back without text, e.g. `finishReason: OTHER`.
+    Empty { finish_reason: Option<String> },
+    /// The body couldn't be read as a Gemini response at all.
+    Malformed(String),
+    /// A structured reply wasn't JSON or didn't match the requested schema.
+    Schema(String),
+}
+
+impl fmt::Display for GeminiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeminiError::Api { status, code: Some(code), message } => write!(f, "Gemini API error ({} {}): {}", status, code, message),
+            GeminiError::Api { status, code: None, message } => write!(f, "Gemini API error ({}): {}", status, message),
+            GeminiError::Blocked { prompt, reason, categories } => {
+                write!(f, "Gemini blocked the {} ({}", if *prompt { "prompt" } else { "response" }, reason)?;
+                if !categories.is_empty() {
+                    write!(f, ": {}", categories.join(", "))?;
+                }
+                write!(f, ")")
+            }
+            GeminiError::Empty { finish_reason: Some(r) } => write!(f, "Gemini returned no text (finish reason: {})", r),
+            GeminiError::Empty { finish_reason: None } => write!(f, "Gemini returned no candidates"),
+            GeminiError::Malformed(e) => write!(f, "Gemini returned a malformed response: {}", e),
+            GeminiError::Schema(e) => write!(f, "Gemini's structured reply is invalid: {}", e),
+        }
+    }
+}
+
+impl From<GeminiError> for String {
+    fn from(e: GeminiError) -> Self {
+        e.to_string()
+    }
+}
+
+/// Error for a non-2xx response, using the API's own message when the body has one.
+pub fn api_error(status: u16, body: &str) -> GeminiError {
+    let json: Value = serde_json::from_str(body).unwrap_or_default();
+    let error = if json.is_array() { &json[0]["error"] } else { &json["error"] };
+    match error["message"].as_str() {
+        Some(message) => GeminiError::Api { status, code: error["status"].as_str().map(str::to_string), message: message.to_string() },
+        None => GeminiError::Api { status, code: None, message: body.chars().take(500).collect() },
+    }
+}
+
+/// How long a 429 error asks to wait, from its `RetryInfo` detail (`"retryDelay": "12s"`).
+pub fn retry_delay(body: &str) -> Option<Duration> {
+    let json: Value = serde_json::from_str(body).ok()?;
+    let error = if json.is_array() { &json[0]["error"] } else { &json["error"] };
+    let delay = error["details"].as_array()?.iter().find_map(|d| d["retryDelay"].as_str())?;
+    let secs: f64 = delay.trim().strip_suffix('s')?.parse().ok()?;
+    (secs.is_finite() && secs >= 0.0).then(|| Duration::from_secs_f64(secs))
+}
+
+/// Safety categories rated as the reason for a block.
+fn flagged_categories(ratings: &Value) -> Vec<String> {
+    ratings
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|r| r["blocked"].as_bool() == Some(true) || matches!(r["probability"].as_str(), Some("HIGH") | Some("MEDIUM")))
+        .filter_map(|r| r["category"].as_str().map(|c| c.trim_start_matches("HARM_CATEGORY_").to_string()))
+        .collect()
+}
+
+/// Errors when the prompt was blocked, or the first candidate was withheld.
+pub fn check_blocked(json: &Value) -> Result<(), GeminiError> {
+    if let Some(reason) = json["promptFeedback"]["blockReason"].as_str() {
+        let categories = flagged_categories(&json["promptFeedback"]["safetyRatings"]);
+        return Err(GeminiError::Blocked { prompt: true, reason: reason.to_string(), categories });
+    }
+    let candidate = &json["candidates"][0];
+    match candidate["finishReason"].as_str() {
+        Some(reason) if BLOCK_REASONS.contains(&reason) && candidate["content"]["parts"].as_array().map_or(true, |p| p.is_empty()) => {
+            Err(GeminiError::Blocked { prompt: false, reason: reason.to_string(), categories: flagged_categories(&candidate["safetyRatings"]) })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Reads one `GenerateContentResponse`.
+fn reply_from_json(json: &Value) -> Result<GeminiReply, GeminiError> {
+    check_blocked(json)?;
+    let usage = Usage::from_gemini(json);
+    let Some(candidate) = json["candidates"].get(0) else {
+        return Ok(GeminiReply { usage, ..GeminiReply::default() });
+    };
+    // Parts marked `thought` are thinking summaries, not the answer.
+    let text: String = candidate["content"]["parts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|p| p["thought"].as_bool() != Some(true))
+        .filter_map(|p| p["text"].as_str())
+        .collect();
+    let finish_reason = candidate["finishReason"].as_str().map(str::to_string);
+    Ok(GeminiReply { truncated: finish_reason.as_deref() == Some("MAX_TOKENS"), text, finish_reason, usage, ..GeminiReply::default() })
+}
+
+/// Reads a JSON string literal starting right after its opening quote. A literal cut
+/// off by the end of the input is returned as far as it goes.
+fn read_string_literal(chars: &mut std::str::Chars) -> String {
+    let mut out = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => break,
+            '\\' => match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some('r') => out.push('\r'),
+                Some('b') => out.push('\u{8}'),
+                Some('f') => out.push('\u{c}'),
+                Some('u') => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    let Ok(mut code) = u32::from_str_radix(&hex, 16) else { break };
+                    // High surrogate: combine with the following `\uXXXX`.
+                    if (0xD800..0xDC00).contains(&code) && chars.next() == Some('\\') && chars.next() == Some('u') {
+                        let low: String = chars.by_ref().take(4).collect();
+                        if let Ok(low) = u32::from_str_radix(&low, 16) {
+                            code = 0x10000 + ((code - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+                        }
+                    }
+                    out.push(char::from_u32(code).unwrap_or('\u{FFFD}'));
+                }
+                Some(other) => out.push(other),
+                None => break,
+            },
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Salvages the `"text"` values from a body that isn't valid JSON, typically one cut off
+/// mid-transfer. `thought` parts can't be told apart here and are kept.
+fn recover_text(body: &str) -> Option<String> {
+    let mut texts = Vec::new();
+    let mut rest = body;
+    while let Some(i) = rest.find("\"text\"") {
+        rest = &rest[i + 6..];
+        let value = rest.trim_start();
+        let Some(value) = value.strip_prefix(':') else { continue };
+        let Some(value) = value.trim_start().strip_prefix('"') else { continue };
+        let mut chars = value.chars();
+        texts.push(read_string_literal(&mut chars));
+        rest = chars.as_str();
+    }
+    (!texts.is_empty()).then(|| texts.concat())
+}
+
+/// Extracts the reply from a successful `generateContent` body (or the JSON array that
+/// `streamGenerateContent` returns). Blocked prompts and candidates, empty candidates
+/// and unreadable bodies become specific errors; text is salvaged from truncated JSON.
+pub fn parse_reply(body: &str) -> Result<GeminiReply, GeminiError> {
+    let json: Value = match serde_json::from_str(body) {
+        Ok(json) => json,
+        Err(e) => {
+            return match recover_text(body) {
+                Some(text) => Ok(GeminiReply { text, truncated: true, recovered: true, ..GeminiReply::default() }),
+                None => Err(GeminiError::Malformed(e.to_string())),
+            }
+        }
+    };
+    let chunks = match json.as_array() {
+        Some(chunks) => chunks.as_slice(),
+        None => std::slice::from_ref(&json),
+    };
+    let mut reply = GeminiReply::default();
+    for chunk in chunks {
+        let part = reply_from_json(chunk)?;
+        reply.text.push_str(&part.text);
+        reply.finish_reason = part.finish_reason.or(reply.finish_reason);
+        reply.truncated = part.truncated;
+        reply.usage = part.usage.or(reply.usage);
+    }
+    if reply.text.is_empty() {
+        return Err(GeminiError::Empty { finish_reason: reply.finish_reason });
+    }
+    Ok(reply)
+}
+
+fn schema_type(schema: &Value) -> Option<String> {
+    schema["type"].as_str().map(str::to_uppercase)
+}
+
+/// Checks `value` against a `responseSchema` (Gemini's OpenAPI subset: `type`,
+/// `nullable`, `enum`, `properties`, `required`, `items`, `minItems`, `maxItems` and
+/// `anyOf`). `path` names the value in errors, e.g. `$.files[2].path`.
+pub fn validate_schema(value: &Value, schema: &Value, path: &str) -> Result<(), String> {
+    if value.is_null() && schema["nullable"].as_bool() == Some(true) {
+        return Ok(());
+    }
+    if let Some(options) = schema["anyOf"].as_array() {
+        return match options.iter().any(|option| validate_schema(value, option, path).is_ok()) {
+            true => Ok(()),
+            false => Err(format!("{} matches none of the allowed schemas", path)),
+        };
+    }
+    let ty = schema_type(schema);
+    let matches = match ty.as_deref() {
+        None => true,
+        Some("STRING") => value.is_string(),
+        Some("NUMBER") => value.is_number(),
+        Some("INTEGER") => value.is_i64() || value.is_u64(),
+        Some("BOOLEAN") => value.is_boolean(),
+        Some("ARRAY") => value.is_array(),
+        Some("OBJECT") => value.is_object(),
+        Some(other) => return Err(format!("Unsupported schema type at {}: {}", path, other)),
+    };
+    if !matches {
+        return Err(format!("{} should be {} but is {}", path, ty.unwrap_or_default().to_lowercase(), json_kind(value)));
+    }
+    if let Some(allowed) = schema["enum"].as_array() {
+        if !allowed.contains(value) {
+            return Err(format!("{} is not one of the allowed values", path));
+        }
+    }
+    if let Some(items) = value.as_array() {
+        let len = items.len() as u64;
+        if let Some(min) = schema["minItems"].as_u64().or_else(|| schema["minItems"].as_str()?.parse().ok()) {
+            if len < min {
+                return Err(format!("{} has {} items, fewer than {}", path, len, min));
+            }
+        }
+        if let Some(max) = schema["maxItems"].as_u64().or_else(|| schema["maxItems"].as_str()?.parse().ok()) {
+            if len > max {
+                return Err(format!("{} has {} items, more than {}", path, len, max));
+            }
+        }
+        if schema["items"].is_object() {
+            for (i, item) in items.iter().enumerate() {
+                validate_schema(item, &schema["items"], &format!("{}[{}]", path, i))?;
+            }
+        }
+    }
+    if let Some(object) = value.as_object() {
+        for name in schema["required"].as_array().into_iter().flatten().filter_map(Value::as_str) {
+            if !object.contains_key(name) {
+                return Err(format!("{} is missing the required field {}", path, name));
+            }
+        }
+        if let Some(properties) = schema["properties"].as_object() {
+            for (name, property) in properties {
+                if let Some(field) = object.get(name) {
+                    validate_schema(field, property, &format!("{}.{}", path, name))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn json_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Parses the text of a reply requested with `responseMimeType: application/json` and,
+/// when `schema` is given, checks it with [`validate_schema`]. A Markdown fence around the
+/// JSON is tolerated.
+pub fn parse_structured(text: &str, schema: Option<&Value>) -> Result<Value, GeminiError> {
+    let trimmed = text.trim();
+    let unfenced = trimmed
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        .map(|inner| inner.trim_start_matches(|c: char| c.is_ascii_alphabetic()).trim())
+        .unwrap_or(trimmed);
+    let value: Value = serde_json::from_str(unfenced).map_err(|e| GeminiError::Schema(format!("not valid JSON ({})", e)))?;
+    if let Some(schema) = schema {
+        validate_schema(&value, schema, "$").map_err(GeminiError::Schema)?;
+    }
+    Ok(value)
+}
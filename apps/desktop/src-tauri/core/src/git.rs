@@ -0,0 +1,24 @@
+/// Whether `s` is a full 40-character commit SHA, i.e. a ref that can never move.
+pub fn is_full_sha(s: &str) -> bool {
+    s.len() == 40 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Rejects refs that git itself would refuse (`git check-ref-format` rules, roughly),
+/// before spending a request on them.
+pub fn validate_ref(git_ref: &str) -> Result<(), String> {
+    let invalid = git_ref.is_empty()
+        || git_ref.len() > 255
+        || git_ref.starts_with('-')
+        || git_ref.starts_with('/')
+        || git_ref.ends_with('/')
+        || git_ref.ends_with('.')
+        || git_ref.ends_with(".lock")
+        || git_ref.contains("..")
+        || git_ref.contains("@{")
+        || git_ref.contains("//")
+        || git_ref.chars().any(|c| c.is_control() || c.is_whitespace() || "~^:?*[\\".contains(c));
+    if invalid {
+        return Err(format!("'{}' is not a valid branch, tag or commit name", git_ref));
+    }
+    Ok(())
+}
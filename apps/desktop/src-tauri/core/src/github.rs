@@ -0,0 +1,84 @@
+use crate::paths::encode_path;
+use std::collections::HashMap;
+use std::io::Read;
+
+/// Files larger than this are listed in the tree but their content is not kept.
+const MAX_TARBALL_FILE_BYTES: u64 = 1_000_000;
+/// Upper bound on the total text kept in memory from one archive.
+const MAX_TARBALL_TOTAL_BYTES: usize = 200_000_000;
+
+/// The repository snapshot unpacked from a GitHub tarball.
+pub struct TarballContents {
+    /// Every regular file path, relative to the repository root.
+    pub paths: Vec<String>,
+    /// UTF-8 contents of files under the size limits.
+    pub files: HashMap<String, String>,
+}
+
+impl TarballContents {
+    /// The README in `dir` (the root when `None`), preferring `README.md` over other variants.
+    pub fn readme(&self, dir: Option<&str>) -> Option<String> {
+        let prefix = dir.map(|d| format!("{}/", d)).unwrap_or_default();
+        let mut candidates: Vec<&String> = self
+            .paths
+            .iter()
+            .filter_map(|p| p.strip_prefix(&prefix).map(|name| (p, name)))
+            .filter(|(_, name)| !name.contains('/') && name.to_lowercase().starts_with("readme"))
+            .map(|(p, _)| p)
+            .collect();
+        candidates.sort_by_key(|p| (!p.to_lowercase().ends_with("readme.md"), p.len()));
+        candidates.into_iter().find_map(|p| self.files.get(p).cloned())
+    }
+}
+
+/// Unpacks a gzipped tarball as produced by the GitHub `tarball` endpoint. The archive's
+/// single top-level directory (`owner-repo-sha/`) is stripped from every path.
+pub fn extract_tarball(reader: impl Read) -> Result<TarballContents, String> {
+    let decoder = flate2::read::GzDecoder::new(reader);
+    let mut archive = tar::Archive::new(decoder);
+    let mut paths = Vec::new();
+    let mut files = HashMap::new();
+    let mut total = 0usize;
+
+    for entry in archive.entries().map_err(|e| format!("Failed to read tarball: {}", e))? {
+        let mut entry = entry.map_err(|e| format!("Failed to read tarball entry: {}", e))?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let full_path = entry.path().map_err(|e| e.to_string())?.to_string_lossy().replace('\\', "/");
+        let Some((_, path)) = full_path.split_once('/') else { continue };
+        let path = path.to_string();
+
+        let size = entry.header().size().unwrap_or(0);
+        if size <= MAX_TARBALL_FILE_BYTES && total + size as usize <= MAX_TARBALL_TOTAL_BYTES {
+            let mut buf = Vec::with_capacity(size as usize);
+            if entry.read_to_end(&mut buf).is_ok() {
+                if let Ok(text) = String::from_utf8(buf) {
+                    total += text.len();
+                    files.insert(path.clone(), text);
+                }
+            }
+        }
+        paths.push(path);
+    }
+
+    paths.sort();
+    Ok(TarballContents { paths, files })
+}
+
+/// Builds a `github.com` link to `path` at `sha`, optionally anchored to a line range.
+pub fn blob_url(owner: &str, repo: &str, sha: &str, path: &str, lines: Option<(u32, u32)>) -> String {
+    let anchor = match lines {
+        Some((start, end)) if end > start => format!("#L{}-L{}", start, end),
+        Some((start, _)) => format!("#L{}", start),
+        None => String::new(),
+    };
+    format!("https://github.com/{}/{}/blob/{}/{}{}", owner, repo, sha, encode_path(path), anchor)
+}
+
+/// Decodes the base64 `content` field of a contents/readme API response.
+pub fn decode_content(json: &serde_json::Value) -> Option<String> {
+    let cleaned = json["content"].as_str()?.replace(['\n', '\r'], "");
+    let decoded = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, cleaned).ok()?;
+    Some(String::from_utf8_lossy(&decoded).to_string())
+}
@@ -0,0 +1,92 @@
+use crate::paths::glob_match;
+use std::path::Path;
+
+/// Project-level ignore file, in gitignore syntax, at the repository root.
+pub const IGNORE_FILE: &str = ".repopromptignore";
+
+#[derive(Clone, Debug)]
+struct Rule {
+    pattern: String,
+    /// `!pattern`: includes again what an earlier rule ignored.
+    negated: bool,
+    /// `pattern/`: only matches directories.
+    dir_only: bool,
+    /// Contains a `/` other than a trailing one, so it is matched against the whole path
+    /// instead of any file or directory name.
+    anchored: bool,
+}
+
+impl Rule {
+    fn parse(line: &str) -> Option<Rule> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').filter(|l| l.starts_with(['#', '!'])).unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let pattern = line.trim_start_matches('/').to_string();
+        (!pattern.is_empty()).then_some(Rule { pattern, negated, dir_only, anchored })
+    }
+
+    fn matches(&self, path: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let target = if self.anchored { path } else { path.rsplit('/').next().unwrap_or(path) };
+        glob_match(&self.pattern, target)
+    }
+}
+
+/// Paths to leave out of scans and fetches, in gitignore syntax. Rules are layered:
+/// those added later take precedence, and `!pattern` includes a file again unless one of
+/// its directories is ignored.
+#[derive(Clone, Debug, Default)]
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+impl IgnoreRules {
+    /// Rules from the lines of an ignore file.
+    pub fn parse(text: &str) -> Self {
+        IgnoreRules { rules: text.lines().filter_map(Rule::parse).collect() }
+    }
+
+    /// The user's patterns (from the settings), overridden by the repository's own
+    /// [`IGNORE_FILE`] content when it has one.
+    pub fn layered(user: &[String], project: Option<&str>) -> Self {
+        let mut rules = IgnoreRules { rules: user.iter().filter_map(|p| Rule::parse(p)).collect() };
+        rules.rules.extend(IgnoreRules::parse(project.unwrap_or_default()).rules);
+        rules
+    }
+
+    /// [`IgnoreRules::layered`] with the [`IGNORE_FILE`] at the root of a local checkout.
+    pub fn for_directory(root: &Path, user: &[String]) -> Self {
+        IgnoreRules::layered(user, std::fs::read_to_string(root.join(IGNORE_FILE)).ok().as_deref())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether the last rule matching `path` ignores it.
+    fn matched(&self, path: &str, is_dir: bool) -> bool {
+        self.rules.iter().rev().find(|r| r.matches(path, is_dir)).is_some_and(|r| !r.negated)
+    }
+
+    /// Whether `path` (relative to the repository root, `/`-separated) is ignored, by a
+    /// rule for it or for one of its directories.
+    pub fn is_ignored(&self, path: &str, is_dir: bool) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let mut dirs = path.match_indices('/').map(|(i, _)| &path[..i]);
+        dirs.any(|dir| self.matched(dir, true)) || self.matched(path, is_dir)
+    }
+}
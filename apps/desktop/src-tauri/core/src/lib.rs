@@ -0,0 +1,107 @@
+//! The engine behind Repo Prompt Generator, usable without the desktop app: scanning a
+//! directory, ranking the files worth putting in a prompt, packing them within a token
+//! budget, outlining and chunking source code, and the provider-independent parts of talking to GitHub, GitLab, Gemini and
+//! Ollama (URL handling, response parsing, permalinks, token usage and cost).
+//!
+//! Everything network-bound stays with the caller, who brings their own HTTP client;
+//! this crate only builds and reads what goes over the wire.
+//!
+//! # Features
+//!
+//! All enabled by default.
+//!
+//! - `github`: tarball extraction, contents decoding, ref validation, github.com links.
+//! - `gitlab`: GitLab permalinks.
+//! - `ollama`: Ollama server URL normalization.
+//! - `gemini`: `generateContent` response parsing, including blocked and truncated replies.
+//! - `embeddings`: vector normalization and similarity ranking for retrieval.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn run() {
+//! use repo_prompt_core::{chunking, paths, ranking, scan};
+//!
+//! let root = std::path::PathBuf::from("./my-project");
+//! let mut files = scan::read_directory(root.clone()).await;
+//! paths::make_relative(&mut files, &root);
+//!
+//! let all: Vec<String> = files.iter().map(|f| f.path.clone()).collect();
+//! let best = ranking::select_source_files(&all, "", &[], 20, &ranking::ScoringRules::default());
+//! for file in files.iter().filter(|f| best.contains(&f.path)) {
+//!     for chunk in chunking::chunk_file(file, 512, 32) {
+//!         println!("{}:{}-{} ({} tokens)", chunk.path, chunk.start_line, chunk.end_line, chunk.tokens);
+//!     }
+//! }
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+pub mod chunking;
+pub mod continuation;
+pub mod dedup;
+pub mod dependencies;
+#[cfg(feature = "embeddings")]
+pub mod embeddings;
+#[cfg(feature = "gemini")]
+pub mod gemini;
+pub mod git;
+#[cfg(feature = "github")]
+pub mod github;
+pub mod ignore;
+pub mod normalize;
+#[cfg(feature = "ollama")]
+pub mod ollama;
+pub mod outline;
+pub mod pack;
+pub mod paths;
+#[cfg(any(feature = "github", feature = "gitlab"))]
+pub mod permalink;
+pub mod ranking;
+pub mod repourl;
+pub mod scan;
+pub mod tokens;
+pub mod tree;
+pub mod usage;
+
+/// A file as it goes into a prompt: its path (see [`paths::prompt_path`]) and text, with
+/// the [`tokens::FileStats`] of the text. Build it with [`FileEntry::new`] and change the
+/// text with [`FileEntry::set_content`] so the stats stay in step.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", from = "StoredFileEntry")]
+pub struct FileEntry {
+    pub path: String,
+    pub content: String,
+    pub stats: tokens::FileStats,
+}
+
+impl FileEntry {
+    pub fn new(path: String, content: String) -> Self {
+        let stats = tokens::FileStats::of(&content);
+        FileEntry { path, content, stats }
+    }
+
+    pub fn set_content(&mut self, content: String) {
+        self.stats = tokens::FileStats::of(&content);
+        self.content = content;
+    }
+}
+
+/// A [`FileEntry`] as read back; stats that are missing or don't match the content are
+/// counted again.
+#[derive(Deserialize)]
+struct StoredFileEntry {
+    path: String,
+    content: String,
+    stats: Option<tokens::FileStats>,
+}
+
+impl From<StoredFileEntry> for FileEntry {
+    fn from(stored: StoredFileEntry) -> Self {
+        match stored.stats {
+            Some(stats) if stats.bytes == stored.content.len() => FileEntry { path: stored.path, content: stored.content, stats },
+            _ => FileEntry::new(stored.path, stored.content),
+        }
+    }
+}
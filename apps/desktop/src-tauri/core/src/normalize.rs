@@ -0,0 +1,107 @@
+use crate::FileEntry;
+use serde::{Deserialize, Serialize};
+
+/// Cleanups applied to file contents before packing, each opt-in; the default changes
+/// nothing.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Normalization {
+    /// Turn CRLF and lone CR line endings into LF.
+    pub line_endings: bool,
+    pub trim_trailing_whitespace: bool,
+    /// Runs of more blank lines than this are cut down to this many.
+    pub max_blank_lines: Option<usize>,
+    /// Expand tabs to spaces, up to the next multiple of this width.
+    pub tab_width: Option<usize>,
+    /// Cut lines longer than this many characters (minified bundles) with an elision marker.
+    pub max_line_chars: Option<usize>,
+}
+
+impl Normalization {
+    pub fn is_noop(&self) -> bool {
+        *self == Normalization::default()
+    }
+}
+
+fn expand_tabs(line: &str, width: usize) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut column = 0;
+    for c in line.chars() {
+        if c == '\t' {
+            let spaces = width - column % width;
+            out.push_str(&" ".repeat(spaces));
+            column += spaces;
+        } else {
+            out.push(c);
+            column += 1;
+        }
+    }
+    out
+}
+
+fn truncate_line(line: &mut String, max_chars: usize) {
+    if let Some((cut, _)) = line.char_indices().nth(max_chars) {
+        let elided = line[cut..].chars().count();
+        line.truncate(line[..cut].trim_end().len());
+        line.push_str(&format!(" … [{} more characters]", elided));
+    }
+}
+
+/// `content` with the cleanups `options` turn on. Line endings other than LF are kept
+/// unless `line_endings` is set.
+pub fn normalize(content: &str, options: &Normalization) -> String {
+    if options.is_noop() {
+        return content.to_string();
+    }
+    let unified;
+    let content = if options.line_endings {
+        unified = content.replace("\r\n", "\n").replace('\r', "\n");
+        unified.as_str()
+    } else {
+        content
+    };
+
+    let mut out = String::with_capacity(content.len());
+    let mut blank_run = 0;
+    for raw in content.split_inclusive('\n') {
+        let (body, ending) = match raw.strip_suffix("\r\n") {
+            Some(body) => (body, "\r\n"),
+            None => raw.strip_suffix('\n').map_or((raw, ""), |body| (body, "\n")),
+        };
+        let mut line = match options.tab_width.filter(|w| *w > 0) {
+            Some(width) if body.contains('\t') => expand_tabs(body, width),
+            _ => body.to_string(),
+        };
+        if options.trim_trailing_whitespace {
+            line.truncate(line.trim_end().len());
+        }
+        if let Some(max) = options.max_line_chars.filter(|m| *m > 0) {
+            truncate_line(&mut line, max);
+        }
+        if line.trim().is_empty() {
+            blank_run += 1;
+            if options.max_blank_lines.is_some_and(|max| blank_run > max) {
+                continue;
+            }
+        } else {
+            blank_run = 0;
+        }
+        out.push_str(&line);
+        out.push_str(ending);
+    }
+    out
+}
+
+/// Normalizes every file in place. Returns the number of characters removed.
+pub fn normalize_files(files: &mut [FileEntry], options: &Normalization) -> usize {
+    if options.is_noop() {
+        return 0;
+    }
+    let mut removed = 0;
+    for file in files {
+        let normalized = normalize(&file.content, options);
+        removed += file.content.chars().count().saturating_sub(normalized.chars().count());
+        file.set_content(normalized);
+    }
+    removed
+}
@@ -0,0 +1,16 @@
+/// Where a default Ollama install listens.
+pub const DEFAULT_OLLAMA_URL: &str = "http://127.0.0.1:11434";
+
+/// Trims the URL, adds a scheme if missing, and maps `localhost` to 127.0.0.1 (Ollama
+/// listens on IPv4 only while `localhost` may resolve to ::1).
+pub fn normalize_url(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+    let url = if url.contains("://") { url.to_string() } else { format!("http://{}", url) };
+    url.replace("localhost", "127.0.0.1")
+}
+
+/// Base URL for an `OLLAMA_HOST` value, which may omit the scheme and port.
+pub fn host_url(host: &str) -> String {
+    let url = normalize_url(&host.replace("0.0.0.0", "127.0.0.1"));
+    if url.rsplit(':').next().is_some_and(|p| p.parse::<u16>().is_ok()) { url } else { format!("{}:11434", url) }
+}
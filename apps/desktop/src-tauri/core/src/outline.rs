@@ -0,0 +1,278 @@
+use tree_sitter::{Language, Node, Parser};
+
+/// How a node kind contributes to the outline.
+#[derive(Clone, Copy, PartialEq)]
+enum Emit {
+    /// Emit the node verbatim (imports, type aliases, small declarations).
+    Full,
+    /// Emit everything before the `body` field and elide the body.
+    Signature,
+    /// Emit the signature, then recurse into the body for member signatures.
+    Container,
+    /// Emit the first line only (e.g. `const handler = async (req) => {`).
+    FirstLine,
+    /// Transparent wrapper (`export ...`, decorators): emit the inner `declaration`/`definition`
+    /// but keep the wrapper's leading text.
+    Wrapper,
+}
+
+struct LangSpec {
+    language: Language,
+    kinds: &'static [(&'static str, Emit)],
+    brace_body: bool,
+    is_doc: fn(&str) -> bool,
+}
+
+const MAX_FULL_LINES: usize = 30;
+
+const RUST_KINDS: &[(&str, Emit)] = &[
+    ("use_declaration", Emit::Full),
+    ("extern_crate_declaration", Emit::Full),
+    ("function_item", Emit::Signature),
+    ("function_signature_item", Emit::Full),
+    ("struct_item", Emit::Full),
+    ("enum_item", Emit::Full),
+    ("union_item", Emit::Full),
+    ("type_item", Emit::Full),
+    ("const_item", Emit::FirstLine),
+    ("static_item", Emit::FirstLine),
+    ("macro_definition", Emit::FirstLine),
+    ("trait_item", Emit::Container),
+    ("impl_item", Emit::Container),
+    ("mod_item", Emit::Container),
+];
+
+const PYTHON_KINDS: &[(&str, Emit)] = &[
+    ("import_statement", Emit::Full),
+    ("import_from_statement", Emit::Full),
+    ("future_import_statement", Emit::Full),
+    ("function_definition", Emit::Signature),
+    ("class_definition", Emit::Container),
+    ("decorated_definition", Emit::Wrapper),
+];
+
+const JS_KINDS: &[(&str, Emit)] = &[
+    ("import_statement", Emit::Full),
+    ("export_statement", Emit::Wrapper),
+    ("function_declaration", Emit::Signature),
+    ("generator_function_declaration", Emit::Signature),
+    ("class_declaration", Emit::Container),
+    ("abstract_class_declaration", Emit::Container),
+    ("method_definition", Emit::Signature),
+    ("method_signature", Emit::Full),
+    ("abstract_method_signature", Emit::Full),
+    ("field_definition", Emit::FirstLine),
+    ("public_field_definition", Emit::FirstLine),
+    ("lexical_declaration", Emit::FirstLine),
+    ("interface_declaration", Emit::Full),
+    ("type_alias_declaration", Emit::Full),
+    ("enum_declaration", Emit::Full),
+];
+
+const GO_KINDS: &[(&str, Emit)] = &[
+    ("package_clause", Emit::Full),
+    ("import_declaration", Emit::Full),
+    ("function_declaration", Emit::Signature),
+    ("method_declaration", Emit::Signature),
+    ("type_declaration", Emit::Full),
+    ("const_declaration", Emit::Full),
+];
+
+fn rust_doc(c: &str) -> bool { c.starts_with("///") || c.starts_with("//!") || c.starts_with("/**") }
+fn js_doc(c: &str) -> bool { c.starts_with("/**") }
+fn any_comment(_: &str) -> bool { true }
+
+fn spec_for_path(path: &str) -> Option<LangSpec> {
+    let lower = path.to_lowercase();
+    let ext = lower.rsplit('.').next().unwrap_or("");
+    let spec = match ext {
+        "rs" => LangSpec { language: tree_sitter_rust::LANGUAGE.into(), kinds: RUST_KINDS, brace_body: true, is_doc: rust_doc },
+        "py" | "pyi" => LangSpec { language: tree_sitter_python::LANGUAGE.into(), kinds: PYTHON_KINDS, brace_body: false, is_doc: any_comment },
+        "js" | "jsx" | "mjs" | "cjs" => LangSpec { language: tree_sitter_javascript::LANGUAGE.into(), kinds: JS_KINDS, brace_body: true, is_doc: js_doc },
+        "ts" | "mts" | "cts" => LangSpec { language: tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(), kinds: JS_KINDS, brace_body: true, is_doc: js_doc },
+        "tsx" => LangSpec { language: tree_sitter_typescript::LANGUAGE_TSX.into(), kinds: JS_KINDS, brace_body: true, is_doc: js_doc },
+        "go" => LangSpec { language: tree_sitter_go::LANGUAGE.into(), kinds: GO_KINDS, brace_body: true, is_doc: any_comment },
+        _ => return None,
+    };
+    Some(spec)
+}
+
+/// Tree-sitter grammar bundled for the file type, if any.
+pub fn language_for_path(path: &str) -> Option<Language> {
+    spec_for_path(path).map(|spec| spec.language)
+}
+
+struct OutlineWriter<'a> {
+    src: &'a str,
+    spec: &'a LangSpec,
+    out: String,
+}
+
+impl<'a> OutlineWriter<'a> {
+    fn kind_of(&self, node: &Node) -> Option<Emit> {
+        self.spec.kinds.iter().find(|(k, _)| *k == node.kind()).map(|(_, e)| *e)
+    }
+
+    fn text(&self, start: usize, end: usize) -> &'a str {
+        &self.src[start..end]
+    }
+
+    fn column(&self, byte: usize) -> usize {
+        byte - self.src[..byte].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    /// Appends `text` at the given depth. Continuation lines are dedented by `base_col`
+    /// (the source column the text started at) so nested layout is preserved.
+    fn push_lines(&mut self, text: &str, base_col: usize, depth: usize) {
+        let indent = "    ".repeat(depth);
+        for (i, line) in text.lines().enumerate() {
+            let line = if i == 0 {
+                line.trim_start()
+            } else {
+                // Only ASCII indentation is stripped, so the cut stays on a char boundary.
+                let strip = line.len() - line.trim_start_matches([' ', '\t']).len();
+                &line[strip.min(base_col)..]
+            };
+            self.out.push_str(&indent);
+            self.out.push_str(line.trim_end());
+            self.out.push('\n');
+        }
+    }
+
+    /// Emits the doc comments (and, for Rust, attributes) directly above `node`.
+    fn push_doc_comments(&mut self, node: &Node, depth: usize) {
+        let mut comments = Vec::new();
+        let mut expected_row = node.start_position().row;
+        let mut prev = node.prev_sibling();
+        while let Some(p) = prev {
+            let is_attribute = p.kind() == "attribute_item";
+            if !(is_attribute || p.kind().contains("comment")) || p.end_position().row + 1 < expected_row {
+                break;
+            }
+            let text = self.text(p.start_byte(), p.end_byte());
+            if !is_attribute && !(self.spec.is_doc)(text.trim_start()) {
+                break;
+            }
+            comments.push((text, p.start_position().column));
+            expected_row = p.start_position().row;
+            prev = p.prev_sibling();
+        }
+        for (c, col) in comments.into_iter().rev() {
+            self.push_lines(c, col, depth);
+        }
+    }
+
+    fn python_docstring(&self, body: &Node) -> Option<(&'a str, usize)> {
+        let first = body.named_child(0)?;
+        if first.kind() != "expression_statement" {
+            return None;
+        }
+        let inner = first.named_child(0)?;
+        (inner.kind() == "string").then(|| (self.text(inner.start_byte(), inner.end_byte()), inner.start_position().column))
+    }
+
+    fn visit_children(&mut self, parent: &Node, depth: usize) {
+        let mut cursor = parent.walk();
+        let children: Vec<Node> = parent.named_children(&mut cursor).collect();
+        for child in children {
+            self.visit(&child, child.start_byte(), depth);
+        }
+    }
+
+    fn visit(&mut self, node: &Node, start: usize, depth: usize) {
+        let Some(emit) = self.kind_of(node) else { return };
+        let col = self.column(start);
+        if start == node.start_byte() {
+            self.push_doc_comments(node, depth);
+        }
+
+        match emit {
+            Emit::Wrapper => {
+                let inner = node.child_by_field_name("declaration").or_else(|| node.child_by_field_name("definition"));
+                match inner {
+                    Some(inner) if self.kind_of(&inner).is_some() => self.visit(&inner, start, depth),
+                    _ => {
+                        let text = self.text(start, node.end_byte());
+                        self.push_full(text, col, depth);
+                    }
+                }
+            }
+            Emit::Full => {
+                let text = self.text(start, node.end_byte());
+                self.push_full(text, col, depth);
+            }
+            Emit::FirstLine => {
+                let text = self.text(start, node.end_byte());
+                let first = text.lines().next().unwrap_or_default();
+                if text.lines().nth(1).is_some() {
+                    self.push_lines(&format!("{} ...", first.trim_end()), col, depth);
+                } else {
+                    self.push_lines(first, col, depth);
+                }
+            }
+            Emit::Signature | Emit::Container => {
+                let Some(body) = node.child_by_field_name("body") else {
+                    let text = self.text(start, node.end_byte());
+                    self.push_full(text, col, depth);
+                    return;
+                };
+                let signature = self.text(start, body.start_byte()).trim_end().to_string();
+                let docstring = if self.spec.brace_body { None } else { self.python_docstring(&body) };
+
+                if emit == Emit::Signature {
+                    if self.spec.brace_body {
+                        self.push_lines(&format!("{} {{ ... }}", signature), col, depth);
+                    } else {
+                        self.push_lines(&signature, col, depth);
+                        if let Some((doc, doc_col)) = docstring {
+                            self.push_lines(doc, doc_col, depth + 1);
+                        }
+                        self.push_lines("...", 0, depth + 1);
+                    }
+                    return;
+                }
+
+                if self.spec.brace_body {
+                    self.push_lines(&format!("{} {{", signature), col, depth);
+                    self.visit_children(&body, depth + 1);
+                    self.push_lines("}", 0, depth);
+                } else {
+                    self.push_lines(&signature, col, depth);
+                    if let Some((doc, doc_col)) = docstring {
+                        self.push_lines(doc, doc_col, depth + 1);
+                    }
+                    let before = self.out.len();
+                    self.visit_children(&body, depth + 1);
+                    if self.out.len() == before {
+                        self.push_lines("...", 0, depth + 1);
+                    }
+                }
+            }
+        }
+    }
+
+    fn push_full(&mut self, text: &str, col: usize, depth: usize) {
+        let line_count = text.lines().count();
+        if line_count > MAX_FULL_LINES {
+            let head: Vec<&str> = text.lines().take(MAX_FULL_LINES).collect();
+            self.push_lines(&head.join("\n"), col, depth);
+            self.push_lines(&format!("... ({} more lines)", line_count - MAX_FULL_LINES), 0, depth + 1);
+        } else {
+            self.push_lines(text, col, depth);
+        }
+    }
+}
+
+/// Builds a signatures-only outline (imports, type/function signatures, doc comments)
+/// for a single file. Returns `None` when no grammar is bundled for the file type.
+pub fn outline_source(path: &str, source: &str) -> Option<String> {
+    let spec = spec_for_path(path)?;
+    let mut parser = Parser::new();
+    parser.set_language(&spec.language).ok()?;
+    let tree = parser.parse(source, None)?;
+
+    let mut writer = OutlineWriter { src: source, spec: &spec, out: String::new() };
+    let root = tree.root_node();
+    writer.visit_children(&root, 0);
+    Some(writer.out)
+}
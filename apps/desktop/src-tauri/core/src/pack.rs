@@ -0,0 +1,250 @@
+use crate::dedup::{self, DuplicateGroup, VendoredFile};
+use crate::normalize::{self, Normalization};
+use crate::ranking::ScoringRules;
+use crate::tokens::estimate_tokens;
+use crate::tree::{self, TreeEntry, TreeOptions};
+use crate::FileEntry;
+
+/// Tree lines listed before the rest is summarized as a count.
+const MAX_TREE_LINES: usize = 1000;
+/// Tokens charged per file for its heading or tags, on top of its content.
+const FILE_OVERHEAD_TOKENS: usize = 12;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PackFormat {
+    Markdown,
+    Xml,
+    Plain,
+}
+
+impl PackFormat {
+    /// `md` (or `markdown`), `xml`, or `txt` (or `plain`).
+    pub fn parse(name: &str) -> Result<PackFormat, String> {
+        match name.trim().to_lowercase().as_str() {
+            "md" | "markdown" => Ok(PackFormat::Markdown),
+            "xml" => Ok(PackFormat::Xml),
+            "txt" | "text" | "plain" => Ok(PackFormat::Plain),
+            other => Err(format!("Unknown format: {} (expected md, xml or txt)", other)),
+        }
+    }
+}
+
+/// A repository packed into one prompt.
+pub struct Pack {
+    /// `owner/repo` or the directory name.
+    pub name: String,
+    /// Branch, tag or commit the files were taken from, if any.
+    pub git_ref: Option<String>,
+    /// Every file path in the repository, included or not.
+    pub tree: Vec<String>,
+    pub readme: Option<String>,
+    pub files: Vec<FileEntry>,
+    /// Files left out to stay within the budget.
+    pub omitted: Vec<String>,
+    /// Identical files included once; see [`crate::dedup::dedup`].
+    pub duplicates: Vec<DuplicateGroup>,
+    /// Vendored or generated files left out.
+    pub vendored: Vec<VendoredFile>,
+}
+
+/// How [`assemble`] packs a repository's files.
+#[derive(Clone, Debug)]
+pub struct PackOptions {
+    pub format: PackFormat,
+    /// Token budget for the whole pack; without one every file is kept.
+    pub budget: Option<usize>,
+    /// Include every copy of identical files instead of one.
+    pub keep_duplicates: bool,
+    /// Include vendored, generated and minified files.
+    pub keep_vendored: bool,
+    /// Decides which files make the budget.
+    pub scoring: ScoringRules,
+    /// Cleanups applied to every file before anything else.
+    pub normalization: Normalization,
+}
+
+impl Default for PackOptions {
+    fn default() -> Self {
+        PackOptions { format: PackFormat::Markdown, budget: None, keep_duplicates: false, keep_vendored: false, scoring: ScoringRules::default(), normalization: Normalization::default() }
+    }
+}
+
+fn is_root_readme(path: &str, subpath: Option<&str>) -> bool {
+    let name = match subpath {
+        Some(sub) => path.strip_prefix(sub).and_then(|p| p.strip_prefix('/')).unwrap_or(path),
+        None => path,
+    };
+    !name.contains('/') && name.to_lowercase().starts_with("readme")
+}
+
+/// Packs `files` (all those of the repository, or of its `subpath`): contents are
+/// normalized as `options` ask, the README at the root becomes the pack's README, empty
+/// files are dropped, vendored files and duplicates are left out unless `options` keep
+/// them, and what doesn't fit the budget is omitted.
+pub fn assemble(name: String, git_ref: Option<String>, mut files: Vec<FileEntry>, subpath: Option<&str>, options: &PackOptions) -> Pack {
+    normalize::normalize_files(&mut files, &options.normalization);
+    let mut tree: Vec<String> = files.iter().map(|f| f.path.clone()).collect();
+    tree.sort();
+    let (readme, files): (Vec<FileEntry>, Vec<FileEntry>) = files.into_iter().partition(|f| is_root_readme(&f.path, subpath));
+    // Tarballs list files they don't keep the content of (binary or too large).
+    let files: Vec<FileEntry> = files.into_iter().filter(|f| !f.content.is_empty()).collect();
+    let (files, vendored) = if options.keep_vendored { (files, Vec::new()) } else { dedup::split_vendored(files) };
+    let (files, duplicates) = if options.keep_duplicates { (files, Vec::new()) } else { dedup::dedup(files) };
+    let mut pack = Pack {
+        name,
+        git_ref,
+        tree,
+        readme: readme.into_iter().next().map(|f| f.content),
+        files: Vec::new(),
+        omitted: Vec::new(),
+        duplicates,
+        vendored,
+    };
+    match options.budget {
+        Some(budget) => {
+            let reserved = header_tokens(&pack, options.format);
+            (pack.files, pack.omitted) = fit_budget(files, budget, reserved, &options.scoring);
+        }
+        None => pack.files = files,
+    }
+    pack
+}
+
+/// What including `file` costs: its path, content and heading.
+pub fn file_tokens(file: &FileEntry) -> usize {
+    estimate_tokens(&file.path) + file.stats.tokens + FILE_OVERHEAD_TOKENS
+}
+
+/// Keeps the files that matter most (by `rules`) within `budget` tokens, of which
+/// `reserved` are already spent on the tree and README. Returns the kept files in path
+/// order and the paths of those left out.
+pub fn fit_budget(files: Vec<FileEntry>, budget: usize, reserved: usize, rules: &ScoringRules) -> (Vec<FileEntry>, Vec<String>) {
+    let mut ranked = files;
+    ranked.sort_by(|a, b| rules.score(&b.path).cmp(&rules.score(&a.path)).then_with(|| a.path.cmp(&b.path)));
+    let mut left = budget.saturating_sub(reserved);
+    let (mut kept, mut omitted) = (Vec::new(), Vec::new());
+    for file in ranked {
+        let cost = file_tokens(&file);
+        if cost <= left {
+            left -= cost;
+            kept.push(file);
+        } else {
+            omitted.push(file.path);
+        }
+    }
+    kept.sort_by(|a, b| a.path.cmp(&b.path));
+    omitted.sort();
+    (kept, omitted)
+}
+
+/// The pack without its files, for working out how much of a budget is left for them.
+pub fn header_tokens(pack: &Pack, format: PackFormat) -> usize {
+    let header = Pack {
+        name: pack.name.clone(),
+        git_ref: pack.git_ref.clone(),
+        tree: pack.tree.clone(),
+        readme: pack.readme.clone(),
+        files: Vec::new(),
+        omitted: Vec::new(),
+        duplicates: pack.duplicates.clone(),
+        vendored: pack.vendored.clone(),
+    };
+    estimate_tokens(&render(&header, format))
+}
+
+fn tree_text(paths: &[String]) -> String {
+    let entries: Vec<TreeEntry> = paths.iter().map(|p| TreeEntry::from(p.as_str())).collect();
+    tree::render(&entries, &TreeOptions { max_entries: Some(MAX_TREE_LINES), ..Default::default() })
+}
+
+/// A Markdown fence longer than any backtick run in `content`.
+fn fence(content: &str) -> String {
+    let longest = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn fence_language(path: &str) -> &str {
+    path.rsplit_once('.').map(|(_, ext)| ext).filter(|ext| !ext.contains('/')).unwrap_or("")
+}
+
+fn xml_attr(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;").replace('<', "&lt;")
+}
+
+/// What was left out or collapsed, one line each.
+fn notes(pack: &Pack) -> Vec<String> {
+    let mut notes = Vec::new();
+    if !pack.omitted.is_empty() {
+        notes.push(format!("{} files were left out to fit the token budget.", pack.omitted.len()));
+    }
+    if !pack.vendored.is_empty() {
+        notes.push(format!("{} vendored or generated files were left out.", pack.vendored.len()));
+    }
+    for group in &pack.duplicates {
+        notes.push(format!("{} is identical to {} (included once).", group.duplicates.join(", "), group.kept));
+    }
+    notes
+}
+
+/// Renders `pack` as one prompt: name and ref, file tree, README, then each file.
+pub fn render(pack: &Pack, format: PackFormat) -> String {
+    let mut out = String::new();
+    let notes = notes(pack);
+    let note = (!notes.is_empty()).then(|| notes.join("\n"));
+    match format {
+        PackFormat::Markdown => {
+            out.push_str(&format!("# {}\n\n", pack.name));
+            if let Some(git_ref) = &pack.git_ref {
+                out.push_str(&format!("Ref: {}\n\n", git_ref));
+            }
+            out.push_str(&format!("## File tree\n\n```\n{}\n```\n\n", tree_text(&pack.tree)));
+            if let Some(readme) = pack.readme.as_deref().filter(|r| !r.trim().is_empty()) {
+                out.push_str(&format!("## README\n\n{}\n\n", readme.trim()));
+            }
+            out.push_str("## Files\n");
+            if let Some(note) = &note {
+                out.push_str(&format!("\n{}\n", note));
+            }
+            for file in &pack.files {
+                let fence = fence(&file.content);
+                out.push_str(&format!("\n### {}\n\n{}{}\n{}\n{}\n", file.path, fence, fence_language(&file.path), file.content.trim_end(), fence));
+            }
+        }
+        PackFormat::Xml => {
+            out.push_str(&format!("<repository name=\"{}\"", xml_attr(&pack.name)));
+            if let Some(git_ref) = &pack.git_ref {
+                out.push_str(&format!(" ref=\"{}\"", xml_attr(git_ref)));
+            }
+            out.push_str(">\n");
+            out.push_str(&format!("<file_tree>\n{}\n</file_tree>\n", tree_text(&pack.tree)));
+            if let Some(readme) = pack.readme.as_deref().filter(|r| !r.trim().is_empty()) {
+                out.push_str(&format!("<readme>\n{}\n</readme>\n", readme.trim()));
+            }
+            if let Some(note) = &note {
+                out.push_str(&format!("<note>{}</note>\n", note));
+            }
+            out.push_str("<files>\n");
+            for file in &pack.files {
+                out.push_str(&format!("<file path=\"{}\">\n{}\n</file>\n", xml_attr(&file.path), file.content.trim_end()));
+            }
+            out.push_str("</files>\n</repository>\n");
+        }
+        PackFormat::Plain => {
+            out.push_str(&format!("Repository Name: {}\n", pack.name));
+            if let Some(git_ref) = &pack.git_ref {
+                out.push_str(&format!("Ref: {}\n", git_ref));
+            }
+            out.push_str(&format!("\nFile Tree:\n{}\n\n", tree_text(&pack.tree)));
+            if let Some(readme) = pack.readme.as_deref().filter(|r| !r.trim().is_empty()) {
+                out.push_str(&format!("README:\n{}\n\n", readme.trim()));
+            }
+            if let Some(note) = &note {
+                out.push_str(&format!("{}\n", note));
+            }
+            for file in &pack.files {
+                out.push_str(&format!("\n--- {} ---\n{}\n", file.path, file.content));
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,65 @@
+use crate::FileEntry;
+use std::path::Path;
+
+/// A path as it should appear in prompts: `/`-separated on every OS and, when it lies
+/// under `root`, relative to it (which also drops the drive letter). Mixed separators
+/// confuse models and make the paths in their suggested patches unusable elsewhere.
+pub fn prompt_path(path: &str, root: Option<&Path>) -> String {
+    if let Some(rel) = root.and_then(|root| Path::new(path).strip_prefix(root).ok()) {
+        return rel.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+    }
+    path.strip_prefix(r"\\?\").unwrap_or(path).replace('\\', "/")
+}
+
+/// Rewrites the paths of scanned files with [`prompt_path`]. Files outside `root` keep
+/// their full (but `/`-separated) path.
+pub fn make_relative(files: &mut [FileEntry], root: &Path) {
+    for file in files {
+        file.path = prompt_path(&file.path, Some(root));
+    }
+}
+
+/// Percent-encodes each segment of a repository path, keeping the separators.
+pub fn encode_path(path: &str) -> String {
+    path.split('/').map(|seg| urlencoding::encode(seg).into_owned()).collect::<Vec<_>>().join("/")
+}
+
+/// Whether `path` matches `pattern`: `*` and `?` stay within one directory, `**` spans
+/// any number of them (`a/**/b` includes `a/b`), `[abc]`/`[a-z]`/`[!a]` match one
+/// character of a set and `\` escapes the next character.
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    glob_bytes(pattern.as_bytes(), path.as_bytes())
+}
+
+fn glob_bytes(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', b'/', rest @ ..] => (0..=text.len()).any(|i| (i == 0 || text[i - 1] == b'/') && glob_bytes(rest, &text[i..])),
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|i| glob_bytes(rest, &text[i..])),
+        [b'*', rest @ ..] => (0..=text.len()).take_while(|&i| i == 0 || text[i - 1] != b'/').any(|i| glob_bytes(rest, &text[i..])),
+        [b'?', rest @ ..] => text.first().is_some_and(|&c| c != b'/') && glob_bytes(rest, &text[1..]),
+        [b'[', class @ ..] if class.contains(&b']') => {
+            let end = class.iter().skip(1).position(|&c| c == b']').map_or(0, |p| p + 1);
+            let (set, rest) = (&class[..end], &class[end + 1..]);
+            let (negated, set) = match set {
+                [b'!' | b'^', set @ ..] => (true, set),
+                set => (false, set),
+            };
+            let Some(&c) = text.first().filter(|&&c| c != b'/') else { return false };
+            let mut found = false;
+            let mut i = 0;
+            while i < set.len() {
+                if i + 2 < set.len() && set[i + 1] == b'-' {
+                    found |= (set[i]..=set[i + 2]).contains(&c);
+                    i += 3;
+                } else {
+                    found |= set[i] == c;
+                    i += 1;
+                }
+            }
+            found != negated && glob_bytes(rest, &text[1..])
+        }
+        [b'\\', c, rest @ ..] => text.first() == Some(c) && glob_bytes(rest, &text[1..]),
+        [c, rest @ ..] => text.first() == Some(c) && glob_bytes(rest, &text[1..]),
+    }
+}
@@ -0,0 +1,59 @@
+use crate::git::is_full_sha;
+#[cfg(feature = "gitlab")]
+use crate::paths::encode_path;
+
+/// Builds the permalink for `path` (optionally anchored to `[start, end]` lines) at a
+/// pinned commit. `host` defaults to `github.com`; any host containing "gitlab" uses
+/// GitLab's URL scheme, any other host is taken for GitHub Enterprise. Each scheme needs
+/// its feature (`github` or `gitlab`).
+pub fn permalink(host: Option<&str>, owner: &str, repo: &str, commit_sha: &str, path: &str, line_range: Option<(u32, u32)>) -> Result<String, String> {
+    if !is_full_sha(commit_sha) {
+        return Err(format!("Permalinks need a full commit SHA, got '{}'", commit_sha));
+    }
+    let path = path.trim().trim_start_matches("./").trim_start_matches('/').replace('\\', "/");
+    if path.is_empty() {
+        return Err("Path is empty".to_string());
+    }
+    if let Some((start, end)) = line_range {
+        if start == 0 || end < start {
+            return Err(format!("Invalid line range {}-{}", start, end));
+        }
+    }
+
+    let host = host.map(|h| h.trim().trim_start_matches("https://").trim_end_matches('/')).filter(|h| !h.is_empty());
+    match host {
+        Some(h) if h.contains("gitlab") => gitlab_link(h, owner, repo, commit_sha, &path, line_range),
+        None | Some("github.com") => github_link(None, owner, repo, commit_sha, &path, line_range),
+        Some(h) => github_link(Some(h), owner, repo, commit_sha, &path, line_range),
+    }
+}
+
+#[cfg(feature = "github")]
+fn github_link(host: Option<&str>, owner: &str, repo: &str, commit_sha: &str, path: &str, line_range: Option<(u32, u32)>) -> Result<String, String> {
+    let url = crate::github::blob_url(owner, repo, commit_sha, path, line_range);
+    // GitHub Enterprise uses the same layout as github.com on its own host.
+    Ok(match host {
+        Some(h) => url.replacen("github.com", h, 1),
+        None => url,
+    })
+}
+
+#[cfg(not(feature = "github"))]
+fn github_link(_: Option<&str>, _: &str, _: &str, _: &str, _: &str, _: Option<(u32, u32)>) -> Result<String, String> {
+    Err("GitHub permalinks need the `github` feature".to_string())
+}
+
+#[cfg(feature = "gitlab")]
+fn gitlab_link(host: &str, owner: &str, repo: &str, commit_sha: &str, path: &str, line_range: Option<(u32, u32)>) -> Result<String, String> {
+    let anchor = match line_range {
+        Some((start, end)) if end > start => format!("#L{}-{}", start, end),
+        Some((start, _)) => format!("#L{}", start),
+        None => String::new(),
+    };
+    Ok(format!("https://{}/{}/{}/-/blob/{}/{}{}", host, owner, repo, commit_sha, encode_path(path), anchor))
+}
+
+#[cfg(not(feature = "gitlab"))]
+fn gitlab_link(_: &str, _: &str, _: &str, _: &str, _: &str, _: Option<(u32, u32)>) -> Result<String, String> {
+    Err("GitLab permalinks need the `gitlab` feature".to_string())
+}
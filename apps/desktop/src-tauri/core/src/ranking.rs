@@ -0,0 +1,106 @@
+use crate::paths::glob_match;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+/// A weight added to files whose path matches `glob`. `*` and `?` stay within one
+/// directory, `**` spans any number of them; a glob without `/` is matched against the
+/// file name only. Matching ignores case.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobRule {
+    pub glob: String,
+    pub weight: i32,
+}
+
+/// How files are ranked by [`ScoringRules::score`]. Within each of the three rule sets
+/// only the largest boost and the largest penalty that match count, so a file matching
+/// several test globs is down-ranked once.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScoringRules {
+    /// Weights for keywords found in the file name (`main`, `index`, ...).
+    pub name_keywords: BTreeMap<String, i32>,
+    /// Weights for keywords found anywhere in the path (`config`, `build`, ...).
+    pub path_keywords: BTreeMap<String, i32>,
+    pub globs: Vec<GlobRule>,
+    /// Subtracted per path component, so shallow files win ties.
+    pub depth_penalty: i32,
+}
+
+impl Default for ScoringRules {
+    /// Source directories and entry points rank up; tests, build configuration and deep
+    /// nesting rank down.
+    fn default() -> Self {
+        let keywords = |words: &[&str], weight: i32| words.iter().map(|w| (w.to_string(), weight)).collect::<BTreeMap<_, _>>();
+        let globs = |globs: &[&str], weight: i32| globs.iter().map(|g| GlobRule { glob: g.to_string(), weight }).collect::<Vec<_>>();
+        ScoringRules {
+            name_keywords: keywords(&["main", "index", "app", "server", "core", "api", "service", "model"], 10),
+            path_keywords: keywords(&["build", "setup", "config", "webpack", "vite", "docs/"], -30),
+            globs: [
+                globs(&["**/test/**", "**/tests/**", "**/__tests__/**", "*.test.*", "*.spec.*"], -50),
+                globs(&["**/src/**", "**/lib/**", "**/app/**", "**/core/**"], 20),
+            ]
+            .concat(),
+            depth_penalty: 1,
+        }
+    }
+}
+
+/// The largest boost plus the largest penalty among `weights`.
+fn strongest(weights: impl Iterator<Item = i32>) -> i32 {
+    let (boost, penalty) = weights.fold((0, 0), |(b, p), w| (b.max(w), p.min(w)));
+    boost + penalty
+}
+
+impl ScoringRules {
+    /// How likely a file is to matter for understanding the project.
+    pub fn score(&self, path: &str) -> i32 {
+        let lower = path.to_lowercase();
+        let parts: Vec<&str> = lower.split('/').collect();
+        let name = parts.last().unwrap_or(&"");
+        let name_score = strongest(self.name_keywords.iter().filter(|(k, _)| name.contains(&k.to_lowercase())).map(|(_, &w)| w));
+        let path_score = strongest(self.path_keywords.iter().filter(|(k, _)| lower.contains(&k.to_lowercase())).map(|(_, &w)| w));
+        let glob_score = strongest(self.globs.iter().filter(|r| self.glob_matches(&r.glob, &lower, name)).map(|r| r.weight));
+        name_score + path_score + glob_score - self.depth_penalty * parts.len() as i32
+    }
+
+    fn glob_matches(&self, glob: &str, path: &str, name: &str) -> bool {
+        let glob = glob.to_lowercase();
+        let target = if glob.contains('/') { path } else { name };
+        glob_match(&glob, target)
+    }
+
+    /// Checks the rules before they are used: globs must be non-empty and weights
+    /// reasonable.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(rule) = self.globs.iter().find(|r| r.glob.trim().is_empty()) {
+            return Err(format!("Scoring glob with weight {} is empty", rule.weight));
+        }
+        let weights = self.name_keywords.values().chain(self.path_keywords.values()).chain(self.globs.iter().map(|r| &r.weight));
+        if let Some(w) = weights.chain([&self.depth_penalty]).find(|w| w.abs() > 10_000) {
+            return Err(format!("Scoring weights must be between -10000 and 10000, got {}", w));
+        }
+        Ok(())
+    }
+}
+
+/// [`ScoringRules::score`] with the default rules.
+pub fn file_score(path: &str) -> i32 {
+    static DEFAULT_RULES: OnceLock<ScoringRules> = OnceLock::new();
+    DEFAULT_RULES.get_or_init(ScoringRules::default).score(path)
+}
+
+/// Picks the `limit` best-scoring source files under `prefix` by `rules`, skipping
+/// `exclude` (dependency manifests) and the README.
+pub fn select_source_files(paths: &[String], prefix: &str, exclude: &[String], limit: usize, rules: &ScoringRules) -> Vec<String> {
+    let source_extensions = [".ts", ".tsx", ".js", ".jsx", ".py", ".go", ".rs", ".java", ".cpp", ".c", ".h", ".cs", ".md"];
+    let mut files: Vec<String> = paths.iter()
+        .filter(|p| p.starts_with(prefix) && source_extensions.iter().any(|ext| p.ends_with(ext)))
+        .filter(|p| !exclude.contains(p) && p[prefix.len()..].to_lowercase() != "readme.md")
+        .cloned().collect();
+    // Score relative to the subpath so `packages/api/src/` ranks like a top-level `src/`.
+    files.sort_by_cached_key(|f| std::cmp::Reverse(rules.score(&f[prefix.len()..])));
+    files.truncate(limit);
+    files
+}
@@ -0,0 +1,114 @@
+use serde::Serialize;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Provider {
+    Github,
+    Gitlab,
+}
+
+/// A repository location taken apart from whatever the user pasted.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoUrl {
+    pub provider: Provider,
+    /// `github.com`, `gitlab.com` or a self-hosted instance.
+    pub host: String,
+    /// User or organization; on GitLab, the full group path (`group/subgroup`).
+    pub owner: String,
+    pub repo: String,
+    /// Branch, tag or commit from a `/tree/`, `/blob/` or `/commit/` URL.
+    pub git_ref: Option<String>,
+    /// Directory the URL points into: the directory itself for `/tree/`, the file's
+    /// directory for `/blob/`.
+    pub subpath: Option<String>,
+    /// The file of a `/blob/` URL.
+    pub file: Option<String>,
+}
+
+fn valid_name(s: &str) -> bool {
+    !s.is_empty() && s != "." && s != ".." && s.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+}
+
+/// Splits `input` into host and path: `https://host/path`, `git@host:path`,
+/// `ssh://git@host/path`, `host/path`, or a bare `owner/repo` (GitHub).
+fn split_host(input: &str) -> (String, &str) {
+    if let Some(rest) = input.split_once("://").map(|(_, rest)| rest) {
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+        // Drops `user@` and `:port`.
+        let host = authority.rsplit('@').next().unwrap_or(authority);
+        return (host.split(':').next().unwrap_or(host).to_lowercase(), path);
+    }
+    if let Some((user_host, path)) = input.split_once(':').filter(|(h, _)| h.contains('@') && !h.contains('/')) {
+        return (user_host.rsplit('@').next().unwrap_or(user_host).to_lowercase(), path);
+    }
+    match input.split_once('/') {
+        Some((first, rest)) if first.contains('.') && rest.contains('/') => (first.to_lowercase(), rest),
+        _ => ("github.com".to_string(), input),
+    }
+}
+
+/// Parses a repository reference as pasted from a browser or a clone dialog:
+/// `owner/repo`, `https://github.com/owner/repo(.git)`, `.../tree/<ref>/<dir>`,
+/// `.../blob/<ref>/<file>`, `.../commit/<sha>`, `git@host:owner/repo.git` and
+/// `ssh://git@host/owner/repo`. Hosts containing "gitlab" are GitLab (nested groups and
+/// `/-/tree/` URLs included); any other host is taken for GitHub or GitHub Enterprise.
+/// A ref is read as one path segment, so in `/tree/feature/x/src` the branch is
+/// `feature` and `x/src` the directory.
+pub fn parse_repo_url(input: &str) -> Result<RepoUrl, String> {
+    let trimmed = input.trim();
+    let cleaned = trimmed.split(['?', '#']).next().unwrap_or_default();
+    if cleaned.is_empty() {
+        return Err("Repository URL is empty".to_string());
+    }
+    let (host, path) = split_host(cleaned);
+    let provider = if host.contains("gitlab") { Provider::Gitlab } else { Provider::Github };
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    // Everything before the first route segment names the repository.
+    let (repo_path, route): (&[&str], &[&str]) = match provider {
+        Provider::Github => segments.split_at(segments.len().min(2)),
+        Provider::Gitlab => match segments.iter().position(|s| *s == "-") {
+            Some(i) => (&segments[..i], &segments[i + 1..]),
+            None => match segments.iter().position(|s| ["tree", "blob", "commit"].contains(s)) {
+                Some(i) => segments.split_at(i),
+                None => (&segments[..], &[][..]),
+            },
+        },
+    };
+    let invalid = || format!("Not a repository URL: {}", trimmed);
+    let (repo, owner) = repo_path.split_last().ok_or_else(invalid)?;
+    let repo = repo.strip_suffix(".git").unwrap_or(repo);
+    if owner.is_empty() || !owner.iter().all(|s| valid_name(s)) || !valid_name(repo) {
+        return Err(invalid());
+    }
+
+    let mut url = RepoUrl {
+        provider,
+        host,
+        owner: owner.join("/"),
+        repo: repo.to_string(),
+        git_ref: None,
+        subpath: None,
+        file: None,
+    };
+    match route {
+        [] => {}
+        ["tree", git_ref, dir @ ..] => {
+            url.git_ref = Some(git_ref.to_string());
+            url.subpath = (!dir.is_empty()).then(|| dir.join("/"));
+        }
+        ["blob", git_ref, file @ ..] if !file.is_empty() => {
+            url.git_ref = Some(git_ref.to_string());
+            url.subpath = (file.len() > 1).then(|| file[..file.len() - 1].join("/"));
+            url.file = Some(file.join("/"));
+        }
+        ["commit", sha, ..] => url.git_ref = Some(sha.to_string()),
+        // Other pages of the repository (issues, pulls, ...) still name it.
+        _ => {}
+    }
+    if url.subpath.as_deref().is_some_and(|p| p.split('/').any(|s| s == "..")) {
+        return Err(invalid());
+    }
+    Ok(url)
+}
@@ -0,0 +1,83 @@
+use crate::ignore::IgnoreRules;
+use crate::paths::prompt_path;
+use crate::FileEntry;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Whether a file or directory named `name` is skipped by every scan: VCS and editor
+/// metadata, virtual environments, dependencies and build output.
+pub fn is_skipped_name(name: &str) -> bool {
+    let is_hidden = name.starts_with(".git") || name == ".venv" || name == ".idea" || name == ".vscode";
+    let is_heavy = name == "node_modules" || name == "target" || name == "venv" || name == "build" || name == "__pycache__";
+    is_hidden || is_heavy
+}
+
+/// Reads every text file under `root`, skipping VCS, editor and build directories and
+/// files over 1MB. Paths are reported as full paths; see [`crate::paths::make_relative`].
+/// Must be called inside a Tokio runtime.
+pub async fn read_directory(root: PathBuf) -> Vec<FileEntry> {
+    read_directory_with_progress(root, |_, _| {}).await
+}
+
+/// Like [`read_directory`], calling `progress(files_done, files_total)` as each file is
+/// read. The total is known once the directory walk is over, before the first call.
+pub async fn read_directory_with_progress(root: PathBuf, progress: impl Fn(usize, usize)) -> Vec<FileEntry> {
+    let base = root.clone();
+    read_directory_ignoring(root, &base, &IgnoreRules::default(), progress).await
+}
+
+/// Like [`read_directory_with_progress`], also skipping what `ignore` matches, on top of
+/// the built-in skips. Paths are matched relative to `base`, the repository root, which
+/// `root` may lie under. Ignored directories aren't walked at all.
+pub async fn read_directory_ignoring(root: PathBuf, base: &Path, ignore: &IgnoreRules, progress: impl Fn(usize, usize)) -> Vec<FileEntry> {
+    use tokio::task::JoinSet;
+    let mut files = Vec::new();
+    let mut set = JoinSet::new();
+
+    let walker = walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| {
+            let is_ignored = e.depth() > 0 && !ignore.is_empty() && ignore.is_ignored(&prompt_path(&e.path().to_string_lossy(), Some(base)), e.file_type().is_dir());
+            !is_skipped_name(&e.file_name().to_string_lossy()) && !is_ignored
+        });
+
+    for entry in walker.filter_map(|e| e.ok()) {
+        if entry.file_type().is_file() {
+            if let Ok(metadata) = entry.metadata() {
+                if metadata.len() > 1_000_000 {
+                    continue; // Skip files > 1MB
+                }
+            }
+
+            let file_path = entry.path().to_path_buf();
+            set.spawn_blocking(move || {
+                match fs::read_to_string(&file_path) {
+                    Ok(content) => Some(FileEntry::new(file_path.display().to_string(), content)),
+                    Err(_) => None,
+                }
+            });
+        }
+    }
+
+    let total = set.len();
+    let mut done = 0;
+    while let Some(result) = set.join_next().await {
+        done += 1;
+        progress(done, total);
+        if let Ok(Some(file_entry)) = result {
+            files.push(file_entry);
+        }
+    }
+    files
+}
+
+/// Normalizes a monorepo subpath such as `/packages\api/` to `packages/api`. Empty input
+/// means the whole repository; paths that would escape the repository are rejected.
+pub fn normalize_subpath(subpath: Option<String>) -> Result<Option<String>, String> {
+    let Some(raw) = subpath else { return Ok(None) };
+    let parts: Vec<&str> = raw.split(['/', '\\']).filter(|p| !p.is_empty() && *p != ".").collect();
+    if parts.contains(&"..") {
+        return Err(format!("Invalid subpath: {}", raw));
+    }
+    Ok(if parts.is_empty() { None } else { Some(parts.join("/")) })
+}
@@ -0,0 +1,59 @@
+use crate::FileEntry;
+use serde::{Deserialize, Serialize};
+
+/// Rough token count, the same ~4 characters per token heuristic the UI uses.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Size of one file's content.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStats {
+    pub lines: usize,
+    pub words: usize,
+    pub bytes: usize,
+    /// Estimated with [`estimate_tokens`].
+    pub tokens: usize,
+}
+
+impl FileStats {
+    pub fn of(content: &str) -> Self {
+        FileStats { lines: content.lines().count(), words: content.split_whitespace().count(), bytes: content.len(), tokens: estimate_tokens(content) }
+    }
+}
+
+/// Totals over the files of a pack, with the largest ones, for seeing what takes up the
+/// context.
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PackStats {
+    pub files: usize,
+    pub lines: usize,
+    pub words: usize,
+    pub bytes: usize,
+    pub tokens: usize,
+    /// Up to [`LARGEST_FILES`] paths by tokens, largest first.
+    pub largest: Vec<String>,
+}
+
+/// Files named in [`PackStats::largest`].
+pub const LARGEST_FILES: usize = 10;
+
+impl PackStats {
+    pub fn of(files: &[FileEntry]) -> Self {
+        let mut stats = PackStats { files: files.len(), ..PackStats::default() };
+        let mut sizes = Vec::with_capacity(files.len());
+        for file in files {
+            let s = file.stats;
+            stats.lines += s.lines;
+            stats.words += s.words;
+            stats.bytes += s.bytes;
+            stats.tokens += s.tokens;
+            sizes.push((s.tokens, file.path.as_str()));
+        }
+        sizes.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+        stats.largest = sizes.into_iter().take(LARGEST_FILES).map(|(_, p)| p.to_string()).collect();
+        stats
+    }
+}
@@ -0,0 +1,211 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// A file to place in the tree, with its size when known.
+#[derive(Clone, Debug, Default)]
+pub struct TreeEntry {
+    pub path: String,
+    pub bytes: Option<u64>,
+    pub tokens: Option<usize>,
+}
+
+impl From<&str> for TreeEntry {
+    fn from(path: &str) -> Self {
+        TreeEntry { path: path.to_string(), ..Default::default() }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum TreeStyle {
+    /// `├──`/`└──` connectors, as printed by `tree`.
+    #[default]
+    Ascii,
+    /// A nested Markdown list.
+    Markdown,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TreeOptions {
+    pub style: TreeStyle,
+    /// First line of the tree, e.g. the repository name; without it the top-level
+    /// entries start the tree.
+    pub root: Option<String>,
+    /// Levels shown; deeper directories are collapsed into a file count.
+    pub max_depth: Option<usize>,
+    /// Lines shown before the rest is summarized as a count.
+    pub max_entries: Option<usize>,
+    pub show_size: bool,
+    pub show_tokens: bool,
+    /// Directories before files on each level, rather than one alphabetical list.
+    pub directories_first: bool,
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        TreeOptions { style: TreeStyle::Ascii, root: None, max_depth: None, max_entries: None, show_size: false, show_tokens: false, directories_first: true }
+    }
+}
+
+#[derive(Default)]
+struct Dir {
+    dirs: BTreeMap<String, Dir>,
+    files: BTreeMap<String, (Option<u64>, Option<usize>)>,
+}
+
+/// Files, bytes and tokens under a directory; sizes count the files that have one.
+#[derive(Default)]
+struct Totals {
+    files: usize,
+    bytes: Option<u64>,
+    tokens: Option<usize>,
+}
+
+impl Dir {
+    fn insert(&mut self, entry: &TreeEntry) {
+        let mut parts: Vec<&str> = entry.path.split(['/', '\\']).filter(|p| !p.is_empty() && *p != ".").collect();
+        let Some(name) = parts.pop() else { return };
+        let dir = parts.into_iter().fold(self, |dir, part| dir.dirs.entry(part.to_string()).or_default());
+        dir.files.insert(name.to_string(), (entry.bytes, entry.tokens));
+    }
+
+    fn totals(&self) -> Totals {
+        let mut totals = Totals { files: self.files.len(), ..Default::default() };
+        let mut add = |bytes: Option<u64>, tokens: Option<usize>| {
+            if let Some(b) = bytes {
+                totals.bytes = Some(totals.bytes.unwrap_or(0) + b);
+            }
+            if let Some(t) = tokens {
+                totals.tokens = Some(totals.tokens.unwrap_or(0) + t);
+            }
+        };
+        let subdirs: Vec<Totals> = self.dirs.values().map(Dir::totals).collect();
+        for (bytes, tokens) in self.files.values() {
+            add(*bytes, *tokens);
+        }
+        for sub in &subdirs {
+            add(sub.bytes, sub.tokens);
+        }
+        totals.files += subdirs.iter().map(|s| s.files).sum::<usize>();
+        totals
+    }
+}
+
+enum Child<'a> {
+    Dir(&'a Dir),
+    File(Option<u64>, Option<usize>),
+}
+
+/// `512 B`, `1.2 KB`, `3.4 MB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+struct Renderer<'a> {
+    options: &'a TreeOptions,
+    lines: Vec<String>,
+    limit: usize,
+    hidden: usize,
+    /// Markdown list levels ahead of the top-level entries.
+    indent: usize,
+}
+
+impl Renderer<'_> {
+    fn push(&mut self, line: String) {
+        if self.lines.len() < self.limit {
+            self.lines.push(line);
+        } else {
+            self.hidden += 1;
+        }
+    }
+
+    /// ` (12 files, 40.2 KB, 10300 tokens)`, with what the options ask for.
+    fn annotation(&self, files: Option<usize>, bytes: Option<u64>, tokens: Option<usize>) -> String {
+        let mut parts = Vec::new();
+        if let Some(files) = files {
+            parts.push(format!("{} {}", files, if files == 1 { "file" } else { "files" }));
+        }
+        if let Some(bytes) = bytes.filter(|_| self.options.show_size) {
+            parts.push(format_bytes(bytes));
+        }
+        if let Some(tokens) = tokens.filter(|_| self.options.show_tokens) {
+            parts.push(format!("{} tokens", tokens));
+        }
+        if parts.is_empty() { String::new() } else { format!(" ({})", parts.join(", ")) }
+    }
+
+    fn line(&self, prefix: &str, depth: usize, last: bool, label: String) -> String {
+        match self.options.style {
+            TreeStyle::Ascii => format!("{}{}{}", prefix, if last { "└── " } else { "├── " }, label),
+            TreeStyle::Markdown => format!("{}- {}", "  ".repeat(self.indent + depth), label),
+        }
+    }
+
+    fn dir(&mut self, dir: &Dir, prefix: &str, depth: usize) {
+        let mut children: Vec<(&String, Child)> = dir.dirs.iter().map(|(name, d)| (name, Child::Dir(d))).collect();
+        children.extend(dir.files.iter().map(|(name, (bytes, tokens))| (name, Child::File(*bytes, *tokens))));
+        if !self.options.directories_first {
+            children.sort_by(|a, b| a.0.cmp(b.0));
+        }
+        let count = children.len();
+        for (i, (name, child)) in children.into_iter().enumerate() {
+            let last = i + 1 == count;
+            match child {
+                Child::File(bytes, tokens) => {
+                    let label = format!("{}{}", name, self.annotation(None, bytes, tokens));
+                    self.push(self.line(prefix, depth, last, label));
+                }
+                Child::Dir(sub) => {
+                    let totals = sub.totals();
+                    let collapsed = self.options.max_depth.is_some_and(|max| depth + 1 >= max);
+                    let label = format!("{}/{}", name, self.annotation(collapsed.then_some(totals.files), totals.bytes, totals.tokens));
+                    self.push(self.line(prefix, depth, last, label));
+                    if !collapsed {
+                        self.dir(sub, &format!("{}{}", prefix, if last { "    " } else { "│   " }), depth + 1);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Renders `entries` as a directory tree, one line per directory and file, sorted by name
+/// on each level. `max_depth` and `max_entries` keep the tree of a large repository short;
+/// sizes and tokens of directories are the totals of the files under them.
+pub fn render(entries: &[TreeEntry], options: &TreeOptions) -> String {
+    let mut root = Dir::default();
+    for entry in entries {
+        root.insert(entry);
+    }
+    let mut renderer = Renderer { options, lines: Vec::new(), limit: options.max_entries.unwrap_or(usize::MAX), hidden: 0, indent: 0 };
+    renderer.indent = match options.root.as_deref().map(|r| r.trim_end_matches('/')).filter(|r| !r.is_empty()) {
+        Some(name) => {
+            let totals = root.totals();
+            let label = format!("{}/{}", name, renderer.annotation(None, totals.bytes, totals.tokens));
+            renderer.lines.push(match options.style {
+                TreeStyle::Ascii => label,
+                TreeStyle::Markdown => format!("- {}", label),
+            });
+            // Markdown nests the entries under the root; ASCII trees start them at the margin.
+            usize::from(options.style == TreeStyle::Markdown)
+        }
+        None => 0,
+    };
+    renderer.dir(&root, "", 0);
+    let mut text = renderer.lines.join("\n");
+    if renderer.hidden > 0 {
+        text.push_str(&format!("\n... and {} more", renderer.hidden));
+    }
+    text
+}
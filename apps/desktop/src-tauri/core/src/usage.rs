@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// List prices in USD per million input and output tokens, matched by model-name prefix
+/// (the longest matching prefix wins). Prices change; treat costs as estimates.
+const PRICES: &[(&str, f64, f64)] = &[
+    ("gemini-3.1-pro", 2.00, 12.00),
+    ("gemini-3-pro", 2.00, 12.00),
+    ("gemini-3-flash", 0.50, 3.00),
+    ("gemini-2.5-pro", 1.25, 10.00),
+    ("gemini-2.5-flash-lite", 0.10, 0.40),
+    ("gemini-2.5-flash", 0.30, 2.50),
+    ("gemini-2.0-flash-lite", 0.075, 0.30),
+    ("gemini-2.0-flash", 0.10, 0.40),
+    ("gpt-4.1-nano", 0.10, 0.40),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1", 2.00, 8.00),
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+];
+
+/// Tokens a model call consumed.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Usage {
+    pub prompt_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+}
+
+impl Usage {
+    pub fn add(&mut self, other: &Usage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.output_tokens += other.output_tokens;
+        self.total_tokens += other.total_tokens;
+    }
+
+    /// From a Gemini response's `usageMetadata`.
+    pub fn from_gemini(json: &Value) -> Option<Usage> {
+        let u = json.get("usageMetadata")?;
+        Some(Usage {
+            prompt_tokens: u["promptTokenCount"].as_u64().unwrap_or_default(),
+            output_tokens: u["candidatesTokenCount"].as_u64().unwrap_or_default(),
+            total_tokens: u["totalTokenCount"].as_u64().unwrap_or_default(),
+        })
+    }
+
+    /// From the eval counts of a finished Ollama `/api/generate` or `/api/chat` reply (or
+    /// the final chunk of a stream).
+    pub fn from_ollama(json: &Value) -> Option<Usage> {
+        let prompt_tokens = json.get("prompt_eval_count").and_then(Value::as_u64);
+        let output_tokens = json.get("eval_count").and_then(Value::as_u64);
+        if prompt_tokens.is_none() && output_tokens.is_none() {
+            return None;
+        }
+        let (prompt_tokens, output_tokens) = (prompt_tokens.unwrap_or_default(), output_tokens.unwrap_or_default());
+        Some(Usage { prompt_tokens, output_tokens, total_tokens: prompt_tokens + output_tokens })
+    }
+
+    /// From the `usage` of an OpenAI-compatible chat completion.
+    pub fn from_openai(json: &Value) -> Option<Usage> {
+        let u = json.get("usage")?;
+        let prompt_tokens = u["prompt_tokens"].as_u64().unwrap_or_default();
+        let output_tokens = u["completion_tokens"].as_u64().unwrap_or_default();
+        Some(Usage { prompt_tokens, output_tokens, total_tokens: u["total_tokens"].as_u64().unwrap_or(prompt_tokens + output_tokens) })
+    }
+}
+
+/// Estimated cost in USD of `usage` on `model`: nothing for local Ollama models, list
+/// price for known hosted models, `None` when the price is unknown.
+pub fn estimate_cost(provider: &str, model: &str, usage: &Usage) -> Option<f64> {
+    if provider == "ollama" {
+        return Some(0.0);
+    }
+    let model = model.trim().trim_start_matches("models/").to_lowercase();
+    let (_, input, output) = PRICES.iter().filter(|(prefix, _, _)| model.starts_with(prefix)).max_by_key(|(prefix, _, _)| prefix.len())?;
+    Some((usage.prompt_tokens as f64 * input + usage.output_tokens as f64 * output) / 1_000_000.0)
+}
@@ -0,0 +1,43 @@
+import { createServer } from "node:http";
+import * as store from "./store.js";
+
+const PORT = Number(process.env.PORT) || 3000;
+
+function send(res, status, body) {
+  res.writeHead(status, { "Content-Type": "application/json" });
+  res.end(body === undefined ? "" : JSON.stringify(body));
+}
+
+async function readJson(req) {
+  let data = "";
+  for await (const chunk of req) data += chunk;
+  return data ? JSON.parse(data) : {};
+}
+
+const server = createServer(async (req, res) => {
+  const [, resource, rawId] = req.url.split("/");
+  if (resource !== "todos") return send(res, 404, { error: "Not found" });
+  const id = rawId ? Number(rawId) : null;
+
+  try {
+    if (req.method === "GET" && id === null) return send(res, 200, store.list());
+    if (req.method === "POST" && id === null) {
+      const { title } = await readJson(req);
+      if (!title) return send(res, 400, { error: "title is required" });
+      return send(res, 201, store.add(title));
+    }
+    if (req.method === "PATCH" && id !== null) {
+      const { done } = await readJson(req);
+      const item = store.update(id, { done: Boolean(done) });
+      return item ? send(res, 200, item) : send(res, 404, { error: "No such item" });
+    }
+    if (req.method === "DELETE" && id !== null) {
+      return store.remove(id) ? send(res, 204) : send(res, 404, { error: "No such item" });
+    }
+    send(res, 405, { error: "Method not allowed" });
+  } catch {
+    send(res, 400, { error: "Invalid JSON body" });
+  }
+});
+
+server.listen(PORT, () => console.log(`tiny-todo listening on http://localhost:${PORT}`));
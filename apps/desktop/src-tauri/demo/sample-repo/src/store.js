@@ -0,0 +1,24 @@
+// In-memory store for to-do items.
+let nextId = 1;
+const items = new Map();
+
+export function list() {
+  return [...items.values()];
+}
+
+export function add(title) {
+  const item = { id: nextId++, title, done: false };
+  items.set(item.id, item);
+  return item;
+}
+
+export function update(id, changes) {
+  const item = items.get(id);
+  if (!item) return null;
+  Object.assign(item, changes);
+  return item;
+}
+
+export function remove(id) {
+  return items.delete(id);
+}
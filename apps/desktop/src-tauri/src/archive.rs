@@ -0,0 +1,181 @@
+use crate::error::AppError;
+use crate::{log_status, normalize_subpath, AppState, FileEntry};
+use repo_prompt_core::ignore::{IgnoreRules, IGNORE_FILE};
+use repo_prompt_core::scan::is_skipped_name;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use tauri::{AppHandle, State};
+
+/// Entries larger than this are skipped, like files over 1MB in a directory scan.
+const MAX_ENTRY_BYTES: u64 = 1_000_000;
+/// Upper bound on the text read from one archive, so a zip bomb can't exhaust memory.
+const MAX_TOTAL_BYTES: u64 = 200_000_000;
+const MAX_ENTRIES: usize = 100_000;
+
+enum Format {
+    Zip,
+    TarGz,
+    Tar,
+}
+
+fn format_of(path: &Path) -> Option<Format> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if name.ends_with(".zip") {
+        Some(Format::Zip)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(Format::TarGz)
+    } else if name.ends_with(".tar") {
+        Some(Format::Tar)
+    } else {
+        None
+    }
+}
+
+/// The `/`-separated path of an entry, or an error when it is absolute or climbs out of
+/// the archive with `..` (zip-slip). Nothing is written to disk, but such an archive is
+/// not one to trust with the rest of its paths either.
+fn entry_path(raw: &str) -> Result<String, String> {
+    let raw = raw.replace('\\', "/");
+    let is_absolute = raw.starts_with('/') || raw.as_bytes().get(1) == Some(&b':');
+    let parts: Vec<&str> = raw.split('/').filter(|p| !p.is_empty() && *p != ".").collect();
+    if is_absolute || parts.contains(&"..") {
+        return Err(format!("Archive entry escapes the archive: {}", raw));
+    }
+    Ok(parts.join("/"))
+}
+
+/// Reads the text entries of the archive. Entries over the size cap and binary entries
+/// are left out; so are the ones read after the total cap was reached.
+struct Collector {
+    files: Vec<(String, String)>,
+    entries: usize,
+    total: u64,
+    skipped: usize,
+}
+
+impl Collector {
+    fn add(&mut self, raw_path: &str, size: u64, reader: impl Read) -> Result<(), String> {
+        self.entries += 1;
+        if self.entries > MAX_ENTRIES {
+            return Err(format!("Archive has more than {} entries", MAX_ENTRIES));
+        }
+        let path = entry_path(raw_path)?;
+        if path.is_empty() || size > MAX_ENTRY_BYTES || self.total + size > MAX_TOTAL_BYTES {
+            self.skipped += 1;
+            return Ok(());
+        }
+        // The declared size may lie; never read more than the cap.
+        let mut buf = Vec::with_capacity(size as usize);
+        reader.take(MAX_ENTRY_BYTES + 1).read_to_end(&mut buf).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        if buf.len() as u64 > MAX_ENTRY_BYTES {
+            self.skipped += 1;
+            return Ok(());
+        }
+        self.total += buf.len() as u64;
+        match String::from_utf8(buf) {
+            Ok(text) => self.files.push((path, text)),
+            Err(_) => self.skipped += 1,
+        }
+        Ok(())
+    }
+}
+
+fn read_zip(file: File, collector: &mut Collector) -> Result<(), String> {
+    let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| format!("Failed to open zip archive: {}", e))?;
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i).map_err(|e| format!("Failed to read zip entry: {}", e))?;
+        if entry.is_file() {
+            let (name, size) = (entry.name().to_string(), entry.size());
+            collector.add(&name, size, entry)?;
+        }
+    }
+    Ok(())
+}
+
+fn read_tar(reader: impl Read, collector: &mut Collector) -> Result<(), String> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries().map_err(|e| format!("Failed to read tarball: {}", e))? {
+        let entry = entry.map_err(|e| format!("Failed to read tarball entry: {}", e))?;
+        // Links are skipped: their targets may point anywhere.
+        if entry.header().entry_type().is_file() {
+            let name = entry.path().map_err(|e| e.to_string())?.to_string_lossy().into_owned();
+            let size = entry.header().size().unwrap_or(0);
+            collector.add(&name, size, entry)?;
+        }
+    }
+    Ok(())
+}
+
+/// Drops the single top-level directory release archives wrap everything in
+/// (`project-1.2.0/`), when there is one.
+fn strip_top_directory(files: &mut [(String, String)]) {
+    let Some(top) = files.first().and_then(|(p, _)| p.split_once('/')).map(|(dir, _)| format!("{}/", dir)) else { return };
+    if files.iter().all(|(p, _)| p.starts_with(&top)) {
+        for (path, _) in files.iter_mut() {
+            path.drain(..top.len());
+        }
+    }
+}
+
+fn extract(path: &Path, format: Format, user_ignore: &[String], subpath: Option<&str>) -> Result<(Vec<FileEntry>, usize), String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut collector = Collector { files: Vec::new(), entries: 0, total: 0, skipped: 0 };
+    match format {
+        Format::Zip => read_zip(file, &mut collector)?,
+        Format::TarGz => read_tar(flate2::read::GzDecoder::new(BufReader::new(file)), &mut collector)?,
+        Format::Tar => read_tar(BufReader::new(file), &mut collector)?,
+    }
+    let mut files = collector.files;
+    strip_top_directory(&mut files);
+
+    let project_ignore = files.iter().find(|(p, _)| p == IGNORE_FILE).map(|(_, c)| c.as_str());
+    let ignore = IgnoreRules::layered(user_ignore, project_ignore);
+    let prefix = subpath.map(|s| format!("{}/", s)).unwrap_or_default();
+    let mut entries: Vec<FileEntry> = files
+        .into_iter()
+        .filter(|(p, _)| p.starts_with(&prefix))
+        // What a directory scan skips is skipped here too.
+        .filter(|(p, _)| !p.split('/').any(is_skipped_name))
+        .filter(|(p, _)| !ignore.is_ignored(p, false))
+        .map(|(path, content)| FileEntry::new(path, content))
+        .collect();
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok((entries, collector.skipped))
+}
+
+/// Reads a local `.zip`, `.tar.gz`/`.tgz` or `.tar` archive (a downloaded release, say)
+/// like a scanned directory, without extracting it to disk. A single top-level directory
+/// is stripped, entries over 1MB and binary files are skipped, and the settings' ignore
+/// patterns and the archive's own `.repopromptignore` apply. Archives with entries that
+/// would escape the archive are rejected.
+#[tauri::command]
+pub async fn scan_archive(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    subpath: Option<String>,
+    operation_id: Option<String>,
+) -> Result<Vec<FileEntry>, AppError> {
+    let subpath = normalize_subpath(subpath)?;
+    let archive = Path::new(&path).to_path_buf();
+    if !archive.is_file() {
+        return Err(AppError::NotFound(format!("Archive not found: {}", archive.display())));
+    }
+    let format = format_of(&archive)
+        .ok_or_else(|| AppError::InvalidInput(format!("Unsupported archive (expected .zip, .tar.gz, .tgz or .tar): {}", archive.display())))?;
+    state.policy.check_scan_path(&app, &archive)?;
+
+    log_status(&app, format!("Reading archive {}", archive.display()));
+    let progress = state.progress.start(&app, "scan", operation_id);
+    progress.stage("extracting", format!("Reading {}", archive.display()));
+    let span = state.trace.span("scan", "read_archive").attr("archive", archive.display());
+    let user_ignore = state.ignore_patterns.read().await.clone();
+    let (files, skipped) = tokio::task::spawn_blocking(move || extract(&archive, format, &user_ignore, subpath.as_deref()))
+        .await?
+        .map_err(AppError::InvalidInput)?;
+    span.attr("files", files.len()).end();
+    log_status(&app, format!("Archive read: {} files ({} skipped as too large or binary)", files.len(), skipped));
+    progress.finish(format!("{} files read", files.len()));
+    Ok(files)
+}
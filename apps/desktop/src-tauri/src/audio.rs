@@ -0,0 +1,135 @@
+use crate::error::AppError;
+use crate::AppState;
+use base64::Engine;
+use isahc::prelude::*;
+use tauri::State;
+
+const MAX_AUDIO_BYTES: usize = 20 * 1024 * 1024;
+const SUPPORTED_AUDIO: &[&str] = &[
+    "audio/wav", "audio/x-wav", "audio/mpeg", "audio/mp3", "audio/mp4", "audio/m4a", "audio/aac",
+    "audio/ogg", "audio/webm", "audio/flac",
+];
+
+fn file_extension(mime: &str) -> &'static str {
+    match mime {
+        "audio/wav" | "audio/x-wav" => "wav",
+        "audio/mpeg" | "audio/mp3" => "mp3",
+        "audio/mp4" | "audio/m4a" | "audio/aac" => "m4a",
+        "audio/ogg" => "ogg",
+        "audio/flac" => "flac",
+        _ => "webm",
+    }
+}
+
+async fn transcribe_with_gemini(state: &AppState, audio_b64: &str, mime: &str, model: Option<String>) -> Result<String, AppError> {
+    let key = state.gemini_api_key.read().await.clone();
+    if key.is_empty() {
+        return Err(AppError::missing_gemini_key());
+    }
+    let model_name = model.unwrap_or_else(|| "gemini-3-flash-preview".to_string());
+    let url = format!("https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent", model_name);
+    let body = serde_json::json!({
+        "contents": [{ "parts": [
+            { "text": "Transcribe this audio verbatim. Return only the transcript text, without commentary." },
+            { "inline_data": { "mime_type": mime, "data": audio_b64 } }
+        ] }]
+    });
+
+    let request = isahc::Request::post(url)
+        .header("Content-Type", "application/json")
+        .header("x-goog-api-key", &key)
+        .body(body.to_string())
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let client = state.http_client.read().await.clone();
+    let mut res = client.send_async(request).await?;
+    let status = res.status();
+    let text = res.text().await?;
+    if !status.is_success() {
+        return Err(AppError::from_status(status.as_u16(), format!("Gemini API error ({}): {}", status, text), None));
+    }
+
+    let json: serde_json::Value = serde_json::from_str(&text).map_err(|e| AppError::Provider { status: None, message: e.to_string() })?;
+    let transcript: String = json["candidates"][0]["content"]["parts"]
+        .as_array()
+        .map(|parts| parts.iter().filter_map(|p| p["text"].as_str()).collect())
+        .unwrap_or_default();
+    Ok(transcript.trim().to_string())
+}
+
+async fn transcribe_with_openai(state: &AppState, audio: &[u8], mime: &str, base_url: &str, api_key: &str, model: Option<String>) -> Result<String, AppError> {
+    let boundary = format!("----repo-prompt-{:x}", std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_nanos());
+    let model = model.unwrap_or_else(|| "whisper-1".to_string());
+
+    let mut body = Vec::with_capacity(audio.len() + 512);
+    body.extend_from_slice(format!("--{}\r\nContent-Disposition: form-data; name=\"model\"\r\n\r\n{}\r\n", boundary, model).as_bytes());
+    body.extend_from_slice(format!("--{}\r\nContent-Disposition: form-data; name=\"response_format\"\r\n\r\njson\r\n", boundary).as_bytes());
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"audio.{}\"\r\nContent-Type: {}\r\n\r\n",
+            boundary,
+            file_extension(mime),
+            mime
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(audio);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+
+    let url = format!("{}/audio/transcriptions", base_url.trim_end_matches('/'));
+    let mut builder = isahc::Request::post(url).header("Content-Type", format!("multipart/form-data; boundary={}", boundary));
+    if !api_key.is_empty() {
+        builder = builder.header("Authorization", format!("Bearer {}", api_key));
+    }
+    let request = builder.body(body).map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let client = state.http_client.read().await.clone();
+    let mut res = client.send_async(request).await?;
+    let status = res.status();
+    let text = res.text().await?;
+    if !status.is_success() {
+        return Err(AppError::from_status(status.as_u16(), format!("Transcription API error ({}): {}", status, text), None));
+    }
+    let json: serde_json::Value = serde_json::from_str(&text).map_err(|e| AppError::Provider { status: None, message: e.to_string() })?;
+    json["text"]
+        .as_str()
+        .map(|t| t.trim().to_string())
+        .ok_or_else(|| AppError::Provider { status: None, message: "No text field in transcription response".to_string() })
+}
+
+/// Transcribes a recorded question (base64 audio) into text for the normal ask pipeline.
+/// Uses Gemini by default, or an OpenAI-compatible `/audio/transcriptions` endpoint
+/// when `provider` is `"openai"`.
+#[tauri::command]
+pub async fn transcribe_audio(
+    state: State<'_, AppState>,
+    audio: String,
+    mime_type: String,
+    provider: Option<String>,
+    base_url: Option<String>,
+    api_key: Option<String>,
+    model: Option<String>,
+) -> Result<String, AppError> {
+    // MediaRecorder reports types such as "audio/webm;codecs=opus".
+    let mime = mime_type.split(';').next().unwrap_or_default().trim().to_lowercase();
+    if !SUPPORTED_AUDIO.contains(&mime.as_str()) {
+        return Err(AppError::InvalidInput(format!("Unsupported audio format: {}", mime_type)));
+    }
+    let raw = audio.split_once("base64,").map(|(_, d)| d).unwrap_or(&audio).trim();
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(raw)
+        .map_err(|e| AppError::InvalidInput(format!("Audio data is not valid base64: {}", e)))?;
+    if bytes.len() > MAX_AUDIO_BYTES {
+        return Err(AppError::InvalidInput(format!("Audio is too large ({} MB, limit {} MB)", bytes.len() / 1_048_576, MAX_AUDIO_BYTES / 1_048_576)));
+    }
+
+    let provider = provider.as_deref().unwrap_or("gemini");
+    state.policy.check_provider(provider)?;
+    match provider {
+        "gemini" => transcribe_with_gemini(&state, raw, &mime, model).await,
+        "openai" | "custom" => {
+            let base_url = base_url.unwrap_or_else(|| "https://api.openai.com/v1".to_string());
+            transcribe_with_openai(&state, &bytes, &mime, &base_url, &api_key.unwrap_or_default(), model).await
+        }
+        other => Err(AppError::InvalidInput(format!("Transcription is not supported for provider: {}", other))),
+    }
+}
@@ -0,0 +1,124 @@
+use crate::error::AppError;
+use crate::{log_status, retry, AppState};
+use isahc::prelude::*;
+use repo_prompt_core::usage::Usage;
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+/// Latest generally available data-plane version of the chat completions API.
+const DEFAULT_API_VERSION: &str = "2024-10-21";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AzureReply {
+    text: String,
+    finish_reason: Option<String>,
+    /// The reply stopped at `max_tokens`.
+    truncated: bool,
+    usage: Option<Usage>,
+}
+
+/// `https://<resource>.openai.azure.com` from the endpoint as copied from the portal, with
+/// or without scheme and trailing path.
+fn normalize_endpoint(endpoint: &str) -> Result<String, AppError> {
+    let endpoint = endpoint.trim().trim_end_matches('/');
+    let host = endpoint.strip_prefix("https://").unwrap_or(endpoint);
+    if host.starts_with("http://") {
+        return Err(AppError::InvalidInput("Azure OpenAI endpoints must use https".to_string()));
+    }
+    let host = host.split('/').next().unwrap_or_default();
+    if host.is_empty() || !host.contains('.') {
+        return Err(AppError::InvalidInput(format!("Invalid Azure OpenAI endpoint: {}", endpoint)));
+    }
+    Ok(format!("https://{}", host))
+}
+
+/// Chat completions URL of `deployment`; Azure routes by deployment, not model name.
+fn chat_url(endpoint: &str, deployment: &str, api_version: &str) -> Result<String, AppError> {
+    let valid = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c));
+    if !valid(deployment) {
+        return Err(AppError::InvalidInput(format!("Invalid deployment name: {}", deployment)));
+    }
+    if !valid(api_version) {
+        return Err(AppError::InvalidInput(format!("Invalid api-version: {}", api_version)));
+    }
+    Ok(format!("{}/openai/deployments/{}/chat/completions?api-version={}", normalize_endpoint(endpoint)?, deployment, api_version))
+}
+
+/// Sends a prompt to an Azure OpenAI deployment. `endpoint` is the resource endpoint
+/// (`https://<resource>.openai.azure.com`), `deployment` the deployment name chosen in
+/// the portal, and `api_version` defaults to the latest GA version. The key goes in the
+/// `api-key` header, as Azure expects, rather than as a bearer token.
+#[tauri::command(rename_all = "snake_case")]
+#[allow(clippy::too_many_arguments)]
+pub async fn call_azure_openai(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    prompt: String,
+    endpoint: String,
+    deployment: String,
+    api_key: String,
+    api_version: Option<String>,
+    system_prompt: Option<String>,
+    max_tokens: Option<u32>,
+    temperature: Option<f32>,
+) -> Result<AzureReply, AppError> {
+    if api_key.trim().is_empty() {
+        return Err(AppError::MissingCredentials("Azure OpenAI API key is missing".to_string()));
+    }
+    state.policy.check_provider("azure")?;
+    if let Some(t) = temperature.filter(|t| !(0.0..=2.0).contains(t)) {
+        return Err(AppError::InvalidInput(format!("temperature must be between 0 and 2, got {}", t)));
+    }
+    let deployment = deployment.trim().to_string();
+    let api_version = api_version.filter(|v| !v.trim().is_empty()).unwrap_or_else(|| DEFAULT_API_VERSION.to_string());
+    let url = chat_url(&endpoint, &deployment, api_version.trim())?;
+
+    let mut messages = Vec::new();
+    if let Some(system) = system_prompt.filter(|s| !s.trim().is_empty()) {
+        messages.push(serde_json::json!({ "role": "system", "content": state.policy.redact(&system) }));
+    }
+    messages.push(serde_json::json!({ "role": "user", "content": state.policy.redact(&prompt) }));
+    let mut body = serde_json::json!({ "messages": messages });
+    if let Some(n) = max_tokens {
+        body["max_tokens"] = n.into();
+    }
+    if let Some(t) = temperature {
+        body["temperature"] = t.into();
+    }
+    let body = body.to_string();
+    let key = api_key.trim().to_string();
+    let make = || {
+        isahc::Request::builder()
+            .method("POST")
+            .uri(&url)
+            .header("Content-Type", "application/json")
+            .header("api-key", &key)
+            .body(body.clone())
+    };
+
+    log_status(&app, format!("Sending prompt to Azure OpenAI ({})", deployment));
+    let client = state.http_client.read().await.clone();
+    let _span = state.trace.span("llm", "azure_chat").attr("deployment", &deployment);
+    let mut response = retry::send(&client, "Azure OpenAI", true, make).await?;
+    let status = response.status().as_u16();
+    let text = response.text().await?;
+    if !(200..300).contains(&status) {
+        log_status(&app, format!("Azure OpenAI request failed ({})", status));
+        let retry_after = response.headers().get("retry-after").and_then(|v| v.to_str().ok()).and_then(|v| v.trim().parse().ok());
+        return Err(AppError::from_status(status, format!("Azure OpenAI request failed ({}): {}", status, text), retry_after));
+    }
+    let data: serde_json::Value = serde_json::from_str(&text).map_err(|e| AppError::Provider { status: None, message: e.to_string() })?;
+    let usage = Usage::from_openai(&data);
+    state.usage.record("azure", &deployment, usage.as_ref());
+    let choice = &data["choices"][0];
+    let finish_reason = choice["finish_reason"].as_str().map(str::to_string);
+    if finish_reason.as_deref() == Some("content_filter") {
+        return Err(AppError::Provider { status: None, message: "Azure's content filter blocked the reply".to_string() });
+    }
+    let reply = choice["message"]["content"]
+        .as_str()
+        .ok_or_else(|| AppError::Provider { status: None, message: "The reply had no message content".to_string() })?;
+    log_status(&app, "Azure OpenAI response received");
+    Ok(AzureReply { text: reply.to_string(), truncated: finish_reason.as_deref() == Some("length"), finish_reason, usage })
+}
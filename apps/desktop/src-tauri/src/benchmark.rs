@@ -0,0 +1,124 @@
+use crate::error::AppError;
+use crate::{log_status, ollama, AppState};
+use isahc::prelude::*;
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+/// Same input for every model so the numbers are comparable.
+const BENCHMARK_PROMPT: &str = "Summarize the following module description in three bullet points.\n\n\
+The scanner walks a repository, skips dependency and build directories, reads every text file \
+under one megabyte and scores paths so that entry points, services and core modules are selected \
+first while tests, configuration and documentation are deprioritised. The selected files, the \
+README and the dependency manifests are then assembled into a single prompt together with a \
+directory tree, and the prompt is sent to either a hosted model or a local Ollama instance.";
+const BENCHMARK_MAX_TOKENS: u32 = 128;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelBenchmark {
+    model: String,
+    /// Time to load the model into memory from a cold start.
+    load_ms: u64,
+    prompt_tokens_per_sec: f64,
+    tokens_per_sec: f64,
+    total_ms: u64,
+    /// Memory used by the loaded model, and the part of it in VRAM.
+    memory_bytes: Option<u64>,
+    vram_bytes: Option<u64>,
+    error: Option<String>,
+}
+
+async fn post(state: &AppState, url: &str, path: &str, body: serde_json::Value) -> Result<serde_json::Value, String> {
+    let mut res = ollama::post_json(state, url, path, &body).await?;
+    let status = res.status();
+    let text = res.text().await.map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        return Err(format!("Ollama error ({}): {}", status, text));
+    }
+    serde_json::from_str(&text).map_err(|e| e.to_string())
+}
+
+fn per_sec(count: &serde_json::Value, duration_ns: &serde_json::Value) -> f64 {
+    match (count.as_f64(), duration_ns.as_f64()) {
+        (Some(c), Some(d)) if d > 0.0 => c / (d / 1e9),
+        _ => 0.0,
+    }
+}
+
+async fn run_one(state: &AppState, url: &str, model: &str) -> Result<ModelBenchmark, String> {
+    // Start from a cold model so load time is measured, not a leftover warm instance.
+    let _ = ollama::unload(state, url, model).await;
+    let body = serde_json::json!({
+        "model": model,
+        "prompt": BENCHMARK_PROMPT,
+        "stream": false,
+        "keep_alive": "1m",
+        "options": { "num_predict": BENCHMARK_MAX_TOKENS, "temperature": 0, "seed": 42 }
+    });
+    let data = post(state, url, "/api/generate", body).await?;
+
+    let ps = ollama::get(state, url, "/api/ps").await.ok();
+    let loaded = match ps {
+        Some(mut res) => res.text().await.ok().and_then(|t| serde_json::from_str::<serde_json::Value>(&t).ok()),
+        None => None,
+    };
+    let entry = loaded
+        .as_ref()
+        .and_then(|l| l["models"].as_array())
+        .and_then(|models| models.iter().find(|m| m["name"].as_str() == Some(model) || m["model"].as_str() == Some(model)));
+
+    let _ = ollama::unload(state, url, model).await;
+    Ok(ModelBenchmark {
+        model: model.to_string(),
+        load_ms: data["load_duration"].as_u64().unwrap_or_default() / 1_000_000,
+        prompt_tokens_per_sec: per_sec(&data["prompt_eval_count"], &data["prompt_eval_duration"]),
+        tokens_per_sec: per_sec(&data["eval_count"], &data["eval_duration"]),
+        total_ms: data["total_duration"].as_u64().unwrap_or_default() / 1_000_000,
+        memory_bytes: entry.and_then(|e| e["size"].as_u64()),
+        vram_bytes: entry.and_then(|e| e["size_vram"].as_u64()),
+        error: None,
+    })
+}
+
+/// Runs a fixed summarization task on each model (all installed models by default), one
+/// at a time, and reports load time, throughput and memory use.
+#[tauri::command]
+pub async fn benchmark_local_models(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    url: String,
+    models: Option<Vec<String>>,
+) -> Result<Vec<ModelBenchmark>, AppError> {
+    let models = match models.filter(|m| !m.is_empty()) {
+        Some(m) => m,
+        None => {
+            let mut res = ollama::get(&state, &url, "/api/tags").await?;
+            let data: serde_json::Value = serde_json::from_str(&res.text().await.map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
+            data["models"]
+                .as_array()
+                .map(|a| a.iter().filter_map(|m| m["name"].as_str().map(|s| s.to_string())).collect())
+                .unwrap_or_default()
+        }
+    };
+    if models.is_empty() {
+        return Err(AppError::NotFound("No Ollama models installed".to_string()));
+    }
+
+    let mut results = Vec::with_capacity(models.len());
+    for (i, model) in models.iter().enumerate() {
+        log_status(&app, format!("Benchmarking {} ({}/{})", model, i + 1, models.len()));
+        let result = run_one(&state, &url, model).await.unwrap_or_else(|e| ModelBenchmark {
+            model: model.clone(),
+            load_ms: 0,
+            prompt_tokens_per_sec: 0.0,
+            tokens_per_sec: 0.0,
+            total_ms: 0,
+            memory_bytes: None,
+            vram_bytes: None,
+            error: Some(e),
+        });
+        results.push(result);
+    }
+    log_status(&app, "Benchmark finished");
+    Ok(results)
+}
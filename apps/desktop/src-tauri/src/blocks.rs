@@ -0,0 +1,153 @@
+use crate::error::AppError;
+use crate::tokens::estimate_tokens;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+const BLOCKS_FILE: &str = "context_blocks.json";
+
+/// A reusable piece of context ("our coding standards", "API conventions") that can be
+/// included by id in any prompt.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextBlock {
+    id: String,
+    name: String,
+    content: String,
+    tokens: usize,
+    /// Unix seconds.
+    updated_at: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTokens {
+    id: String,
+    name: String,
+    tokens: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComposedContext {
+    /// The blocks in the requested order, as one prompt section.
+    packed: String,
+    blocks: Vec<BlockTokens>,
+    total_tokens: usize,
+    /// Requested ids that no longer exist.
+    missing: Vec<String>,
+}
+
+fn blocks_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| format!("Failed to resolve data dir: {}", e))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+    Ok(dir.join(BLOCKS_FILE))
+}
+
+fn load_blocks(app: &AppHandle) -> Result<Vec<ContextBlock>, String> {
+    match fs::read_to_string(blocks_path(app)?) {
+        Ok(text) => serde_json::from_str(&text).map_err(|e| format!("Context blocks file is corrupt: {}", e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to read context blocks: {}", e)),
+    }
+}
+
+/// Writes to a temporary file first so a crash mid-write can't lose the saved blocks.
+fn store_blocks(app: &AppHandle, blocks: &[ContextBlock]) -> Result<(), String> {
+    let path = blocks_path(app)?;
+    let tmp = path.with_extension("json.tmp");
+    let text = serde_json::to_string_pretty(blocks).map_err(|e| e.to_string())?;
+    fs::write(&tmp, text).map_err(|e| format!("Failed to save context blocks: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to save context blocks: {}", e))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+/// Id derived from the name, made unique among `existing`.
+fn new_id(name: &str, existing: &[ContextBlock]) -> String {
+    let slug: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let base = if slug.is_empty() { "block".to_string() } else { slug };
+    let mut id = base.clone();
+    let mut n = 2;
+    while existing.iter().any(|b| b.id == id) {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+    id
+}
+
+#[tauri::command]
+pub fn list_context_blocks(app: AppHandle) -> Result<Vec<ContextBlock>, AppError> {
+    Ok(load_blocks(&app)?)
+}
+
+/// Creates a block, or replaces the one with `id`.
+#[tauri::command]
+pub fn save_context_block(app: AppHandle, id: Option<String>, name: String, content: String) -> Result<ContextBlock, AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("A context block needs a name".to_string()));
+    }
+    let mut blocks = load_blocks(&app)?;
+    let block = ContextBlock {
+        id: match id {
+            Some(id) if blocks.iter().any(|b| b.id == id) => id,
+            Some(id) => return Err(AppError::NotFound(format!("Context block not found: {}", id))),
+            None => new_id(&name, &blocks),
+        },
+        tokens: estimate_tokens(&content),
+        name,
+        content,
+        updated_at: now_secs(),
+    };
+    match blocks.iter_mut().find(|b| b.id == block.id) {
+        Some(existing) => *existing = block.clone(),
+        None => blocks.push(block.clone()),
+    }
+    store_blocks(&app, &blocks)?;
+    Ok(block)
+}
+
+#[tauri::command]
+pub fn delete_context_block(app: AppHandle, id: String) -> Result<(), AppError> {
+    let mut blocks = load_blocks(&app)?;
+    let before = blocks.len();
+    blocks.retain(|b| b.id != id);
+    if blocks.len() == before {
+        return Err(AppError::NotFound(format!("Context block not found: {}", id)));
+    }
+    Ok(store_blocks(&app, &blocks)?)
+}
+
+/// Resolves the referenced blocks, in order, into one section ready to add to a prompt,
+/// with the token cost of each.
+#[tauri::command]
+pub fn compose_context_blocks(app: AppHandle, ids: Vec<String>) -> Result<ComposedContext, AppError> {
+    let saved = load_blocks(&app)?;
+    let mut packed = String::new();
+    let mut blocks = Vec::new();
+    let mut missing = Vec::new();
+    for id in ids {
+        match saved.iter().find(|b| b.id == id) {
+            Some(block) => {
+                packed.push_str(&format!("## {}\n{}\n\n", block.name, block.content.trim_end()));
+                blocks.push(BlockTokens { id: block.id.clone(), name: block.name.clone(), tokens: block.tokens });
+            }
+            None => missing.push(id),
+        }
+    }
+    let total_tokens = estimate_tokens(&packed);
+    Ok(ComposedContext { packed, blocks, total_tokens, missing })
+}
@@ -0,0 +1,209 @@
+use crate::error::AppError;
+use crate::export::write_atomic;
+use crate::AppState;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Manager, State};
+
+pub const DEFAULT_COMPRESSION_LEVEL: i32 = 3;
+
+const BLOB_DIR: &str = "blobs";
+const REF_EXT: &str = "ref";
+
+fn cache_root(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_cache_dir()
+        .map_err(|e| format!("Failed to resolve cache dir: {}", e))
+}
+
+/// Content-addressed store of zstd-compressed blobs, keyed by the blake3 hash of the
+/// uncompressed content. Identical files fetched from different repos or branches are
+/// stored once.
+pub struct BlobStore {
+    root: PathBuf,
+    level: i32,
+}
+
+impl BlobStore {
+    pub fn open(cache_root: &Path, level: i32) -> Result<Self, String> {
+        let root = cache_root.join(BLOB_DIR);
+        fs::create_dir_all(&root).map_err(|e| format!("Failed to create blob dir: {}", e))?;
+        Ok(Self { root, level })
+    }
+
+    fn blob_path(&self, hash: &str) -> PathBuf {
+        self.root.join(&hash[..2]).join(format!("{}.zst", &hash[2..]))
+    }
+
+    /// Stores `data` (if not already present) and returns its hash.
+    pub fn put(&self, data: &[u8]) -> Result<String, String> {
+        let hash = blake3::hash(data).to_hex().to_string();
+        let path = self.blob_path(&hash);
+        if !path.exists() {
+            let compressed = zstd::encode_all(data, self.level).map_err(|e| format!("Compression failed: {}", e))?;
+            fs::create_dir_all(path.parent().unwrap()).map_err(|e| format!("Failed to create blob dir: {}", e))?;
+            write_atomic(&path, None, &compressed).map_err(String::from)?;
+        }
+        Ok(hash)
+    }
+
+    pub fn get(&self, hash: &str) -> Option<Vec<u8>> {
+        if hash.len() < 3 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let compressed = fs::read(self.blob_path(hash)).ok()?;
+        zstd::decode_all(compressed.as_slice()).ok()
+    }
+
+    /// Deletes every blob whose hash is not in `live`. Returns (blobs removed, bytes freed).
+    fn sweep(&self, live: &HashSet<String>) -> (usize, u64) {
+        let mut removed = 0;
+        let mut freed = 0;
+        for entry in walkdir::WalkDir::new(&self.root).into_iter().filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let path = entry.path();
+            let prefix = path.parent().and_then(|p| p.file_name()).map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let stem = path.file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            if live.contains(&format!("{}{}", prefix, stem)) {
+                continue;
+            }
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            if fs::remove_file(path).is_ok() {
+                removed += 1;
+                freed += size;
+            }
+        }
+        (removed, freed)
+    }
+}
+
+/// A namespace of named entries (URL, path, snapshot name, ...) that point into the
+/// shared [`BlobStore`].
+pub struct DiskCache {
+    refs: PathBuf,
+    blobs: BlobStore,
+}
+
+fn key_file_name(key: &str) -> String {
+    format!("{}.{}", blake3::hash(key.as_bytes()).to_hex(), REF_EXT)
+}
+
+impl DiskCache {
+    pub fn open(app: &AppHandle, namespace: &str, level: i32) -> Result<Self, String> {
+        let root = cache_root(app)?;
+        let refs = root.join(namespace);
+        fs::create_dir_all(&refs).map_err(|e| format!("Failed to create cache dir: {}", e))?;
+        Ok(Self { refs, blobs: BlobStore::open(&root, level)? })
+    }
+
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let hash = fs::read_to_string(self.refs.join(key_file_name(key))).ok()?;
+        self.blobs.get(hash.trim())
+    }
+
+    /// Stores `data` under `key` and returns its content hash.
+    pub fn put(&self, key: &str, data: &[u8]) -> Result<String, String> {
+        let hash = self.blobs.put(data)?;
+        write_atomic(&self.refs.join(key_file_name(key)), None, hash.as_bytes()).map_err(String::from)?;
+        Ok(hash)
+    }
+
+    /// Forgets `key`. Its blob stays until the next garbage collection.
+    pub fn remove(&self, key: &str) -> bool {
+        fs::remove_file(self.refs.join(key_file_name(key))).is_ok()
+    }
+}
+
+/// Opens a cache namespace using the compression level from the current settings.
+pub fn open_cache(app: &AppHandle, state: &AppState, namespace: &str) -> Result<DiskCache, String> {
+    DiskCache::open(app, namespace, state.cache_compression_level.load(Ordering::Relaxed))
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GcReport {
+    blobs_removed: usize,
+    bytes_freed: u64,
+    live_blobs: usize,
+}
+
+/// Deletes the blobs no namespace refers to. Blocking; run it off the async runtime.
+pub fn collect_garbage(app: &AppHandle, level: i32) -> Result<GcReport, String> {
+    let root = cache_root(app)?;
+    let blobs = BlobStore::open(&root, level)?;
+    let mut live = HashSet::new();
+    let ref_files = walkdir::WalkDir::new(&root)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != BLOB_DIR)
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|x| x == REF_EXT));
+    for entry in ref_files {
+        if let Ok(hash) = fs::read_to_string(entry.path()) {
+            live.insert(hash.trim().to_string());
+        }
+    }
+    let (blobs_removed, bytes_freed) = blobs.sweep(&live);
+    Ok(GcReport { blobs_removed, bytes_freed, live_blobs: live.len() })
+}
+
+pub struct CacheUsage {
+    /// Entries per namespace.
+    pub namespaces: Vec<(String, usize)>,
+    pub blob_count: usize,
+    pub blob_bytes: u64,
+}
+
+pub fn usage(app: &AppHandle) -> Result<CacheUsage, String> {
+    let root = cache_root(app)?;
+    let mut namespaces = Vec::new();
+    let (mut blob_count, mut blob_bytes) = (0, 0);
+    for entry in fs::read_dir(&root).into_iter().flatten().filter_map(|e| e.ok()).filter(|e| e.path().is_dir()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let files = walkdir::WalkDir::new(entry.path()).into_iter().filter_map(|e| e.ok()).filter(|e| e.file_type().is_file());
+        if name == BLOB_DIR {
+            for file in files {
+                blob_count += 1;
+                blob_bytes += file.metadata().map(|m| m.len()).unwrap_or(0);
+            }
+        } else {
+            namespaces.push((name, files.filter(|e| e.path().extension().is_some_and(|x| x == REF_EXT)).count()));
+        }
+    }
+    namespaces.sort();
+    Ok(CacheUsage { namespaces, blob_count, blob_bytes })
+}
+
+/// Drops every entry of a namespace. Blobs stay until the next garbage collection.
+pub fn clear_namespace(app: &AppHandle, namespace: &str) -> Result<(), String> {
+    match fs::remove_dir_all(cache_root(app)?.join(namespace)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("Failed to clear the {} cache: {}", namespace, e)),
+        _ => Ok(()),
+    }
+}
+
+/// Removes blobs that are no longer referenced by any cache namespace.
+#[tauri::command]
+pub async fn cache_gc(app: AppHandle, state: State<'_, AppState>) -> Result<GcReport, AppError> {
+    let level = state.cache_compression_level.load(Ordering::Relaxed);
+    Ok(tokio::task::spawn_blocking(move || collect_garbage(&app, level)).await??)
+}
+
+/// Saves an assembled context under `name`. Returns the snapshot's content hash.
+#[tauri::command]
+pub async fn save_context_snapshot(app: AppHandle, state: State<'_, AppState>, name: String, content: String) -> Result<String, AppError> {
+    let cache = open_cache(&app, &state, "snapshots")?;
+    Ok(tokio::task::spawn_blocking(move || cache.put(&name, content.as_bytes())).await??)
+}
+
+#[tauri::command]
+pub async fn load_context_snapshot(app: AppHandle, state: State<'_, AppState>, name: String) -> Result<String, AppError> {
+    let cache = open_cache(&app, &state, "snapshots")?;
+    let bytes = tokio::task::spawn_blocking(move || cache.get(&name))
+        .await?
+        .ok_or_else(|| AppError::NotFound("Snapshot not found".to_string()))?;
+    String::from_utf8(bytes).map_err(|e| AppError::Io(format!("Snapshot is not valid UTF-8: {}", e)))
+}
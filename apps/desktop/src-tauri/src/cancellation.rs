@@ -0,0 +1,93 @@
+use crate::error::AppError;
+use crate::progress::sanitize_id;
+use crate::AppState;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::State;
+use tokio::sync::Notify;
+
+#[derive(Default)]
+struct Flag {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+/// Cancellation of one operation; clones share it.
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<Flag>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::SeqCst);
+        self.0.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once the operation is cancelled, right away if it already was.
+    pub async fn cancelled(&self) {
+        let notified = self.0.notify.notified();
+        tokio::pin!(notified);
+        // Registered before the flag is checked, so a cancel in between isn't missed.
+        notified.as_mut().enable();
+        if !self.is_cancelled() {
+            notified.await;
+        }
+    }
+}
+
+/// Cancellable operations in flight, by operation ID as it appears in progress events.
+#[derive(Default)]
+pub struct Cancellations {
+    operations: Mutex<HashMap<String, CancelToken>>,
+    counter: AtomicU64,
+}
+
+impl Cancellations {
+    /// An operation ID like `github-fetch-3`, for callers that didn't choose one.
+    pub fn new_id(&self, kind: &str) -> String {
+        format!("{}-{}", kind, self.counter.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    /// Makes `id` cancellable until [`Cancellations::finish`]; an operation already
+    /// registered under the same ID is cancelled.
+    pub fn register(&self, id: &str) -> CancelToken {
+        let token = CancelToken::default();
+        if let Some(previous) = self.operations.lock().unwrap().insert(sanitize_id(id.trim()), token.clone()) {
+            previous.cancel();
+        }
+        token
+    }
+
+    pub fn finish(&self, id: &str, token: &CancelToken) {
+        let id = sanitize_id(id.trim());
+        let mut operations = self.operations.lock().unwrap();
+        // A newer operation may have taken over the ID.
+        if operations.get(&id).is_some_and(|t| Arc::ptr_eq(&t.0, &token.0)) {
+            operations.remove(&id);
+        }
+    }
+
+    /// Cancels `id`; false when no such operation is running.
+    pub fn cancel(&self, id: &str) -> bool {
+        match self.operations.lock().unwrap().remove(&sanitize_id(id.trim())) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Cancels the `fetch_github_repo` (or `finish_github_device_flow`) running under
+/// `operation_id` (the one given to it, or the ID in its progress events). Requests in
+/// flight are aborted and it fails with a `cancelled` error. Returns false when no such
+/// operation is running.
+#[tauri::command]
+pub fn cancel_fetch(state: State<'_, AppState>, operation_id: String) -> Result<bool, AppError> {
+    Ok(state.cancellations.cancel(&operation_id))
+}
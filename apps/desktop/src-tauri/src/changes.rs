@@ -0,0 +1,181 @@
+use crate::error::AppError;
+use crate::review::ChangedFile;
+use crate::{github, log_status, policy, AppState, FileEntry};
+use git2::{Delta, DiffFindOptions, DiffOptions, Patch, Repository, RepositoryOpenFlags};
+use repo_prompt_core::ignore::IgnoreRules;
+use repo_prompt_core::scan::is_skipped_name;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+
+/// Changed files over this size are listed without their contents, like in a scan.
+const MAX_FILE_BYTES: u64 = 1_000_000;
+/// Patches longer than this are replaced by a note so one generated file can't swamp the prompt.
+const MAX_PATCH_BYTES: usize = 200_000;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalChanges {
+    /// Working tree root of the repository.
+    root: String,
+    /// Checked-out branch; `None` on a detached HEAD.
+    branch: Option<String>,
+    /// `HEAD`, or the base ref the changes are compared with.
+    base: String,
+    /// Commit the diff starts from (the merge base with `base`); `None` before the first commit.
+    base_sha: Option<String>,
+    changed_files: Vec<ChangedFile>,
+    diff: String,
+    /// Working tree contents of the changed files that still exist.
+    source_files: Vec<FileEntry>,
+    /// Only the diff and the touched files, packed for a "review my changes" prompt.
+    packed: String,
+}
+
+fn status_name(status: Delta) -> &'static str {
+    match status {
+        Delta::Added | Delta::Untracked => "added",
+        Delta::Deleted => "removed",
+        Delta::Renamed => "renamed",
+        Delta::Copied => "copied",
+        Delta::Typechange => "changed",
+        _ => "modified",
+    }
+}
+
+fn read_source(path: &Path) -> Option<String> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if !metadata.is_file() || metadata.len() > MAX_FILE_BYTES {
+        return None;
+    }
+    String::from_utf8(std::fs::read(path).ok()?).ok()
+}
+
+/// Diffs the working tree (staged, unstaged and untracked changes) against HEAD, or against
+/// the merge base of HEAD and `base_ref`, limited to `scope` when the path given was a
+/// subdirectory of the repository. The search for the repository stops at `ceiling`.
+fn collect(path: &Path, base_ref: Option<&str>, user_ignore: &[String], ceiling: Option<&Path>) -> Result<LocalChanges, String> {
+    let start = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let repo = Repository::open_ext(&start, RepositoryOpenFlags::empty(), ceiling).map_err(|e| format!("{} is not inside a git repository: {}", path.display(), e.message()))?;
+    let root = repo.workdir().ok_or("Bare repositories have no working tree to diff")?.to_path_buf();
+    let root = root.canonicalize().unwrap_or(root);
+    let scope = path.canonicalize().ok().and_then(|p| p.strip_prefix(&root).ok().map(Path::to_path_buf)).filter(|p| !p.as_os_str().is_empty());
+
+    let head = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let branch = repo.head().ok().filter(|h| h.is_branch()).and_then(|h| h.shorthand().map(str::to_string));
+    let base_commit = match base_ref {
+        Some(base) => {
+            let base = repo
+                .revparse_single(base)
+                .and_then(|obj| obj.peel_to_commit())
+                .map_err(|e| format!("Unknown base ref '{}': {}", base, e.message()))?;
+            match &head {
+                Some(head) => {
+                    let merge_base = repo.merge_base(base.id(), head.id()).map_err(|e| format!("No common history with '{}': {}", base_ref.unwrap_or_default(), e.message()))?;
+                    Some(repo.find_commit(merge_base).map_err(|e| e.message().to_string())?)
+                }
+                None => Some(base),
+            }
+        }
+        None => head,
+    };
+    let base_tree = base_commit.as_ref().map(|c| c.tree()).transpose().map_err(|e| e.message().to_string())?;
+
+    let mut options = DiffOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true).show_untracked_content(true);
+    if let Some(scope) = &scope {
+        options.pathspec(scope.to_string_lossy().replace('\\', "/"));
+    }
+    let mut diff = repo.diff_tree_to_workdir_with_index(base_tree.as_ref(), Some(&mut options)).map_err(|e| e.message().to_string())?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true).for_untracked(true))).map_err(|e| e.message().to_string())?;
+
+    let ignore = IgnoreRules::for_directory(&root, user_ignore);
+    let mut changed_files = Vec::new();
+    let mut text = String::new();
+    let mut source_files = Vec::new();
+    for (i, delta) in diff.deltas().enumerate() {
+        let file_path = |f: git2::DiffFile| f.path().map(|p| p.to_string_lossy().replace('\\', "/"));
+        let Some(new_path) = file_path(delta.new_file()).or_else(|| file_path(delta.old_file())) else { continue };
+        if new_path.split('/').any(is_skipped_name) || ignore.is_ignored(&new_path, false) {
+            continue;
+        }
+        let old_path = file_path(delta.old_file()).unwrap_or_else(|| new_path.clone());
+        let mut patch = Patch::from_diff(&diff, i).map_err(|e| e.message().to_string())?;
+        let (_, additions, deletions) = patch.as_ref().and_then(|p| p.line_stats().ok()).unwrap_or_default();
+        let patch_text = patch.as_mut().and_then(|p| p.to_buf().ok()).and_then(|b| b.as_str().map(str::to_string));
+        match patch_text {
+            Some(p) if p.len() <= MAX_PATCH_BYTES => text.push_str(&p),
+            Some(_) => text.push_str(&format!("diff --git a/{} b/{}\n(diff too large)\n", old_path, new_path)),
+            None => text.push_str(&format!("diff --git a/{} b/{}\n(diff not available)\n", old_path, new_path)),
+        }
+        if delta.status() != Delta::Deleted {
+            if let Some(content) = read_source(&root.join(&new_path)) {
+                source_files.push(FileEntry::new(new_path.clone(), content));
+            }
+        }
+        changed_files.push(ChangedFile {
+            status: status_name(delta.status()).to_string(),
+            additions: additions as u64,
+            deletions: deletions as u64,
+            previous_path: (old_path != new_path).then_some(old_path),
+            path: new_path,
+        });
+    }
+
+    Ok(LocalChanges {
+        root: root.to_string_lossy().to_string(),
+        branch,
+        base: base_ref.unwrap_or("HEAD").to_string(),
+        base_sha: base_commit.map(|c| c.id().to_string()),
+        changed_files,
+        diff: text,
+        source_files,
+        packed: String::new(),
+    })
+}
+
+fn pack_changes(c: &LocalChanges) -> String {
+    let against = match &c.base_sha {
+        Some(sha) if c.base == "HEAD" => format!("the last commit ({})", &sha[..7]),
+        Some(sha) => format!("{} (merge base {})", c.base, &sha[..7]),
+        None => "an empty repository".to_string(),
+    };
+    let mut out = format!("# Local changes{}\n\nCompared with {}; {} files changed\n\n## Changed files\n\n", c.branch.as_ref().map(|b| format!(" on {}", b)).unwrap_or_default(), against, c.changed_files.len());
+    for f in &c.changed_files {
+        out.push_str(&format!("- {} ({}, +{} -{})\n", f.path, f.status, f.additions, f.deletions));
+    }
+    out.push_str(&format!("\n## Diff\n\n```diff\n{}```\n\n## Touched files in the working tree\n", c.diff));
+    for f in &c.source_files {
+        out.push_str(&format!("\n--- {} ---\n{}\n", f.path, f.content));
+    }
+    out
+}
+
+/// Packs only what changed in the local repository at `path`: staged, unstaged and
+/// untracked changes against HEAD, or everything since the branch left `base_ref` (e.g.
+/// `main`) when given. Returns the changed files, the unified diff and the touched files'
+/// current contents, packed for a "review/describe my changes" prompt. A `path` below the
+/// repository root limits the changes to that directory.
+#[tauri::command]
+pub async fn scan_local_changes(app: AppHandle, state: State<'_, AppState>, path: String, base_ref: Option<String>) -> Result<LocalChanges, AppError> {
+    let base_ref = base_ref.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+    if let Some(r) = &base_ref {
+        github::validate_ref(r).map_err(AppError::InvalidInput)?;
+    }
+    let dir = PathBuf::from(&path);
+    if !dir.is_dir() {
+        return Err(AppError::NotFound(format!("Directory not found: {}", dir.display())));
+    }
+    state.policy.check_scan_path(&app, &dir)?;
+
+    log_status(&app, format!("Collecting changes in {} against {}", path, base_ref.as_deref().unwrap_or("HEAD")));
+    let span = state.trace.span("scan", "local_changes").attr("root", dir.display());
+    let user_ignore = state.ignore_patterns.read().await.clone();
+    // In demo mode a repository above the sample one must not be opened instead.
+    let ceiling = if state.policy.is_demo() { policy::demo_sample_dir(&app).and_then(|d| d.parent().map(Path::to_path_buf)) } else { None };
+    let mut changes = tokio::task::spawn_blocking(move || collect(&dir, base_ref.as_deref(), &user_ignore, ceiling.as_deref())).await?.map_err(AppError::InvalidInput)?;
+    changes.packed = state.policy.redact(&pack_changes(&changes));
+    span.attr("files", changes.changed_files.len()).end();
+    log_status(&app, format!("{} changed files collected", changes.changed_files.len()));
+    Ok(changes)
+}
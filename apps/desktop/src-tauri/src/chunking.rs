@@ -0,0 +1,17 @@
+use crate::error::AppError;
+use crate::FileEntry;
+
+pub use repo_prompt_core::chunking::{chunk_file, Chunk};
+
+const DEFAULT_MAX_TOKENS: usize = 512;
+const DEFAULT_OVERLAP: usize = 32;
+
+/// Splits files into chunks of at most `max_tokens` (default 512) with `overlap`
+/// (default 32) tokens repeated between neighbours, preferring function and class
+/// boundaries for languages with a bundled grammar.
+#[tauri::command]
+pub async fn chunk_files(entries: Vec<FileEntry>, max_tokens: Option<usize>, overlap: Option<usize>) -> Result<Vec<Chunk>, AppError> {
+    let max_tokens = max_tokens.unwrap_or(DEFAULT_MAX_TOKENS);
+    let overlap = overlap.unwrap_or(DEFAULT_OVERLAP);
+    Ok(tokio::task::spawn_blocking(move || entries.iter().flat_map(|f| chunk_file(f, max_tokens, overlap)).collect()).await?)
+}
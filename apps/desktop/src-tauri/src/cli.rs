@@ -0,0 +1,242 @@
+use crate::github::GithubClient;
+use crate::policy::OrgPolicy;
+use crate::{export, network, paths, FileEntry};
+use repo_prompt_core::pack::{self, PackFormat, PackOptions};
+use repo_prompt_core::ranking::ScoringRules;
+use repo_prompt_core::repourl::{parse_repo_url, RepoUrl};
+use repo_prompt_core::ignore::{IgnoreRules, IGNORE_FILE};
+use repo_prompt_core::scan::{normalize_subpath, read_directory_ignoring};
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "Usage: repo-prompt-generator pack <path|owner/repo> [options]
+
+Packs a local directory or a GitHub repository into one prompt.
+
+Options:
+  --format <md|xml|txt>   Output format (default: md)
+  --budget <tokens>       Keep the most relevant files within this many tokens, e.g. 100k or 1.5m
+  -o, --output <file>     Write to a file instead of standard output
+  --ref <ref>             Branch, tag or commit to fetch (GitHub only; default: the default branch)
+  --subpath <dir>         Only pack this directory of the repository
+  --token <token>         GitHub token (default: the GITHUB_TOKEN environment variable)
+  --keep-duplicates       Include every copy of identical files (default: include one)
+  --keep-vendored         Include vendored, generated and minified files (default: leave out)
+  --scoring <file>        JSON file of ranking rules deciding which files fit the budget
+                          (nameKeywords, pathKeywords, globs, depthPenalty)
+
+Normalization (off by default; applied before packing):
+  --lf                    Convert CRLF and CR line endings to LF
+  --trim-trailing         Strip trailing whitespace from every line
+  --max-blank-lines <n>   Collapse longer runs of blank lines to n
+  --tab-width <n>         Expand tabs to spaces
+  --max-line-length <n>   Cut longer lines (minified code) with an elision marker
+";
+
+struct PackArgs {
+    source: String,
+    output: Option<PathBuf>,
+    git_ref: Option<String>,
+    subpath: Option<String>,
+    token: String,
+    pack: PackOptions,
+}
+
+/// Parses token counts such as `100k`, `1.5m` or `20000`.
+fn parse_budget(raw: &str) -> Result<usize, String> {
+    let lower = raw.trim().to_lowercase();
+    let (number, factor) = match lower.strip_suffix('k') {
+        Some(n) => (n, 1_000.0),
+        None => match lower.strip_suffix('m') {
+            Some(n) => (n, 1_000_000.0),
+            None => (lower.as_str(), 1.0),
+        },
+    };
+    match number.trim().parse::<f64>() {
+        Ok(n) if n > 0.0 => Ok((n * factor) as usize),
+        _ => Err(format!("Invalid budget: {}", raw)),
+    }
+}
+
+fn parse_count(flag: &str, raw: &str) -> Result<usize, String> {
+    raw.trim().parse().map_err(|_| format!("{} needs a number, got {}", flag, raw))
+}
+
+fn parse_pack_args(args: &[String]) -> Result<PackArgs, String> {
+    let mut parsed = PackArgs {
+        source: String::new(),
+        output: None,
+        git_ref: None,
+        subpath: None,
+        token: std::env::var("GITHUB_TOKEN").unwrap_or_default(),
+        pack: PackOptions::default(),
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().cloned().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--format" | "-f" => parsed.pack.format = PackFormat::parse(&value()?)?,
+            "--budget" | "-b" => parsed.pack.budget = Some(parse_budget(&value()?)?),
+            "--output" | "-o" => parsed.output = Some(PathBuf::from(value()?)),
+            "--ref" => parsed.git_ref = Some(value()?),
+            "--subpath" => parsed.subpath = normalize_subpath(Some(value()?))?,
+            "--token" => parsed.token = value()?,
+            "--keep-duplicates" => parsed.pack.keep_duplicates = true,
+            "--keep-vendored" => parsed.pack.keep_vendored = true,
+            "--scoring" => parsed.pack.scoring = read_scoring(&value()?)?,
+            "--lf" => parsed.pack.normalization.line_endings = true,
+            "--trim-trailing" => parsed.pack.normalization.trim_trailing_whitespace = true,
+            "--max-blank-lines" => parsed.pack.normalization.max_blank_lines = Some(parse_count(arg, &value()?)?),
+            "--tab-width" => parsed.pack.normalization.tab_width = Some(parse_count(arg, &value()?)?),
+            "--max-line-length" => parsed.pack.normalization.max_line_chars = Some(parse_count(arg, &value()?)?),
+            flag if flag.starts_with('-') => return Err(format!("Unknown option: {}", flag)),
+            source if parsed.source.is_empty() => parsed.source = source.to_string(),
+            extra => return Err(format!("Unexpected argument: {}", extra)),
+        }
+    }
+    if parsed.source.is_empty() {
+        return Err("Give a directory or an owner/repo to pack".to_string());
+    }
+    Ok(parsed)
+}
+
+/// Ranking rules from a JSON file; fields left out keep their defaults.
+fn read_scoring(path: &str) -> Result<ScoringRules, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let rules: ScoringRules = serde_json::from_str(&text).map_err(|e| format!("Invalid scoring rules in {}: {}", path, e))?;
+    rules.validate()?;
+    Ok(rules)
+}
+
+/// `owner/repo`, or a github.com URL of one (see [`parse_repo_url`]).
+fn github_repo(source: &str) -> Option<RepoUrl> {
+    parse_repo_url(source).ok().filter(|url| url.host == "github.com")
+}
+
+/// Files of a local directory, with paths relative to it, minus those its
+/// `.repopromptignore` leaves out.
+async fn scan_local(root: &Path, subpath: Option<&str>) -> Result<(String, Vec<FileEntry>), String> {
+    let dir = subpath.map_or_else(|| root.to_path_buf(), |sub| root.join(sub));
+    if !dir.is_dir() {
+        return Err(format!("Directory not found: {}", dir.display()));
+    }
+    let ignore = IgnoreRules::for_directory(root, &[]);
+    let mut files = read_directory_ignoring(dir, root, &ignore, |_, _| {}).await;
+    paths::make_relative(&mut files, root);
+    let name = root.canonicalize().ok().and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string())).unwrap_or_else(|| root.display().to_string());
+    Ok((name, files))
+}
+
+/// Files of a GitHub repository at `git_ref` (default branch when `None`), downloaded as
+/// one tarball, minus those its `.repopromptignore` leaves out. Returns the commit SHA too.
+async fn fetch_github(owner: &str, repo: &str, args: &PackArgs) -> Result<(String, Vec<FileEntry>), String> {
+    let client = network::build_client(&network::NetworkConfig::default())?;
+    let gh = GithubClient::new(client, args.token.clone());
+    let git_ref = match &args.git_ref {
+        Some(r) => r.clone(),
+        None => {
+            let info = gh.get_json(&format!("/repos/{}/{}", owner, repo)).await?;
+            info["default_branch"].as_str().unwrap_or("main").to_string()
+        }
+    };
+    let sha = gh.resolve_ref(owner, repo, &git_ref).await?;
+    eprintln!("Downloading {}/{} at {} ({})", owner, repo, git_ref, &sha[..7.min(sha.len())]);
+
+    let work_dir = std::env::temp_dir().join(format!("repo-prompt-cli-{}", std::process::id()));
+    std::fs::create_dir_all(&work_dir).map_err(|e| format!("Failed to create temp dir: {}", e))?;
+    let contents = gh.fetch_tarball(owner, repo, &sha, &work_dir).await;
+    let _ = std::fs::remove_dir_all(&work_dir);
+    let contents = contents?;
+
+    let prefix = args.subpath.as_ref().map(|s| format!("{}/", s)).unwrap_or_default();
+    let ignore = IgnoreRules::parse(contents.files.get(IGNORE_FILE).map_or("", String::as_str));
+    let files = contents
+        .paths
+        .iter()
+        .filter(|p| p.starts_with(&prefix) && !ignore.is_ignored(p, false))
+        .map(|p| FileEntry::new(p.clone(), contents.files.get(p).cloned().unwrap_or_default()))
+        .collect();
+    Ok((sha, files))
+}
+
+/// Packs under the same organization policy as the app: demo mode, export locations and
+/// redaction rules apply. Without the app's resources the demo sample can't be located,
+/// so no directory can be scanned in demo mode.
+async fn run_pack(mut args: PackArgs) -> Result<(), String> {
+    let policy = OrgPolicy::load();
+    if let Some(path) = &args.output {
+        policy.check_export(path)?;
+    }
+    let local = Path::new(&args.source);
+    let (name, git_ref, files) = if local.is_dir() {
+        policy.check_scan_path_within(None, local)?;
+        let (name, files) = scan_local(local, args.subpath.as_deref()).await?;
+        (name, None, files)
+    } else if let Some(RepoUrl { owner, repo, git_ref, subpath, .. }) = github_repo(&args.source) {
+        // A `/tree/<ref>/<dir>` URL stands in for `--ref` and `--subpath`.
+        args.git_ref = args.git_ref.or(git_ref);
+        args.subpath = args.subpath.or(subpath);
+        policy.check_not_demo("GitHub access")?;
+        let (sha, files) = fetch_github(&owner, &repo, &args).await?;
+        (format!("{}/{}", owner, repo), Some(sha), files)
+    } else {
+        return Err(format!("{} is neither a directory nor an owner/repo", args.source));
+    };
+
+    let pack = pack::assemble(name, git_ref, files, args.subpath.as_deref(), &args.pack);
+    let text = policy.redact(&pack::render(&pack, args.pack.format));
+    let tokens = repo_prompt_core::tokens::estimate_tokens(&text);
+    match &args.output {
+        Some(path) => export::write_atomic(path, None, text.as_bytes()).map_err(String::from)?,
+        None => print!("{}", text),
+    }
+    let mut left_out = Vec::new();
+    if !pack.omitted.is_empty() {
+        left_out.push(format!("{} left out to fit the budget", pack.omitted.len()));
+    }
+    if !pack.vendored.is_empty() {
+        left_out.push(format!("{} vendored or generated left out", pack.vendored.len()));
+    }
+    let duplicates: usize = pack.duplicates.iter().map(|g| g.duplicates.len()).sum();
+    if duplicates > 0 {
+        left_out.push(format!("{} duplicates collapsed", duplicates));
+    }
+    eprintln!("Packed {} files (~{} tokens){}", pack.files.len(), tokens, left_out.iter().map(|n| format!(", {}", n)).collect::<String>());
+    Ok(())
+}
+
+/// Headless mode: `repo-prompt-generator pack <path|owner/repo> [options]` scans or
+/// fetches a repository and writes the packed prompt without opening a window. Runs the
+/// command in `args` (without the program name) and returns the exit code, or `None` when
+/// the arguments aren't a CLI command and the app should start as usual. Release builds
+/// on Windows have no console attached, so pass `-o` there.
+pub fn run(args: &[String]) -> Option<i32> {
+    match args.first().map(String::as_str) {
+        Some("pack") => {}
+        Some("help" | "--help" | "-h") => {
+            print!("{}", USAGE);
+            return Some(0);
+        }
+        _ => return None,
+    }
+    let args = match parse_pack_args(&args[1..]) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return Some(2);
+        }
+    };
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start: {}", e);
+            return Some(1);
+        }
+    };
+    match runtime.block_on(run_pack(args)) {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            Some(1)
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use crate::error::AppError;
+use crate::AppState;
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::State;
+
+/// The native clipboard, opened on first use. It is kept open because on Linux the
+/// copied text is served by this process and vanishes when the handle is dropped.
+#[derive(Default)]
+pub struct NativeClipboard {
+    handle: Mutex<Option<arboard::Clipboard>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopiedText {
+    bytes: usize,
+    chars: usize,
+}
+
+/// Copies `content` to the system clipboard natively, for prompts too large for the
+/// webview clipboard API. Returns the size copied.
+#[tauri::command]
+pub async fn copy_to_clipboard(state: State<'_, AppState>, content: String) -> Result<CopiedText, AppError> {
+    let content = state.policy.redact(&content);
+    let copied = CopiedText { bytes: content.len(), chars: content.chars().count() };
+    let mut handle = state.clipboard.handle.lock().unwrap();
+    if handle.is_none() {
+        *handle = Some(arboard::Clipboard::new().map_err(|e| AppError::Internal(format!("Cannot open the clipboard: {}", e)))?);
+    }
+    if let Some(clipboard) = handle.as_mut() {
+        clipboard.set_text(content).map_err(|e| AppError::Internal(format!("Failed to copy to the clipboard: {}", e)))?;
+    }
+    Ok(copied)
+}
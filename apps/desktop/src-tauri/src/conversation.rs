@@ -0,0 +1,170 @@
+use crate::error::AppError;
+use crate::providers;
+use crate::tokens::estimate_tokens;
+use serde::{Deserialize, Serialize};
+
+/// Tokens kept free for the reply when fitting a conversation into a window.
+const REPLY_RESERVE: usize = 1024;
+/// Context is never cut below this many tokens; turns are dropped first.
+const MIN_CONTEXT_TOKENS: usize = 512;
+
+/// One exchange in a conversation, in provider-neutral form. `role` is `user` or
+/// `assistant` (Gemini's `model` is accepted as `assistant`).
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Turn {
+    pub role: String,
+    pub content: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumedConversation {
+    provider: String,
+    /// Request body fragment in the provider's format: `systemInstruction` + `contents`
+    /// for Gemini, `messages` for Ollama and OpenAI-compatible providers.
+    payload: serde_json::Value,
+    kept_turns: usize,
+    /// Oldest turns left out so the conversation fits the new window.
+    dropped_turns: usize,
+    context_truncated: bool,
+    estimated_tokens: usize,
+    context_window: Option<u64>,
+}
+
+pub fn normalize_role(role: &str) -> Result<&'static str, String> {
+    match role {
+        "user" => Ok("user"),
+        "assistant" | "model" => Ok("assistant"),
+        other => Err(format!("Unsupported role in history: {}", other)),
+    }
+}
+
+fn clip_tokens(text: &str, tokens: usize) -> &str {
+    match text.char_indices().nth(tokens * 4) {
+        Some((i, _)) => &text[..i],
+        None => text,
+    }
+}
+
+/// Drops the oldest turns (whole user/assistant pairs, so the history still alternates)
+/// and then cuts the context until everything fits `budget` tokens. The latest turn is
+/// always kept. Returns how many turns were dropped and whether the context was cut.
+fn fit(system: &str, context: &mut String, turns: &mut Vec<Turn>, budget: usize) -> (usize, bool) {
+    let total = |context: &str, turns: &[Turn]| {
+        estimate_tokens(system) + estimate_tokens(context) + turns.iter().map(|t| estimate_tokens(&t.content)).sum::<usize>()
+    };
+    let mut dropped = 0;
+    while total(context, turns) > budget && turns.len() > 1 {
+        let n = if turns.len() > 2 && turns[0].role == "user" && turns[1].role == "assistant" { 2 } else { 1 };
+        turns.drain(..n);
+        dropped += n;
+    }
+    let over = total(context, turns).saturating_sub(budget);
+    let mut truncated = false;
+    if over > 0 && !context.is_empty() {
+        let keep = estimate_tokens(context).saturating_sub(over).max(MIN_CONTEXT_TOKENS);
+        if keep < estimate_tokens(context) {
+            *context = format!("{}\n[... context truncated to fit the model's window ...]", clip_tokens(context, keep));
+            truncated = true;
+        }
+    }
+    (dropped, truncated)
+}
+
+fn omitted_note(dropped: usize) -> Option<String> {
+    (dropped > 0).then(|| format!("[{} earlier messages of this conversation were omitted to fit the context window.]", dropped))
+}
+
+fn gemini_payload(system: &str, context: &str, turns: &[Turn], dropped: usize) -> serde_json::Value {
+    // Gemini works best with long context placed before the questions, in the first user turn.
+    let mut preamble: Vec<String> = Vec::new();
+    if !context.is_empty() {
+        preamble.push(format!("Repository context:\n{}", context));
+    }
+    preamble.extend(omitted_note(dropped));
+
+    let mut contents = Vec::new();
+    for (i, turn) in turns.iter().enumerate() {
+        let role = if turn.role == "assistant" { "model" } else { "user" };
+        let text = if i == 0 && !preamble.is_empty() {
+            if role == "user" {
+                format!("{}\n\n{}", preamble.join("\n\n"), turn.content)
+            } else {
+                contents.push(serde_json::json!({ "role": "user", "parts": [{ "text": preamble.join("\n\n") }] }));
+                turn.content.clone()
+            }
+        } else {
+            turn.content.clone()
+        };
+        contents.push(serde_json::json!({ "role": role, "parts": [{ "text": text }] }));
+    }
+    let mut payload = serde_json::json!({ "contents": contents });
+    if !system.is_empty() {
+        payload["systemInstruction"] = serde_json::json!({ "parts": [{ "text": system }] });
+    }
+    payload
+}
+
+fn chat_payload(system: &str, context: &str, turns: &[Turn], dropped: usize) -> serde_json::Value {
+    // Chat-style APIs take the instructions and context together as the system message.
+    let mut system_parts: Vec<String> = Vec::new();
+    if !system.is_empty() {
+        system_parts.push(system.to_string());
+    }
+    if !context.is_empty() {
+        system_parts.push(format!("Repository context:\n{}", context));
+    }
+    system_parts.extend(omitted_note(dropped));
+
+    let mut messages = Vec::new();
+    if !system_parts.is_empty() {
+        messages.push(serde_json::json!({ "role": "system", "content": system_parts.join("\n\n") }));
+    }
+    messages.extend(turns.iter().map(|t| serde_json::json!({ "role": t.role, "content": t.content })));
+    serde_json::json!({ "messages": messages })
+}
+
+/// Renders `system`, `context` and `turns` as a request body fragment for `provider`,
+/// fitting them into `context_window` tokens when it's known (see [`fit`]). Returns the
+/// payload, how many turns were dropped and whether the context was cut.
+pub fn render(provider: &str, system: &str, context: &mut String, turns: &mut Vec<Turn>, context_window: Option<u64>) -> Result<(serde_json::Value, usize, bool), String> {
+    let (dropped, truncated) = match context_window {
+        Some(window) => fit(system, context, turns, (window as usize).saturating_sub(REPLY_RESERVE)),
+        None => (0, false),
+    };
+    let payload = match provider {
+        "gemini" => gemini_payload(system, context, turns, dropped),
+        "ollama" | "openai" | "custom" => chat_payload(system, context, turns, dropped),
+        other => return Err(format!("Unknown provider: {}", other)),
+    };
+    Ok((payload, dropped, truncated))
+}
+
+/// Rebuilds a conversation for the provider/model the user just switched to: the system
+/// prompt and context are re-rendered in that provider's format and the history is
+/// re-anchored after them, dropping the oldest turns (and as a last resort cutting the
+/// context) when the new model's window is smaller. `context_window` overrides the
+/// known window for the model.
+#[tauri::command]
+pub fn resume_conversation(
+    provider: String,
+    model: Option<String>,
+    system_prompt: Option<String>,
+    context: Option<String>,
+    history: Vec<Turn>,
+    context_window: Option<u64>,
+) -> Result<ResumedConversation, AppError> {
+    let provider = provider.trim().to_lowercase();
+    let context_window = context_window.or_else(|| providers::context_window(&provider, model.as_deref()));
+    let system = system_prompt.unwrap_or_default();
+    let mut context = context.unwrap_or_default();
+    let mut turns = history
+        .into_iter()
+        .map(|t| Ok(Turn { role: normalize_role(&t.role)?.to_string(), content: t.content }))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let (payload, dropped_turns, context_truncated) =
+        render(&provider, &system, &mut context, &mut turns, context_window).map_err(AppError::InvalidInput)?;
+    let estimated_tokens = estimate_tokens(&payload.to_string());
+    Ok(ResumedConversation { provider, payload, kept_turns: turns.len(), dropped_turns, context_truncated, estimated_tokens, context_window })
+}
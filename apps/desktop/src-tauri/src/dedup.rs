@@ -0,0 +1,51 @@
+use crate::error::AppError;
+use crate::{projects, AppState, FileEntry};
+use repo_prompt_core::dedup::{self, DuplicateGroup, VendoredFile};
+use repo_prompt_core::tokens::PackStats;
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DedupResult {
+    /// The files to pack: one copy of each duplicate, vendored files removed when asked.
+    files: Vec<FileEntry>,
+    duplicates: Vec<DuplicateGroup>,
+    /// Files that look vendored or generated; removed from `files` only with
+    /// `exclude_vendored`.
+    vendored: Vec<VendoredFile>,
+    /// Estimated tokens the removed files would have taken.
+    tokens_saved: usize,
+    /// Size of `files`.
+    stats: PackStats,
+}
+
+/// Collapses identical files (same content up to line endings and trailing whitespace)
+/// into one copy and flags vendored, generated and minified files, removing those too
+/// with `exclude_vendored` (default true). Works on `files`, or on those of the last load
+/// of `project`.
+#[tauri::command]
+pub fn dedup_files(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    files: Option<Vec<FileEntry>>,
+    project: Option<String>,
+    exclude_vendored: Option<bool>,
+) -> Result<DedupResult, AppError> {
+    let files = match (files, project) {
+        (Some(files), _) => files,
+        (None, Some(project)) => projects::loaded_files(&app, &state, &project)?,
+        (None, None) => return Err(AppError::InvalidInput("Give the files to check or a loaded project".to_string())),
+    };
+    let tokens = |files: &[FileEntry]| files.iter().map(|f| f.stats.tokens).sum::<usize>();
+    let before = tokens(&files);
+    let (files, vendored) = if exclude_vendored.unwrap_or(true) {
+        dedup::split_vendored(files)
+    } else {
+        let vendored = files.iter().filter_map(|f| dedup::vendored_reason(&f.path, &f.content).map(|reason| VendoredFile { path: f.path.clone(), reason })).collect();
+        (files, vendored)
+    };
+    let (files, duplicates) = dedup::dedup(files);
+    let tokens_saved = before - tokens(&files);
+    Ok(DedupResult { stats: PackStats::of(&files), files, duplicates, vendored, tokens_saved })
+}
@@ -0,0 +1,27 @@
+use crate::error::AppError;
+use crate::{AppState, FileEntry};
+use repo_prompt_core::dependencies::{self, Manifest};
+use serde::Serialize;
+use tauri::State;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencySummary {
+    /// The dependency section for the prompt.
+    text: String,
+    manifests: Vec<Manifest>,
+}
+
+/// Parses the dependency manifests among `files` (found by file name, in any directory, as
+/// for GitHub fetches; other files are ignored) into a normalized list per ecosystem and
+/// renders the compact summary used in prompts. Manifests without a parser, such as those
+/// added in the settings, or that fail to parse are included raw; `raw` includes all of
+/// them raw.
+#[tauri::command]
+pub async fn summarize_dependencies(state: State<'_, AppState>, files: Vec<FileEntry>, raw: Option<bool>) -> Result<DependencySummary, AppError> {
+    let paths: Vec<String> = files.iter().map(|f| f.path.clone()).collect();
+    let found = dependencies::find_manifests(&paths, &state.manifest_files.read().await);
+    let manifests: Vec<(String, String)> = files.into_iter().filter(|f| found.contains(&f.path)).map(|f| (f.path, f.content)).collect();
+    let (text, manifests) = dependencies::describe(&manifests, raw.unwrap_or(false));
+    Ok(DependencySummary { text, manifests })
+}
@@ -0,0 +1,175 @@
+use crate::error::AppError;
+use crate::AppState;
+use isahc::config::{Configurable, Dialer};
+use isahc::prelude::*;
+use isahc::HttpClient;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::State;
+
+/// Port Ollama listens on inside its container.
+const OLLAMA_CONTAINER_PORT: u64 = 11434;
+const DOCKER_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OllamaContainer {
+    id: String,
+    name: String,
+    image: String,
+    /// `running`, `exited`, `created`, ...
+    state: String,
+    status: String,
+    /// Host port mapped to the container's 11434, if published.
+    host_port: Option<u64>,
+    /// Base URL to reach the server from the host, when the port is published.
+    pub url: Option<String>,
+}
+
+/// Docker Engine endpoints to try: `DOCKER_HOST`, then the usual socket locations
+/// (system, rootless, Docker Desktop), or the TCP endpoint Docker Desktop can expose on Windows.
+fn docker_endpoints() -> Vec<String> {
+    let mut endpoints = Vec::new();
+    if let Ok(host) = std::env::var("DOCKER_HOST") {
+        endpoints.push(host.replace("localhost", "127.0.0.1"));
+    }
+    #[cfg(unix)]
+    {
+        endpoints.push("unix:///var/run/docker.sock".to_string());
+        if let Ok(runtime) = std::env::var("XDG_RUNTIME_DIR") {
+            endpoints.push(format!("unix://{}/docker.sock", runtime));
+        }
+        if let Some(home) = std::env::var_os("HOME") {
+            endpoints.push(format!("unix://{}/.docker/run/docker.sock", home.to_string_lossy()));
+        }
+    }
+    #[cfg(not(unix))]
+    endpoints.push("tcp://127.0.0.1:2375".to_string());
+    endpoints
+}
+
+fn client_for(endpoint: &str) -> Option<HttpClient> {
+    #[cfg(unix)]
+    if let Some(path) = endpoint.strip_prefix("unix://") {
+        if !std::path::Path::new(path).exists() {
+            return None;
+        }
+    }
+    let dialer: Dialer = endpoint.parse().ok()?;
+    HttpClient::builder().dial(dialer).timeout(DOCKER_TIMEOUT).build().ok()
+}
+
+/// Calls the Docker Engine API on the first endpoint that answers.
+async fn docker_request(method: &str, path: &str) -> Result<(u16, String), String> {
+    let mut last_error = "Docker is not available (no Docker socket found)".to_string();
+    for endpoint in docker_endpoints() {
+        let Some(client) = client_for(&endpoint) else { continue };
+        // The host part is ignored when dialing a socket.
+        let request = isahc::Request::builder()
+            .method(method)
+            .uri(format!("http://docker{}", path))
+            .body(())
+            .map_err(|e| e.to_string())?;
+        match client.send_async(request).await {
+            Ok(mut res) => {
+                let status = res.status().as_u16();
+                return Ok((status, res.text().await.unwrap_or_default()));
+            }
+            Err(e) => last_error = format!("Docker API error at {}: {}", endpoint, e),
+        }
+    }
+    Err(last_error)
+}
+
+/// Lists containers (running or not) whose image or name mentions Ollama.
+pub async fn list_ollama_containers() -> Result<Vec<OllamaContainer>, String> {
+    let (status, body) = docker_request("GET", "/containers/json?all=1").await?;
+    if status != 200 {
+        return Err(format!("Docker API error ({}): {}", status, body));
+    }
+    let json: serde_json::Value = serde_json::from_str(&body).map_err(|e| e.to_string())?;
+    let containers = json
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|c| {
+            let image = c["Image"].as_str().unwrap_or_default().to_string();
+            let name = c["Names"][0].as_str().unwrap_or_default().trim_start_matches('/').to_string();
+            if !image.contains("ollama") && !name.contains("ollama") {
+                return None;
+            }
+            let port = c["Ports"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find(|p| p["PrivatePort"].as_u64() == Some(OLLAMA_CONTAINER_PORT) && p["PublicPort"].is_u64());
+            let host_port = port.and_then(|p| p["PublicPort"].as_u64());
+            let url = port.map(|p| {
+                let ip = match p["IP"].as_str() {
+                    None | Some("") | Some("0.0.0.0") | Some("::") => "127.0.0.1",
+                    Some(ip) => ip,
+                };
+                format!("http://{}:{}", ip, p["PublicPort"])
+            });
+            Some(OllamaContainer {
+                id: c["Id"].as_str().unwrap_or_default().to_string(),
+                name,
+                image,
+                state: c["State"].as_str().unwrap_or_default().to_string(),
+                status: c["Status"].as_str().unwrap_or_default().to_string(),
+                host_port,
+                url,
+            })
+        })
+        .collect();
+    Ok(containers)
+}
+
+/// Whether an Ollama container is currently running.
+pub async fn ollama_container_running() -> bool {
+    list_ollama_containers().await.is_ok_and(|list| list.iter().any(|c| c.state == "running"))
+}
+
+/// Base URLs of running Ollama containers with a published port.
+pub async fn running_container_urls() -> Vec<String> {
+    list_ollama_containers()
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|c| c.state == "running")
+        .filter_map(|c| c.url)
+        .collect()
+}
+
+fn validate_container_id(id: &str) -> Result<(), String> {
+    let valid = !id.is_empty() && id.len() <= 128 && id.chars().all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c));
+    if valid { Ok(()) } else { Err(format!("Invalid container id: {}", id)) }
+}
+
+/// Lists Ollama containers known to the local Docker daemon, with their port mapping.
+#[tauri::command]
+pub async fn detect_ollama_containers() -> Result<Vec<OllamaContainer>, AppError> {
+    Ok(list_ollama_containers().await?)
+}
+
+#[tauri::command]
+pub async fn start_ollama_container(state: State<'_, AppState>, id: String) -> Result<String, AppError> {
+    state.policy.check_not_demo("Managing Docker containers")?;
+    validate_container_id(&id)?;
+    match docker_request("POST", &format!("/containers/{}/start", id)).await? {
+        (204, _) => Ok(format!("Container {} started", id)),
+        (304, _) => Ok(format!("Container {} is already running", id)),
+        (status, body) => Err(AppError::from_status(status, format!("Failed to start container ({}): {}", status, body), None)),
+    }
+}
+
+#[tauri::command]
+pub async fn stop_ollama_container(state: State<'_, AppState>, id: String) -> Result<String, AppError> {
+    state.policy.check_not_demo("Managing Docker containers")?;
+    validate_container_id(&id)?;
+    match docker_request("POST", &format!("/containers/{}/stop", id)).await? {
+        (204, _) => Ok(format!("Container {} stopped", id)),
+        (304, _) => Ok(format!("Container {} is not running", id)),
+        (status, body) => Err(AppError::from_status(status, format!("Failed to stop container ({}): {}", status, body), None)),
+    }
+}
@@ -0,0 +1,318 @@
+use crate::cache::{self, DiskCache, GcReport};
+use crate::error::AppError;
+use crate::{log_status, AppState};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, State};
+
+const NAMESPACE: &str = "embeddings";
+const MANIFEST_KEY: &str = "manifest";
+pub const DEFAULT_MAX_BYTES: u64 = 512 * 1024 * 1024;
+/// A hit refreshes an entry's last use at most this often, so lookups rarely rewrite shards.
+const TOUCH_INTERVAL_SECS: u64 = 24 * 60 * 60;
+/// Hash, last use and dimension count ahead of each vector.
+const ENTRY_HEADER_BYTES: usize = 32 + 8 + 4;
+
+/// Limit and bookkeeping of the embedding cache shared by every index.
+pub struct EmbeddingStore {
+    /// Least recently used vectors are evicted beyond this many bytes.
+    pub max_bytes: AtomicU64,
+    /// Held while shards and the manifest are rewritten.
+    lock: Mutex<()>,
+}
+
+impl Default for EmbeddingStore {
+    fn default() -> Self {
+        EmbeddingStore { max_bytes: AtomicU64::new(DEFAULT_MAX_BYTES), lock: Mutex::new(()) }
+    }
+}
+
+/// One shard of a model's vectors: those whose content hash starts with `shard`.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ShardInfo {
+    provider: String,
+    model: String,
+    shard: String,
+    entries: usize,
+    bytes: u64,
+}
+
+impl ShardInfo {
+    fn key(&self) -> String {
+        shard_key(&self.provider, &self.model, &self.shard)
+    }
+}
+
+struct Entry {
+    used: u64,
+    vector: Vec<f32>,
+}
+
+type Shard = HashMap<String, Entry>;
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+fn shard_key(provider: &str, model: &str, shard: &str) -> String {
+    format!("{}:{}:{}", provider, model, shard)
+}
+
+fn entry_bytes(vector: &[f32]) -> u64 {
+    (ENTRY_HEADER_BYTES + vector.len() * 4) as u64
+}
+
+fn encode(shard: &Shard) -> Vec<u8> {
+    let mut out = Vec::with_capacity(shard.values().map(|e| entry_bytes(&e.vector) as usize).sum());
+    for (hash, entry) in shard {
+        let Ok(hash) = blake3::Hash::from_hex(hash) else { continue };
+        out.extend_from_slice(hash.as_bytes());
+        out.extend_from_slice(&entry.used.to_le_bytes());
+        out.extend_from_slice(&(entry.vector.len() as u32).to_le_bytes());
+        entry.vector.iter().for_each(|x| out.extend_from_slice(&x.to_le_bytes()));
+    }
+    out
+}
+
+/// Reads what [`encode`] wrote; a truncated tail is dropped.
+fn decode(bytes: &[u8]) -> Shard {
+    let mut shard = Shard::new();
+    let mut rest = bytes;
+    while rest.len() >= ENTRY_HEADER_BYTES {
+        let hash: [u8; 32] = rest[..32].try_into().unwrap();
+        let used = u64::from_le_bytes(rest[32..40].try_into().unwrap());
+        let dims = u32::from_le_bytes(rest[40..44].try_into().unwrap()) as usize;
+        let Some(data) = rest[ENTRY_HEADER_BYTES..].get(..dims * 4) else { break };
+        let vector = data.chunks_exact(4).map(|b| f32::from_le_bytes(b.try_into().unwrap())).collect();
+        shard.insert(blake3::Hash::from_bytes(hash).to_hex().to_string(), Entry { used, vector });
+        rest = &rest[ENTRY_HEADER_BYTES + dims * 4..];
+    }
+    shard
+}
+
+fn load_manifest(cache: &DiskCache) -> Vec<ShardInfo> {
+    cache.get(MANIFEST_KEY).and_then(|bytes| serde_json::from_slice(&bytes).ok()).unwrap_or_default()
+}
+
+fn store_manifest(cache: &DiskCache, manifest: &[ShardInfo]) -> Result<(), String> {
+    cache.put(MANIFEST_KEY, &serde_json::to_vec(manifest).map_err(|e| e.to_string())?).map(|_| ())
+}
+
+fn load_shard(cache: &DiskCache, key: &str) -> Shard {
+    cache.get(key).map(|bytes| decode(&bytes)).unwrap_or_default()
+}
+
+/// Writes `shard` and records its size in `manifest`; an empty shard is dropped.
+fn store_shard(cache: &DiskCache, manifest: &mut Vec<ShardInfo>, provider: &str, model: &str, prefix: &str, shard: &Shard) -> Result<(), String> {
+    let key = shard_key(provider, model, prefix);
+    manifest.retain(|s| s.key() != key);
+    if shard.is_empty() {
+        cache.remove(&key);
+        return Ok(());
+    }
+    cache.put(&key, &encode(shard))?;
+    manifest.push(ShardInfo {
+        provider: provider.to_string(),
+        model: model.to_string(),
+        shard: prefix.to_string(),
+        entries: shard.len(),
+        bytes: shard.values().map(|e| entry_bytes(&e.vector)).sum(),
+    });
+    Ok(())
+}
+
+/// Content hashes grouped by the shard they belong in.
+fn by_shard<'a>(hashes: impl Iterator<Item = &'a String>) -> BTreeMap<String, Vec<&'a String>> {
+    let mut shards: BTreeMap<String, Vec<&String>> = BTreeMap::new();
+    for hash in hashes.filter(|h| h.len() >= 2) {
+        shards.entry(hash[..2].to_string()).or_default().push(hash);
+    }
+    shards
+}
+
+/// Vectors embedded by one provider and model, by blake3 hash of the embedded text.
+/// Shared by every project, so a chunk is only embedded once per model however many
+/// projects, branches or index rebuilds it turns up in.
+pub struct EmbeddingCache<'a> {
+    cache: DiskCache,
+    store: &'a EmbeddingStore,
+    provider: String,
+    model: String,
+}
+
+impl<'a> EmbeddingCache<'a> {
+    pub fn open(app: &AppHandle, state: &'a AppState, provider: &str, model: &str) -> Result<Self, String> {
+        Ok(EmbeddingCache { cache: cache::open_cache(app, state, NAMESPACE)?, store: &state.embeddings, provider: provider.to_string(), model: model.to_string() })
+    }
+
+    /// The cached vectors of `hashes`, marking them as used.
+    pub fn get_many<'h>(&self, hashes: impl Iterator<Item = &'h String>) -> HashMap<String, Vec<f32>> {
+        let _guard = self.store.lock.lock().unwrap();
+        let now = now_secs();
+        let mut manifest = None;
+        let mut found = HashMap::new();
+        for (prefix, hashes) in by_shard(hashes) {
+            let mut shard = load_shard(&self.cache, &shard_key(&self.provider, &self.model, &prefix));
+            let mut touched = false;
+            for hash in hashes {
+                if let Some(entry) = shard.get_mut(hash) {
+                    touched |= now.saturating_sub(entry.used) > TOUCH_INTERVAL_SECS;
+                    entry.used = now;
+                    found.insert(hash.clone(), entry.vector.clone());
+                }
+            }
+            if touched {
+                let manifest = manifest.get_or_insert_with(|| load_manifest(&self.cache));
+                let _ = store_shard(&self.cache, manifest, &self.provider, &self.model, &prefix, &shard);
+            }
+        }
+        if let Some(manifest) = manifest {
+            let _ = store_manifest(&self.cache, &manifest);
+        }
+        found
+    }
+
+    /// Adds freshly embedded vectors, then evicts the least recently used vectors of any
+    /// model if the cache grew past its limit. Returns the number evicted.
+    pub fn put_many(&self, vectors: &[(String, Vec<f32>)]) -> Result<usize, String> {
+        if vectors.is_empty() {
+            return Ok(0);
+        }
+        let _guard = self.store.lock.lock().unwrap();
+        let now = now_secs();
+        let mut manifest = load_manifest(&self.cache);
+        let lookup: HashMap<&String, &Vec<f32>> = vectors.iter().map(|(h, v)| (h, v)).collect();
+        for (prefix, hashes) in by_shard(lookup.keys().copied()) {
+            let mut shard = load_shard(&self.cache, &shard_key(&self.provider, &self.model, &prefix));
+            for hash in hashes {
+                shard.insert(hash.clone(), Entry { used: now, vector: lookup[hash].clone() });
+            }
+            store_shard(&self.cache, &mut manifest, &self.provider, &self.model, &prefix, &shard)?;
+        }
+        let (evicted, _) = evict(&self.cache, &mut manifest, self.store.max_bytes.load(Ordering::Relaxed))?;
+        store_manifest(&self.cache, &manifest)?;
+        Ok(evicted)
+    }
+}
+
+/// Drops the least recently used vectors until the cache holds at most `max_bytes`.
+/// Returns (vectors evicted, bytes freed).
+fn evict(cache: &DiskCache, manifest: &mut Vec<ShardInfo>, max_bytes: u64) -> Result<(usize, u64), String> {
+    let total: u64 = manifest.iter().map(|s| s.bytes).sum();
+    if total <= max_bytes {
+        return Ok((0, 0));
+    }
+    let mut shards: Vec<(ShardInfo, Shard)> = manifest.iter().map(|info| (info.clone(), load_shard(cache, &info.key()))).collect();
+    let mut by_age: Vec<(u64, usize, String, u64)> = shards
+        .iter()
+        .enumerate()
+        .flat_map(|(i, (_, shard))| shard.iter().map(move |(hash, e)| (e.used, i, hash.clone(), entry_bytes(&e.vector))))
+        .collect();
+    by_age.sort();
+
+    let (mut evicted, mut freed) = (0, 0);
+    let mut changed = vec![false; shards.len()];
+    for (_, i, hash, bytes) in by_age {
+        if total - freed <= max_bytes {
+            break;
+        }
+        shards[i].1.remove(&hash);
+        changed[i] = true;
+        evicted += 1;
+        freed += bytes;
+    }
+    for ((info, shard), _) in shards.iter().zip(changed).filter(|(_, changed)| *changed) {
+        store_shard(cache, manifest, &info.provider, &info.model, &info.shard, shard)?;
+    }
+    Ok((evicted, freed))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelStats {
+    provider: String,
+    model: String,
+    vectors: usize,
+    bytes: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddingCacheStats {
+    models: Vec<ModelStats>,
+    /// Size of the cached vectors before compression.
+    total_bytes: u64,
+    max_bytes: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvictionReport {
+    vectors_removed: usize,
+    bytes_freed: u64,
+    gc: GcReport,
+}
+
+/// Vectors in the embedding cache per provider and model, and the size limit.
+#[tauri::command]
+pub async fn get_embedding_cache_stats(app: AppHandle, state: State<'_, AppState>) -> Result<EmbeddingCacheStats, AppError> {
+    let cache = cache::open_cache(&app, &state, NAMESPACE)?;
+    let mut models: Vec<ModelStats> = Vec::new();
+    for shard in load_manifest(&cache) {
+        match models.iter_mut().find(|m| m.provider == shard.provider && m.model == shard.model) {
+            Some(m) => {
+                m.vectors += shard.entries;
+                m.bytes += shard.bytes;
+            }
+            None => models.push(ModelStats { provider: shard.provider, model: shard.model, vectors: shard.entries, bytes: shard.bytes }),
+        }
+    }
+    models.sort_by_key(|m| std::cmp::Reverse(m.bytes));
+    Ok(EmbeddingCacheStats { total_bytes: models.iter().map(|m| m.bytes).sum(), models, max_bytes: state.embeddings.max_bytes.load(Ordering::Relaxed) })
+}
+
+/// Evicts the least recently used vectors until the embedding cache holds at most
+/// `max_bytes` (default: the limit from the settings), then frees their disk space.
+#[tauri::command]
+pub async fn evict_embedding_cache(app: AppHandle, state: State<'_, AppState>, max_bytes: Option<u64>) -> Result<EvictionReport, AppError> {
+    let max_bytes = max_bytes.unwrap_or_else(|| state.embeddings.max_bytes.load(Ordering::Relaxed));
+    let cache = cache::open_cache(&app, &state, NAMESPACE)?;
+    let level = state.cache_compression_level.load(Ordering::Relaxed);
+    let (vectors_removed, bytes_freed) = {
+        let _guard = state.embeddings.lock.lock().unwrap();
+        let mut manifest = load_manifest(&cache);
+        let evicted = evict(&cache, &mut manifest, max_bytes)?;
+        store_manifest(&cache, &manifest)?;
+        evicted
+    };
+    let gc = tokio::task::spawn_blocking(move || cache::collect_garbage(&app, level)).await??;
+    Ok(EvictionReport { vectors_removed, bytes_freed, gc })
+}
+
+/// Drops the cached vectors of `provider`/`model`, or every cached vector when neither
+/// is given. Project indexes are kept.
+#[tauri::command]
+pub async fn clear_embedding_cache(app: AppHandle, state: State<'_, AppState>, provider: Option<String>, model: Option<String>) -> Result<EvictionReport, AppError> {
+    let cache = cache::open_cache(&app, &state, NAMESPACE)?;
+    let level = state.cache_compression_level.load(Ordering::Relaxed);
+    let (vectors_removed, bytes_freed) = {
+        let _guard = state.embeddings.lock.lock().unwrap();
+        let (removed, kept): (Vec<ShardInfo>, Vec<ShardInfo>) = match (&provider, &model) {
+            (None, None) => (load_manifest(&cache), Vec::new()),
+            (Some(provider), Some(model)) => load_manifest(&cache).into_iter().partition(|s| &s.provider == provider && &s.model == model),
+            _ => return Err(AppError::InvalidInput("Give both provider and model, or neither to clear every cached embedding".to_string())),
+        };
+        for shard in &removed {
+            cache.remove(&shard.key());
+        }
+        store_manifest(&cache, &kept)?;
+        (removed.iter().map(|s| s.entries).sum(), removed.iter().map(|s| s.bytes).sum())
+    };
+    log_status(&app, format!("Cleared {} cached embeddings", vectors_removed));
+    let gc = tokio::task::spawn_blocking(move || cache::collect_garbage(&app, level)).await??;
+    Ok(EvictionReport { vectors_removed, bytes_freed, gc })
+}
@@ -0,0 +1,212 @@
+use crate::gemini::GeminiError;
+use serde::ser::{SerializeStruct, Serializer};
+use serde::Serialize;
+use std::fmt;
+
+/// Error returned by every command. It reaches the frontend as
+/// `{ code, category, message, retryAfterSecs, retryable }`, so the UI can ask for a key, wait out a
+/// rate limit or offer a retry without matching on message text.
+#[derive(Debug)]
+pub enum AppError {
+    /// No API key or token was given.
+    MissingCredentials(String),
+    /// The service rejected the key or token.
+    Unauthorized(String),
+    /// The service couldn't be reached, or the connection dropped.
+    Network(String),
+    Timeout(String),
+    /// Out of quota. `retry_after_secs` is set when the service said how long to wait.
+    RateLimited { message: String, retry_after_secs: Option<u64> },
+    NotFound(String),
+    Io(String),
+    /// Arguments or settings the command can't work with.
+    InvalidInput(String),
+    /// Refused by the organization policy or by demo mode.
+    PolicyDenied(String),
+    /// The provider answered with an error, or withheld its answer.
+    Provider { status: Option<u16>, message: String },
+    /// Stopped on the user's request.
+    Cancelled(String),
+    /// Anything not classified more precisely.
+    Internal(String),
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorCategory {
+    Auth,
+    Network,
+    RateLimit,
+    NotFound,
+    Io,
+    Invalid,
+    Policy,
+    Provider,
+    Cancelled,
+    Internal,
+}
+
+impl AppError {
+    /// Stable identifier of the error kind, for the frontend to switch on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::MissingCredentials(_) => "missing_credentials",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::Network(_) => "network",
+            AppError::Timeout(_) => "timeout",
+            AppError::RateLimited { .. } => "rate_limited",
+            AppError::NotFound(_) => "not_found",
+            AppError::Io(_) => "io",
+            AppError::InvalidInput(_) => "invalid_input",
+            AppError::PolicyDenied(_) => "policy_denied",
+            AppError::Provider { .. } => "provider",
+            AppError::Cancelled(_) => "cancelled",
+            AppError::Internal(_) => "internal",
+        }
+    }
+
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            AppError::MissingCredentials(_) | AppError::Unauthorized(_) => ErrorCategory::Auth,
+            AppError::Network(_) | AppError::Timeout(_) => ErrorCategory::Network,
+            AppError::RateLimited { .. } => ErrorCategory::RateLimit,
+            AppError::NotFound(_) => ErrorCategory::NotFound,
+            AppError::Io(_) => ErrorCategory::Io,
+            AppError::InvalidInput(_) => ErrorCategory::Invalid,
+            AppError::PolicyDenied(_) => ErrorCategory::Policy,
+            AppError::Provider { .. } => ErrorCategory::Provider,
+            AppError::Cancelled(_) => ErrorCategory::Cancelled,
+            AppError::Internal(_) => ErrorCategory::Internal,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::MissingCredentials(m)
+            | AppError::Unauthorized(m)
+            | AppError::Network(m)
+            | AppError::Timeout(m)
+            | AppError::NotFound(m)
+            | AppError::Io(m)
+            | AppError::InvalidInput(m)
+            | AppError::PolicyDenied(m)
+            | AppError::Cancelled(m)
+            | AppError::Internal(m) => m,
+            AppError::RateLimited { message, .. } | AppError::Provider { message, .. } => message,
+        }
+    }
+
+    pub fn retry_after_secs(&self) -> Option<u64> {
+        match self {
+            AppError::RateLimited { retry_after_secs, .. } => *retry_after_secs,
+            _ => None,
+        }
+    }
+
+    /// Whether trying the same thing again later could succeed.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            AppError::Provider { status, .. } => status.is_some_and(|s| s >= 500),
+            other => matches!(other.category(), ErrorCategory::Network | ErrorCategory::RateLimit),
+        }
+    }
+
+    pub fn missing_gemini_key() -> AppError {
+        AppError::MissingCredentials("Gemini API key is missing. Please enter it in the settings or set the GEMINI_API_KEY environment variable.".to_string())
+    }
+
+    /// Classifies an HTTP error status.
+    pub fn from_status(status: u16, message: String, retry_after_secs: Option<u64>) -> AppError {
+        match status {
+            401 | 403 => AppError::Unauthorized(message),
+            404 => AppError::NotFound(message),
+            408 | 504 => AppError::Timeout(message),
+            429 => AppError::RateLimited { message, retry_after_secs },
+            400 | 422 => AppError::InvalidInput(message),
+            _ => AppError::Provider { status: Some(status), message },
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("AppError", 5)?;
+        s.serialize_field("code", self.code())?;
+        s.serialize_field("category", &self.category())?;
+        s.serialize_field("message", self.message())?;
+        s.serialize_field("retryAfterSecs", &self.retry_after_secs())?;
+        s.serialize_field("retryable", &self.is_retryable())?;
+        s.end()
+    }
+}
+
+/// Messages from helpers that still report plain strings.
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Internal(message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        AppError::Internal(message.to_string())
+    }
+}
+
+/// For helpers that still report plain strings; only the message survives.
+impl From<AppError> for String {
+    fn from(e: AppError) -> Self {
+        e.message().to_string()
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        match e.kind() {
+            std::io::ErrorKind::NotFound => AppError::NotFound(e.to_string()),
+            _ => AppError::Io(e.to_string()),
+        }
+    }
+}
+
+impl From<isahc::Error> for AppError {
+    fn from(e: isahc::Error) -> Self {
+        if e.is_timeout() {
+            AppError::Timeout(e.to_string())
+        } else if e.is_network() || e.is_tls() {
+            AppError::Network(e.to_string())
+        } else {
+            AppError::Internal(e.to_string())
+        }
+    }
+}
+
+impl From<GeminiError> for AppError {
+    fn from(e: GeminiError) -> Self {
+        let message = e.to_string();
+        match e {
+            GeminiError::Api { status, .. } => AppError::from_status(status, message, None),
+            GeminiError::Blocked { .. } | GeminiError::Empty { .. } | GeminiError::Malformed(_) | GeminiError::Schema(_) => AppError::Provider { status: None, message },
+        }
+    }
+}
+
+impl From<tokio::task::JoinError> for AppError {
+    fn from(e: tokio::task::JoinError) -> Self {
+        AppError::Internal(format!("Background task failed: {}", e))
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(e: tauri::Error) -> Self {
+        AppError::Internal(e.to_string())
+    }
+}
@@ -0,0 +1,212 @@
+use crate::error::AppError;
+use crate::{projects, AppState, FileEntry};
+use repo_prompt_core::tokens::PackStats;
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tauri::{AppHandle, State};
+
+/// Part size for `export_pack` when none is given.
+const DEFAULT_PART_BYTES: usize = 1_000_000;
+const ZIP_INDEX: &str = "INDEX.md";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedFile {
+    path: String,
+    bytes_written: u64,
+    appended: bool,
+}
+
+/// Hidden sibling of `path` that is renamed over it once complete. Being in the same
+/// directory keeps the rename on one file system, where it is atomic. The counter keeps
+/// concurrent writes of the same path from sharing one.
+fn temp_sibling(path: &Path) -> PathBuf {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    path.with_file_name(format!(".{}.{}.{}.tmp", name, std::process::id(), n))
+}
+
+/// Writes `prefix` followed by `data` to a temporary file next to `path`, flushes it to
+/// disk and renames it into place, creating missing parent directories. Readers see the
+/// old file or the new one, never a partial write. A replaced file keeps its permissions.
+pub fn write_atomic(path: &Path, prefix: Option<&Path>, data: &[u8]) -> Result<(), AppError> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| AppError::Io(format!("Failed to create {}: {}", parent.display(), e)))?;
+    }
+    let tmp = temp_sibling(path);
+    let write = || -> std::io::Result<()> {
+        if let Some(prefix) = prefix {
+            fs::copy(prefix, &tmp)?;
+        }
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(&tmp)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        if let Ok(existing) = fs::metadata(path) {
+            fs::set_permissions(&tmp, existing.permissions())?;
+        }
+        fs::rename(&tmp, path)
+    };
+    write().map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        AppError::Io(format!("Failed to save {}: {}", path.display(), e))
+    })
+}
+
+/// Saves `content` to `path`, creating missing parent directories and writing atomically.
+/// An existing file is only replaced when `overwrite` is set; with `append` the content is
+/// added to its end instead. Returns the number of bytes written.
+#[tauri::command]
+pub async fn save_text_file(
+    state: State<'_, AppState>,
+    path: String,
+    content: String,
+    append: Option<bool>,
+    overwrite: Option<bool>,
+) -> Result<SavedFile, AppError> {
+    let target = PathBuf::from(&path);
+    state.policy.check_export(&target)?;
+    let append = append.unwrap_or(false);
+    let exists = target.exists();
+    if target.is_dir() {
+        return Err(AppError::InvalidInput(format!("{} is a directory", path)));
+    }
+    if exists && !append && !overwrite.unwrap_or(false) {
+        return Err(AppError::InvalidInput(format!("{} already exists; pass overwrite to replace it", path)));
+    }
+    let data = state.policy.redact(&content).into_bytes();
+    let bytes_written = data.len() as u64;
+    let appended = append && exists;
+    tokio::task::spawn_blocking(move || write_atomic(&target, appended.then_some(target.as_path()), &data)).await??;
+    Ok(SavedFile { path, bytes_written, appended })
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackExport {
+    /// Files written: the numbered parts, or the single archive.
+    paths: Vec<String>,
+    files: usize,
+    bytes_written: u64,
+    /// Source files that alone exceed the part size; each got a part of its own.
+    oversized: Vec<String>,
+    stats: PackStats,
+}
+
+fn file_block(file: &FileEntry) -> String {
+    format!("\n--- {} ---\n{}\n", file.path, file.content)
+}
+
+/// Packs `header` and `files` into parts of at most `max_bytes`, breaking only between
+/// files. The header opens the first part; a file too large for any part gets its own.
+fn split_parts(header: &str, files: &[FileEntry], max_bytes: usize, oversized: &mut Vec<String>) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = header.to_string();
+    for file in files {
+        let block = file_block(file);
+        if block.len() > max_bytes {
+            oversized.push(file.path.clone());
+        }
+        if !current.is_empty() && current.len() + block.len() > max_bytes {
+            parts.push(std::mem::take(&mut current));
+        }
+        current.push_str(&block);
+    }
+    if !current.is_empty() || parts.is_empty() {
+        parts.push(current);
+    }
+    let count = parts.len();
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(i, body)| if count > 1 { format!("[Part {} of {}]\n{}", i + 1, count, body) } else { body })
+        .collect()
+}
+
+/// `pack.md` becomes `pack.part01.md`, `pack.part02.md`, ...
+fn part_path(dest: &Path, index: usize, count: usize) -> PathBuf {
+    let stem = dest.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| "pack".to_string());
+    let width = count.to_string().len().max(2);
+    let name = match dest.extension() {
+        Some(ext) => format!("{}.part{:0width$}.{}", stem, index + 1, ext.to_string_lossy(), width = width),
+        None => format!("{}.part{:0width$}", stem, index + 1, width = width),
+    };
+    dest.with_file_name(name)
+}
+
+/// A zip archive with each file at its repository path, plus an index listing them (after
+/// `header`, when given).
+fn build_zip(header: &str, files: &[FileEntry]) -> Result<Vec<u8>, AppError> {
+    use zip::write::SimpleFileOptions;
+
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let mut index = header.trim_end().to_string();
+    if !index.is_empty() {
+        index.push_str("\n\n");
+    }
+    index.push_str(&format!("# Files ({})\n\n", files.len()));
+    let zip_error = |e: zip::result::ZipError| AppError::Internal(format!("Failed to build the archive: {}", e));
+    for file in files {
+        index.push_str(&format!("- {} ({} bytes)\n", file.path, file.content.len()));
+        zip.start_file(file.path.trim_start_matches('/'), options).map_err(zip_error)?;
+        zip.write_all(file.content.as_bytes())?;
+    }
+    zip.start_file(ZIP_INDEX, options).map_err(zip_error)?;
+    zip.write_all(index.as_bytes())?;
+    Ok(zip.finish().map_err(zip_error)?.into_inner())
+}
+
+/// Writes a pack of `files` (default: those of the last load of `project`) for models
+/// with upload limits. `mode` `split` writes numbered parts of at most `max_part_bytes`
+/// (default 1 MB) next to `dest`, breaking only between files; `zip` writes `dest` as an
+/// archive with one entry per file and an `INDEX.md`. `header` (e.g. the instructions and
+/// file tree) opens the first part or the index. Existing files are only replaced with
+/// `overwrite`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn export_pack(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    dest: String,
+    mode: String,
+    files: Option<Vec<FileEntry>>,
+    project: Option<String>,
+    header: Option<String>,
+    max_part_bytes: Option<usize>,
+    overwrite: Option<bool>,
+) -> Result<PackExport, AppError> {
+    let dest = PathBuf::from(&dest);
+    state.policy.check_export(&dest)?;
+    let files = match (files, project) {
+        (Some(files), _) => files,
+        (None, Some(project)) => projects::loaded_files(&app, &state, &project)?,
+        (None, None) => return Err(AppError::InvalidInput("Give the files to export or a loaded project".to_string())),
+    };
+    let files: Vec<FileEntry> = files.into_iter().map(|f| FileEntry::new(f.path, state.policy.redact(&f.content))).collect();
+    let header = state.policy.redact(header.as_deref().unwrap_or_default());
+
+    let mut oversized = Vec::new();
+    let outputs: Vec<(PathBuf, Vec<u8>)> = match mode.as_str() {
+        "split" => {
+            let parts = split_parts(&header, &files, max_part_bytes.unwrap_or(DEFAULT_PART_BYTES).max(1), &mut oversized);
+            let count = parts.len();
+            parts.into_iter().enumerate().map(|(i, part)| (part_path(&dest, i, count), part.into_bytes())).collect()
+        }
+        "zip" => vec![(dest.clone(), build_zip(&header, &files)?)],
+        other => return Err(AppError::InvalidInput(format!("Unknown export mode: {} (expected split or zip)", other))),
+    };
+    if !overwrite.unwrap_or(false) {
+        if let Some((path, _)) = outputs.iter().find(|(path, _)| path.exists()) {
+            return Err(AppError::InvalidInput(format!("{} already exists; pass overwrite to replace it", path.display())));
+        }
+    }
+
+    let bytes_written = outputs.iter().map(|(_, data)| data.len() as u64).sum();
+    let paths = outputs.iter().map(|(path, _)| path.display().to_string()).collect();
+    tokio::task::spawn_blocking(move || outputs.iter().try_for_each(|(path, data)| write_atomic(path, None, data))).await??;
+    Ok(PackExport { paths, files: files.len(), bytes_written, oversized, stats: PackStats::of(&files) })
+}
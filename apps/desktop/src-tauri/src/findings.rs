@@ -0,0 +1,387 @@
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+const FINDINGS_FILE: &str = "audit_findings.json";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    Critical,
+    High,
+    Medium,
+    Low,
+    Info,
+}
+
+impl Severity {
+    fn parse(text: &str) -> Option<Severity> {
+        let t = text.to_lowercase();
+        if t.contains("critical") {
+            Some(Severity::Critical)
+        } else if t.contains("high") {
+            Some(Severity::High)
+        } else if t.contains("medium") || t.contains("moderate") {
+            Some(Severity::Medium)
+        } else if t.contains("low") {
+            Some(Severity::Low)
+        } else if t.contains("info") {
+            Some(Severity::Info)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FindingStatus {
+    Open,
+    /// Known and deliberately left as is.
+    Accepted,
+    Fixed,
+}
+
+/// A finding as read from one audit response.
+struct ParsedFinding {
+    severity: Severity,
+    file: Option<String>,
+    line: Option<u32>,
+    title: String,
+    description: String,
+    recommendation: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    id: String,
+    severity: Severity,
+    file: Option<String>,
+    line: Option<u32>,
+    title: String,
+    description: String,
+    recommendation: Option<String>,
+    status: FindingStatus,
+    note: Option<String>,
+    /// Snapshot (commit SHA) of the audit that last reported it.
+    snapshot: Option<String>,
+    /// Unix seconds.
+    first_seen: u64,
+    last_seen: u64,
+    updated_at: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditImport {
+    /// Findings not reported by earlier audits of the project.
+    added: usize,
+    /// Findings already known; a fixed one that shows up again is reopened.
+    seen_again: usize,
+    reopened: usize,
+    /// Every finding of the project, most severe first.
+    findings: Vec<Finding>,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+fn findings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| format!("Failed to resolve data dir: {}", e))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+    Ok(dir.join(FINDINGS_FILE))
+}
+
+/// Findings keyed by project (`owner/repo` or the local path).
+fn load_findings(app: &AppHandle) -> Result<HashMap<String, Vec<Finding>>, String> {
+    match fs::read_to_string(findings_path(app)?) {
+        Ok(text) => serde_json::from_str(&text).map_err(|e| format!("Findings database is corrupt: {}", e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+        Err(e) => Err(format!("Failed to read findings: {}", e)),
+    }
+}
+
+fn store_findings(app: &AppHandle, findings: &HashMap<String, Vec<Finding>>) -> Result<(), String> {
+    let path = findings_path(app)?;
+    let tmp = path.with_extension("json.tmp");
+    let text = serde_json::to_string(findings).map_err(|e| e.to_string())?;
+    fs::write(&tmp, text).map_err(|e| format!("Failed to save findings: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to save findings: {}", e))
+}
+
+fn clean(text: &str) -> String {
+    text.trim().trim_matches(|c| c == '`' || c == '*' || c == '_').trim().to_string()
+}
+
+/// Splits `src/auth.rs:42`, `src/auth.rs:42-50` or `src/auth.rs (line 42)` into path and line.
+fn parse_location(text: &str) -> (Option<String>, Option<u32>) {
+    let text = clean(text);
+    let (path, rest) = match text.split_once(" (") {
+        Some((path, rest)) => (path.to_string(), rest.to_string()),
+        None => match text.rsplit_once(':') {
+            Some((path, line)) if line.trim().starts_with(|c: char| c.is_ascii_digit()) => (path.to_string(), line.to_string()),
+            _ => (text.clone(), String::new()),
+        },
+    };
+    let line = rest.split(|c: char| !c.is_ascii_digit()).find(|s| !s.is_empty()).and_then(|s| s.parse().ok());
+    let path = clean(&path);
+    let path = (!path.is_empty() && !matches!(path.to_lowercase().as_str(), "n/a" | "-" | "none")).then_some(path);
+    (path, line)
+}
+
+fn json_str(item: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter().find_map(|k| match &item[*k] {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+fn from_json(text: &str) -> Option<Vec<ParsedFinding>> {
+    let json: Value = serde_json::from_str(text.trim()).ok()?;
+    let items = json.as_array().or_else(|| json["findings"].as_array())?;
+    let findings = items
+        .iter()
+        .filter_map(|item| {
+            let severity = Severity::parse(&json_str(item, &["severity", "level", "risk"])?)?;
+            let (mut file, mut line) = json_str(item, &["file", "path", "location"]).map(|l| parse_location(&l)).unwrap_or_default();
+            if let Some(n) = json_str(item, &["line", "lineNumber", "startLine"]).and_then(|l| l.parse().ok()) {
+                line = Some(n);
+            }
+            file = file.filter(|f| !f.is_empty());
+            let description = json_str(item, &["description", "details", "issue", "explanation"]).unwrap_or_default();
+            let title = json_str(item, &["title", "name", "type", "category"])
+                .unwrap_or_else(|| description.lines().next().unwrap_or_default().to_string());
+            Some(ParsedFinding {
+                severity,
+                file,
+                line,
+                title,
+                description,
+                recommendation: json_str(item, &["recommendation", "fix", "remediation", "mitigation"]),
+            })
+        })
+        .collect();
+    Some(findings)
+}
+
+fn table_cells(line: &str) -> Vec<String> {
+    line.trim().trim_matches('|').split('|').map(clean).collect()
+}
+
+/// Markdown table with a `Severity` column.
+fn from_table(text: &str) -> Vec<ParsedFinding> {
+    let mut findings = Vec::new();
+    let mut columns: Option<Vec<String>> = None;
+    for line in text.lines().map(str::trim) {
+        if !line.starts_with('|') {
+            columns = None;
+            continue;
+        }
+        let cells = table_cells(line);
+        if cells.iter().all(|c| c.chars().all(|ch| matches!(ch, '-' | ':' | ' '))) {
+            continue;
+        }
+        let Some(header) = &columns else {
+            let header: Vec<String> = cells.iter().map(|c| c.to_lowercase()).collect();
+            if header.iter().any(|c| c.contains("severity") || c == "risk") {
+                columns = Some(header);
+            }
+            continue;
+        };
+        let cell = |names: &[&str]| {
+            header.iter().position(|h| names.iter().any(|n| h.contains(n))).and_then(|i| cells.get(i)).filter(|c| !c.is_empty()).cloned()
+        };
+        let Some(severity) = cell(&["severity", "risk"]).and_then(|s| Severity::parse(&s)) else { continue };
+        let (file, mut line_no) = cell(&["file", "location", "path"]).map(|l| parse_location(&l)).unwrap_or_default();
+        if let Some(n) = cell(&["line"]).and_then(|l| l.parse().ok()) {
+            line_no = Some(n);
+        }
+        let description = cell(&["description", "detail", "issue"]).unwrap_or_default();
+        findings.push(ParsedFinding {
+            severity,
+            file,
+            line: line_no,
+            title: cell(&["title", "finding", "vulnerability", "name"]).unwrap_or_else(|| description.clone()),
+            description,
+            recommendation: cell(&["recommendation", "fix", "remediation", "mitigation"]),
+        });
+    }
+    findings
+}
+
+/// `Severity: High` style label at the start of a line (after list markers and bold).
+fn label<'a>(line: &'a str, names: &[&str]) -> Option<&'a str> {
+    let trimmed = line.trim_start_matches(['-', '*', ' ', '>']);
+    let (key, value) = trimmed.split_once(':')?;
+    let key = key.trim_matches(|c| c == '*' || c == '_' || c == ' ').to_lowercase();
+    names.contains(&key.as_str()).then_some(value.trim_start_matches(['*', ' ']))
+}
+
+/// Heading-per-finding sections with `Severity:` / `File:` / `Description:` lines, or a
+/// severity tag in the heading (`### [High] SQL injection in ...`).
+fn from_sections(text: &str) -> Vec<ParsedFinding> {
+    let mut sections: Vec<(String, Vec<&str>)> = Vec::new();
+    for line in text.lines() {
+        let trimmed = line.trim();
+        let is_heading = trimmed.starts_with('#')
+            || (trimmed.split_once(". ").is_some_and(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+                && label(trimmed.split_once(". ").map_or("", |(_, rest)| rest), &["severity"]).is_none());
+        if is_heading {
+            let title = trimmed.trim_start_matches(|c: char| c == '#' || c.is_ascii_digit() || c == '.' || c == ' ');
+            sections.push((clean(title), Vec::new()));
+        } else if let Some((_, body)) = sections.last_mut() {
+            body.push(line);
+        }
+    }
+
+    let mut findings = Vec::new();
+    for (title, body) in sections {
+        let field = |names: &[&str]| body.iter().find_map(|l| label(l, names)).map(clean).filter(|v| !v.is_empty());
+        // `[High] Title` or `Title (Critical)` when there is no `Severity:` line.
+        let tag = title.split_once(']').map(|(t, _)| t).or_else(|| title.rsplit_once('(').map(|(_, t)| t));
+        let Some(severity) = field(&["severity", "risk"]).and_then(|s| Severity::parse(&s)).or_else(|| tag.and_then(Severity::parse)) else {
+            continue;
+        };
+        let title = match (title.split_once(']'), title.rsplit_once(" (")) {
+            (Some((_, rest)), _) => clean(rest.trim_start_matches([' ', '-', ':'])),
+            (None, Some((rest, tag))) if Severity::parse(tag).is_some() => clean(rest),
+            _ => title,
+        };
+        let (file, mut line) = field(&["file", "location", "path"]).map(|l| parse_location(&l)).unwrap_or_default();
+        if let Some(n) = field(&["line", "lines"]).and_then(|l| parse_location(&format!("x:{}", l)).1) {
+            line = Some(n);
+        }
+        // Without a `Description:` line, the unlabeled text of the section.
+        let description = field(&["description", "details", "issue", "impact"]).unwrap_or_else(|| {
+            let labels = ["severity", "risk", "file", "location", "path", "line", "lines", "recommendation", "fix", "remediation"];
+            let prose: Vec<&str> = body.iter().filter(|l| !l.trim().is_empty() && label(l, &labels).is_none()).map(|l| l.trim()).collect();
+            prose.join(" ")
+        });
+        findings.push(ParsedFinding {
+            severity,
+            file,
+            line,
+            title,
+            description,
+            recommendation: field(&["recommendation", "fix", "remediation", "mitigation"]),
+        });
+    }
+    findings
+}
+
+/// Reads findings from an audit response: a JSON array (or `{ "findings": [...] }`),
+/// raw or in a code block, else a markdown table with a severity column, else one
+/// heading per finding.
+fn parse_findings(response: &str) -> Vec<ParsedFinding> {
+    let fenced = response.split("```").skip(1).step_by(2).map(|block| block.split_once('\n').map_or(block, |(lang, rest)| {
+        if lang.trim().chars().all(char::is_alphanumeric) { rest } else { block }
+    }));
+    if let Some(found) = std::iter::once(response).chain(fenced).find_map(from_json).filter(|f| !f.is_empty()) {
+        return found;
+    }
+    let table = from_table(response);
+    if !table.is_empty() {
+        return table;
+    }
+    from_sections(response)
+}
+
+/// Same finding across audits: same file and the same title, ignoring case and punctuation.
+fn fingerprint(file: Option<&str>, title: &str) -> String {
+    let words: Vec<String> = title.to_lowercase().split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).map(str::to_string).collect();
+    format!("{}|{}", file.unwrap_or_default(), words.join(" "))
+}
+
+fn sorted(mut findings: Vec<Finding>) -> Vec<Finding> {
+    findings.sort_by(|a, b| a.severity.cmp(&b.severity).then_with(|| a.file.cmp(&b.file)).then_with(|| a.line.cmp(&b.line)));
+    findings
+}
+
+/// Parses the findings out of a security-audit response and merges them into the
+/// project's findings. Findings seen before keep their status, except fixed ones that
+/// are reported again, which are reopened.
+#[tauri::command]
+pub fn record_audit_findings(app: AppHandle, project: String, snapshot: Option<String>, response: String) -> Result<AuditImport, AppError> {
+    let parsed = parse_findings(&response);
+    if parsed.is_empty() {
+        return Err(AppError::InvalidInput("No findings with a severity were found in the response".to_string()));
+    }
+    let mut all = load_findings(&app)?;
+    let findings = all.entry(project).or_default();
+    let now = now_secs();
+    let (mut added, mut seen_again, mut reopened) = (0, 0, 0);
+    for p in parsed {
+        let key = fingerprint(p.file.as_deref(), &p.title);
+        match findings.iter_mut().find(|f| fingerprint(f.file.as_deref(), &f.title) == key) {
+            Some(existing) => {
+                seen_again += 1;
+                if existing.status == FindingStatus::Fixed {
+                    existing.status = FindingStatus::Open;
+                    existing.updated_at = now;
+                    reopened += 1;
+                }
+                existing.severity = p.severity;
+                existing.line = p.line.or(existing.line);
+                existing.description = p.description;
+                existing.recommendation = p.recommendation.or(existing.recommendation.take());
+                existing.snapshot = snapshot.clone();
+                existing.last_seen = now;
+            }
+            None => {
+                added += 1;
+                findings.push(Finding {
+                    id: format!("{:x}-{}", now, findings.len() + 1),
+                    severity: p.severity,
+                    file: p.file,
+                    line: p.line,
+                    title: p.title,
+                    description: p.description,
+                    recommendation: p.recommendation,
+                    status: FindingStatus::Open,
+                    note: None,
+                    snapshot: snapshot.clone(),
+                    first_seen: now,
+                    last_seen: now,
+                    updated_at: now,
+                });
+            }
+        }
+    }
+    let result = findings.clone();
+    store_findings(&app, &all)?;
+    Ok(AuditImport { added, seen_again, reopened, findings: sorted(result) })
+}
+
+/// The project's findings, most severe first, optionally only those with `status`.
+#[tauri::command]
+pub fn list_findings(app: AppHandle, project: String, status: Option<FindingStatus>) -> Result<Vec<Finding>, AppError> {
+    let findings = load_findings(&app)?.remove(&project).unwrap_or_default();
+    Ok(sorted(findings.into_iter().filter(|f| status.map_or(true, |s| f.status == s)).collect()))
+}
+
+/// Marks a finding open, accepted or fixed, with an optional note (why it was accepted,
+/// the fixing commit, ...).
+#[tauri::command]
+pub fn set_finding_status(app: AppHandle, project: String, id: String, status: FindingStatus, note: Option<String>) -> Result<Finding, AppError> {
+    let mut all = load_findings(&app)?;
+    let finding = all
+        .get_mut(&project)
+        .and_then(|findings| findings.iter_mut().find(|f| f.id == id))
+        .ok_or_else(|| format!("Finding {} not found", id))?;
+    finding.status = status;
+    if note.is_some() {
+        finding.note = note;
+    }
+    finding.updated_at = now_secs();
+    let updated = finding.clone();
+    store_findings(&app, &all)?;
+    Ok(updated)
+}
@@ -0,0 +1,302 @@
+use crate::error::AppError;
+use crate::{continuation, log_status, retry, AppState};
+use isahc::prelude::*;
+use repo_prompt_core::gemini::{parse_structured, Usage};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+use tauri::{AppHandle, State};
+
+pub use repo_prompt_core::gemini::{api_error, check_blocked, parse_reply, retry_delay, GeminiError, GeminiReply};
+
+pub const DEFAULT_EMBED_MODEL: &str = "text-embedding-004";
+/// `batchEmbedContents` accepts at most this many requests per call.
+const EMBED_BATCH: usize = 100;
+/// Rate-limited batches are retried this many times, waiting as long as Gemini asks.
+const RATE_LIMIT_RETRIES: u32 = 3;
+/// Waits longer than this are reported instead of sat out.
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+const EMBED_TASK_TYPES: &[&str] = &[
+    "RETRIEVAL_QUERY", "RETRIEVAL_DOCUMENT", "SEMANTIC_SIMILARITY", "CLASSIFICATION", "CLUSTERING", "QUESTION_ANSWERING", "FACT_VERIFICATION", "CODE_RETRIEVAL_QUERY",
+];
+
+const HARM_CATEGORIES: &[&str] = &["HARASSMENT", "HATE_SPEECH", "SEXUALLY_EXPLICIT", "DANGEROUS_CONTENT", "CIVIC_INTEGRITY"];
+const BLOCK_THRESHOLDS: &[&str] = &["BLOCK_NONE", "BLOCK_ONLY_HIGH", "BLOCK_MEDIUM_AND_ABOVE", "BLOCK_LOW_AND_ABOVE", "OFF"];
+/// Gemini accepts at most this many stop sequences.
+const MAX_STOP_SEQUENCES: usize = 5;
+
+#[derive(Deserialize, Clone)]
+pub struct SafetySetting {
+    /// `HARM_CATEGORY_HARASSMENT` or just `harassment`.
+    pub category: String,
+    /// `BLOCK_NONE`, `BLOCK_ONLY_HIGH`, `BLOCK_MEDIUM_AND_ABOVE`, `BLOCK_LOW_AND_ABOVE` or `OFF`.
+    pub threshold: String,
+}
+
+/// Sampling, length, system instruction and safety settings for a `generateContent`
+/// request. Unset fields keep the model's defaults.
+#[derive(Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationOptions {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub top_k: Option<u32>,
+    pub max_output_tokens: Option<u32>,
+    #[serde(default)]
+    pub stop_sequences: Vec<String>,
+    pub system_instruction: Option<String>,
+    #[serde(default)]
+    pub safety_settings: Vec<SafetySetting>,
+}
+
+impl GenerationOptions {
+    /// Adds the options to `body` as `generationConfig`, `systemInstruction` and
+    /// `safetySettings`, keeping whatever `generationConfig` already holds. Out-of-range
+    /// values and unknown categories or thresholds are rejected before anything is sent.
+    pub fn apply(&self, body: &mut Value, redact: impl Fn(&str) -> String) -> Result<(), AppError> {
+        let invalid = |message: String| Err(AppError::InvalidInput(message));
+        if let Some(t) = self.temperature.filter(|t| !(0.0..=2.0).contains(t)) {
+            return invalid(format!("temperature must be between 0 and 2, got {}", t));
+        }
+        if let Some(p) = self.top_p.filter(|p| !(0.0..=1.0).contains(p)) {
+            return invalid(format!("topP must be between 0 and 1, got {}", p));
+        }
+        if self.top_k == Some(0) || self.max_output_tokens == Some(0) {
+            return invalid("topK and maxOutputTokens must be at least 1".to_string());
+        }
+        if self.stop_sequences.len() > MAX_STOP_SEQUENCES {
+            return invalid(format!("At most {} stop sequences are allowed", MAX_STOP_SEQUENCES));
+        }
+
+        let mut config = serde_json::Map::new();
+        if let Some(t) = self.temperature { config.insert("temperature".to_string(), Value::from(t)); }
+        if let Some(p) = self.top_p { config.insert("topP".to_string(), Value::from(p)); }
+        if let Some(k) = self.top_k { config.insert("topK".to_string(), Value::from(k)); }
+        if let Some(n) = self.max_output_tokens { config.insert("maxOutputTokens".to_string(), Value::from(n)); }
+        if !self.stop_sequences.is_empty() { config.insert("stopSequences".to_string(), Value::from(self.stop_sequences.clone())); }
+        if !config.is_empty() {
+            if !body["generationConfig"].is_object() {
+                body["generationConfig"] = serde_json::json!({});
+            }
+            if let Some(existing) = body["generationConfig"].as_object_mut() {
+                existing.extend(config);
+            }
+        }
+
+        if let Some(instruction) = self.system_instruction.as_deref().filter(|s| !s.trim().is_empty()) {
+            body["systemInstruction"] = serde_json::json!({ "parts": [{ "text": redact(instruction) }] });
+        }
+
+        let mut safety = Vec::new();
+        for setting in &self.safety_settings {
+            let category = setting.category.trim().to_uppercase();
+            let category = category.strip_prefix("HARM_CATEGORY_").unwrap_or(&category);
+            if !HARM_CATEGORIES.contains(&category) {
+                return invalid(format!("Unknown harm category: {}", setting.category));
+            }
+            let threshold = setting.threshold.trim().to_uppercase();
+            if !BLOCK_THRESHOLDS.contains(&threshold.as_str()) {
+                return invalid(format!("Unknown block threshold: {}", setting.threshold));
+            }
+            safety.push(serde_json::json!({ "category": format!("HARM_CATEGORY_{}", category), "threshold": threshold }));
+        }
+        if !safety.is_empty() {
+            body["safetySettings"] = Value::from(safety);
+        }
+        Ok(())
+    }
+}
+
+/// How long a 429 response asks to wait: the `Retry-After` header, else the delay in the
+/// error body.
+fn rate_limit_wait(headers: &isahc::http::HeaderMap, body: &str) -> Option<Duration> {
+    let header = headers.get("retry-after").and_then(|v| v.to_str().ok()).and_then(|v| v.trim().parse().ok()).map(Duration::from_secs);
+    header.or_else(|| retry_delay(body))
+}
+
+/// A failed response as an [`AppError`], with the wait a rate limit asked for.
+fn api_failure(status: u16, headers: &isahc::http::HeaderMap, body: &str) -> AppError {
+    let error = api_error(status, body);
+    match status {
+        429 => AppError::RateLimited { message: error.to_string(), retry_after_secs: rate_limit_wait(headers, body).map(|d| d.as_secs().max(1)) },
+        _ => error.into(),
+    }
+}
+
+/// Sends a `generateContent` request body to `model` and extracts the reply. The caller
+/// checks the policy and redacts the body.
+pub async fn generate(state: &AppState, model: &str, body: &Value) -> Result<GeminiReply, AppError> {
+    let key = state.gemini_api_key.read().await.clone();
+    if key.is_empty() {
+        return Err(AppError::missing_gemini_key());
+    }
+    let body = body.to_string();
+    let make = || {
+        isahc::Request::builder()
+            .method("POST")
+            .uri(format!("https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent", model))
+            .header("Content-Type", "application/json")
+            .header("x-goog-api-key", &key)
+            .body(body.clone())
+    };
+
+    let client = state.http_client.read().await.clone();
+    let _span = state.trace.span("llm", "gemini_generate").attr("model", model);
+    // Generating has no side effects, so failed attempts are safe to repeat.
+    let mut response = retry::send(&client, "Gemini", true, make).await?;
+    let status = response.status();
+    let text = response.text().await.unwrap_or_else(|_| "Could not read response body".to_string());
+    if !status.is_success() {
+        return Err(api_failure(status.as_u16(), response.headers(), &text));
+    }
+    let reply = parse_reply(&text)?;
+    state.usage.record("gemini", model, reply.usage.as_ref());
+    Ok(reply)
+}
+
+/// Like [`generate`], but while the reply stops at the output token limit, asks (up to
+/// `max_continuations` times) for the rest and stitches the segments into one reply.
+pub async fn generate_complete(state: &AppState, model: &str, body: &Value, max_continuations: u32) -> Result<GeminiReply, AppError> {
+    let mut reply = generate(state, model, body).await?;
+    let mut body = body.clone();
+    let mut last_segment = reply.text.clone();
+    while reply.truncated && reply.continuations < max_continuations {
+        let Some(contents) = body["contents"].as_array_mut() else { break };
+        contents.push(serde_json::json!({ "role": "model", "parts": [{ "text": last_segment }] }));
+        contents.push(serde_json::json!({ "role": "user", "parts": [{ "text": continuation::CONTINUE_PROMPT }] }));
+        let next = generate(state, model, &body).await?;
+        continuation::stitch(&mut reply.text, &next.text);
+        reply.continuations += 1;
+        reply.truncated = next.truncated;
+        reply.finish_reason = next.finish_reason;
+        reply.recovered |= next.recovered;
+        if let (Some(total), Some(more)) = (reply.usage.as_mut(), next.usage) {
+            total.add(&more);
+        }
+        last_segment = next.text;
+    }
+    Ok(reply)
+}
+
+/// Embeds `inputs` with `batchEmbedContents`, in order, calling `progress(done)` after each
+/// batch. A rate-limited batch is retried after the wait Gemini asks for, when that is
+/// reasonably short.
+pub async fn embed_many(state: &AppState, model: &str, inputs: &[String], task_type: &str, progress: impl Fn(usize)) -> Result<Vec<Vec<f32>>, AppError> {
+    let key = state.gemini_api_key.read().await.clone();
+    if key.is_empty() {
+        return Err(AppError::missing_gemini_key());
+    }
+    let client = state.http_client.read().await.clone();
+    let _span = state.trace.span("llm", "gemini_embed").attr("model", model).attr("inputs", inputs.len());
+    let mut vectors = Vec::with_capacity(inputs.len());
+    for batch in inputs.chunks(EMBED_BATCH) {
+        let requests: Vec<Value> = batch
+            .iter()
+            .map(|text| serde_json::json!({ "model": format!("models/{}", model), "content": { "parts": [{ "text": text }] }, "taskType": task_type }))
+            .collect();
+        let body = serde_json::json!({ "requests": requests }).to_string();
+        let make = || {
+            isahc::Request::builder()
+                .method("POST")
+                .uri(format!("https://generativelanguage.googleapis.com/v1beta/models/{}:batchEmbedContents", model))
+                .header("Content-Type", "application/json")
+                .header("x-goog-api-key", &key)
+                .body(body.clone())
+        };
+        let mut rate_limited = 0;
+        let text = loop {
+            let mut response = retry::send(&client, "Gemini", true, make).await?;
+            let status = response.status().as_u16();
+            let text = response.text().await?;
+            if status == 429 && rate_limited < RATE_LIMIT_RETRIES {
+                if let Some(wait) = rate_limit_wait(response.headers(), &text).filter(|w| *w <= MAX_RATE_LIMIT_WAIT) {
+                    rate_limited += 1;
+                    tokio::time::sleep(wait).await;
+                    continue;
+                }
+            }
+            if !(200..300).contains(&status) {
+                return Err(api_failure(status, response.headers(), &text));
+            }
+            break text;
+        };
+        let malformed = |message: String| AppError::Provider { status: None, message };
+        let json: Value = serde_json::from_str(&text).map_err(|e| malformed(e.to_string()))?;
+        let embeddings = json["embeddings"].as_array().ok_or_else(|| malformed("No embeddings field in response".to_string()))?;
+        if embeddings.len() != batch.len() {
+            return Err(malformed(format!("Gemini returned {} embeddings for {} inputs", embeddings.len(), batch.len())));
+        }
+        for e in embeddings {
+            vectors.push(serde_json::from_value(e["values"].clone()).map_err(|_| malformed("Malformed embedding in response".to_string()))?);
+        }
+        progress(vectors.len());
+    }
+    Ok(vectors)
+}
+
+/// Embeds `inputs` with Gemini (`model` defaults to `text-embedding-004`), batching them
+/// and waiting out rate limits. `task_type` is one of Gemini's task types, by default
+/// `RETRIEVAL_DOCUMENT`; use `RETRIEVAL_QUERY` for search queries.
+#[tauri::command]
+pub async fn gemini_embed(state: State<'_, AppState>, inputs: Vec<String>, model: Option<String>, task_type: Option<String>) -> Result<Vec<Vec<f32>>, AppError> {
+    state.policy.check_provider("gemini")?;
+    let task_type = task_type.map(|t| t.trim().to_uppercase()).unwrap_or_else(|| "RETRIEVAL_DOCUMENT".to_string());
+    if !EMBED_TASK_TYPES.contains(&task_type.as_str()) {
+        return Err(AppError::InvalidInput(format!("Unknown embedding task type: {}", task_type)));
+    }
+    let model = model.filter(|m| !m.trim().is_empty()).unwrap_or_else(|| DEFAULT_EMBED_MODEL.to_string());
+    let inputs: Vec<String> = inputs.iter().map(|i| state.policy.redact(i)).collect();
+    embed_many(&state, &model, &inputs, &task_type, |_| {}).await
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuredReply {
+    /// The parsed reply, matching the schema when one was given.
+    value: Value,
+    /// The raw reply text.
+    text: String,
+    usage: Option<Usage>,
+}
+
+/// Asks Gemini for a JSON reply (`responseMimeType: application/json`), constrained to
+/// `response_schema` when given (Gemini's OpenAPI subset, e.g. `{"type": "ARRAY", "items":
+/// {"type": "STRING"}}`). The reply is parsed and checked against the schema here, so a
+/// successful result is always usable as is. Replies cut off at the output token limit
+/// are errors, since the JSON is incomplete. `options` are as for `call_gemini_secure`.
+#[tauri::command(rename_all = "snake_case")]
+pub async fn call_gemini_json(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    prompt: String,
+    response_schema: Option<Value>,
+    model: Option<String>,
+    options: Option<GenerationOptions>,
+) -> Result<StructuredReply, AppError> {
+    state.policy.check_provider("gemini")?;
+    if response_schema.as_ref().is_some_and(|s| !s.is_object()) {
+        return Err(AppError::InvalidInput("response_schema must be a JSON object".to_string()));
+    }
+    let prompt = state.policy.redact(&prompt);
+    let model = model.filter(|m| !m.trim().is_empty()).unwrap_or_else(|| "gemini-3-flash-preview".to_string());
+    let mut config = serde_json::json!({ "responseMimeType": "application/json" });
+    if let Some(schema) = &response_schema {
+        config["responseSchema"] = schema.clone();
+    }
+    let mut body = serde_json::json!({
+        "contents": [{ "role": "user", "parts": [{ "text": prompt }] }],
+        "generationConfig": config,
+    });
+    if let Some(options) = &options {
+        options.apply(&mut body, |s| state.policy.redact(s))?;
+    }
+
+    log_status(&app, format!("Requesting structured output from Gemini ({})", model));
+    let reply = generate(&state, &model, &body).await.inspect_err(|e| log_status(&app, format!("Gemini request failed: {}", e)))?;
+    if reply.truncated {
+        return Err(GeminiError::Schema("the reply was cut off at the output token limit".to_string()).into());
+    }
+    let value = parse_structured(&reply.text, response_schema.as_ref())?;
+    log_status(&app, "Gemini structured response received");
+    Ok(StructuredReply { value, text: reply.text, usage: reply.usage })
+}
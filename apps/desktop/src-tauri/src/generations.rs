@@ -0,0 +1,273 @@
+use crate::error::AppError;
+use crate::export::write_atomic;
+use crate::{usage, AppState};
+use repo_prompt_core::tokens::estimate_tokens;
+use repo_prompt_core::usage::{estimate_cost, Usage};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
+
+/// One JSON record per line, so recording a run appends instead of rewriting the file.
+const GENERATIONS_FILE: &str = "generations.jsonl";
+/// Past this size the oldest runs are dropped, down to three quarters of it so the file
+/// isn't rewritten on every following run.
+const MAX_FILE_BYTES: u64 = 64 * 1024 * 1024;
+const DEFAULT_LIMIT: usize = 50;
+/// Characters of the response shown in a listing.
+const PREVIEW_CHARS: usize = 240;
+
+/// A generation run: what was sent where, and what came back.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationRecord {
+    id: String,
+    /// `owner/repo` or the local path.
+    repo: String,
+    /// Commit SHA (or other snapshot id) the prompt was built from.
+    snapshot: Option<String>,
+    /// The options the prompt was built with (template, budget, format, ...), as sent.
+    #[serde(default)]
+    options: serde_json::Value,
+    provider: String,
+    model: Option<String>,
+    /// blake3 of the prompt; the prompt itself isn't kept.
+    prompt_hash: String,
+    prompt_tokens: usize,
+    response: String,
+    usage: Option<Usage>,
+    /// Estimated USD, when the model's price is known.
+    cost: Option<f64>,
+    /// Unix milliseconds.
+    created_at: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationSummary {
+    id: String,
+    repo: String,
+    snapshot: Option<String>,
+    provider: String,
+    model: Option<String>,
+    prompt_hash: String,
+    /// Start of the response.
+    preview: String,
+    response_tokens: usize,
+    usage: Option<Usage>,
+    cost: Option<f64>,
+    created_at: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryPage {
+    items: Vec<GenerationSummary>,
+    /// Runs matching the filters, across all pages.
+    total: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryExport {
+    path: String,
+    entries: usize,
+    bytes_written: u64,
+}
+
+impl GenerationRecord {
+    fn summary(&self) -> GenerationSummary {
+        let mut preview: String = self.response.chars().take(PREVIEW_CHARS).collect();
+        if preview.len() < self.response.len() {
+            preview.push('…');
+        }
+        GenerationSummary {
+            id: self.id.clone(),
+            repo: self.repo.clone(),
+            snapshot: self.snapshot.clone(),
+            provider: self.provider.clone(),
+            model: self.model.clone(),
+            prompt_hash: self.prompt_hash.clone(),
+            preview,
+            response_tokens: estimate_tokens(&self.response),
+            usage: self.usage,
+            cost: self.cost,
+            created_at: self.created_at,
+        }
+    }
+
+    fn markdown(&self) -> String {
+        let mut out = format!("## {} — {}", self.repo, self.provider);
+        if let Some(model) = &self.model {
+            out.push_str(&format!(" / {}", model));
+        }
+        out.push_str(&format!("\n\n- Id: {}\n- Created: {} (Unix ms)\n", self.id, self.created_at));
+        if let Some(snapshot) = &self.snapshot {
+            out.push_str(&format!("- Snapshot: {}\n", snapshot));
+        }
+        out.push_str(&format!("- Prompt: {} tokens, blake3 {}\n", self.prompt_tokens, self.prompt_hash));
+        if let Some(u) = &self.usage {
+            out.push_str(&format!("- Usage: {} prompt + {} output tokens\n", u.prompt_tokens, u.output_tokens));
+        }
+        if let Some(cost) = self.cost {
+            out.push_str(&format!("- Estimated cost: ${:.4}\n", cost));
+        }
+        out.push_str(&format!("\n{}\n", self.response.trim_end()));
+        out
+    }
+}
+
+fn generations_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| format!("Failed to resolve data dir: {}", e))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+    Ok(dir.join(GENERATIONS_FILE))
+}
+
+/// Oldest first. A line that doesn't parse (a write cut short by a crash) is skipped.
+fn load(app: &AppHandle) -> Result<Vec<GenerationRecord>, String> {
+    match fs::read_to_string(generations_path(app)?) {
+        Ok(text) => Ok(text.lines().filter_map(|line| serde_json::from_str(line).ok()).collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to read generation history: {}", e)),
+    }
+}
+
+fn to_lines(records: &[GenerationRecord]) -> Result<String, String> {
+    let mut text = String::new();
+    for record in records {
+        text.push_str(&serde_json::to_string(record).map_err(|e| e.to_string())?);
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+fn append(app: &AppHandle, record: &GenerationRecord) -> Result<(), String> {
+    let path = generations_path(app)?;
+    let line = format!("{}\n", serde_json::to_string(record).map_err(|e| e.to_string())?);
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(&path).map_err(|e| format!("Failed to save generation history: {}", e))?;
+    file.write_all(line.as_bytes()).map_err(|e| format!("Failed to save generation history: {}", e))?;
+    if file.metadata().is_ok_and(|m| m.len() > MAX_FILE_BYTES) {
+        trim(&path)?;
+    }
+    Ok(())
+}
+
+/// Keeps the newest lines that fit in three quarters of [`MAX_FILE_BYTES`].
+fn trim(path: &Path) -> Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read generation history: {}", e))?;
+    let keep = (MAX_FILE_BYTES / 4 * 3) as usize;
+    let mut start = text.len();
+    for line in text.split_inclusive('\n').rev() {
+        if text.len() - start + line.len() > keep {
+            break;
+        }
+        start -= line.len();
+    }
+    write_atomic(path, None, &text.as_bytes()[start..]).map_err(String::from)
+}
+
+fn matches(record: &GenerationRecord, repo: Option<&str>, query: Option<&str>) -> bool {
+    repo.map_or(true, |r| record.repo == r)
+        && query.map_or(true, |q| [record.response.as_str(), &record.repo, &record.provider, record.model.as_deref().unwrap_or_default()].iter().any(|s| s.to_lowercase().contains(q)))
+}
+
+/// Stores a generation run so its answer can be found again without re-running it.
+/// Secrets in the response are redacted as the policy asks.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn record_generation(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    repo: String,
+    snapshot: Option<String>,
+    options: Option<serde_json::Value>,
+    provider: String,
+    model: Option<String>,
+    prompt: String,
+    response: String,
+    usage: Option<Usage>,
+) -> Result<GenerationSummary, AppError> {
+    if response.trim().is_empty() {
+        return Err(AppError::InvalidInput("Nothing to record: the response is empty".to_string()));
+    }
+    let prompt_hash = blake3::hash(prompt.as_bytes()).to_hex().to_string();
+    let created_at = usage::now_ms();
+    let record = GenerationRecord {
+        id: format!("{:x}-{}", created_at, &prompt_hash[..8]),
+        cost: usage.as_ref().and_then(|u| estimate_cost(&provider, model.as_deref().unwrap_or_default(), u)),
+        repo,
+        snapshot,
+        options: options.unwrap_or_default(),
+        provider,
+        model,
+        prompt_tokens: estimate_tokens(&prompt),
+        prompt_hash,
+        response: state.policy.redact(&response),
+        usage,
+        created_at,
+    };
+    let summary = record.summary();
+    tokio::task::spawn_blocking(move || append(&app, &record)).await?.map_err(AppError::Io)?;
+    Ok(summary)
+}
+
+/// Recorded runs, newest first, optionally only those of `repo` or containing `query`
+/// (case-insensitive, in the response, repository, provider or model).
+#[tauri::command]
+pub async fn list_history(app: AppHandle, repo: Option<String>, query: Option<String>, limit: Option<usize>, offset: Option<usize>) -> Result<HistoryPage, AppError> {
+    let query = query.map(|q| q.trim().to_lowercase()).filter(|q| !q.is_empty());
+    let records = tokio::task::spawn_blocking(move || load(&app)).await?.map_err(AppError::Io)?;
+    let matching: Vec<&GenerationRecord> = records.iter().rev().filter(|r| matches(r, repo.as_deref(), query.as_deref())).collect();
+    let items = matching.iter().skip(offset.unwrap_or(0)).take(limit.unwrap_or(DEFAULT_LIMIT)).map(|r| r.summary()).collect();
+    Ok(HistoryPage { items, total: matching.len() })
+}
+
+/// A recorded run with its full response.
+#[tauri::command]
+pub async fn get_history_item(app: AppHandle, id: String) -> Result<GenerationRecord, AppError> {
+    let records = tokio::task::spawn_blocking(move || load(&app)).await?.map_err(AppError::Io)?;
+    records.into_iter().find(|r| r.id == id).ok_or_else(|| AppError::NotFound(format!("Generation not found: {}", id)))
+}
+
+/// Writes recorded runs to `path`: the ones in `ids`, or all those of `repo`, or all.
+/// `format` is `jsonl` (default), `json` or `md`. Oldest first. An existing file is only
+/// replaced with `overwrite`.
+#[tauri::command]
+pub async fn export_history(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    format: Option<String>,
+    repo: Option<String>,
+    ids: Option<Vec<String>>,
+    overwrite: Option<bool>,
+) -> Result<HistoryExport, AppError> {
+    let target = PathBuf::from(&path);
+    state.policy.check_export(&target)?;
+    if target.is_dir() {
+        return Err(AppError::InvalidInput(format!("{} is a directory", path)));
+    }
+    if target.exists() && !overwrite.unwrap_or(false) {
+        return Err(AppError::InvalidInput(format!("{} already exists; pass overwrite to replace it", path)));
+    }
+    let records: Vec<GenerationRecord> = tokio::task::spawn_blocking(move || load(&app))
+        .await?
+        .map_err(AppError::Io)?
+        .into_iter()
+        .filter(|r| ids.as_ref().map_or(true, |ids| ids.contains(&r.id)) && matches(r, repo.as_deref(), None))
+        .collect();
+    let text = match format.as_deref().map(|f| f.trim().to_lowercase()).as_deref().unwrap_or("jsonl") {
+        "jsonl" => to_lines(&records)?,
+        "json" => serde_json::to_string_pretty(&records).map_err(|e| e.to_string())?,
+        "md" | "markdown" => {
+            let sections: Vec<String> = records.iter().map(GenerationRecord::markdown).collect();
+            format!("# Generation history\n\n{}", sections.join("\n"))
+        }
+        other => return Err(AppError::InvalidInput(format!("Unknown export format: {} (expected jsonl, json or md)", other))),
+    };
+    let data = text.into_bytes();
+    let bytes_written = data.len() as u64;
+    tokio::task::spawn_blocking(move || write_atomic(&target, None, &data)).await??;
+    Ok(HistoryExport { path, entries: records.len(), bytes_written })
+}
@@ -0,0 +1,336 @@
+use isahc::config::{Configurable, RedirectPolicy};
+use crate::cache::DiskCache;
+use crate::error::AppError;
+use crate::retry;
+use isahc::http::{HeaderMap, StatusCode};
+use isahc::prelude::*;
+use isahc::{AsyncBody, HttpClient, Response};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use repo_prompt_core::github::{decode_content, extract_tarball, TarballContents};
+pub use repo_prompt_core::git::{is_full_sha, validate_ref};
+pub use repo_prompt_core::github::blob_url;
+pub use repo_prompt_core::paths::encode_path;
+
+const API_ROOT: &str = "https://api.github.com";
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+/// Waits longer than this are reported as errors instead of silently blocking the fetch.
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(90);
+
+/// Parallel directory listings when a truncated tree has to be walked by hand.
+const TREE_WALK_CONCURRENCY: usize = 8;
+
+/// Quota information from the most recent GitHub response.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitInfo {
+    pub limit: Option<u32>,
+    pub remaining: Option<u32>,
+    /// Unix timestamp (seconds) at which the quota resets.
+    pub reset_at: Option<u64>,
+    /// Unauthenticated clients get 60 requests/hour instead of 5000.
+    pub authenticated: bool,
+}
+
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// How long to wait before retrying a 403/429, or `None` if the response isn't a rate limit
+/// (e.g. a 403 for a private repository).
+fn rate_limit_wait(headers: &HeaderMap) -> Option<Duration> {
+    if let Some(secs) = header_u64(headers, "retry-after") {
+        return Some(Duration::from_secs(secs.max(1)));
+    }
+    if header_u64(headers, "x-ratelimit-remaining") == Some(0) {
+        let reset = header_u64(headers, "x-ratelimit-reset").unwrap_or(0);
+        return Some(Duration::from_secs(reset.saturating_sub(now_secs()).max(1)));
+    }
+    None
+}
+
+fn buffered_response(status: StatusCode, headers: &HeaderMap, body: Vec<u8>) -> Response<AsyncBody> {
+    let mut res = Response::new(AsyncBody::from(body));
+    *res.status_mut() = status;
+    *res.headers_mut() = headers.clone();
+    res
+}
+
+/// Thin wrapper used for every GitHub API call: adds auth/headers, tracks the rate-limit
+/// headers and transparently waits out short rate limits.
+pub struct GithubClient {
+    http: HttpClient,
+    token: String,
+    rate_limit: Mutex<RateLimitInfo>,
+    cache: Option<DiskCache>,
+}
+
+impl GithubClient {
+    pub fn new(http: HttpClient, token: String) -> Self {
+        let rate_limit = Mutex::new(RateLimitInfo { authenticated: !token.is_empty(), ..Default::default() });
+        Self { http, token, rate_limit, cache: None }
+    }
+
+    /// Enables the ETag cache: responses are stored on disk and revalidated with
+    /// `If-None-Match`, so unchanged resources come back as 304s that cost no quota.
+    pub fn with_cache(mut self, cache: Option<DiskCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Returns the cached (etag, body) pair for `url`, if both are present.
+    fn cached(&self, url: &str) -> Option<(String, Vec<u8>)> {
+        let cache = self.cache.as_ref()?;
+        let etag = String::from_utf8(cache.get(&format!("etag:{}", url))?).ok()?;
+        let body = cache.get(&format!("body:{}", url))?;
+        Some((etag, body))
+    }
+
+    /// Serves 304s from the cache and stores fresh 200s that carry an ETag.
+    async fn apply_cache(&self, url: &str, cached: Option<(String, Vec<u8>)>, mut res: Response<AsyncBody>) -> Result<Response<AsyncBody>, String> {
+        let Some(cache) = &self.cache else { return Ok(res) };
+        if res.status() == StatusCode::NOT_MODIFIED {
+            if let Some((_, body)) = cached {
+                return Ok(buffered_response(StatusCode::OK, res.headers(), body));
+            }
+        }
+        let etag = res.headers().get("etag").and_then(|v| v.to_str().ok()).map(|v| v.to_string());
+        match etag {
+            Some(etag) if res.status() == StatusCode::OK => {
+                let body = res.bytes().await.map_err(|e| e.to_string())?;
+                if cache.put(&format!("body:{}", url), &body).is_ok() {
+                    let _ = cache.put(&format!("etag:{}", url), etag.as_bytes());
+                }
+                Ok(buffered_response(res.status(), res.headers(), body))
+            }
+            _ => Ok(res),
+        }
+    }
+
+    pub fn rate_limit(&self) -> RateLimitInfo {
+        self.rate_limit.lock().unwrap().clone()
+    }
+
+    fn record_rate_limit(&self, headers: &HeaderMap) {
+        let mut info = self.rate_limit.lock().unwrap();
+        if let Some(limit) = header_u64(headers, "x-ratelimit-limit") {
+            info.limit = Some(limit as u32);
+        }
+        if let Some(remaining) = header_u64(headers, "x-ratelimit-remaining") {
+            info.remaining = Some(remaining as u32);
+        }
+        if let Some(reset) = header_u64(headers, "x-ratelimit-reset") {
+            info.reset_at = Some(reset);
+        }
+    }
+
+    fn builder(&self, method: &str, url: &str) -> isahc::http::request::Builder {
+        let builder = isahc::Request::builder()
+            .method(method)
+            .uri(url)
+            .redirect_policy(RedirectPolicy::Follow)
+            .header("Accept", "application/vnd.github.v3+json")
+            .header("User-Agent", "Tauri/Prompt-Generator");
+        if self.token.is_empty() {
+            builder
+        } else {
+            builder.header("Authorization", format!("token {}", self.token))
+        }
+    }
+
+    fn request(&self, url: &str, etag: Option<&str>) -> Result<isahc::Request<()>, isahc::http::Error> {
+        let mut builder = self.builder("GET", url);
+        if let Some(etag) = etag {
+            builder = builder.header("If-None-Match", etag);
+        }
+        builder.body(())
+    }
+
+    /// GETs `path` (relative to the API root, or an absolute URL), retrying on rate limits
+    /// and transient failures.
+    pub async fn get(&self, path: &str) -> Result<Response<AsyncBody>, AppError> {
+        let url = if path.starts_with("http") { path.to_string() } else { format!("{}{}", API_ROOT, path) };
+        let cached = self.cached(&url);
+        let mut attempt = 0;
+        loop {
+            let etag = cached.as_ref().map(|(etag, _)| etag.as_str());
+            let res = retry::send(&self.http, "GitHub", true, || self.request(&url, etag)).await?;
+            self.record_rate_limit(res.headers());
+
+            let status = res.status().as_u16();
+            if (status == 403 || status == 429) && attempt < MAX_RATE_LIMIT_RETRIES {
+                if let Some(wait) = rate_limit_wait(res.headers()) {
+                    if wait > MAX_RATE_LIMIT_WAIT {
+                        let message = format!(
+                            "GitHub API rate limit exceeded; it resets in {} minutes.{}",
+                            wait.as_secs().div_ceil(60),
+                            if self.token.is_empty() { " Add a GitHub token to raise the limit from 60 to 5000 requests per hour." } else { "" }
+                        );
+                        return Err(AppError::RateLimited { message, retry_after_secs: Some(wait.as_secs()) });
+                    }
+                    attempt += 1;
+                    tokio::time::sleep(wait).await;
+                    continue;
+                }
+            }
+            return Ok(self.apply_cache(&url, cached, res).await?);
+        }
+    }
+
+    /// GETs `path` and parses the body as JSON, failing on non-success statuses.
+    pub async fn get_json(&self, path: &str) -> Result<serde_json::Value, AppError> {
+        let mut res = self.get(path).await?;
+        let status = res.status();
+        let text = res.text().await?;
+        if !status.is_success() {
+            return Err(AppError::from_status(status.as_u16(), format!("GitHub API error ({}): {}", status, text), rate_limit_wait(res.headers()).map(|w| w.as_secs())));
+        }
+        serde_json::from_str(&text).map_err(|e| AppError::Provider { status: None, message: e.to_string() })
+    }
+
+    /// Lists every file path at `git_ref` under `within` (a `dir/` prefix, or empty for the
+    /// whole repository). The recursive trees API gives up on very large repositories and
+    /// sets `truncated`; the tree is then walked one directory at a time instead.
+    pub async fn fetch_tree(&self, owner: &str, repo: &str, git_ref: &str, within: &str, progress: impl Fn(String)) -> Result<Vec<String>, AppError> {
+        use futures_util::stream::{self, StreamExt};
+
+        let json = self
+            .get_json(&format!("/repos/{}/{}/git/trees/{}?recursive=1", owner, repo, urlencoding::encode(git_ref)))
+            .await?;
+        let blobs = |json: &serde_json::Value| -> Vec<String> {
+            json["tree"]
+                .as_array()
+                .map(|a| a.iter().filter(|i| i["type"] == "blob").filter_map(|i| i["path"].as_str().map(|s| s.to_string())).collect())
+                .unwrap_or_default()
+        };
+        if !json["truncated"].as_bool().unwrap_or(false) {
+            return Ok(blobs(&json));
+        }
+
+        progress("File tree is too large for a single listing, walking directories individually".to_string());
+        let root_sha = json["sha"]
+            .as_str()
+            .ok_or_else(|| AppError::Provider { status: None, message: "Tree response has no SHA".to_string() })?
+            .to_string();
+        // Only descend into directories that lead to, or lie inside, `within`.
+        let wanted = |dir: &str| {
+            let dir = format!("{}/", dir);
+            within.starts_with(&dir) || dir.starts_with(within)
+        };
+
+        let mut paths = Vec::new();
+        let mut pending = vec![(String::new(), root_sha)];
+        while !pending.is_empty() {
+            let listings: Vec<Result<(String, serde_json::Value), AppError>> = stream::iter(std::mem::take(&mut pending))
+                .map(|(dir, sha)| async move {
+                    self.get_json(&format!("/repos/{}/{}/git/trees/{}", owner, repo, sha)).await.map(|json| (dir, json))
+                })
+                .buffer_unordered(TREE_WALK_CONCURRENCY)
+                .collect()
+                .await;
+
+            for listing in listings {
+                let (dir, json) = listing?;
+                for item in json["tree"].as_array().into_iter().flatten() {
+                    let (Some(name), Some(sha)) = (item["path"].as_str(), item["sha"].as_str()) else { continue };
+                    let path = if dir.is_empty() { name.to_string() } else { format!("{}/{}", dir, name) };
+                    match item["type"].as_str() {
+                        Some("blob") if path.starts_with(within) => paths.push(path),
+                        Some("tree") if wanted(&path) => pending.push((path, sha.to_string())),
+                        _ => {}
+                    }
+                }
+            }
+            progress(format!("Listed {} files, {} directories left", paths.len(), pending.len()));
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// POSTs a JSON body to `path` and returns the parsed response. Writes are not retried
+    /// on rate limits, timeouts or 5xx responses since they may not be idempotent.
+    pub async fn post_json(&self, path: &str, body: &serde_json::Value) -> Result<serde_json::Value, AppError> {
+        let url = format!("{}{}", API_ROOT, path);
+        let make = || self.builder("POST", &url).header("Content-Type", "application/json").body(body.to_string());
+        let mut res = retry::send(&self.http, "GitHub", false, make).await?;
+        self.record_rate_limit(res.headers());
+        let status = res.status();
+        let text = res.text().await?;
+        if !status.is_success() {
+            return Err(AppError::from_status(status.as_u16(), format!("GitHub API error ({}): {}", status, text), rate_limit_wait(res.headers()).map(|w| w.as_secs())));
+        }
+        serde_json::from_str(&text).map_err(|e| AppError::Provider { status: None, message: e.to_string() })
+    }
+
+    /// Resolves a branch, tag or commit SHA to the full commit SHA, with a readable error
+    /// when the ref doesn't exist.
+    pub async fn resolve_ref(&self, owner: &str, repo: &str, git_ref: &str) -> Result<String, AppError> {
+        validate_ref(git_ref).map_err(AppError::InvalidInput)?;
+        let mut res = self.get(&format!("/repos/{}/{}/commits/{}", owner, repo, urlencoding::encode(git_ref))).await?;
+        let status = res.status().as_u16();
+        if status == 404 || status == 422 {
+            return Err(AppError::NotFound(format!("Ref '{}' was not found in {}/{}. Check the branch, tag or commit SHA.", git_ref, owner, repo)));
+        }
+        if !res.status().is_success() {
+            return Err(AppError::from_status(status, format!("Failed to resolve ref '{}': {}", git_ref, res.status()), rate_limit_wait(res.headers()).map(|w| w.as_secs())));
+        }
+        let json: serde_json::Value = serde_json::from_str(&res.text().await?).map_err(|e| AppError::Provider { status: None, message: e.to_string() })?;
+        json["sha"]
+            .as_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| AppError::Provider { status: None, message: format!("Failed to resolve ref '{}'", git_ref) })
+    }
+
+    /// Fetches one file through the contents API and decodes it. Returns `None` on any
+    /// failure so a single missing file doesn't abort the whole fetch.
+    pub async fn fetch_file_content(&self, owner: &str, repo: &str, path: &str, git_ref: &str) -> Option<String> {
+        let json = self.get_json(&format!("/repos/{}/{}/contents/{}{}", owner, repo, path, ref_query(git_ref))).await.ok()?;
+        decode_content(&json)
+    }
+
+    /// Fetches the README of `dir` (the repository root when `None`).
+    pub async fn fetch_readme(&self, owner: &str, repo: &str, git_ref: &str, dir: Option<&str>) -> Option<String> {
+        let dir = dir.map(|d| format!("/{}", d)).unwrap_or_default();
+        let json = self.get_json(&format!("/repos/{}/{}/readme{}{}", owner, repo, dir, ref_query(git_ref))).await.ok()?;
+        decode_content(&json)
+    }
+
+    /// Downloads `owner/repo` at `git_ref` in a single request into `work_dir`, then
+    /// unpacks the text files into memory.
+    pub async fn fetch_tarball(&self, owner: &str, repo: &str, git_ref: &str, work_dir: &Path) -> Result<TarballContents, String> {
+        let mut res = self
+            .get(&format!("/repos/{}/{}/tarball/{}", owner, repo, git_ref))
+            .await
+            .map_err(|e| format!("Failed to download tarball: {}", e))?;
+        if !res.status().is_success() {
+            return Err(format!("Failed to download tarball: {}", res.status()));
+        }
+        let archive_path = work_dir.join("archive.tar.gz");
+        let bytes = res.bytes().await.map_err(|e| format!("Failed to download tarball: {}", e))?;
+        tokio::fs::write(&archive_path, &bytes).await.map_err(|e| format!("Failed to write tarball: {}", e))?;
+        drop(bytes);
+
+        tokio::task::spawn_blocking(move || {
+            let file = std::fs::File::open(&archive_path).map_err(|e| format!("Failed to open tarball: {}", e))?;
+            extract_tarball(std::io::BufReader::new(file))
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+}
+
+fn ref_query(git_ref: &str) -> String {
+    if git_ref.is_empty() {
+        String::new()
+    } else {
+        format!("?ref={}", urlencoding::encode(git_ref))
+    }
+}
+
@@ -0,0 +1,258 @@
+use crate::error::AppError;
+use crate::github::{GithubClient, RateLimitInfo};
+use crate::{log_status, retry, usage, AppState};
+use isahc::AsyncReadResponseExt;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, State};
+
+const DEVICE_CODE_URL: &str = "https://github.com/login/device/code";
+const ACCESS_TOKEN_URL: &str = "https://github.com/login/oauth/access_token";
+/// OAuth app used for the device flow when the command isn't given one.
+const CLIENT_ID_ENV: &str = "GITHUB_OAUTH_CLIENT_ID";
+/// Read access to private repositories.
+const DEFAULT_SCOPES: &str = "repo";
+/// Tokens expiring sooner than this get a warning.
+const EXPIRY_WARNING_DAYS: i64 = 7;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GithubTokenInfo {
+    login: String,
+    name: Option<String>,
+    /// `classic`, `fine-grained`, `oauth`, `app` or `unknown`, from the token's prefix.
+    kind: String,
+    /// OAuth scopes of a classic or OAuth token. Fine-grained tokens have per-repository
+    /// permissions instead and report none.
+    scopes: Option<Vec<String>>,
+    /// Whether private repositories can be read; `None` when that depends on
+    /// per-repository permissions.
+    private_repos: Option<bool>,
+    /// When the token expires, as GitHub reports it (`2026-11-01 12:00:00 UTC`).
+    expires_at: Option<String>,
+    rate_limit: RateLimitInfo,
+    /// `standard` (5000 requests/hour), `enterprise` (15000) or `unknown`.
+    rate_limit_tier: String,
+    warnings: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceCode {
+    device_code: String,
+    /// Code the user enters at `verification_uri`.
+    user_code: String,
+    verification_uri: String,
+    expires_in: u64,
+    /// Seconds to wait between polls.
+    interval: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GithubSignIn {
+    token: String,
+    info: GithubTokenInfo,
+}
+
+fn token_kind(token: &str) -> &'static str {
+    match token.split('_').next().unwrap_or_default() {
+        "ghp" => "classic",
+        "github" if token.starts_with("github_pat_") => "fine-grained",
+        "gho" | "ghu" => "oauth",
+        "ghs" => "app",
+        _ => "unknown",
+    }
+}
+
+fn rate_limit_tier(limit: Option<u32>) -> &'static str {
+    match limit {
+        Some(l) if l >= 15_000 => "enterprise",
+        Some(l) if l >= 5_000 => "standard",
+        _ => "unknown",
+    }
+}
+
+/// Days until a `github-authentication-token-expiration` timestamp (`2026-11-01 12:00:00 UTC`).
+fn days_until(expiration: &str) -> Option<i64> {
+    let date = expiration.split_whitespace().next()?;
+    let mut parts = date.split('-').map(|p| p.parse::<i64>().ok());
+    let (y, m, d) = (parts.next()??, parts.next()??, parts.next()??);
+    // Days since the epoch of a proleptic Gregorian date (Howard Hinnant's algorithm).
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (m + if m > 2 { -3 } else { 9 }) + 2) / 5 + d - 1;
+    let days = era * 146_097 + yoe * 365 + yoe / 4 - yoe / 100 + doy - 719_468;
+    Some(days - usage::now_ms() as i64 / 86_400_000)
+}
+
+async fn inspect_token(state: &AppState, token: &str) -> Result<GithubTokenInfo, AppError> {
+    let gh = GithubClient::new(state.http_client.read().await.clone(), token.to_string());
+    let mut res = gh.get("/user").await?;
+    let status = res.status().as_u16();
+    let text = res.text().await?;
+    if status == 401 {
+        return Err(AppError::Unauthorized("GitHub rejected the token: it is invalid, expired or revoked. Create a new one or sign in again.".to_string()));
+    }
+    if !(200..300).contains(&status) {
+        return Err(AppError::from_status(status, format!("GitHub API error ({}): {}", status, text), None));
+    }
+    let user: serde_json::Value = serde_json::from_str(&text).map_err(|e| AppError::Provider { status: None, message: e.to_string() })?;
+    let header = |name: &str| res.headers().get(name).and_then(|v| v.to_str().ok()).map(|v| v.trim().to_string());
+
+    let kind = token_kind(token);
+    let scopes: Option<Vec<String>> = header("x-oauth-scopes").map(|s| s.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect());
+    let private_repos = match (&scopes, kind) {
+        (_, "fine-grained" | "app") => None,
+        (Some(scopes), _) => Some(scopes.iter().any(|s| s == "repo")),
+        (None, _) => None,
+    };
+    let expires_at = header("github-authentication-token-expiration");
+    let rate_limit = gh.rate_limit();
+
+    let mut warnings = Vec::new();
+    if private_repos == Some(false) {
+        warnings.push("The token has no `repo` scope, so only public repositories can be read.".to_string());
+    }
+    if let Some(days) = expires_at.as_deref().and_then(days_until).filter(|d| *d <= EXPIRY_WARNING_DAYS) {
+        warnings.push(if days < 1 { "The token expires today.".to_string() } else { format!("The token expires in {} days.", days) });
+    }
+    if rate_limit.remaining.is_some_and(|r| r < 100) {
+        warnings.push(format!("Only {} API requests are left until the quota resets.", rate_limit.remaining.unwrap_or_default()));
+    }
+    Ok(GithubTokenInfo {
+        login: user["login"].as_str().unwrap_or_default().to_string(),
+        name: user["name"].as_str().map(str::to_string),
+        kind: kind.to_string(),
+        scopes,
+        private_repos,
+        expires_at,
+        rate_limit_tier: rate_limit_tier(rate_limit.limit).to_string(),
+        rate_limit,
+        warnings,
+    })
+}
+
+/// Checks a GitHub token against `/user` before it is used for fetching: who it belongs
+/// to, its kind and scopes, when it expires and its rate limit, with warnings for tokens
+/// that can't read private repositories or are about to expire. A bad token fails with
+/// an `unauthorized` error.
+#[tauri::command]
+pub async fn validate_github_token(app: AppHandle, state: State<'_, AppState>, token: String) -> Result<GithubTokenInfo, AppError> {
+    let token = token.trim();
+    if token.is_empty() {
+        return Err(AppError::MissingCredentials("Enter a GitHub token to check".to_string()));
+    }
+    state.policy.check_not_demo("GitHub access")?;
+    let info = inspect_token(&state, token).await?;
+    log_status(&app, format!("GitHub token is valid for {}", info.login));
+    Ok(info)
+}
+
+fn client_id(client_id: Option<String>) -> Result<String, AppError> {
+    client_id
+        .or_else(|| std::env::var(CLIENT_ID_ENV).ok())
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| AppError::MissingCredentials(format!("Signing in with GitHub needs the client ID of an OAuth app with device flow enabled (or the {} environment variable)", CLIENT_ID_ENV)))
+}
+
+/// POSTs a form to one of GitHub's OAuth endpoints, which answer errors with a 200 and
+/// an `error` field as often as with an error status.
+async fn post_form(state: &AppState, url: &str, form: &[(&str, &str)]) -> Result<serde_json::Value, AppError> {
+    let body = form.iter().map(|(k, v)| format!("{}={}", k, urlencoding::encode(v))).collect::<Vec<_>>().join("&");
+    let make = || {
+        isahc::Request::builder()
+            .method("POST")
+            .uri(url)
+            .header("Accept", "application/json")
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("User-Agent", "Tauri/Prompt-Generator")
+            .body(body.clone())
+    };
+    let client = state.http_client.read().await.clone();
+    let mut res = retry::send(&client, "GitHub", false, make).await?;
+    let status = res.status().as_u16();
+    let text = res.text().await?;
+    if !(200..300).contains(&status) {
+        return Err(AppError::from_status(status, format!("GitHub sign-in failed ({}): {}", status, text), None));
+    }
+    serde_json::from_str(&text).map_err(|e| AppError::Provider { status: None, message: e.to_string() })
+}
+
+/// Starts signing in with GitHub's device flow: returns the code the user enters at
+/// `verificationUri` (https://github.com/login/device). Pass `deviceCode` to
+/// `finish_github_device_flow` to wait for the token. `scopes` default to `repo`.
+#[tauri::command]
+pub async fn start_github_device_flow(app: AppHandle, state: State<'_, AppState>, client_id: Option<String>, scopes: Option<Vec<String>>) -> Result<DeviceCode, AppError> {
+    state.policy.check_not_demo("GitHub access")?;
+    let client_id = self::client_id(client_id)?;
+    let scopes = scopes.filter(|s| !s.is_empty()).map(|s| s.join(" ")).unwrap_or_else(|| DEFAULT_SCOPES.to_string());
+    let json = post_form(&state, DEVICE_CODE_URL, &[("client_id", &client_id), ("scope", &scopes)]).await?;
+    if let Some(error) = json["error"].as_str() {
+        let message = json["error_description"].as_str().unwrap_or(error);
+        return Err(AppError::InvalidInput(format!("GitHub refused to start the sign-in: {}", message)));
+    }
+    let code = DeviceCode {
+        device_code: json["device_code"].as_str().unwrap_or_default().to_string(),
+        user_code: json["user_code"].as_str().unwrap_or_default().to_string(),
+        verification_uri: json["verification_uri"].as_str().unwrap_or("https://github.com/login/device").to_string(),
+        expires_in: json["expires_in"].as_u64().unwrap_or(900),
+        interval: json["interval"].as_u64().unwrap_or(5),
+    };
+    if code.device_code.is_empty() {
+        return Err(AppError::Provider { status: None, message: "GitHub did not return a device code".to_string() });
+    }
+    log_status(&app, format!("Enter code {} at {} to sign in", code.user_code, code.verification_uri));
+    Ok(code)
+}
+
+async fn poll_for_token(state: &AppState, client_id: &str, device_code: &str, mut interval: u64) -> Result<String, AppError> {
+    loop {
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+        let json = post_form(state, ACCESS_TOKEN_URL, &[("client_id", client_id), ("device_code", device_code), ("grant_type", "urn:ietf:params:oauth:grant-type:device_code")]).await?;
+        match json["error"].as_str() {
+            None => {
+                return json["access_token"].as_str().map(str::to_string).ok_or_else(|| AppError::Provider { status: None, message: "GitHub did not return a token".to_string() });
+            }
+            Some("authorization_pending") => {}
+            // GitHub asks for a longer interval and says which.
+            Some("slow_down") => interval = json["interval"].as_u64().unwrap_or(interval + 5),
+            Some("expired_token") => return Err(AppError::Timeout("The sign-in code expired before it was entered; start again".to_string())),
+            Some("access_denied") => return Err(AppError::Unauthorized("Sign-in was cancelled on GitHub".to_string())),
+            Some(error) => {
+                let message = json["error_description"].as_str().unwrap_or(error);
+                return Err(AppError::InvalidInput(format!("GitHub sign-in failed: {}", message)));
+            }
+        }
+    }
+}
+
+/// Waits until the user has entered the code from `start_github_device_flow`, then returns
+/// the token with what `validate_github_token` reports about it. Cancel it with
+/// `cancel_fetch` and `operationId`.
+#[tauri::command]
+pub async fn finish_github_device_flow(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    device_code: String,
+    client_id: Option<String>,
+    interval: Option<u64>,
+    operation_id: Option<String>,
+) -> Result<GithubSignIn, AppError> {
+    state.policy.check_not_demo("GitHub access")?;
+    let client_id = self::client_id(client_id)?;
+    let operation_id = operation_id.map(|id| id.trim().to_string()).filter(|id| !id.is_empty()).unwrap_or_else(|| state.cancellations.new_id("github-sign-in"));
+    let cancel = state.cancellations.register(&operation_id);
+    let result = tokio::select! {
+        biased;
+        _ = cancel.cancelled() => Err(AppError::Cancelled("Signing in with GitHub was cancelled".to_string())),
+        result = poll_for_token(&state, &client_id, device_code.trim(), interval.unwrap_or(5).max(1)) => result,
+    };
+    state.cancellations.finish(&operation_id, &cancel);
+    let token = result?;
+    let info = inspect_token(&state, &token).await?;
+    log_status(&app, format!("Signed in to GitHub as {}", info.login));
+    Ok(GithubSignIn { token, info })
+}
@@ -0,0 +1,150 @@
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+const HISTORY_FILE: &str = "question_history.json";
+/// Oldest answers are dropped past this many.
+const MAX_ENTRIES: usize = 2000;
+const DEFAULT_SIMILARITY: f64 = 0.8;
+
+/// Words that say nothing about what is being asked.
+const STOPWORDS: &[&str] = &[
+    "a", "an", "the", "is", "are", "was", "were", "be", "do", "does", "did", "how", "what", "why", "where", "which", "who",
+    "can", "could", "should", "would", "i", "we", "you", "it", "this", "that", "of", "in", "on", "to", "for", "with", "and",
+    "or", "me", "my", "our", "please", "there", "here", "from", "by", "about", "into", "at", "as",
+];
+
+/// A question answered on a specific repository snapshot.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AnsweredQuestion {
+    /// `owner/repo` or the local path.
+    repo: String,
+    /// Commit SHA (or other snapshot id) the answer was given for.
+    snapshot: String,
+    question: String,
+    answer: String,
+    provider: Option<String>,
+    model: Option<String>,
+    /// Unix seconds.
+    created_at: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarQuestion {
+    #[serde(flatten)]
+    previous: AnsweredQuestion,
+    /// 0.0 to 1.0.
+    similarity: f64,
+}
+
+fn history_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| format!("Failed to resolve data dir: {}", e))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+    Ok(dir.join(HISTORY_FILE))
+}
+
+fn load_history(app: &AppHandle) -> Result<Vec<AnsweredQuestion>, String> {
+    match fs::read_to_string(history_path(app)?) {
+        Ok(text) => serde_json::from_str(&text).map_err(|e| format!("Question history is corrupt: {}", e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to read question history: {}", e)),
+    }
+}
+
+fn store_history(app: &AppHandle, entries: &[AnsweredQuestion]) -> Result<(), String> {
+    let path = history_path(app)?;
+    let tmp = path.with_extension("json.tmp");
+    let text = serde_json::to_string(entries).map_err(|e| e.to_string())?;
+    fs::write(&tmp, text).map_err(|e| format!("Failed to save question history: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to save question history: {}", e))
+}
+
+/// Content words of a question, lowercased and with common suffixes stripped, counted.
+fn terms(text: &str) -> HashMap<String, f64> {
+    let mut counts = HashMap::new();
+    for word in text.to_lowercase().split(|c: char| !c.is_alphanumeric() && c != '_') {
+        if word.len() < 2 || STOPWORDS.contains(&word) {
+            continue;
+        }
+        let stem = ["ing", "ed", "s"]
+            .iter()
+            .find_map(|suffix| word.strip_suffix(suffix).filter(|s| s.len() >= 3))
+            .unwrap_or(word);
+        *counts.entry(stem.to_string()).or_insert(0.0) += 1.0;
+    }
+    counts
+}
+
+/// Cosine similarity of the two questions' term counts. Insensitive to word order,
+/// phrasing filler and simple inflections; it doesn't catch synonyms.
+fn similarity(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+    let dot: f64 = a.iter().filter_map(|(t, x)| b.get(t).map(|y| x * y)).sum();
+    let norm = |v: &HashMap<String, f64>| v.values().map(|x| x * x).sum::<f64>().sqrt();
+    let denom = norm(a) * norm(b);
+    if denom == 0.0 { 0.0 } else { dot / denom }
+}
+
+/// Stores an answer so the same question on the same snapshot can be answered from history.
+#[tauri::command]
+pub fn record_answer(
+    app: AppHandle,
+    repo: String,
+    snapshot: String,
+    question: String,
+    answer: String,
+    provider: Option<String>,
+    model: Option<String>,
+) -> Result<(), AppError> {
+    if question.trim().is_empty() || answer.trim().is_empty() {
+        return Ok(());
+    }
+    let mut entries = load_history(&app)?;
+    entries.push(AnsweredQuestion {
+        repo,
+        snapshot,
+        question,
+        answer,
+        provider,
+        model,
+        created_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default(),
+    });
+    if entries.len() > MAX_ENTRIES {
+        entries.drain(..entries.len() - MAX_ENTRIES);
+    }
+    Ok(store_history(&app, &entries)?)
+}
+
+/// Before a question is sent, looks for an earlier question on the same repository
+/// snapshot that is worded nearly the same and returns its answer as a suggestion.
+/// `threshold` is the minimum similarity (default 0.8).
+#[tauri::command]
+pub fn find_similar_question(
+    app: AppHandle,
+    repo: String,
+    snapshot: String,
+    question: String,
+    threshold: Option<f64>,
+) -> Result<Option<SimilarQuestion>, AppError> {
+    let threshold = threshold.unwrap_or(DEFAULT_SIMILARITY).clamp(0.0, 1.0);
+    let wanted = terms(&question);
+    if wanted.is_empty() {
+        return Ok(None);
+    }
+    let best = load_history(&app)?
+        .into_iter()
+        .filter(|e| e.repo == repo && e.snapshot == snapshot)
+        .map(|e| {
+            let score = similarity(&wanted, &terms(&e.question));
+            (e, score)
+        })
+        .filter(|(_, score)| *score >= threshold)
+        // Latest answer wins a tie.
+        .max_by(|(a, x), (b, y)| x.total_cmp(y).then(a.created_at.cmp(&b.created_at)));
+    Ok(best.map(|(previous, similarity)| SimilarQuestion { previous, similarity }))
+}
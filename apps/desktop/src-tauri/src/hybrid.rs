@@ -0,0 +1,76 @@
+use crate::error::AppError;
+use crate::{fetch_github_repo, log_status, scan_local_repository, AppState, GithubRepoData};
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HybridFile {
+    /// `local` or `remote`.
+    origin: &'static str,
+    /// Which project the file belongs to: the local folder name or `owner/repo@sha`.
+    source: String,
+    path: String,
+    content: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HybridProject {
+    /// Local files first, then the remote ones.
+    files: Vec<HybridFile>,
+    /// Everything fetched for the remote repository (tree, README, dependencies, ...)
+    /// except its source files, which are in `files`.
+    remote: GithubRepoData,
+    /// Both file sets as one context, each file headed by its origin.
+    packed: String,
+}
+
+/// Loads a project that is partly local (e.g. uncommitted work) and partly a remote
+/// dependency repository: the local directory is scanned while the GitHub repository is
+/// fetched, and the two are merged with an origin label on every file.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn load_hybrid_project(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    local_path: String,
+    local_subpath: Option<String>,
+    owner: String,
+    repo: String,
+    git_ref: Option<String>,
+    subpath: Option<String>,
+    token: Option<String>,
+    max_files: Option<u32>,
+) -> Result<HybridProject, AppError> {
+    let local_source = std::path::Path::new(&local_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| local_path.clone());
+    log_status(&app, format!("Loading {} together with {}/{}", local_source, owner, repo));
+
+    let (local, remote) = tokio::join!(
+        scan_local_repository(app.clone(), state.clone(), local_path, local_subpath, None),
+        fetch_github_repo(app.clone(), state.clone(), owner, repo, None, token, max_files, None, None, git_ref, subpath, None, None, None, None, None, None),
+    );
+    let (local, mut remote) = (local?, remote?);
+    let remote_source = format!("{}/{}@{}", remote.info.owner, remote.info.repo, &remote.info.commit_sha[..7.min(remote.info.commit_sha.len())]);
+
+    let mut files: Vec<HybridFile> = local
+        .into_iter()
+        .map(|f| HybridFile { origin: "local", source: local_source.clone(), path: f.path, content: f.content })
+        .collect();
+    files.extend(
+        std::mem::take(&mut remote.source_files)
+            .into_iter()
+            .map(|f| HybridFile { origin: "remote", source: remote_source.clone(), path: f.path, content: f.content }),
+    );
+
+    let mut packed = String::new();
+    for f in &files {
+        packed.push_str(&format!("\n--- {} ({}: {}) ---\n{}\n", f.path, f.origin, f.source, f.content));
+    }
+    let local_count = files.iter().filter(|f| f.origin == "local").count();
+    log_status(&app, format!("Merged {} local and {} remote files", local_count, files.len() - local_count));
+    Ok(HybridProject { files, remote, packed })
+}
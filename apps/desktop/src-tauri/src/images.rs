@@ -0,0 +1,75 @@
+use crate::error::AppError;
+use crate::AppState;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+/// Inline image payloads above this size are rejected by most providers.
+const MAX_IMAGE_BYTES: usize = 15 * 1024 * 1024;
+const MAX_IMAGES: usize = 8;
+
+/// An image attached to a prompt, already base64-encoded for the provider APIs.
+/// OpenAI-compatible providers take it as `data:{mimeType};base64,{data}`.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageAttachment {
+    pub mime_type: String,
+    /// Base64 (standard alphabet, no data-URL prefix).
+    pub data: String,
+    /// Size of the decoded image in bytes.
+    #[serde(default)]
+    pub size: usize,
+}
+
+fn sniff_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// Validates raw image bytes and encodes them as an attachment.
+pub fn encode_image(bytes: &[u8]) -> Result<ImageAttachment, String> {
+    if bytes.len() > MAX_IMAGE_BYTES {
+        return Err(format!("Image is too large ({} MB, limit {} MB)", bytes.len() / 1_048_576, MAX_IMAGE_BYTES / 1_048_576));
+    }
+    let mime_type = sniff_mime(bytes).ok_or("Unsupported image format. Use PNG, JPEG, GIF or WebP.")?;
+    Ok(ImageAttachment {
+        mime_type: mime_type.to_string(),
+        data: base64::engine::general_purpose::STANDARD.encode(bytes),
+        size: bytes.len(),
+    })
+}
+
+/// Re-checks attachments coming from the frontend (e.g. pasted screenshots): the data must
+/// decode, be a supported format and fit the limits. The MIME type is taken from the bytes.
+pub fn validate_images(images: Vec<ImageAttachment>) -> Result<Vec<ImageAttachment>, String> {
+    if images.len() > MAX_IMAGES {
+        return Err(format!("Too many images attached ({}, limit {})", images.len(), MAX_IMAGES));
+    }
+    images
+        .into_iter()
+        .map(|img| {
+            let raw = img.data.split_once("base64,").map(|(_, d)| d).unwrap_or(&img.data);
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(raw.trim())
+                .map_err(|e| format!("Image data is not valid base64: {}", e))?;
+            encode_image(&bytes)
+        })
+        .collect()
+}
+
+/// Reads an image from disk and returns it ready to attach to a prompt.
+#[tauri::command]
+pub async fn prepare_image_attachment(state: State<'_, AppState>, path: String) -> Result<ImageAttachment, AppError> {
+    state.policy.check_not_demo("Attaching files")?;
+    let bytes = tokio::fs::read(&path).await.map_err(|e| AppError::Io(format!("Failed to read image: {}", e)))?;
+    Ok(encode_image(&bytes)?)
+}
@@ -0,0 +1,283 @@
+use crate::FileEntry;
+use serde::{Deserialize, Serialize};
+
+/// Commands kept per category, so the section stays short.
+const MAX_PER_CATEGORY: usize = 6;
+/// Workflow files read from `.github/workflows` when fetching a repository.
+pub const MAX_WORKFLOWS: usize = 4;
+
+/// Files (relative to the project root) commands are read from, besides the README and
+/// CI workflows.
+pub const SOURCE_FILES: &[&str] = &[
+    "package.json", "Makefile", "makefile", "GNUmakefile", "justfile", "Justfile", "CONTRIBUTING.md", "Cargo.toml", "go.mod",
+    "pyproject.toml", "pom.xml", "build.gradle",
+];
+/// Lock files that identify the JavaScript package manager.
+pub const LOCK_FILES: &[&str] = &["pnpm-lock.yaml", "yarn.lock", "bun.lockb", "bun.lock"];
+
+/// Tools whose invocations count as toolchain commands when found in prose or CI scripts.
+const KNOWN_TOOLS: &[&str] = &[
+    "npm", "npx", "yarn", "pnpm", "bun", "deno", "node", "cargo", "rustup", "go", "make", "cmake", "just", "python",
+    "python3", "pip", "pip3", "pipx", "poetry", "uv", "pytest", "tox", "nox", "docker", "docker-compose", "podman",
+    "mvn", "./mvnw", "gradle", "./gradlew", "dotnet", "bundle", "rake", "rails", "mix", "composer", "php", "swift",
+    "flutter", "dart", "bazel", "meson", "ninja", "tauri", "ruff", "black", "eslint", "prettier",
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum Category {
+    Install,
+    Build,
+    Run,
+    Test,
+    Lint,
+}
+
+impl Category {
+    fn title(self) -> &'static str {
+        match self {
+            Category::Install => "Install",
+            Category::Build => "Build",
+            Category::Run => "Run",
+            Category::Test => "Test",
+            Category::Lint => "Lint / format",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolchainCommand {
+    category: Category,
+    command: String,
+    /// File the command was found in.
+    source: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildInstructions {
+    commands: Vec<ToolchainCommand>,
+    /// "How to build, run and test" section ready to include in a prompt; empty when
+    /// nothing was found.
+    packed: String,
+}
+
+/// Guesses the category from the command (or script/target name) alone.
+fn categorize(text: &str) -> Option<Category> {
+    let t = text.to_lowercase();
+    let has = |words: &[&str]| words.iter().any(|w| t.contains(w));
+    if has(&["clean", "help"]) {
+        None
+    } else if has(&["lint", "fmt", "format", "clippy", "prettier", "eslint", "ruff", "black", "vet"]) {
+        Some(Category::Lint)
+    } else if has(&["test", "pytest", "tox", "nox", "spec", "coverage", "e2e"]) {
+        Some(Category::Test)
+    } else if has(&["install", "npm ci", "bootstrap", "setup", "uv sync", "poetry lock", "deps", "dependencies", "restore"]) {
+        Some(Category::Install)
+    } else if has(&["build", "compile", "cmake", "bundle", "dist", "package", "release"]) {
+        Some(Category::Build)
+    } else if has(&["run", "start", "serve", "dev", "watch", "up", "preview"]) {
+        Some(Category::Run)
+    } else {
+        None
+    }
+}
+
+/// Whether a shell line starts with a known toolchain command.
+fn is_tool_command(line: &str) -> bool {
+    let first = line.split_whitespace().next().unwrap_or_default();
+    KNOWN_TOOLS.contains(&first)
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+fn is_workflow(path: &str) -> bool {
+    let path = path.replace('\\', "/");
+    path.contains(".github/workflows/") && (path.ends_with(".yml") || path.ends_with(".yaml"))
+}
+
+/// Shell lines from fenced code blocks (plain or shell-flavoured) and `$ ` prompts.
+fn from_readme(content: &str) -> Vec<String> {
+    let mut commands = Vec::new();
+    let mut in_shell_block = false;
+    let mut in_block = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if let Some(lang) = trimmed.strip_prefix("```") {
+            if in_block {
+                in_block = false;
+                in_shell_block = false;
+            } else {
+                in_block = true;
+                in_shell_block = matches!(lang.trim(), "" | "sh" | "bash" | "shell" | "console" | "zsh" | "powershell" | "ps1" | "cmd");
+            }
+            continue;
+        }
+        let candidate = trimmed.strip_prefix("$ ").or_else(|| trimmed.strip_prefix("> ")).unwrap_or(trimmed);
+        if (in_shell_block || trimmed.starts_with("$ ")) && is_tool_command(candidate) {
+            commands.push(candidate.to_string());
+        }
+    }
+    commands
+}
+
+/// `scripts` of a package.json, as commands for the package manager the lock file implies.
+fn from_package_json(content: &str, runner: &str) -> Vec<(String, Category)> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(content) else { return Vec::new() };
+    let mut commands = vec![(format!("{} install", runner), Category::Install)];
+    let scripts = json["scripts"].as_object();
+    // `prebuild` and `posttest` run around the scripts they name; `preview` is a script.
+    let is_hook = |name: &str| {
+        let rest = name.strip_prefix("pre").or_else(|| name.strip_prefix("post"));
+        rest.is_some_and(|rest| scripts.is_some_and(|s| s.contains_key(rest)))
+    };
+    for name in scripts.into_iter().flatten().map(|(k, _)| k) {
+        if is_hook(name) {
+            continue;
+        }
+        if let Some(category) = categorize(name) {
+            let command = match name.as_str() {
+                "start" | "test" if runner == "npm" => format!("npm {}", name),
+                _ => format!("{} run {}", runner, name),
+            };
+            commands.push((command, category));
+        }
+    }
+    commands
+}
+
+/// Non-special targets of a Makefile or justfile.
+fn from_targets(content: &str, tool: &str) -> Vec<(String, Category)> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with([' ', '\t', '#', '.']))
+        .filter_map(|line| {
+            let (target, rest) = line.split_once(':')?;
+            // `VAR := value` assignments and pattern rules are not targets.
+            if rest.starts_with('=') || target.contains(['%', '$', '=']) {
+                return None;
+            }
+            let target = target.split_whitespace().next()?;
+            Some((format!("{} {}", tool, target), categorize(target)?))
+        })
+        .collect()
+}
+
+/// `run:` steps of a GitHub Actions workflow, including `run: |` blocks.
+fn from_workflow(content: &str) -> Vec<String> {
+    let mut commands = Vec::new();
+    let mut block_indent: Option<usize> = None;
+    for line in content.lines() {
+        let indent = line.len() - line.trim_start().len();
+        let trimmed = line.trim();
+        if let Some(min) = block_indent {
+            if trimmed.is_empty() {
+                continue;
+            }
+            if indent > min {
+                if is_tool_command(trimmed) {
+                    commands.push(trimmed.to_string());
+                }
+                continue;
+            }
+            block_indent = None;
+        }
+        let step = trimmed.strip_prefix("- ").unwrap_or(trimmed);
+        if let Some(value) = step.strip_prefix("run:") {
+            let value = value.trim();
+            if value.starts_with('|') || value.starts_with('>') {
+                block_indent = Some(indent);
+            } else if is_tool_command(value.trim_matches(['"', '\''])) {
+                commands.push(value.trim_matches(['"', '\'']).to_string());
+            }
+        }
+    }
+    commands
+}
+
+fn add(found: &mut Vec<ToolchainCommand>, command: String, category: Option<Category>, source: &str) {
+    let command = command.trim().trim_end_matches(['\\', ';']).trim().to_string();
+    let Some(category) = category.or_else(|| categorize(&command)) else { return };
+    if command.is_empty() || found.iter().any(|c| c.command == command) {
+        return;
+    }
+    if found.iter().filter(|c| c.category == category).count() < MAX_PER_CATEGORY {
+        found.push(ToolchainCommand { category, command, source: source.to_string() });
+    }
+}
+
+/// Extracts toolchain commands from README, package.json scripts, Makefile/justfile
+/// targets and CI workflows, falling back to the default commands of a detected
+/// toolchain when nothing explicit was found for it.
+pub fn extract(files: &[FileEntry]) -> BuildInstructions {
+    let names: Vec<&str> = files.iter().map(|f| file_name(&f.path)).collect();
+    let runner = if names.contains(&"pnpm-lock.yaml") {
+        "pnpm"
+    } else if names.contains(&"yarn.lock") {
+        "yarn"
+    } else if names.contains(&"bun.lockb") || names.contains(&"bun.lock") {
+        "bun"
+    } else {
+        "npm"
+    };
+
+    let mut found: Vec<ToolchainCommand> = Vec::new();
+    // Explicit project scripts first, then what the docs and CI say.
+    for file in files {
+        match file_name(&file.path) {
+            "package.json" => from_package_json(&file.content, runner).into_iter().for_each(|(c, cat)| add(&mut found, c, Some(cat), &file.path)),
+            "Makefile" | "makefile" | "GNUmakefile" => from_targets(&file.content, "make").into_iter().for_each(|(c, cat)| add(&mut found, c, Some(cat), &file.path)),
+            "justfile" | "Justfile" => from_targets(&file.content, "just").into_iter().for_each(|(c, cat)| add(&mut found, c, Some(cat), &file.path)),
+            _ => {}
+        }
+    }
+    for file in files {
+        let name = file_name(&file.path);
+        if name.to_lowercase().starts_with("readme") || name.eq_ignore_ascii_case("CONTRIBUTING.md") {
+            from_readme(&file.content).into_iter().for_each(|c| add(&mut found, c, None, &file.path));
+        } else if is_workflow(&file.path) {
+            from_workflow(&file.content).into_iter().for_each(|c| add(&mut found, c, None, &file.path));
+        }
+    }
+
+    let defaults: &[(&str, &[(&str, Category)])] = &[
+        ("Cargo.toml", &[("cargo build", Category::Build), ("cargo test", Category::Test)]),
+        ("go.mod", &[("go build ./...", Category::Build), ("go test ./...", Category::Test)]),
+        ("pyproject.toml", &[("pip install -e .", Category::Install), ("pytest", Category::Test)]),
+        ("pom.xml", &[("mvn package", Category::Build), ("mvn test", Category::Test)]),
+        ("build.gradle", &[("gradle build", Category::Build), ("gradle test", Category::Test)]),
+    ];
+    for (manifest, commands) in defaults {
+        let tool = commands[0].0.split_whitespace().next().unwrap_or_default();
+        if let Some(file) = files.iter().find(|f| file_name(&f.path) == *manifest) {
+            if !found.iter().any(|c| c.command.split_whitespace().next() == Some(tool)) {
+                commands.iter().for_each(|(c, cat)| add(&mut found, c.to_string(), Some(*cat), &file.path));
+            }
+        }
+    }
+
+    found.sort_by_key(|c| c.category);
+    let mut packed = String::new();
+    if !found.is_empty() {
+        packed.push_str("## How to build, run and test\n");
+        let mut current = None;
+        for c in &found {
+            if current != Some(c.category) {
+                packed.push_str(&format!("{}:\n", c.category.title()));
+                current = Some(c.category);
+            }
+            packed.push_str(&format!("- `{}`\n", c.command));
+        }
+    }
+    BuildInstructions { commands: found, packed }
+}
+
+/// Builds the toolchain section from scanned files (only READMEs, manifests, Makefiles
+/// and CI workflows are looked at).
+#[tauri::command]
+pub fn extract_build_instructions(files: Vec<FileEntry>) -> BuildInstructions {
+    extract(&files)
+}
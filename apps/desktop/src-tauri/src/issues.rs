@@ -0,0 +1,158 @@
+use crate::error::AppError;
+use crate::{cache, github, log_status, AppState, DEFAULT_FETCH_CONCURRENCY};
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+/// Issue bodies and comments are cut to this many characters to keep the context small.
+const MAX_ISSUE_TEXT: usize = 2000;
+const MAX_ISSUES: usize = 200;
+/// Pull requests share the listing, so stop paging after this many pages regardless.
+const MAX_ISSUE_PAGES: u32 = 10;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueComment {
+    author: String,
+    body: String,
+    reactions: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Issue {
+    number: u64,
+    title: String,
+    labels: Vec<String>,
+    author: String,
+    body: String,
+    comment_count: u64,
+    /// The most-reacted comments, up to the requested count.
+    top_comments: Vec<IssueComment>,
+    html_url: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueList {
+    issues: Vec<Issue>,
+    /// "Known problems" section ready to include in a prompt.
+    packed: String,
+}
+
+fn clip(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(MAX_ISSUE_TEXT) {
+        Some((i, _)) => format!("{}…", &text[..i]),
+        None => text.to_string(),
+    }
+}
+
+fn pack_issues(issues: &[Issue]) -> String {
+    let mut out = String::from("## Known problems (open issues)\n");
+    for issue in issues {
+        let labels = if issue.labels.is_empty() { String::new() } else { format!(" [{}]", issue.labels.join(", ")) };
+        out.push_str(&format!("\n### #{} {}{}\n", issue.number, issue.title, labels));
+        if !issue.body.is_empty() {
+            out.push_str(&format!("{}\n", issue.body));
+        }
+        for c in &issue.top_comments {
+            out.push_str(&format!("> {}: {}\n", c.author, c.body.replace('\n', "\n> ")));
+        }
+    }
+    out
+}
+
+/// Fetches open issues (pull requests excluded), newest activity first, with their top
+/// comments, for "known problems" context. `labels` is a comma-separated filter.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn fetch_github_issues(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    owner: String,
+    repo: String,
+    token: Option<String>,
+    limit: Option<usize>,
+    comments_per_issue: Option<usize>,
+    labels: Option<String>,
+) -> Result<IssueList, AppError> {
+    use futures_util::stream::{self, StreamExt};
+
+    state.policy.check_not_demo("GitHub access")?;
+    let gh = github::GithubClient::new(state.http_client.read().await.clone(), token.unwrap_or_default())
+        .with_cache(cache::open_cache(&app, &state, "github").ok());
+    let limit = limit.unwrap_or(20).clamp(1, MAX_ISSUES);
+    let comments_per_issue = comments_per_issue.unwrap_or(3).min(10);
+    let label_filter = labels
+        .map(|l| format!("&labels={}", urlencoding::encode(l.trim())))
+        .filter(|l| l != "&labels=")
+        .unwrap_or_default();
+
+    log_status(&app, format!("Fetching open issues for {}/{}", owner, repo));
+    let mut raw = Vec::new();
+    let mut page = 1;
+    while raw.len() < limit {
+        let json = gh
+            .get_json(&format!("/repos/{}/{}/issues?state=open&sort=updated&per_page=100&page={}{}", owner, repo, page, label_filter))
+            .await?;
+        let items = json.as_array().cloned().unwrap_or_default();
+        let page_len = items.len();
+        // The issues endpoint also returns pull requests.
+        raw.extend(items.into_iter().filter(|i| i.get("pull_request").is_none()));
+        if page_len < 100 || page >= MAX_ISSUE_PAGES {
+            break;
+        }
+        page += 1;
+    }
+    raw.truncate(limit);
+
+    let mut issues: Vec<Issue> = stream::iter(raw)
+        .map(|item| {
+            let gh = &gh;
+            async move {
+                let comment_count = item["comments"].as_u64().unwrap_or_default();
+                let mut top_comments = Vec::new();
+                if comments_per_issue > 0 && comment_count > 0 {
+                    if let Some(url) = item["comments_url"].as_str() {
+                        let comments = gh.get_json(&format!("{}?per_page=30", url)).await.ok();
+                        top_comments = comments
+                            .as_ref()
+                            .and_then(|c| c.as_array())
+                            .map(|list| {
+                                list.iter()
+                                    .map(|c| IssueComment {
+                                        author: c["user"]["login"].as_str().unwrap_or_default().to_string(),
+                                        body: clip(c["body"].as_str().unwrap_or_default()),
+                                        reactions: c["reactions"]["total_count"].as_u64().unwrap_or_default(),
+                                    })
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+                        top_comments.sort_by_key(|c: &IssueComment| std::cmp::Reverse(c.reactions));
+                        top_comments.truncate(comments_per_issue);
+                    }
+                }
+                Issue {
+                    number: item["number"].as_u64().unwrap_or_default(),
+                    title: item["title"].as_str().unwrap_or_default().to_string(),
+                    labels: item["labels"]
+                        .as_array()
+                        .map(|l| l.iter().filter_map(|l| l["name"].as_str().map(|s| s.to_string())).collect())
+                        .unwrap_or_default(),
+                    author: item["user"]["login"].as_str().unwrap_or_default().to_string(),
+                    body: clip(item["body"].as_str().unwrap_or_default()),
+                    comment_count,
+                    top_comments,
+                    html_url: item["html_url"].as_str().unwrap_or_default().to_string(),
+                }
+            }
+        })
+        .buffered(DEFAULT_FETCH_CONCURRENCY)
+        .collect()
+        .await;
+    issues.retain(|i| i.number > 0);
+
+    log_status(&app, format!("Fetched {} open issues", issues.len()));
+    let packed = pack_issues(&issues);
+    Ok(IssueList { issues, packed })
+}
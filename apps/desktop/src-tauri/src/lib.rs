@@ -1,129 +1,241 @@
+use crate::error::AppError;
 use isahc::prelude::*;
 use isahc::HttpClient;
 use serde::{Deserialize, Serialize};
 use std::ffi::OsStr;
-use std::fs;
-use std::process::Command;
-use std::time::Duration;
-use std::sync::atomic::{AtomicBool, Ordering};
-use sysinfo::{System, ProcessRefreshKind};
-use tauri::{State, RunEvent, Manager};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, AtomicU64, Ordering};
+use sysinfo::System;
+use tauri::{AppHandle, State, RunEvent, Manager};
 use tokio::sync::RwLock;
 
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
-
-#[derive(Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct FileEntry {
-    path: String,
-    content: String,
-}
+mod archive;
+mod audio;
+mod azure;
+mod benchmark;
+mod blocks;
+mod cache;
+mod cancellation;
+mod changes;
+mod chunking;
+mod cli;
+mod clipboard;
+mod clone;
+mod conversation;
+mod dedup;
+mod dependencies;
+mod docker;
+mod embedcache;
+mod error;
+mod export;
+mod findings;
+mod gemini;
+mod generations;
+mod github;
+mod githubauth;
+mod history;
+mod hybrid;
+mod images;
+mod instructions;
+mod issues;
+mod llm;
+mod lmstudio;
+mod logs;
+mod network;
+mod normalize;
+mod ollama;
+mod onboarding;
+mod outline;
+mod permalink;
+mod policy;
+mod prefetch;
+mod progress;
+mod projects;
+mod providers;
+mod recent;
+mod repocache;
+mod repourl;
+mod resources;
+mod retry;
+mod review;
+mod scanstream;
+mod sessions;
+mod settings;
+mod stats;
+mod status;
+mod submodules;
+mod summarize;
+mod symbols;
+mod tempdirs;
+mod templates;
+mod testgen;
+mod tokens;
+mod trace;
+mod tree;
+mod usage;
+mod vectors;
+mod watch;
+mod workspace;
+
+use status::log_status;
+
+pub use repo_prompt_core::FileEntry;
+use repo_prompt_core::ranking::{select_source_files, ScoringRules};
+use repo_prompt_core::ignore::{IgnoreRules, IGNORE_FILE};
+use repo_prompt_core::scan::{normalize_subpath, read_directory_ignoring};
+use repo_prompt_core::{continuation, paths};
 
 pub struct AppState {
     pub gemini_api_key: RwLock<String>,
     pub http_client: RwLock<HttpClient>,
-    pub ollama_client: HttpClient,
+    pub ollama_client: RwLock<HttpClient>,
+    /// Proxy and TLS settings every client above is built with.
+    pub network: RwLock<network::NetworkConfig>,
+    /// Last address where Ollama answered, used when reconnecting.
+    pub ollama_url: RwLock<Option<String>>,
     pub we_started_ollama: AtomicBool,
+    /// PID of the `ollama serve` we spawned, 0 when none.
+    pub ollama_pid: AtomicU32,
+    /// Local repositories re-packed as they change.
+    pub watches: watch::Watches,
+    /// Operations `cancel_fetch` can stop.
+    pub cancellations: cancellation::Cancellations,
+    /// Restart the Ollama server we started if it crashes.
+    pub ollama_auto_restart: AtomicBool,
+    /// The `lms` binary that started LM Studio's server, when this app started it.
+    pub lmstudio_started_with: std::sync::Mutex<Option<String>>,
+    /// Held while the recent repositories file is read, changed and written back.
+    pub recent_lock: std::sync::Mutex<()>,
+    /// Held while the index of cached snapshots is read, changed and written back.
+    pub snapshot_index_lock: std::sync::Mutex<()>,
+    pub cache_compression_level: AtomicI32,
+    /// How long a cached `fetch_github_repo` result is reused, in seconds.
+    pub repo_cache_ttl_secs: AtomicU64,
+    pub embeddings: embedcache::EmbeddingStore,
+    /// Dependency manifests added in the settings, besides the built-in ones.
+    pub manifest_files: RwLock<Vec<String>>,
+    /// Gitignore-style patterns from the settings, left out of every scan and fetch.
+    pub ignore_patterns: RwLock<Vec<String>>,
+    /// How source files are ranked when not all of them are taken, from the settings.
+    pub scoring: RwLock<ScoringRules>,
+    /// Bumped to stop the running background prefetch.
+    pub prefetch_generation: AtomicU64,
+    pub status_log: status::StatusLog,
+    pub clipboard: clipboard::NativeClipboard,
+    pub progress: progress::ProgressTracker,
+    pub sessions: sessions::SessionStore,
+    pub usage: usage::UsageTracker,
+    pub temp_dirs: tempdirs::TempDirManager,
+    pub trace: trace::TraceRecorder,
+    pub policy: policy::OrgPolicy,
 }
 
+/// Updates the API key, network options and cache compression. Each `None` leaves that
+/// setting as it is; an empty `proxy` or `ca_certificate_file` turns it off.
 #[tauri::command]
-async fn set_app_config(state: State<'_, AppState>, gemini_key: Option<String>, proxy: Option<String>) -> Result<(), String> {
+async fn set_app_config(
+    state: State<'_, AppState>,
+    gemini_key: Option<String>,
+    proxy: Option<String>,
+    cache_compression_level: Option<i32>,
+    ca_certificate_file: Option<String>,
+    danger_accept_invalid_certs: Option<bool>,
+) -> Result<(), AppError> {
+    state.policy.check_not_demo("Changing API keys and network settings")?;
     if let Some(key) = gemini_key {
         *state.gemini_api_key.write().await = key.trim().to_string();
     }
 
-    let proxy_url = proxy.as_deref().map(|s| s.trim()).filter(|s| !s.is_empty());
-    
-    let client = if let Some(url) = proxy_url {
-        let proxy_uri = if !url.starts_with("http") {
-            format!("http://{}", url)
-        } else {
-            url.to_string()
-        };
-        
-        let old_https = std::env::var("HTTPS_PROXY").ok();
-        let old_http = std::env::var("HTTP_PROXY").ok();
-        
-        std::env::set_var("HTTPS_PROXY", &proxy_uri);
-        std::env::set_var("HTTP_PROXY", &proxy_uri);
-        
-        let c = isahc::HttpClient::builder()
-            .timeout(std::time::Duration::from_secs(3600))
-            .build()
-            .map_err(|e| format!("Failed to create proxy client: {}", e))?;
-            
-        if let Some(old) = old_https {
-            std::env::set_var("HTTPS_PROXY", old);
-        } else {
-            std::env::remove_var("HTTPS_PROXY");
-        }
-        if let Some(old) = old_http {
-            std::env::set_var("HTTP_PROXY", old);
-        } else {
-            std::env::remove_var("HTTP_PROXY");
-        }
-        c
-    } else {
-        isahc::HttpClient::builder()
-            .timeout(std::time::Duration::from_secs(3600))
-            .build()
-            .map_err(|e| format!("Failed to create client: {}", e))?
-    };
+    if let Some(level) = cache_compression_level {
+        state.cache_compression_level.store(level.clamp(1, 19), Ordering::Relaxed);
+    }
 
-    *state.http_client.write().await = client;
-    Ok(())
+    if proxy.is_none() && ca_certificate_file.is_none() && danger_accept_invalid_certs.is_none() {
+        return Ok(());
+    }
+    let mut config = state.network.read().await.clone();
+    if let Some(proxy) = proxy.as_deref().map(str::trim) {
+        config.proxy = if proxy.is_empty() { None } else { Some(network::Proxy::parse(proxy).map_err(AppError::InvalidInput)?) };
+    }
+    if let Some(path) = ca_certificate_file.as_deref().map(str::trim) {
+        config.ca_file = if path.is_empty() { None } else { Some(network::check_ca_file(path).map_err(AppError::InvalidInput)?) };
+    }
+    if let Some(insecure) = danger_accept_invalid_certs {
+        config.danger_accept_invalid_certs = insecure;
+    }
+    Ok(network::apply(&state, config).await?)
 }
 
+/// Sends a prompt to Gemini and returns the reply text with its finish reason. Blocked,
+/// empty and unreadable responses come back as errors saying which it was. A reply cut
+/// off at the token limit is continued up to `max_continuations` times (default 3).
+/// `options` sets sampling, output length, stop sequences, a system instruction and
+/// safety thresholds.
 #[tauri::command(rename_all = "snake_case")]
-async fn call_gemini_secure(state: State<'_, AppState>, prompt: String, model: Option<String>) -> Result<String, String> {
+#[allow(clippy::too_many_arguments)]
+async fn call_gemini_secure(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    prompt: String,
+    model: Option<String>,
+    images: Option<Vec<images::ImageAttachment>>,
+    max_continuations: Option<u32>,
+    operation_id: Option<String>,
+    options: Option<gemini::GenerationOptions>,
+) -> Result<gemini::GeminiReply, AppError> {
     let key = state.gemini_api_key.read().await.clone();
 
     if key.is_empty() {
-        return Err("Gemini API key is missing. Please enter it in the settings or set the GEMINI_API_KEY environment variable.".to_string());
+        return Err(AppError::missing_gemini_key());
     }
 
     println!("[Gemini] Using key: {}... (len: {})", &key[..std::cmp::min(4, key.len())], key.len());
+    state.policy.check_provider("gemini")?;
+    let prompt = state.policy.redact(&prompt);
 
     let model_name = model.unwrap_or_else(|| "gemini-3-flash-preview".to_string());
-    let url = format!("https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent", model_name);
 
-    let body = serde_json::json!({
-        "contents": [{ "parts": [{ "text": prompt }] }]
-    });
+    let mut parts = vec![serde_json::json!({ "text": prompt })];
+    for image in images::validate_images(images.unwrap_or_default()).map_err(AppError::InvalidInput)? {
+        parts.push(serde_json::json!({ "inline_data": { "mime_type": image.mime_type, "data": image.data } }));
+    }
 
-    let request = isahc::Request::builder()
-        .method("POST")
-        .uri(url)
-        .header("Content-Type", "application/json")
-        .header("x-goog-api-key", &key)
-        .body(serde_json::to_string(&body).unwrap())
-        .map_err(|e| e.to_string())?;
+    let mut body = serde_json::json!({
+        "contents": [{ "role": "user", "parts": parts }]
+    });
+    if let Some(options) = &options {
+        options.apply(&mut body, |s| state.policy.redact(s))?;
+    }
 
-    let client = state.http_client.read().await.clone();
-    let mut response = client
-        .send_async(request)
+    log_status(&app, format!("Sending prompt to Gemini ({})", model_name));
+    let progress = state.progress.start(&app, "generate", operation_id);
+    progress.stage("generating", format!("Waiting for Gemini ({})", model_name));
+    let max_continuations = max_continuations.unwrap_or(continuation::DEFAULT_MAX_CONTINUATIONS);
+    let reply = gemini::generate_complete(&state, &model_name, &body, max_continuations)
         .await
-        .map_err(|e| format!("Gemini API connection error: {}", e))?;
-
-    let status = response.status();
-    let res_text = response.text().await.unwrap_or_else(|_| "Could not read response body".to_string());
-
-    if !status.is_success() {
-        return Err(format!("Gemini API error ({}): {}", status, res_text));
+        .inspect_err(|e| log_status(&app, format!("Gemini request failed: {}", e)))?;
+    if reply.truncated {
+        log_status(&app, "Gemini response received (cut off at the output token limit)");
+    } else if reply.continuations > 0 {
+        log_status(&app, format!("Gemini response received ({} continuations)", reply.continuations));
+    } else {
+        log_status(&app, "Gemini response received");
     }
-
-    Ok(res_text)
+    progress.finish("Gemini response received");
+    Ok(reply)
 }
 
 #[tauri::command]
-async fn call_gemini_advanced(state: State<'_, AppState>, contents: serde_json::Value, tools: Option<serde_json::Value>, model: Option<String>) -> Result<serde_json::Value, String> {
+async fn call_gemini_advanced(state: State<'_, AppState>, contents: serde_json::Value, tools: Option<serde_json::Value>, model: Option<String>) -> Result<serde_json::Value, AppError> {
     let key = state.gemini_api_key.read().await.clone();
 
     if key.is_empty() {
-        return Err("Gemini API key is missing. Please enter it in the settings or set the GEMINI_API_KEY environment variable.".to_string());
+        return Err(AppError::missing_gemini_key());
     }
 
+    state.policy.check_provider("gemini")?;
+    let mut contents = contents;
+    state.policy.redact_json(&mut contents);
+
     let model_name = model.unwrap_or_else(|| "gemini-3.1-pro-preview".to_string());
     let url = format!("https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent", model_name);
 
@@ -132,71 +244,74 @@ async fn call_gemini_advanced(state: State<'_, AppState>, contents: serde_json::
     if let Some(t) = tools {
         body_map.insert("tools".to_string(), t);
     }
-    let body = serde_json::Value::Object(body_map);
-
-    let request = isahc::Request::builder()
-        .method("POST")
-        .uri(url)
-        .header("Content-Type", "application/json")
-        .header("x-goog-api-key", &key)
-        .body(body.to_string())
-        .map_err(|e| e.to_string())?;
+    let body = serde_json::Value::Object(body_map).to_string();
+
+    let make = || {
+        isahc::Request::builder()
+            .method("POST")
+            .uri(&url)
+            .header("Content-Type", "application/json")
+            .header("x-goog-api-key", &key)
+            .body(body.clone())
+    };
 
     let client = state.http_client.read().await.clone();
-    let mut response = client.send_async(request).await.map_err(|e| e.to_string())?;
+    let span = state.trace.span("llm", "gemini_generate").attr("model", &model_name);
+    let mut response = retry::send(&client, "Gemini", true, make).await?;
 
-    let response_body = response.text_async().await.map_err(|e| e.to_string())?;
+    let response_body = response.text().await?;
+    span.attr("status", response.status().as_u16()).end();
 
     if !response.status().is_success() {
-        return Err(format!("Gemini API error: {} - {}", response.status(), response_body));
+        return Err(gemini::api_error(response.status().as_u16(), &response_body).into());
     }
 
-    let json: serde_json::Value = serde_json::from_str(&response_body).map_err(|e| e.to_string())?;
+    // Function calls can't be salvaged from a cut-off body, so no recovery here.
+    let json: serde_json::Value =
+        serde_json::from_str(&response_body).map_err(|e| String::from(gemini::GeminiError::Malformed(e.to_string())))?;
+    gemini::check_blocked(&json)?;
+    state.usage.record("gemini", &model_name, repo_prompt_core::usage::Usage::from_gemini(&json).as_ref());
     Ok(json)
 }
 
+/// Reads the project at `path` (or only its `subpath`). File paths are reported relative
+/// to `path`, `/`-separated. Paths matched by the settings' ignore patterns or the
+/// project's `.repopromptignore` are left out. Progress is reported on
+/// `progress://{operation_id}`.
 #[tauri::command]
-async fn scan_local_repository(path: String) -> Result<Vec<FileEntry>, String> {
-    use tokio::task::JoinSet;
-    let mut files = Vec::new();
-    let mut set = JoinSet::new();
-
-    let walker = walkdir::WalkDir::new(path)
-        .into_iter()
-        .filter_entry(|e| {
-            let name = e.file_name().to_string_lossy();
-            let is_hidden = name.starts_with(".git") || name == ".venv" || name == ".idea" || name == ".vscode";
-            let is_heavy = name == "node_modules" || name == "target" || name == "venv" || name == "build" || name == "__pycache__";
-            !is_hidden && !is_heavy
-        });
-
-    for entry in walker.filter_map(|e| e.ok()) {
-        if entry.file_type().is_file() {
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.len() > 1_000_000 {
-                    continue; // Skip files > 1MB
-                }
-            }
-            
-            let file_path = entry.path().to_path_buf();
-            set.spawn_blocking(move || {
-                match fs::read_to_string(&file_path) {
-                    Ok(content) => Some(FileEntry {
-                        path: file_path.display().to_string(),
-                        content,
-                    }),
-                    Err(_) => None,
-                }
-            });
-        }
-    }
-
-    while let Some(result) = set.join_next().await {
-        if let Ok(Some(file_entry)) = result {
-            files.push(file_entry);
-        }
+async fn scan_local_repository(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    subpath: Option<String>,
+    operation_id: Option<String>,
+) -> Result<Vec<FileEntry>, AppError> {
+    let subpath = normalize_subpath(subpath)?;
+    let root = match &subpath {
+        Some(sub) => std::path::Path::new(&path).join(sub),
+        None => std::path::PathBuf::from(&path),
+    };
+    if !root.is_dir() {
+        return Err(AppError::NotFound(format!("Directory not found: {}", root.display())));
     }
-
+    state.policy.check_scan_path(&app, &root)?;
+
+    log_status(&app, format!("Scanning {}", root.display()));
+    let progress = state.progress.start(&app, "scan", operation_id);
+    progress.stage("walking", format!("Listing files in {}", root.display()));
+    let span = state.trace.span("scan", "read_directory").attr("root", root.display());
+    // The settings' ignore patterns, then the project's own `.repopromptignore`.
+    let ignore = IgnoreRules::for_directory(std::path::Path::new(&path), &state.ignore_patterns.read().await);
+    let mut files = read_directory_ignoring(root.clone(), std::path::Path::new(&path), &ignore, |done, total| {
+        progress.update("reading", done as u64, Some(total as u64), format!("Read {} of {} files", done, total));
+    })
+    .await;
+    paths::make_relative(&mut files, std::path::Path::new(&path));
+    span.attr("files", files.len()).end();
+    log_status(&app, format!("Scan complete: {} files read", files.len()));
+    progress.finish(format!("{} files read", files.len()));
+    recent::record(&app, recent::RepoKind::Local, path.clone(), serde_json::json!({ "subpath": subpath }));
+    projects::remember(&app, &state, path, None, None, &files);
     Ok(files)
 }
 
@@ -207,6 +322,18 @@ pub struct RepoInfo {
     repo: String,
     default_branch: String,
     description: String,
+    /// Commit the snapshot was taken at. Pass it back as `git_ref` (or to
+    /// `fetch_github_files`) to keep working against exactly this snapshot.
+    commit_sha: String,
+    /// `https://github.com/{owner}/{repo}/blob/{commit_sha}/`, for deep links into the snapshot.
+    blob_base_url: String,
+    stars: u64,
+    topics: Vec<String>,
+    /// SPDX identifier when GitHub recognises the license, otherwise its name.
+    license: Option<String>,
+    /// Repository size as reported by GitHub, in KB.
+    size_kb: u64,
+    languages: stats::LanguageBreakdown,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -215,211 +342,464 @@ pub struct GithubRepoData {
     info: RepoInfo,
     tree: Vec<String>,
     readme: String,
+    /// Summary of the dependency manifests, or their raw contents with `raw_dependencies`.
     dependencies: String,
+    /// The parsed manifests behind `dependencies`; empty with `raw_dependencies`.
+    #[serde(default)]
+    manifests: Vec<repo_prompt_core::dependencies::Manifest>,
     source_files: Vec<FileEntry>,
     is_truncated: bool,
+    rate_limit: github::RateLimitInfo,
+    submodules: Vec<submodules::SubmoduleInfo>,
+    /// Build/run/test commands found in the README, manifests, Makefile and CI.
+    build_instructions: instructions::BuildInstructions,
+    /// Size of `source_files`.
+    #[serde(default)]
+    stats: repo_prompt_core::tokens::PackStats,
+}
+
+/// Fetches `paths` at `git_ref` through the contents API, `concurrency` at a time,
+/// calling `progress(done)` as each request completes. Files that fail to download are
+/// left out.
+async fn fetch_files(
+    gh: &github::GithubClient,
+    owner: &str,
+    repo: &str,
+    git_ref: &str,
+    paths: Vec<String>,
+    concurrency: usize,
+    progress: impl Fn(usize),
+) -> Vec<FileEntry> {
+    use futures_util::stream::{self, StreamExt};
+
+    let mut done = 0;
+    stream::iter(paths)
+        .map(|path| async move {
+            gh.fetch_file_content(owner, repo, &path, git_ref)
+                .await
+                .map(|content| FileEntry::new(path, content))
+        })
+        .buffer_unordered(concurrency)
+        .inspect(|_| {
+            done += 1;
+            progress(done);
+        })
+        .filter_map(|entry| async move { entry })
+        .collect()
+        .await
 }
 
+/// Default number of simultaneous per-file requests when not using the tarball path.
+const DEFAULT_FETCH_CONCURRENCY: usize = 8;
+
+/// Fetches a repository snapshot (info, tree, README, dependencies and the best-ranked
+/// source files). The fetch runs under `operation_id` (made up when not given; it's in
+/// the progress events) and stops when `cancel_fetch` is called with it.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn fetch_github_repo(
+    app: AppHandle,
     state: State<'_, AppState>,
     owner: String,
     repo: String,
     branch: Option<String>,
     token: Option<String>,
     max_files: Option<u32>,
-) -> Result<GithubRepoData, String> {
-    use tokio::task::JoinSet;
-    use std::sync::Arc;
-
-    let client = Arc::new(state.http_client.read().await.clone());
-    let token_arc = Arc::new(token.unwrap_or_default());
-
-    // 1. Fetch basic info
-    let info_url = format!("https://api.github.com/repos/{}/{}", owner, repo);
-    let mut builder = isahc::Request::builder()
-        .method("GET")
-        .uri(&info_url)
-        .header("Accept", "application/vnd.github.v3+json")
-        .header("User-Agent", "Tauri/Prompt-Generator");
+    use_tarball: Option<bool>,
+    concurrency: Option<usize>,
+    git_ref: Option<String>,
+    subpath: Option<String>,
+    include_submodules: Option<bool>,
+    use_cache: Option<bool>,
+    refresh: Option<bool>,
+    operation_id: Option<String>,
+    raw_dependencies: Option<bool>,
+    scoring: Option<ScoringRules>,
+) -> Result<GithubRepoData, AppError> {
+    let operation_id = operation_id.map(|id| id.trim().to_string()).filter(|id| !id.is_empty()).unwrap_or_else(|| state.cancellations.new_id("github-fetch"));
+    let cancel = state.cancellations.register(&operation_id);
+    let name = format!("{}/{}", owner, repo);
+    let fetch = fetch_snapshot(
+        app, state.clone(), owner, repo, branch, token, max_files, use_tarball, concurrency, git_ref, subpath, include_submodules, use_cache, refresh,
+        Some(operation_id.clone()), raw_dependencies, scoring,
+    );
+    // Dropping the fetch aborts the requests it has in flight.
+    let result = tokio::select! {
+        biased;
+        _ = cancel.cancelled() => Err(AppError::Cancelled(format!("Fetching {} was cancelled", name))),
+        result = fetch => result,
+    };
+    state.cancellations.finish(&operation_id, &cancel);
+    result
+}
 
-    if !token_arc.is_empty() {
-        builder = builder.header("Authorization", format!("token {}", *token_arc));
+#[allow(clippy::too_many_arguments)]
+async fn fetch_snapshot(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    owner: String,
+    repo: String,
+    branch: Option<String>,
+    token: Option<String>,
+    max_files: Option<u32>,
+    use_tarball: Option<bool>,
+    concurrency: Option<usize>,
+    git_ref: Option<String>,
+    subpath: Option<String>,
+    include_submodules: Option<bool>,
+    use_cache: Option<bool>,
+    refresh: Option<bool>,
+    operation_id: Option<String>,
+    raw_dependencies: Option<bool>,
+    scoring: Option<ScoringRules>,
+) -> Result<GithubRepoData, AppError> {
+    state.policy.check_not_demo("GitHub access")?;
+    let subpath = normalize_subpath(subpath)?;
+    let options = serde_json::json!({
+        "gitRef": git_ref.as_deref().or(branch.as_deref()),
+        "subpath": subpath,
+        "maxFiles": max_files,
+        "useTarball": use_tarball,
+        "includeSubmodules": include_submodules,
+        "rawDependencies": raw_dependencies,
+        "scoring": scoring,
+    });
+    // Ranking rules given with the call replace the settings' ones.
+    let scoring = match scoring {
+        Some(rules) => rules,
+        None => state.scoring.read().await.clone(),
+    };
+    scoring.validate().map_err(AppError::InvalidInput)?;
+
+    // A result younger than the cache TTL is reused unless `refresh` asks for a new fetch.
+    let use_cache = use_cache.unwrap_or(true);
+    let requested = git_ref.as_deref().or(branch.as_deref()).map(str::trim).filter(|r| !r.is_empty()).map(str::to_string);
+    let cache_key = repocache::snapshot_key(
+        &owner,
+        &repo,
+        requested.as_deref(),
+        subpath.as_deref(),
+        max_files,
+        include_submodules.unwrap_or(false),
+        raw_dependencies.unwrap_or(false),
+        &scoring,
+        token.as_deref().is_some_and(|t| !t.is_empty()),
+        use_tarball.unwrap_or(false),
+        &state.manifest_files.read().await,
+        &state.ignore_patterns.read().await,
+    );
+    if use_cache && !refresh.unwrap_or(false) {
+        if let Some((data, age)) = repocache::get_snapshot(&app, &state, &cache_key) {
+            log_status(&app, format!("Using cached snapshot of {}/{} ({} min old)", owner, repo, age / 60));
+            let key = format!("{}/{}", owner, repo);
+            projects::remember(&app, &state, key.clone(), Some(data.info.default_branch.clone()), Some(data.info.commit_sha.clone()), &data.source_files);
+            recent::record(&app, recent::RepoKind::Github, key, options);
+            return Ok(data);
+        }
     }
+    // Every path below is compared against this prefix; empty means the whole repository.
+    let prefix = subpath.as_ref().map(|s| format!("{}/", s)).unwrap_or_default();
+
+    let prefetch_token = token.clone().unwrap_or_default();
+    let gh = github::GithubClient::new(state.http_client.read().await.clone(), token.unwrap_or_default())
+        .with_cache(cache::open_cache(&app, &state, "github").ok());
 
-    let mut info_res = client.send_async(builder.body("".to_string()).unwrap()).await.map_err(|e| e.to_string())?;
+    // 1. Fetch basic info
+    log_status(&app, format!("Fetching repository info for {}/{}", owner, repo));
+    let progress = state.progress.start(&app, "github-fetch", operation_id);
+    progress.stage("info", format!("Fetching repository info for {}/{}", owner, repo));
+    let _total = state.trace.span("fetch", "fetch_github_repo").attr("repo", format!("{}/{}", owner, repo));
+    let span = state.trace.span("fetch", "repo_info");
+    let mut info_res = gh.get(&format!("/repos/{}/{}", owner, repo)).await?;
     if !info_res.status().is_success() {
-        return Err(format!("Failed to fetch repo info: {}", info_res.status()));
+        return Err(AppError::from_status(info_res.status().as_u16(), format!("Failed to fetch repo info: {}", info_res.status()), None));
     }
     
     let info_text = info_res.text().await.map_err(|e| e.to_string())?;
     let info_json: serde_json::Value = serde_json::from_str(&info_text).map_err(|e| e.to_string())?;
-    let default_branch = branch.filter(|b| !b.is_empty()).unwrap_or_else(|| {
+    // `git_ref` accepts a branch, tag or commit; `branch` is kept for older callers.
+    let requested_ref = git_ref.or(branch).map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+    let default_branch = requested_ref.unwrap_or_else(|| {
         info_json["default_branch"].as_str().unwrap_or("main").to_string()
     });
     let description = info_json["description"].as_str().unwrap_or("No description.").to_string();
+    let stars = info_json["stargazers_count"].as_u64().unwrap_or_default();
+    let topics = info_json["topics"]
+        .as_array()
+        .map(|t| t.iter().filter_map(|t| t.as_str().map(|s| s.to_string())).collect())
+        .unwrap_or_default();
+    let license = match info_json["license"]["spdx_id"].as_str() {
+        Some(id) if id != "NOASSERTION" => Some(id.to_string()),
+        _ => info_json["license"]["name"].as_str().map(|s| s.to_string()),
+    };
+    let size_kb = info_json["size"].as_u64().unwrap_or_default();
+    span.end();
+
+    // Pin every following request to one commit so tree, contents and tarball agree, and
+    // so later refetches and citations can point at the same snapshot.
+    // GitHub's language numbers are only used for the whole repository (see below).
+    let languages_path = format!("/repos/{}/{}/languages", owner, repo);
+    let fetch_languages = async {
+        match subpath {
+            Some(_) => None,
+            None => gh.get_json(&languages_path).await.ok(),
+        }
+    };
+    let span = state.trace.span("fetch", "resolve_ref").attr("ref", &default_branch);
+    let (commit_sha, languages_json) = tokio::join!(gh.resolve_ref(&owner, &repo, &default_branch), fetch_languages);
+    let commit_sha = commit_sha?;
+    span.end();
+    let blob_base_url = github::blob_url(&owner, &repo, &commit_sha, "", None);
+
+    // With the tarball mode, the whole snapshot is downloaded once and everything below
+    // (tree, README, dependencies, sources) is served from memory.
+    let tarball = if use_tarball.unwrap_or(false) {
+        log_status(&app, format!("Downloading {} snapshot as a tarball", default_branch));
+        progress.stage("tarball", format!("Downloading {} snapshot as a tarball", default_branch));
+        let work_dir = state.temp_dirs.create("tarball")?;
+        let _span = state.trace.span("fetch", "tarball");
+        Some(gh.fetch_tarball(&owner, &repo, &commit_sha, work_dir.path()).await?)
+    } else {
+        None
+    };
 
     // 2. Fetch tree
-    let tree_url = format!("https://api.github.com/repos/{}/{}/git/trees/{}?recursive=1", owner, repo, default_branch);
-    let mut tree_builder = isahc::Request::builder()
-        .method("GET")
-        .uri(&tree_url)
-        .header("Accept", "application/vnd.github.v3+json")
-        .header("User-Agent", "Tauri/Prompt-Generator");
-
-    if !token_arc.is_empty() {
-        tree_builder = tree_builder.header("Authorization", format!("token {}", *token_arc));
+    let mut tree_paths: Vec<String> = if let Some(t) = &tarball {
+        t.paths.clone()
+    } else {
+        log_status(&app, format!("Fetching file tree for {}", default_branch));
+        progress.stage("tree", format!("Fetching file tree for {}", default_branch));
+        let _span = state.trace.span("fetch", "tree");
+        gh.fetch_tree(&owner, &repo, &commit_sha, &prefix, |msg| log_status(&app, msg)).await?
+    };
+    if let Some(sub) = &subpath {
+        tree_paths.retain(|p| p.starts_with(&prefix));
+        if tree_paths.is_empty() {
+            return Err(AppError::NotFound(format!("No files found under '{}' in {}/{}", sub, owner, repo)));
+        }
     }
-
-    let mut tree_res = client.send_async(tree_builder.body("".to_string()).unwrap()).await.map_err(|e| e.to_string())?;
-    let tree_text = tree_res.text().await.map_err(|e| e.to_string())?;
-    let tree_json: serde_json::Value = serde_json::from_str(&tree_text).map_err(|e| e.to_string())?;
-    let mut tree_paths: Vec<String> = tree_json["tree"]
-        .as_array()
-        .map(|a| a.iter().filter(|i| i["type"] == "blob").filter_map(|i| i["path"].as_str().map(|s| s.to_string())).collect())
-        .unwrap_or_default();
+    // The repository's `.repopromptignore` is read from its root even for a subpath,
+    // whose tree doesn't say whether there is one.
+    let project_ignore = match &tarball {
+        Some(t) => t.files.get(IGNORE_FILE).cloned(),
+        None if subpath.is_some() || tree_paths.iter().any(|p| p == IGNORE_FILE) => gh.fetch_file_content(&owner, &repo, IGNORE_FILE, &commit_sha).await,
+        None => None,
+    };
+    let ignore = IgnoreRules::layered(&state.ignore_patterns.read().await, project_ignore.as_deref());
+    tree_paths.retain(|p| !ignore.is_ignored(p, false));
 
     // 3. Parallel fetch for README and dependencies
-    let dep_files_list = ["package.json", "requirements.txt", "go.mod", "Cargo.toml", "pom.xml", "build.gradle"];
-    let mut join_set = JoinSet::new();
-
-    // Fetch README
-    let readme_url = format!("https://api.github.com/repos/{}/{}/readme", owner, repo);
-    let client_c = Arc::clone(&client);
-    let token_c = Arc::clone(&token_arc);
-    join_set.spawn(async move {
-        let mut b = isahc::Request::builder()
-            .uri(&readme_url)
-            .header("Accept", "application/vnd.github.v3+json")
-            .header("User-Agent", "Tauri/Prompt-Generator");
-        if !token_c.is_empty() { b = b.header("Authorization", format!("token {}", *token_c)); }
-        
-        if let Ok(mut res) = client_c.send_async(b.body("".to_string()).unwrap()).await {
-            if res.status().is_success() {
-                if let Ok(text) = res.text().await {
-                    if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
-                        if let Some(content) = json["content"].as_str() {
-                            let cleaned = content.replace('\n', "").replace('\r', "");
-                            if let Ok(decoded) = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, cleaned) {
-                                return Some(("readme", String::from_utf8_lossy(&decoded).to_string()));
-                            }
-                        }
-                    }
-                }
+    // Manifests anywhere in the tree, so each package of a monorepo is covered. Lock files
+    // are only named, so they aren't downloaded.
+    let dep_paths = repo_prompt_core::dependencies::find_manifests(&tree_paths, &state.manifest_files.read().await);
+    let mut manifest_files: Vec<(String, String)> = Vec::new();
+    let readme = if let Some(t) = &tarball {
+        for file in &dep_paths {
+            if repo_prompt_core::dependencies::is_lockfile(file) {
+                manifest_files.push((file.clone(), String::new()));
+            } else if let Some(content) = t.files.get(file) {
+                manifest_files.push((file.clone(), content.clone()));
             }
         }
-        None
-    });
+        t.readme(subpath.as_deref()).or_else(|| t.readme(None)).unwrap_or_default()
+    } else {
+        log_status(&app, "Fetching README and dependency manifests");
+        progress.stage("readme", "Fetching README and dependency manifests");
+        let _span = state.trace.span("fetch", "readme_and_dependencies");
+        let (lockfiles, present_deps): (Vec<&String>, Vec<&String>) = dep_paths.iter().partition(|f| repo_prompt_core::dependencies::is_lockfile(f));
+        manifest_files.extend(lockfiles.into_iter().map(|f| (f.clone(), String::new())));
+        // A package without its own README falls back to the repository one.
+        let readme_fut = async {
+            match gh.fetch_readme(&owner, &repo, &commit_sha, subpath.as_deref()).await {
+                None if subpath.is_some() => gh.fetch_readme(&owner, &repo, &commit_sha, None).await,
+                other => other,
+            }
+        };
+        let (readme_res, dep_contents) = tokio::join!(
+            readme_fut,
+            futures_util::future::join_all(present_deps.iter().map(|f| gh.fetch_file_content(&owner, &repo, f, &commit_sha))),
+        );
+        for (file_name, content) in present_deps.iter().zip(dep_contents) {
+            if let Some(content) = content {
+                manifest_files.push((file_name.to_string(), content));
+            }
+        }
+        readme_res.unwrap_or_default()
+    };
+    let (dependencies, manifests) = repo_prompt_core::dependencies::describe(&manifest_files, raw_dependencies.unwrap_or(false));
+
+    // Toolchain commands, from the README, manifests, Makefile/justfile and CI workflows.
+    // Lock files only tell which package manager is used, so their content isn't needed.
+    let build_instructions = {
+        let _span = state.trace.span("fetch", "build_instructions");
+        let is_source = |p: &&String| {
+            let rest = p.strip_prefix(&prefix).unwrap_or(p);
+            instructions::SOURCE_FILES.contains(&rest)
+        };
+        let workflows = tree_paths
+            .iter()
+            .filter(|p| p.starts_with(".github/workflows/") && (p.ends_with(".yml") || p.ends_with(".yaml")))
+            .take(instructions::MAX_WORKFLOWS);
+        let paths: Vec<String> = tree_paths.iter().filter(is_source).chain(workflows).cloned().collect();
+        let mut sources = match &tarball {
+            Some(t) => paths.into_iter().filter_map(|p| t.files.get(&p).map(|c| FileEntry::new(p, c.clone()))).collect(),
+            None => fetch_files(&gh, &owner, &repo, &commit_sha, paths, DEFAULT_FETCH_CONCURRENCY, |_| {}).await,
+        };
+        sources.extend(tree_paths.iter().filter(|p| instructions::LOCK_FILES.iter().any(|l| p.ends_with(l))).map(|p| FileEntry::new(p.clone(), String::new())));
+        sources.push(FileEntry::new("README.md".to_string(), readme.clone()));
+        instructions::extract(&sources)
+    };
 
-    // Fetch deps
-    for file in dep_files_list {
-        if tree_paths.contains(&file.to_string()) {
-            let client_c = Arc::clone(&client);
-            let token_c = Arc::clone(&token_arc);
-            let file_name = file.to_string();
-            let file_url = format!("https://api.github.com/repos/{}/{}/contents/{}", owner, repo, file);
-            join_set.spawn(async move {
-                let mut b = isahc::Request::builder()
-                    .uri(&file_url)
-                    .header("Accept", "application/vnd.github.v3+json")
-                    .header("User-Agent", "Tauri/Prompt-Generator");
-                if !token_c.is_empty() { b = b.header("Authorization", format!("token {}", *token_c)); }
-                
-                if let Ok(mut res) = client_c.send_async(b.body("".to_string()).unwrap()).await {
-                    if res.status().is_success() {
-                        if let Ok(text) = res.text().await {
-                            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
-                                if let Some(content) = json["content"].as_str() {
-                                    let cleaned = content.replace('\n', "").replace('\r', "");
-                                    if let Ok(decoded) = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, cleaned) {
-                                        return Some(("dep", format!("\n--- {} ---\n{}\n", file_name, String::from_utf8_lossy(&decoded))));
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-                None
-            });
+    // 4. Resolve submodules; when they are fetched, each gets its own share of the budget.
+    let concurrency = concurrency.unwrap_or(DEFAULT_FETCH_CONCURRENCY).clamp(1, 32);
+    let mut limit = max_files.unwrap_or(5).clamp(1, 200) as usize;
+    let gitmodules = if !tree_paths.iter().any(|p| p == ".gitmodules") {
+        None
+    } else if let Some(t) = &tarball {
+        t.files.get(".gitmodules").cloned()
+    } else {
+        gh.fetch_file_content(&owner, &repo, ".gitmodules", &commit_sha).await
+    };
+    let (submodules, submodule_tree, submodule_files) = match gitmodules {
+        Some(content) => {
+            log_status(&app, "Resolving submodules");
+            progress.stage("submodules", "Resolving submodules");
+            let _span = state.trace.span("fetch", "submodules");
+            let declared = content.matches("[submodule").count();
+            let share = include_submodules.unwrap_or(false).then(|| (limit / (declared + 1)).max(1));
+            let resolved = submodules::resolve_submodules(&gh, &owner, &repo, &commit_sha, &content, &prefix, share, concurrency, &scoring).await;
+            let used: usize = resolved.0.iter().map(|s| s.files_fetched).sum();
+            limit = limit.saturating_sub(used).max(1);
+            resolved
         }
+        None => (Vec::new(), Vec::new(), Vec::new()),
+    };
+
+    // 5. Determine and fetch source files in parallel
+    let selected = select_source_files(&tree_paths, &prefix, &dep_paths, limit, &scoring);
+    let mut source_files: Vec<FileEntry> = if let Some(t) = &tarball {
+        selected
+            .into_iter()
+            .filter_map(|path| t.files.get(&path).map(|content| FileEntry::new(path, content.clone())))
+            .collect()
+    } else {
+        log_status(&app, format!("Fetching {} source files", selected.len()));
+        let _span = state.trace.span("fetch", "source_files").attr("files", selected.len());
+        let total = selected.len();
+        fetch_files(&gh, &owner, &repo, &commit_sha, selected, concurrency, |done| {
+            progress.update("files", done as u64, Some(total as u64), format!("Fetched {} of {} source files", done, total));
+        })
+        .await
+    };
+    // The next tier of ranked files is fetched in the background, so widening the
+    // selection afterwards is served from the cache.
+    if tarball.is_none() && use_cache {
+        let taken: Vec<String> = source_files.iter().map(|f| f.path.clone()).chain(dep_paths.iter().cloned()).collect();
+        let next = select_source_files(&tree_paths, &prefix, &taken, prefetch::DEFAULT_PREFETCH_FILES, &scoring);
+        prefetch::start(app.clone(), prefetch_token, owner.clone(), repo.clone(), commit_sha.clone(), next);
     }
+    source_files.extend(submodule_files);
+    tree_paths.extend(submodule_tree);
+
+    log_status(&app, format!("Fetched {} of {} files from {}/{}", source_files.len(), tree_paths.len(), owner, repo));
+    progress.finish(format!("Fetched {} of {} files", source_files.len(), tree_paths.len()));
+
+    // GitHub's own linguist numbers cover the whole repository; for a subpath they are
+    // computed from the files at hand.
+    let languages = match (&tarball, &subpath) {
+        (Some(t), Some(_)) => {
+            let files: Vec<FileEntry> = t
+                .files
+                .iter()
+                .filter(|(p, _)| p.starts_with(&prefix))
+                .map(|(p, c)| FileEntry::new(p.clone(), c.clone()))
+                .collect();
+            stats::language_breakdown(&files)
+        }
+        (None, Some(_)) => stats::language_breakdown(&source_files),
+        (_, None) => languages_json
+            .and_then(|json| serde_json::from_value(json).ok())
+            .map(stats::breakdown_from_bytes)
+            .unwrap_or_default(),
+    };
 
-    let mut readme = String::new();
-    let mut dependencies = String::new();
-    while let Some(res) = join_set.join_next().await {
-        if let Ok(Some((type_tag, content))) = res {
-            if type_tag == "readme" { readme = content; }
-            else { dependencies.push_str(&content); }
+    let rate_limit = gh.rate_limit();
+    if let Some(remaining) = rate_limit.remaining {
+        if !rate_limit.authenticated && remaining < 20 {
+            log_status(&app, format!("GitHub quota low: {} unauthenticated requests left this hour", remaining));
         }
     }
 
-    // 4. Determine and fetch source files in parallel
-    let source_extensions = [".ts", ".tsx", ".js", ".jsx", ".py", ".go", ".rs", ".java", ".cpp", ".c", ".h", ".cs", ".md"];
-    let mut files_to_fetch: Vec<String> = tree_paths.iter()
-        .filter(|p| source_extensions.iter().any(|ext| p.ends_with(ext)))
-        .filter(|p| !dep_files_list.contains(&p.as_str()) && p.to_lowercase() != "readme.md")
-        .cloned().collect();
-
-    fn get_file_score(path: &str) -> i32 {
-        let mut score = 0;
-        let lower = path.to_lowercase();
-        let parts: Vec<&str> = lower.split('/').collect();
-        let name = parts.last().unwrap_or(&"");
-        if lower.contains("/test/") || lower.contains("/tests/") || lower.contains("__tests__") || name.contains(".test.") || name.contains(".spec.") { score -= 50; }
-        if ["build", "setup", "config", "webpack", "vite", "docs/"].iter().any(|&k| lower.contains(k)) { score -= 30; }
-        if ["src/", "lib/", "app/", "core/"].iter().any(|&d| lower.starts_with(d) || lower.contains(&format!("/{}", d))) { score += 20; }
-        if ["main", "index", "app", "server", "core", "api", "service", "model"].iter().any(|&n| name.contains(n)) { score += 10; }
-        score -= parts.len() as i32;
-        score
-    }
-
-    files_to_fetch.sort_by(|a, b| get_file_score(b).cmp(&get_file_score(a)));
-    let limit = max_files.unwrap_or(5).clamp(1, 200) as usize;
-    let selected = if files_to_fetch.len() > limit { files_to_fetch[0..limit].to_vec() } else { files_to_fetch };
-
-    let mut source_join_set = JoinSet::new();
-    for file in selected {
-        let client_c = Arc::clone(&client);
-        let token_c = Arc::clone(&token_arc);
-        let path = file.clone();
-        let file_url = format!("https://api.github.com/repos/{}/{}/contents/{}", owner, repo, file);
-        source_join_set.spawn(async move {
-            let mut b = isahc::Request::builder()
-                .uri(&file_url)
-                .header("Accept", "application/vnd.github.v3+json")
-                .header("User-Agent", "Tauri/Prompt-Generator");
-            if !token_c.is_empty() { b = b.header("Authorization", format!("token {}", *token_c)); }
-            
-            if let Ok(mut res) = client_c.send_async(b.body("".to_string()).unwrap()).await {
-                if res.status().is_success() {
-                    if let Ok(text) = res.text().await {
-                        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
-                            if let Some(content) = json["content"].as_str() {
-                                let cleaned = content.replace('\n', "").replace('\r', "");
-                                if let Ok(decoded) = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, cleaned) {
-                                    return Some(FileEntry { path, content: String::from_utf8_lossy(&decoded).to_string() });
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            None
-        });
+    projects::remember(&app, &state, format!("{}/{}", owner, repo), Some(default_branch.clone()), Some(commit_sha.clone()), &source_files);
+    recent::record(&app, recent::RepoKind::Github, format!("{}/{}", owner, repo), options);
+
+    let mut is_truncated = false;
+    if tree_paths.len() > 1000 { tree_paths.truncate(1000); is_truncated = true; }
+
+    let data = GithubRepoData {
+        info: RepoInfo { owner, repo, default_branch, description, commit_sha, blob_base_url, stars, topics, license, size_kb, languages },
+        stats: repo_prompt_core::tokens::PackStats::of(&source_files),
+        tree: tree_paths, readme, dependencies, manifests, source_files, is_truncated, rate_limit, submodules, build_instructions,
+    };
+    if use_cache {
+        if let Err(e) = repocache::put_snapshot(&app, &state, &cache_key, requested.as_deref(), &data) {
+            log_status(&app, format!("Could not cache the snapshot: {}", e));
+        }
     }
+    Ok(data)
+}
 
-    let mut source_files = Vec::new();
-    while let Some(res) = source_join_set.join_next().await {
-        if let Ok(Some(entry)) = res { source_files.push(entry); }
+/// Refetches individual files from a pinned snapshot, e.g. to pull in a file the model
+/// asked about. Only full commit SHAs are accepted so the answer can't drift from the
+/// snapshot the rest of the context came from.
+#[tauri::command]
+async fn fetch_github_files(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    owner: String,
+    repo: String,
+    commit_sha: String,
+    paths: Vec<String>,
+    token: Option<String>,
+    use_cache: Option<bool>,
+) -> Result<Vec<FileEntry>, AppError> {
+    use futures_util::stream::{self, StreamExt};
+
+    state.policy.check_not_demo("GitHub access")?;
+    if !github::is_full_sha(&commit_sha) {
+        return Err(AppError::InvalidInput(format!("'{}' is not a full commit SHA", commit_sha)));
     }
+    let gh = github::GithubClient::new(state.http_client.read().await.clone(), token.unwrap_or_default())
+        .with_cache(cache::open_cache(&app, &state, "github").ok());
 
-    let mut is_truncated = false;
-    if tree_paths.len() > 1000 { tree_paths.truncate(1000); is_truncated = true; }
+    // Files at a commit never change, so cached copies are always valid.
+    let use_cache = use_cache.unwrap_or(true);
+    let (cached, paths) = if use_cache {
+        repocache::cached_files(&app, &state, &owner, &repo, &commit_sha, paths)
+    } else {
+        (Vec::new(), paths)
+    };
 
-    Ok(GithubRepoData {
-        info: RepoInfo { owner, repo, default_branch, description },
-        tree: tree_paths, readme, dependencies, source_files, is_truncated,
-    })
+    log_status(&app, format!("Fetching {} files from {}/{}@{}", paths.len(), owner, repo, &commit_sha[..7]));
+    let mut files: Vec<FileEntry> = stream::iter(paths)
+        .map(|path| {
+            let gh = &gh;
+            let (owner, repo, commit_sha) = (&owner, &repo, &commit_sha);
+            async move {
+                gh.fetch_file_content(owner, repo, &path, commit_sha)
+                    .await
+                    .map(|content| FileEntry::new(path, content))
+            }
+        })
+        .buffer_unordered(DEFAULT_FETCH_CONCURRENCY)
+        .filter_map(|entry| async move { entry })
+        .collect()
+        .await;
+    if use_cache {
+        repocache::put_files(&app, &state, &owner, &repo, &commit_sha, &files);
+    }
+    files.extend(cached);
+    Ok(files)
 }
 
 #[tauri::command]
@@ -429,62 +809,62 @@ async fn is_ollama_running() -> bool {
     let name_win = OsStr::new("ollama.exe");
     let name_unix = OsStr::new("ollama");
     
-    s.processes().values().any(|p| p.name() == name_win || p.name() == name_unix)
+    // Docker Desktop runs containers in a VM, so their processes don't show up here.
+    s.processes().values().any(|p| p.name() == name_win || p.name() == name_unix) || docker::ollama_container_running().await
 }
 
 #[tauri::command]
-async fn start_ollama(state: State<'_, AppState>) -> Result<String, String> {
-    if is_ollama_running().await {
+async fn start_ollama(app: AppHandle, state: State<'_, AppState>, auto_restart: Option<bool>, options: Option<ollama::ServerOptions>) -> Result<String, AppError> {
+    if let Some(enabled) = auto_restart {
+        state.ollama_auto_restart.store(enabled, Ordering::SeqCst);
+    }
+    let options = options.unwrap_or_default();
+    if let Some(path) = options.binary_path.as_deref().filter(|p| !p.trim().is_empty()) {
+        // Demo mode runs only the installed Ollama, never a program the caller names.
+        state.policy.check_not_demo("Running a custom Ollama executable")?;
+        if !std::path::Path::new(path.trim()).is_file() {
+            return Err(AppError::NotFound(format!("Ollama executable not found: {}", path)));
+        }
+    }
+    // With a custom host, only a server on that address counts as already running.
+    let host_url = options.host.as_deref().filter(|h| !h.trim().is_empty()).map(ollama::host_url);
+    let running = match &host_url {
+        Some(url) => ollama::responds(&state, url).await,
+        None => is_ollama_running().await,
+    };
+    if running {
         return Ok("Ollama is already running".to_string());
     }
 
-    #[cfg(target_os = "windows")]
-    let child = Command::new("ollama")
-        .arg("serve")
-        .creation_flags(0x08000000) // CREATE_NO_WINDOW
-        .spawn();
-
-    #[cfg(not(target_os = "windows"))]
-    let child = Command::new("ollama")
-        .arg("serve")
-        .spawn();
-
-    match child {
-        Ok(_) => {
+    match ollama::spawn_server(&state, &options) {
+        Ok(child) => {
             state.we_started_ollama.store(true, Ordering::SeqCst);
+            ollama::supervise(app.clone(), child, options);
+            // Only report success once the API answers, so the first model-list call
+            // doesn't race the server's startup.
+            let inherited_host = std::env::var("OLLAMA_HOST").ok().filter(|h| !h.trim().is_empty());
+            let base = host_url.or(inherited_host.as_deref().map(ollama::host_url)).unwrap_or_else(|| ollama::DEFAULT_OLLAMA_URL.to_string());
+            log_status(&app, "Waiting for Ollama to become ready");
+            ollama::wait_until_ready(&state, &base, ollama::STARTUP_TIMEOUT).await?;
+            *state.ollama_url.write().await = Some(base);
+            log_status(&app, "Ollama started");
             Ok("Ollama started successfully".to_string())
         }
-        Err(e) => Err(format!("Failed to start Ollama: {}", e)),
-    }
-}
-
-#[tauri::command]
-async fn save_text_file(path: String, content: String) -> Result<String, String> {
-    match fs::write(&path, content) {
-        Ok(_) => Ok(format!("Successfully saved to {}", path)),
-        Err(e) => Err(format!("Failed to save file: {}", e)),
+        Err(e) => {
+            log_status(&app, format!("Failed to start Ollama: {}", e));
+            Err(AppError::Io(format!("Failed to start Ollama: {}", e)))
+        }
     }
 }
 
 #[tauri::command]
-async fn stop_ollama(state: State<'_, AppState>) -> Result<String, String> {
+async fn stop_ollama(app: AppHandle, state: State<'_, AppState>) -> Result<String, AppError> {
     let we_started_it = state.we_started_ollama.swap(false, Ordering::SeqCst);
 
     if we_started_it {
-        let mut s = System::new();
-        s.refresh_processes_specifics(sysinfo::ProcessesToUpdate::All, true, ProcessRefreshKind::everything());
-        let mut killed = 0;
-        
-        for process in s.processes_by_exact_name(OsStr::new("ollama.exe")) {
-            process.kill();
-            killed += 1;
-        }
-        for process in s.processes_by_exact_name(OsStr::new("ollama")) {
-            process.kill();
-            killed += 1;
-        }
-        
+        let killed = ollama::kill_managed(&state);
         if killed > 0 {
+            log_status(&app, format!("Stopped {} Ollama processes", killed));
             return Ok(format!("Stopped {} Ollama processes", killed));
         }
         Ok("Process not found. It may have exited.".to_string())
@@ -494,12 +874,19 @@ async fn stop_ollama(state: State<'_, AppState>) -> Result<String, String> {
 }
 
 #[tauri::command]
-async fn ollama_check_connection(state: State<'_, AppState>, url: String) -> Result<bool, String> {
-    let url = url.replace("localhost", "127.0.0.1");
+async fn ollama_check_connection(state: State<'_, AppState>, url: String) -> Result<bool, AppError> {
+    state.policy.check_local_url(&url)?;
+    let url = ollama::normalize_url(&url);
     let endpoint = format!("{}/api/tags", url);
-    let res = state.ollama_client.get_async(&endpoint).await;
+    let client = state.ollama_client.read().await.clone();
+    let res = client.get_async(&endpoint).await;
     match res {
-        Ok(r) => Ok(r.status().is_success()),
+        Ok(r) => {
+            if r.status().is_success() {
+                *state.ollama_url.write().await = Some(url);
+            }
+            Ok(r.status().is_success())
+        }
         Err(e) => {
             eprintln!("Ollama connection error for {}: {}", endpoint, e);
             Ok(false)
@@ -508,12 +895,11 @@ async fn ollama_check_connection(state: State<'_, AppState>, url: String) -> Res
 }
 
 #[tauri::command]
-async fn ollama_fetch_models(state: State<'_, AppState>, url: String) -> Result<Vec<String>, String> {
-    let url = url.replace("localhost", "127.0.0.1");
-    let endpoint = format!("{}/api/tags", url);
-    let mut res = state.ollama_client.get_async(&endpoint).await.map_err(|e| {
-        eprintln!("Ollama fetch models error for {}: {}", endpoint, e);
-        e.to_string()
+async fn ollama_fetch_models(state: State<'_, AppState>, url: String) -> Result<Vec<String>, AppError> {
+    state.policy.check_local_url(&url)?;
+    let mut res = ollama::get(&state, &url, "/api/tags").await.map_err(|e| {
+        eprintln!("Ollama fetch models error for {}: {}", url, e);
+        e
     })?;
     
     if !res.status().is_success() {
@@ -535,8 +921,12 @@ async fn ollama_fetch_models(state: State<'_, AppState>, url: String) -> Result<
     Ok(models)
 }
 
+/// Generates a reply to `prompt`, with the tokens it took. Without streaming, a reply cut
+/// off at the token limit is continued up to `max_continuations` times (default 3).
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn ollama_generate(
+    app: AppHandle,
     state: State<'_, AppState>,
     url: String,
     model: String,
@@ -545,82 +935,208 @@ async fn ollama_generate(
     num_predict: Option<usize>,
     temperature: Option<f32>,
     format: Option<String>,
-) -> Result<String, String> {
-    let url = url.replace("localhost", "127.0.0.1");
-    let endpoint = format!("{}/api/generate", url);
-    
+    images: Option<Vec<images::ImageAttachment>>,
+    stream: Option<bool>,
+    request_id: Option<String>,
+    keep_alive: Option<serde_json::Value>,
+    max_continuations: Option<u32>,
+    operation_id: Option<String>,
+) -> Result<ollama::OllamaReply, AppError> {
+    let stream = stream.unwrap_or(false);
     let mut options = serde_json::Map::new();
     if let Some(ctx) = num_ctx { options.insert("num_ctx".to_string(), serde_json::Value::from(ctx)); }
     if let Some(predict) = num_predict { options.insert("num_predict".to_string(), serde_json::Value::from(predict)); }
     if let Some(temp) = temperature { options.insert("temperature".to_string(), serde_json::Value::from(temp)); }
 
+    state.policy.check_provider("ollama")?;
+    state.policy.check_local_url(&url)?;
     let mut body_map: serde_json::Map<String, serde_json::Value> = serde_json::Map::new();
-    body_map.insert("model".to_string(), serde_json::Value::from(model));
-    body_map.insert("prompt".to_string(), serde_json::Value::from(prompt));
-    body_map.insert("stream".to_string(), serde_json::Value::from(false));
-    body_map.insert("options".to_string(), serde_json::Value::Object(options));
+    log_status(&app, format!("Generating with Ollama model {}", model));
+    let _span = state.trace.span("llm", "ollama_generate").attr("model", &model);
+    body_map.insert("model".to_string(), serde_json::Value::from(model.as_str()));
+    body_map.insert("prompt".to_string(), serde_json::Value::from(state.policy.redact(&prompt)));
+    body_map.insert("stream".to_string(), serde_json::Value::from(stream));
+    body_map.insert("options".to_string(), serde_json::Value::Object(options.clone()));
+    if let Some(keep_alive) = keep_alive {
+        body_map.insert("keep_alive".to_string(), keep_alive);
+    }
     if let Some(f) = format {
         body_map.insert("format".to_string(), serde_json::Value::from(f));
     }
+    let images = images::validate_images(images.unwrap_or_default()).map_err(AppError::InvalidInput)?;
+    if !images.is_empty() {
+        let encoded: Vec<String> = images.into_iter().map(|i| i.data).collect();
+        body_map.insert("images".to_string(), serde_json::Value::from(encoded));
+    }
     let body = serde_json::Value::Object(body_map);
+    let progress = state.progress.start(&app, "generate", operation_id);
+    progress.stage("generating", format!("Waiting for Ollama ({})", model));
+
+    // Streaming emits `ollama://token` events as chunks arrive and returns the full text.
+    if stream {
+        let result = ollama::stream_ndjson(&app, &state, &url, "/api/generate", &body, |c| c["response"].as_str(), request_id).await;
+        log_status(&app, if result.is_ok() { "Ollama generation finished" } else { "Ollama generation failed" });
+        let result = result?;
+        progress.finish("Ollama generation finished");
+        return Ok(result);
+    }
 
-    let request = isahc::Request::builder()
-        .method("POST")
-        .uri(endpoint)
-        .header("Content-Type", "application/json")
-        .body(serde_json::to_string(&body).unwrap())
-        .map_err(|e| e.to_string())?;
-
-    let mut res = state.ollama_client
-        .send_async(request)
-        .await
-        .map_err(|e| e.to_string())?;
+    let mut res = ollama::post_json(&state, &url, "/api/generate", &body).await?;
 
     let status = res.status();
     let data_text = res.text().await.map_err(|e| e.to_string())?;
 
     if !status.is_success() {
-        return Err(format!("Ollama error: {}", data_text));
+        log_status(&app, format!("Ollama generation failed ({})", status));
+        return Err(AppError::from_status(status.as_u16(), format!("Ollama error: {}", data_text), None));
     }
 
     let data: serde_json::Value = serde_json::from_str(&data_text).map_err(|e| e.to_string())?;
-    let response = data["response"].as_str().unwrap_or_default().to_string();
-    
+    let mut response = ollama::OllamaReply {
+        text: data["response"].as_str().unwrap_or_default().to_string(),
+        usage: ollama::count_usage(&state, &model, &data),
+        continuations: 0,
+    };
+    let done_reason = data["done_reason"].as_str().map(|s| s.to_string());
+    let chat = serde_json::json!({
+        "model": model,
+        "messages": [{ "role": "user", "content": body["prompt"] }],
+        "options": options,
+    });
+    let max = max_continuations.unwrap_or(continuation::DEFAULT_MAX_CONTINUATIONS);
+    ollama::continue_reply(&state, &url, chat, &mut response, done_reason, max).await?;
+    if response.continuations > 0 {
+        log_status(&app, format!("Ollama generation finished ({} continuations)", response.continuations));
+    } else {
+        log_status(&app, "Ollama generation finished");
+    }
+    progress.finish("Ollama generation finished");
     Ok(response)
 }
 
+/// Multi-turn chat through `/api/chat`, so follow-up questions about a packed repository
+/// can build on earlier turns. Images attached to the last user message are validated
+/// like `ollama_generate`'s, and truncated replies are continued the same way. The reply
+/// comes with the tokens it took.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn ollama_chat(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    url: String,
+    model: String,
+    messages: Vec<ollama::ChatMessage>,
+    num_ctx: Option<usize>,
+    num_predict: Option<usize>,
+    temperature: Option<f32>,
+    format: Option<String>,
+    images: Option<Vec<images::ImageAttachment>>,
+    stream: Option<bool>,
+    request_id: Option<String>,
+    keep_alive: Option<serde_json::Value>,
+    max_continuations: Option<u32>,
+    operation_id: Option<String>,
+) -> Result<ollama::OllamaReply, AppError> {
+    state.policy.check_provider("ollama")?;
+    state.policy.check_local_url(&url)?;
+    let mut messages = messages;
+    if messages.is_empty() {
+        return Err(AppError::InvalidInput("The conversation has no messages".to_string()));
+    }
+    for m in messages.iter_mut() {
+        m.content = state.policy.redact(&m.content);
+    }
+    if let Some(m) = messages.iter().find(|m| !matches!(m.role.as_str(), "system" | "user" | "assistant")) {
+        return Err(AppError::InvalidInput(format!("Unsupported message role: {}", m.role)));
+    }
+    let images = images::validate_images(images.unwrap_or_default()).map_err(AppError::InvalidInput)?;
+    if !images.is_empty() {
+        let last_user = messages.iter_mut().rev().find(|m| m.role == "user").ok_or("Images need a user message to attach to")?;
+        last_user.images = Some(images.into_iter().map(|i| i.data).collect());
+    }
+
+    let mut options = serde_json::Map::new();
+    if let Some(ctx) = num_ctx { options.insert("num_ctx".to_string(), serde_json::Value::from(ctx)); }
+    if let Some(predict) = num_predict { options.insert("num_predict".to_string(), serde_json::Value::from(predict)); }
+    if let Some(temp) = temperature { options.insert("temperature".to_string(), serde_json::Value::from(temp)); }
+
+    let stream = stream.unwrap_or(false);
+    let mut body = serde_json::json!({
+        "model": model,
+        "messages": messages,
+        "stream": stream,
+        "options": options,
+    });
+    if let Some(f) = format {
+        body["format"] = serde_json::Value::from(f);
+    }
+    if let Some(keep_alive) = keep_alive {
+        body["keep_alive"] = keep_alive;
+    }
+
+    log_status(&app, format!("Chatting with Ollama model {} ({} messages)", model, messages.len()));
+    let _span = state.trace.span("llm", "ollama_chat").attr("model", &model);
+    let progress = state.progress.start(&app, "generate", operation_id);
+    progress.stage("generating", format!("Waiting for Ollama ({})", model));
+    if stream {
+        let result = ollama::stream_ndjson(&app, &state, &url, "/api/chat", &body, |c| c["message"]["content"].as_str(), request_id).await;
+        log_status(&app, if result.is_ok() { "Ollama chat reply finished" } else { "Ollama chat failed" });
+        let result = result?;
+        progress.finish("Ollama chat reply finished");
+        return Ok(result);
+    }
+
+    let mut res = ollama::post_json(&state, &url, "/api/chat", &body).await?;
+    let status = res.status();
+    let data_text = res.text().await.map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        log_status(&app, format!("Ollama chat failed ({})", status));
+        return Err(AppError::from_status(status.as_u16(), format!("Ollama error: {}", data_text), None));
+    }
+
+    let data: serde_json::Value = serde_json::from_str(&data_text).map_err(|e| e.to_string())?;
+    let mut reply = ollama::OllamaReply {
+        text: data["message"]["content"].as_str().unwrap_or_default().to_string(),
+        usage: ollama::count_usage(&state, &model, &data),
+        continuations: 0,
+    };
+    let done_reason = data["done_reason"].as_str().map(|s| s.to_string());
+    let max = max_continuations.unwrap_or(continuation::DEFAULT_MAX_CONTINUATIONS);
+    ollama::continue_reply(&state, &url, body, &mut reply, done_reason, max).await?;
+    if reply.continuations > 0 {
+        log_status(&app, format!("Ollama chat reply finished ({} continuations)", reply.continuations));
+    } else {
+        log_status(&app, "Ollama chat reply finished");
+    }
+    progress.finish("Ollama chat reply finished");
+    Ok(reply)
+}
+
 #[tauri::command]
 async fn ollama_embed(
     state: State<'_, AppState>,
     url: String,
     model: String,
     prompt: String,
-) -> Result<Vec<f32>, String> {
-    let url = url.replace("localhost", "127.0.0.1");
-    let endpoint = format!("{}/api/embeddings", url);
-    
-    let body = serde_json::json!({
+    keep_alive: Option<serde_json::Value>,
+) -> Result<Vec<f32>, AppError> {
+    state.policy.check_provider("ollama")?;
+    state.policy.check_local_url(&url)?;
+    let mut body = serde_json::json!({
         "model": model,
-        "prompt": prompt
+        "prompt": state.policy.redact(&prompt)
     });
+    if let Some(keep_alive) = keep_alive {
+        body["keep_alive"] = keep_alive;
+    }
 
-    let request = isahc::Request::builder()
-        .method("POST")
-        .uri(endpoint)
-        .header("Content-Type", "application/json")
-        .body(serde_json::to_string(&body).unwrap())
-        .map_err(|e| e.to_string())?;
-
-    let mut res = state.ollama_client
-        .send_async(request)
-        .await
-        .map_err(|e| e.to_string())?;
+    let mut res = ollama::post_json(&state, &url, "/api/embeddings", &body).await?;
 
     let status = res.status();
     let res_text = res.text().await.map_err(|e| e.to_string())?;
 
     if !status.is_success() {
-        return Err(format!("Ollama error: {}", res_text));
+        return Err(AppError::from_status(status.as_u16(), format!("Ollama error: {}", res_text), None));
     }
 
     let data: serde_json::Value = serde_json::from_str(&res_text).map_err(|e| e.to_string())?;
@@ -636,14 +1152,61 @@ async fn ollama_embed(
     Ok(embedding)
 }
 
+/// Embeds many strings with `/api/embed`, batching them into as few requests as the
+/// batch limits allow. Returns one vector per input, in input order.
+#[tauri::command]
+async fn ollama_embed_batch(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    url: String,
+    model: String,
+    inputs: Vec<String>,
+    batch_size: Option<usize>,
+    keep_alive: Option<serde_json::Value>,
+) -> Result<Vec<Vec<f32>>, AppError> {
+    state.policy.check_provider("ollama")?;
+    state.policy.check_local_url(&url)?;
+    if inputs.is_empty() {
+        return Ok(Vec::new());
+    }
+    let inputs: Vec<String> = inputs.iter().map(|i| state.policy.redact(i)).collect();
+    let batch_size = batch_size.unwrap_or(ollama::DEFAULT_EMBED_BATCH).clamp(1, 1024);
+    let total = inputs.len();
+    let _span = state.trace.span("llm", "ollama_embed_batch").attr("inputs", total);
+    let vectors = ollama::embed_many(&state, &url, &model, &inputs, batch_size, keep_alive.as_ref(), |done| {
+        if total > batch_size {
+            log_status(&app, format!("Embedded {} of {} inputs", done, total));
+        }
+    })
+    .await?;
+    Ok(vectors)
+}
+
+/// Sends a request for the frontend's OpenAI-compatible providers. `provider` names the
+/// provider for the policy; without it, GitHub API requests count as GitHub access and
+/// anything else as `openai`. The body is redacted as the policy asks.
 #[tauri::command]
 async fn ai_network_request(
     state: State<'_, AppState>,
     method: String,
     url: String,
     headers: std::collections::HashMap<String, String>,
-    body: Option<String>
-) -> Result<serde_json::Value, String> {
+    body: Option<String>,
+    provider: Option<String>,
+) -> Result<serde_json::Value, AppError> {
+    state.policy.check_local_url(&url)?;
+    match provider.map(|p| p.trim().to_lowercase()).filter(|p| !p.is_empty()) {
+        Some(provider) => state.policy.check_provider(&provider)?,
+        None if policy::url_host(&url).eq_ignore_ascii_case("api.github.com") => state.policy.check_not_demo("GitHub access")?,
+        None => state.policy.check_provider("openai")?,
+    }
+    let body = body.map(|b| match serde_json::from_str::<serde_json::Value>(&b) {
+        Ok(mut json) => {
+            state.policy.redact_json(&mut json);
+            json.to_string()
+        }
+        Err(_) => state.policy.redact(&b),
+    });
     let url = url.replace("localhost", "127.0.0.1");
     let mut builder = isahc::Request::builder()
         .method(method.as_str())
@@ -679,7 +1242,10 @@ async fn ai_network_request(
 }
 
 #[tauri::command]
-async fn get_gemini_key_source(state: State<'_, AppState>) -> Result<String, String> {
+async fn get_gemini_key_source(state: State<'_, AppState>) -> Result<String, AppError> {
+    if state.policy.is_demo() {
+        return Ok("none".to_string());
+    }
     let app_key = state.gemini_api_key.read().await.clone();
     if !app_key.is_empty() {
         return Ok(format!("app_state:{}...{}", &app_key[..std::cmp::min(4, app_key.len())], &app_key[app_key.len().saturating_sub(4)..]));
@@ -696,11 +1262,23 @@ async fn get_gemini_key_source(state: State<'_, AppState>) -> Result<String, Str
     }
 }
 
+/// Runs a headless CLI command when the process arguments hold one; `None` means the app
+/// should start normally.
+pub fn run_cli() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    cli::run(&args)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // Priority: GEMINI_API_KEY (system) > VITE_GEMINI_API_KEY (.env) > empty
+    let policy = policy::OrgPolicy::load();
+
+    // Priority: GEMINI_API_KEY (system) > VITE_GEMINI_API_KEY (.env) > empty. Demo mode
+    // never picks up keys from the machine it runs on.
     let gemini_api_key = std::env::var("GEMINI_API_KEY")
         .or_else(|_| std::env::var("VITE_GEMINI_API_KEY"))
+        .ok()
+        .filter(|_| !policy.is_demo())
         .unwrap_or_default()
         .trim()
         .to_string();
@@ -710,15 +1288,8 @@ pub fn run() {
         gemini_api_key.len()
     );
 
-    let client = HttpClient::builder()
-        .timeout(Duration::from_secs(120))
-        .build()
-        .expect("Failed to create HTTP client");
-
-    let ollama_client = HttpClient::builder()
-        .timeout(Duration::from_secs(3600))
-        .build()
-        .expect("Failed to create Ollama client");
+    let client = network::build_client(&network::NetworkConfig::default()).expect("Failed to create HTTP client");
+    let ollama_client = network::build_client(&network::NetworkConfig::default()).expect("Failed to create Ollama client");
 
     tauri::Builder::default()
         .plugin(tauri_plugin_fs::init())
@@ -727,8 +1298,32 @@ pub fn run() {
         .manage(AppState {
             gemini_api_key: RwLock::new(gemini_api_key),
             http_client: RwLock::new(client),
-            ollama_client,
+            ollama_client: RwLock::new(ollama_client),
+            network: RwLock::new(network::NetworkConfig::default()),
+            ollama_url: RwLock::new(None),
             we_started_ollama: AtomicBool::new(false),
+            ollama_pid: AtomicU32::new(0),
+            watches: watch::Watches::default(),
+            cancellations: cancellation::Cancellations::default(),
+            ollama_auto_restart: AtomicBool::new(true),
+            lmstudio_started_with: std::sync::Mutex::new(None),
+            recent_lock: std::sync::Mutex::new(()),
+            snapshot_index_lock: std::sync::Mutex::new(()),
+            cache_compression_level: AtomicI32::new(cache::DEFAULT_COMPRESSION_LEVEL),
+            repo_cache_ttl_secs: AtomicU64::new(repocache::DEFAULT_TTL_SECS),
+            embeddings: embedcache::EmbeddingStore::default(),
+            manifest_files: RwLock::new(Vec::new()),
+            ignore_patterns: RwLock::new(Vec::new()),
+            scoring: RwLock::new(ScoringRules::default()),
+            prefetch_generation: AtomicU64::new(0),
+            status_log: status::StatusLog::default(),
+            clipboard: clipboard::NativeClipboard::default(),
+            progress: progress::ProgressTracker::default(),
+            sessions: sessions::SessionStore::default(),
+            usage: usage::UsageTracker::default(),
+            temp_dirs: tempdirs::TempDirManager::default(),
+            trace: trace::TraceRecorder::default(),
+            policy,
         })
         .setup(|app| {
             if cfg!(debug_assertions) {
@@ -738,43 +1333,155 @@ pub fn run() {
                         .build(),
                 )?;
             }
+            if let Ok(dir) = app.path().app_cache_dir() {
+                app.state::<AppState>().temp_dirs.init(dir.join("tmp"));
+            }
+            if let Ok(dir) = app.path().app_log_dir() {
+                logs::init(dir);
+            }
+            tauri::async_runtime::spawn(settings::restore(app.handle().clone()));
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             call_gemini_secure,
             call_gemini_advanced,
             scan_local_repository,
+            archive::scan_archive,
             fetch_github_repo,
+            cancellation::cancel_fetch,
+            fetch_github_files,
             is_ollama_running,
             start_ollama,
             stop_ollama,
-            save_text_file,
+            export::save_text_file,
+            export::export_pack,
+            dedup::dedup_files,
+            normalize::normalize_files,
+            dependencies::summarize_dependencies,
+            scanstream::scan_local_repository_streamed,
+            changes::scan_local_changes,
+            tree::render_tree,
+            workspace::pack_workspace,
+            watch::start_watch,
+            watch::stop_watch,
+            scanstream::read_project_files,
+            clipboard::copy_to_clipboard,
             ollama_check_connection,
             ollama_fetch_models,
             ollama_generate,
+            ollama_chat,
             ollama_embed,
+            ollama_embed_batch,
             get_gemini_key_source,
             set_app_config,
-            ai_network_request
+            ai_network_request,
+            outline::generate_outline,
+            cache::save_context_snapshot,
+            cache::load_context_snapshot,
+            cache::cache_gc,
+            embedcache::get_embedding_cache_stats,
+            embedcache::evict_embedding_cache,
+            embedcache::clear_embedding_cache,
+            stats::repo_stats,
+            onboarding::run_onboarding_checks,
+            status::get_status_log,
+            progress::list_operations,
+            providers::get_provider_capabilities,
+            images::prepare_image_attachment,
+            audio::transcribe_audio,
+            tempdirs::get_temp_usage,
+            tempdirs::clear_orphaned_temp_dirs,
+            permalink::make_permalink,
+            azure::call_azure_openai,
+            repourl::parse_repo_url,
+            review::post_review_comments,
+            review::fetch_github_pr,
+            review::fetch_github_compare,
+            githubauth::validate_github_token,
+            githubauth::start_github_device_flow,
+            githubauth::finish_github_device_flow,
+            benchmark::benchmark_local_models,
+            clone::clone_and_scan,
+            tokens::prompt_token_breakdown,
+            templates::render_template,
+            templates::list_templates,
+            templates::save_template,
+            templates::delete_template,
+            issues::fetch_github_issues,
+            gemini::gemini_embed,
+            gemini::call_gemini_json,
+            ollama::probe_ollama,
+            lmstudio::lmstudio_is_running,
+            lmstudio::lmstudio_detect,
+            lmstudio::lmstudio_list_models,
+            lmstudio::lmstudio_start_server,
+            lmstudio::lmstudio_stop_server,
+            resources::get_system_resources,
+            ollama::ollama_pull_model,
+            ollama::ollama_show_model,
+            ollama::ollama_delete_model,
+            ollama::ollama_running_models,
+            ollama::ollama_unload_model,
+            ollama::wait_for_ollama,
+            docker::detect_ollama_containers,
+            docker::start_ollama_container,
+            docker::stop_ollama_container,
+            trace::set_tracing,
+            trace::record_span,
+            trace::export_trace,
+            blocks::list_context_blocks,
+            blocks::save_context_block,
+            blocks::delete_context_block,
+            blocks::compose_context_blocks,
+            policy::get_org_policy,
+            conversation::resume_conversation,
+            sessions::create_session,
+            sessions::send_message,
+            sessions::get_history,
+            sessions::delete_session,
+            usage::get_usage_stats,
+            logs::get_recent_logs,
+            logs::export_logs,
+            instructions::extract_build_instructions,
+            history::record_answer,
+            history::find_similar_question,
+            generations::record_generation,
+            generations::list_history,
+            generations::get_history_item,
+            generations::export_history,
+            projects::refresh_subtree,
+            vectors::index_repository,
+            vectors::query_index,
+            vectors::semantic_search,
+            summarize::summarize_repository,
+            hybrid::load_hybrid_project,
+            chunking::chunk_files,
+            symbols::get_symbol_context,
+            testgen::generate_tests_for,
+            testgen::write_generated_test,
+            settings::load_settings,
+            settings::save_settings,
+            findings::record_audit_findings,
+            findings::list_findings,
+            findings::set_finding_status,
+            recent::get_recent_repos,
+            recent::pin_repo,
+            recent::clear_history,
+            repocache::get_cache_stats,
+            repocache::clear_repo_cache,
+            prefetch::prefetch_files,
+            prefetch::cancel_prefetch
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
         .run(move |app_handle, event| {
             if let RunEvent::Exit = event {
-                let we_started_it = {
-                    let state = app_handle.state::<AppState>();
-                    state.we_started_ollama.swap(false, Ordering::SeqCst)
-                };
-                if we_started_it {
-                    let mut s = System::new();
-                    s.refresh_processes_specifics(sysinfo::ProcessesToUpdate::All, true, ProcessRefreshKind::everything());
-                    for process in s.processes_by_exact_name(OsStr::new("ollama.exe")) {
-                        let _ = process.kill();
-                    }
-                    for process in s.processes_by_exact_name(OsStr::new("ollama")) {
-                        let _ = process.kill();
-                    }
+                let state = app_handle.state::<AppState>();
+                state.temp_dirs.cleanup_session();
+                if state.we_started_ollama.swap(false, Ordering::SeqCst) {
+                    ollama::kill_managed(&state);
                 }
+                lmstudio::stop_managed(&state);
             }
         });
 }
@@ -0,0 +1,54 @@
+use crate::{gemini, ollama, AppState};
+
+const DEFAULT_GEMINI_MODEL: &str = "gemini-3-flash-preview";
+
+/// Provider, model and server a backend flow sends its prompts to.
+pub struct Llm {
+    pub provider: String,
+    pub model: String,
+    pub url: String,
+    /// `num_ctx` for Ollama calls.
+    pub num_ctx: usize,
+}
+
+impl Llm {
+    /// Resolves the UI's choice: `ollama` (default, at `url` or the configured server) or
+    /// `gemini` (default model when none is given). Checks the provider against the policy;
+    /// `purpose` completes "... is not supported for provider".
+    pub async fn resolve(
+        state: &AppState,
+        purpose: &str,
+        provider: Option<String>,
+        model: Option<String>,
+        url: Option<String>,
+        num_ctx: usize,
+    ) -> Result<Llm, String> {
+        let provider = provider.unwrap_or_else(|| "ollama".to_string()).trim().to_lowercase();
+        if provider != "gemini" && provider != "ollama" {
+            return Err(format!("{} is not supported for provider: {}", purpose, provider));
+        }
+        state.policy.check_provider(&provider)?;
+        let model = match (model.filter(|m| !m.trim().is_empty()), provider.as_str()) {
+            (Some(m), _) => m,
+            (None, "gemini") => DEFAULT_GEMINI_MODEL.to_string(),
+            (None, _) => return Err("Choose an Ollama model first".to_string()),
+        };
+        let url = match url.filter(|u| !u.trim().is_empty()) {
+            Some(u) => ollama::normalize_url(&u),
+            None => state.ollama_url.read().await.clone().unwrap_or_else(|| ollama::DEFAULT_OLLAMA_URL.to_string()),
+        };
+        Ok(Llm { provider, model, url, num_ctx })
+    }
+
+    /// Sends one redacted prompt and returns the reply text.
+    pub async fn complete(&self, state: &AppState, prompt: &str) -> Result<String, String> {
+        let prompt = state.policy.redact(prompt);
+        match self.provider.as_str() {
+            "gemini" => {
+                let body = serde_json::json!({ "contents": [{ "parts": [{ "text": prompt }] }] });
+                Ok(gemini::generate(state, &self.model, &body).await?.text)
+            }
+            _ => ollama::generate_text(state, &self.url, &self.model, &prompt, Some(self.num_ctx)).await,
+        }
+    }
+}
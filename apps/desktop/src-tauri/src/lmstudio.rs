@@ -0,0 +1,181 @@
+use crate::error::AppError;
+use crate::{log_status, AppState};
+use isahc::config::Configurable;
+use isahc::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+pub const DEFAULT_LMSTUDIO_URL: &str = "http://localhost:1234";
+const DEFAULT_PORT: u16 = 1234;
+/// Addresses tried, after the user's own, when looking for the server.
+const COMMON_ADDRESSES: &[&str] = &[DEFAULT_LMSTUDIO_URL, "http://127.0.0.1:1234", "http://host.docker.internal:1234"];
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+/// How long `lmstudio_start_server` waits for the server to answer.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LmStudioModel {
+    id: String,
+    /// Whether the model is loaded in memory; `None` when the server doesn't say (LM Studio
+    /// before 0.3.6). Unloaded models are loaded on first use.
+    loaded: Option<bool>,
+    /// `llm`, `vlm` or `embeddings`, when reported.
+    kind: Option<String>,
+}
+
+/// `http://host:port` without the `/v1` suffix people copy from LM Studio's server tab.
+pub fn normalize_url(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+    let url = url.strip_suffix("/v1").unwrap_or(url);
+    if url.contains("://") { url.to_string() } else { format!("http://{}", url) }
+}
+
+/// Whether LM Studio's OpenAI-compatible server answers at `base`.
+async fn responds(state: &AppState, base: &str) -> bool {
+    let Ok(request) = isahc::Request::get(format!("{}/v1/models", base)).timeout(PROBE_TIMEOUT).body(()) else { return false };
+    let client = state.ollama_client.read().await.clone();
+    matches!(client.send_async(request).await, Ok(res) if res.status().is_success())
+}
+
+async fn get_json(state: &AppState, base: &str, path: &str) -> Result<serde_json::Value, AppError> {
+    let client = state.ollama_client.read().await.clone();
+    let mut res = client
+        .get_async(format!("{}{}", base, path))
+        .await
+        .map_err(|e| AppError::Network(format!("Cannot reach LM Studio at {}. Make sure its server is running. ({})", base, e)))?;
+    let status = res.status().as_u16();
+    let text = res.text().await?;
+    if !(200..300).contains(&status) {
+        return Err(AppError::from_status(status, format!("LM Studio request failed ({}): {}", status, text), None));
+    }
+    serde_json::from_str(&text).map_err(|e| AppError::Provider { status: None, message: e.to_string() })
+}
+
+/// Runs `binary`, LM Studio's `lms` command line tool for controlling its server.
+fn lms(binary: &str, args: &[&str]) -> Result<String, AppError> {
+    let mut command = Command::new(binary);
+    command.args(args);
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    let output = command.output().map_err(|e| AppError::Io(format!("Failed to run {} (is LM Studio's `lms` tool installed?): {}", binary, e)))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::Io(format!("`lms {}` failed: {}", args.join(" "), stderr.trim())));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Whether LM Studio's local server answers at `url` (default `http://localhost:1234`).
+#[tauri::command]
+pub async fn lmstudio_is_running(state: State<'_, AppState>, url: Option<String>) -> Result<bool, AppError> {
+    let base = url.filter(|u| !u.trim().is_empty()).map(|u| normalize_url(&u)).unwrap_or_else(|| DEFAULT_LMSTUDIO_URL.to_string());
+    Ok(responds(&state, &base).await)
+}
+
+/// Finds a running LM Studio server, trying `candidates` first, then localhost:1234 and
+/// the Docker host address. Returns its base URL.
+#[tauri::command]
+pub async fn lmstudio_detect(state: State<'_, AppState>, candidates: Option<Vec<String>>) -> Result<String, AppError> {
+    let mut tried = Vec::new();
+    for base in candidates.unwrap_or_default().iter().map(|u| normalize_url(u)).chain(COMMON_ADDRESSES.iter().map(|u| u.to_string())) {
+        if tried.contains(&base) {
+            continue;
+        }
+        if responds(&state, &base).await {
+            return Ok(base);
+        }
+        tried.push(base);
+    }
+    Err(AppError::Network("No LM Studio server found. Start the server in LM Studio (Developer tab) or enter its address.".to_string()))
+}
+
+/// Models LM Studio offers at `url`, from `/v1/models`, with their load state from LM
+/// Studio's own `/api/v0/models` when the server has it.
+#[tauri::command]
+pub async fn lmstudio_list_models(state: State<'_, AppState>, url: String) -> Result<Vec<LmStudioModel>, AppError> {
+    state.policy.check_provider("lmstudio")?;
+    let base = normalize_url(&url);
+    let data = get_json(&state, &base, "/v1/models").await?;
+    let details: HashMap<String, serde_json::Value> = match get_json(&state, &base, "/api/v0/models").await {
+        Ok(json) => json["data"]
+            .as_array()
+            .map(|models| models.iter().filter_map(|m| Some((m["id"].as_str()?.to_string(), m.clone()))).collect())
+            .unwrap_or_default(),
+        Err(_) => HashMap::new(),
+    };
+    let models = data["data"]
+        .as_array()
+        .map(|models| {
+            models
+                .iter()
+                .filter_map(|m| m["id"].as_str())
+                .map(|id| {
+                    let detail = details.get(id);
+                    LmStudioModel {
+                        id: id.to_string(),
+                        loaded: detail.and_then(|d| d["state"].as_str()).map(|s| s == "loaded"),
+                        kind: detail.and_then(|d| d["type"].as_str()).map(str::to_string),
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(models)
+}
+
+/// Starts LM Studio's server on `port` (default 1234) with `lms server start`, and
+/// resolves with its base URL once it answers. `binary_path` points at `lms` when it
+/// isn't on the PATH. A server that is already up is left as is, and isn't stopped by
+/// `lmstudio_stop_server`.
+#[tauri::command]
+pub async fn lmstudio_start_server(app: AppHandle, state: State<'_, AppState>, port: Option<u16>, binary_path: Option<String>) -> Result<String, AppError> {
+    state.policy.check_provider("lmstudio")?;
+    let port = port.unwrap_or(DEFAULT_PORT);
+    let base = format!("http://localhost:{}", port);
+    if responds(&state, &base).await {
+        return Ok(base);
+    }
+    log_status(&app, format!("Starting LM Studio server on port {}", port));
+    let binary = binary_path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).unwrap_or_else(|| "lms".to_string());
+    let (command, port_arg) = (binary.clone(), port.to_string());
+    tokio::task::spawn_blocking(move || lms(&command, &["server", "start", "--port", &port_arg]))
+        .await?
+        .inspect_err(|e| log_status(&app, format!("Failed to start LM Studio server: {}", e)))?;
+    *state.lmstudio_started_with.lock().unwrap() = Some(binary);
+
+    let deadline = Instant::now() + STARTUP_TIMEOUT;
+    while !responds(&state, &base).await {
+        if Instant::now() >= deadline {
+            return Err(AppError::Timeout(format!("LM Studio did not become ready at {} within {}s", base, STARTUP_TIMEOUT.as_secs())));
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    log_status(&app, "LM Studio server started");
+    Ok(base)
+}
+
+/// Stops LM Studio's server with `lms server stop`, if this app started it.
+#[tauri::command]
+pub async fn lmstudio_stop_server(app: AppHandle, state: State<'_, AppState>) -> Result<String, AppError> {
+    state.policy.check_provider("lmstudio")?;
+    let Some(binary) = state.lmstudio_started_with.lock().unwrap().take() else {
+        return Ok("No LM Studio server was started by this application.".to_string());
+    };
+    tokio::task::spawn_blocking(move || lms(&binary, &["server", "stop"])).await??;
+    log_status(&app, "LM Studio server stopped");
+    Ok("LM Studio server stopped".to_string())
+}
+
+/// Stops the server on app exit when we started it; errors are of no use at that point.
+pub fn stop_managed(state: &AppState) {
+    if let Some(binary) = state.lmstudio_started_with.lock().unwrap().take() {
+        let _ = lms(&binary, &["server", "stop"]);
+    }
+}
@@ -0,0 +1,258 @@
+use crate::error::AppError;
+use crate::{export, AppState};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+const LOG_FILE: &str = "operations.log";
+/// The log is rotated past this size; `operations.1.log` is the previous one, and so on.
+const MAX_FILE_BYTES: u64 = 2 * 1024 * 1024;
+const ROTATED_FILES: usize = 3;
+/// Entries kept in memory for [`get_recent_logs`].
+const MAX_RECENT: usize = 1000;
+const REDACTED: &str = "[REDACTED]";
+
+/// Credentials that may turn up in URLs, error messages and bodies: key or token query
+/// parameters, URL user info, bearer tokens, and Google, GitHub and OpenAI keys.
+fn secrets() -> &'static [(Regex, &'static str)] {
+    static SECRETS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    SECRETS.get_or_init(|| {
+        [
+            (r"(?i)([?&](?:key|api_key|apikey|token|access_token|client_secret|password)=)[^&\s#]+", "${1}[REDACTED]"),
+            (r"(://)[^/@\s:]+:[^/@\s]+@", "${1}[REDACTED]@"),
+            (r"(?i)\b(bearer|token)\s+[A-Za-z0-9._~+/=-]{8,}", "${1} [REDACTED]"),
+            (r"AIza[0-9A-Za-z_-]{30,}", REDACTED),
+            (r"\b(?:gh[pousr]_[A-Za-z0-9]{20,}|github_pat_[A-Za-z0-9_]{20,})", REDACTED),
+            (r"\bsk-[A-Za-z0-9_-]{20,}", REDACTED),
+        ]
+        .into_iter()
+        .map(|(pattern, replacement)| (Regex::new(pattern).expect("valid secret pattern"), replacement))
+        .collect()
+    })
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    /// Unix milliseconds.
+    pub timestamp: u64,
+    pub level: LogLevel,
+    /// `request` for an HTTP attempt, `operation` for a scan, fetch, index or generation.
+    pub kind: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Request body size, when known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes_sent: Option<u64>,
+    /// Response size from `Content-Length`, when given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes_received: Option<u64>,
+    /// 1 for the first try of a request, 2 for the first retry, ...
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attempt: Option<u32>,
+}
+
+impl LogEntry {
+    fn new(level: LogLevel, kind: &str, message: String) -> Self {
+        LogEntry {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0),
+            level,
+            kind: kind.to_string(),
+            message,
+            method: None,
+            url: None,
+            status: None,
+            duration_ms: None,
+            bytes_sent: None,
+            bytes_received: None,
+            attempt: None,
+        }
+    }
+}
+
+/// Process-wide, like the retry settings, since requests are also made from places
+/// without the app state. Entries are kept in memory from the start and also appended
+/// to the log file once [`init`] names its directory.
+struct OperationLog {
+    recent: Mutex<VecDeque<LogEntry>>,
+    dir: Mutex<Option<PathBuf>>,
+}
+
+static LOG: OperationLog = OperationLog { recent: Mutex::new(VecDeque::new()), dir: Mutex::new(None) };
+
+/// Replaces credentials in `text` with `[REDACTED]`.
+pub fn redact_secrets(text: &str) -> String {
+    let mut out = text.to_string();
+    for (re, replacement) in secrets() {
+        if re.is_match(&out) {
+            out = re.replace_all(&out, *replacement).into_owned();
+        }
+    }
+    out
+}
+
+/// Starts writing the log to `dir` (the app's log directory).
+pub fn init(dir: PathBuf) {
+    if let Err(e) = fs::create_dir_all(&dir) {
+        log::warn!("Failed to create the log directory {}: {}", dir.display(), e);
+        return;
+    }
+    *LOG.dir.lock().unwrap() = Some(dir);
+}
+
+fn rotated(dir: &Path, index: usize) -> PathBuf {
+    match index {
+        0 => dir.join(LOG_FILE),
+        n => dir.join(format!("operations.{}.log", n)),
+    }
+}
+
+fn append(dir: &Path, line: &str) -> std::io::Result<()> {
+    let path = rotated(dir, 0);
+    if fs::metadata(&path).is_ok_and(|m| m.len() > MAX_FILE_BYTES) {
+        for i in (1..ROTATED_FILES).rev() {
+            let _ = fs::rename(rotated(dir, i - 1), rotated(dir, i));
+        }
+    }
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+/// Redacts and records `entry`.
+pub fn write(mut entry: LogEntry) {
+    entry.message = redact_secrets(&entry.message);
+    entry.url = entry.url.map(|u| redact_secrets(&u));
+    if let Some(dir) = LOG.dir.lock().unwrap().as_deref() {
+        if let Ok(line) = serde_json::to_string(&entry) {
+            let _ = append(dir, &line);
+        }
+    }
+    let mut recent = LOG.recent.lock().unwrap();
+    if recent.len() >= MAX_RECENT {
+        recent.pop_front();
+    }
+    recent.push_back(entry);
+}
+
+/// Records one HTTP attempt: its outcome is the status or the transport error.
+#[allow(clippy::too_many_arguments)]
+pub fn request(
+    service: &str,
+    method: &str,
+    url: &str,
+    outcome: Result<u16, &str>,
+    duration: Duration,
+    bytes_sent: Option<u64>,
+    bytes_received: Option<u64>,
+    attempt: u32,
+) {
+    let (level, message) = match outcome {
+        Ok(status) if status >= 400 => (LogLevel::Warn, format!("{} request returned {}", service, status)),
+        Ok(status) => (LogLevel::Info, format!("{} request returned {}", service, status)),
+        Err(e) => (LogLevel::Error, format!("{} request failed: {}", service, e)),
+    };
+    let mut entry = LogEntry::new(level, "request", message);
+    entry.method = Some(method.to_string());
+    entry.url = Some(url.to_string());
+    entry.status = outcome.ok();
+    entry.duration_ms = Some(duration.as_millis() as u64);
+    entry.bytes_sent = bytes_sent;
+    entry.bytes_received = bytes_received;
+    entry.attempt = Some(attempt);
+    write(entry);
+}
+
+/// Records a finished (or failed) operation of `kind`.
+pub fn operation(kind: &str, id: &str, ok: bool, message: &str, duration: Duration) {
+    let level = if ok { LogLevel::Info } else { LogLevel::Error };
+    let mut entry = LogEntry::new(level, "operation", format!("{} {} {}: {}", kind, id, if ok { "completed" } else { "failed" }, message));
+    entry.duration_ms = Some(duration.as_millis() as u64);
+    write(entry);
+}
+
+/// The latest log entries, oldest first: at most `limit` (default 200), optionally only
+/// those at `min_level` (`info`, `warn`, `error`) or above, or of one `kind`.
+#[tauri::command]
+pub fn get_recent_logs(limit: Option<usize>, min_level: Option<String>, kind: Option<String>) -> Result<Vec<LogEntry>, AppError> {
+    let min_level = match min_level.as_deref().map(str::to_lowercase).as_deref() {
+        None | Some("info") => LogLevel::Info,
+        Some("warn") => LogLevel::Warn,
+        Some("error") => LogLevel::Error,
+        Some(other) => return Err(AppError::InvalidInput(format!("Unknown log level: {}", other))),
+    };
+    let rank = |level: LogLevel| match level {
+        LogLevel::Info => 0,
+        LogLevel::Warn => 1,
+        LogLevel::Error => 2,
+    };
+    let recent = LOG.recent.lock().unwrap();
+    let mut entries: Vec<LogEntry> = recent
+        .iter()
+        .rev()
+        .filter(|e| rank(e.level) >= rank(min_level) && kind.as_deref().map_or(true, |k| e.kind == k))
+        .take(limit.unwrap_or(200))
+        .cloned()
+        .collect();
+    entries.reverse();
+    Ok(entries)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedLogs {
+    path: String,
+    bytes_written: u64,
+}
+
+/// Writes the whole log (rotated files included, oldest first) to `dest` as JSON lines,
+/// for attaching to a bug report. Secrets were redacted when the entries were recorded.
+#[tauri::command]
+pub async fn export_logs(state: State<'_, AppState>, dest: String, overwrite: Option<bool>) -> Result<ExportedLogs, AppError> {
+    let target = PathBuf::from(&dest);
+    state.policy.check_export(&target)?;
+    if target.exists() && !overwrite.unwrap_or(false) {
+        return Err(AppError::InvalidInput(format!("{} already exists; pass overwrite to replace it", dest)));
+    }
+    let dir = LOG.dir.lock().unwrap().clone();
+    let mut data = Vec::new();
+    match dir {
+        Some(dir) => {
+            for i in (0..ROTATED_FILES).rev() {
+                if let Ok(bytes) = fs::read(rotated(&dir, i)) {
+                    data.extend(bytes);
+                }
+            }
+        }
+        // No log directory: all there is are the entries in memory.
+        None => {
+            for entry in LOG.recent.lock().unwrap().iter() {
+                data.extend(serde_json::to_string(entry).unwrap_or_default().into_bytes());
+                data.push(b'\n');
+            }
+        }
+    }
+    let bytes_written = data.len() as u64;
+    tokio::task::spawn_blocking(move || export::write_atomic(&target, None, &data)).await??;
+    Ok(ExportedLogs { path: dest, bytes_written })
+}
@@ -0,0 +1,105 @@
+use crate::AppState;
+use isahc::auth::{Authentication, Credentials};
+use isahc::config::{CaCertificate, Configurable, SslOption};
+use isahc::http::Uri;
+use isahc::HttpClient;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const PROXY_SCHEMES: &[&str] = &["http", "https", "socks4", "socks4a", "socks5", "socks5h"];
+/// Always reached directly, so a local Ollama keeps working behind a proxy.
+const DIRECT_HOSTS: &[&str] = &["localhost", "127.0.0.1", "::1"];
+/// Generations on large prompts can take many minutes.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(3600);
+
+/// How outbound clients reach the network. Every client in [`AppState`] is built from it
+/// by [`build_client`].
+#[derive(Clone, Default)]
+pub struct NetworkConfig {
+    /// `None` leaves proxy selection to the usual `HTTPS_PROXY`/`ALL_PROXY` variables.
+    pub proxy: Option<Proxy>,
+    /// PEM bundle trusted instead of the system's CA store, e.g. one holding the root
+    /// certificate of a TLS-inspecting corporate proxy.
+    pub ca_file: Option<PathBuf>,
+    /// Skips certificate and host name verification entirely. Anyone on the network path
+    /// can then read and alter traffic, API keys included; the last resort when no CA
+    /// bundle can be had.
+    pub danger_accept_invalid_certs: bool,
+}
+
+#[derive(Clone)]
+pub struct Proxy {
+    uri: Uri,
+    credentials: Option<(String, String)>,
+}
+
+impl Proxy {
+    /// Parses `[scheme://][user[:password]@]host[:port]`. Without a scheme the proxy is
+    /// taken for an HTTP one; `socks5h` resolves host names through the proxy. The user
+    /// and password may be percent-encoded.
+    pub fn parse(raw: &str) -> Result<Proxy, String> {
+        let raw = raw.trim();
+        let (scheme, rest) = raw.split_once("://").unwrap_or(("http", raw));
+        let scheme = scheme.to_lowercase();
+        if !PROXY_SCHEMES.contains(&scheme.as_str()) {
+            return Err(format!("Unsupported proxy scheme '{}' (expected one of {})", scheme, PROXY_SCHEMES.join(", ")));
+        }
+        let rest = rest.trim_end_matches('/');
+        let (credentials, host) = match rest.rsplit_once('@') {
+            Some((userinfo, host)) => {
+                let (user, password) = userinfo.split_once(':').unwrap_or((userinfo, ""));
+                let decode = |s: &str| urlencoding::decode(s).map(|s| s.into_owned()).map_err(|e| format!("Invalid proxy credentials: {}", e));
+                (Some((decode(user)?, decode(password)?)), host)
+            }
+            None => (None, rest),
+        };
+        if host.is_empty() {
+            return Err(format!("Proxy address '{}' has no host", raw));
+        }
+        let uri = format!("{}://{}", scheme, host).parse().map_err(|e| format!("Invalid proxy address '{}': {}", host, e))?;
+        Ok(Proxy { uri, credentials })
+    }
+}
+
+/// Checks that a CA bundle exists and looks like PEM, since curl only reports a bad
+/// one as a generic TLS failure on the first request.
+pub fn check_ca_file(path: &str) -> Result<PathBuf, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Cannot read CA certificate file {}: {}", path, e))?;
+    if !text.contains("-----BEGIN CERTIFICATE-----") {
+        return Err(format!("{} is not a PEM certificate bundle", path));
+    }
+    Ok(PathBuf::from(path))
+}
+
+/// A client for `config`: the proxy (with its credentials, for HTTP and SOCKS5 alike) is
+/// used for every host but the local machine, and the TLS options apply to all of them.
+pub fn build_client(config: &NetworkConfig) -> Result<HttpClient, String> {
+    let mut builder = HttpClient::builder().timeout(REQUEST_TIMEOUT);
+    if let Some(path) = &config.ca_file {
+        builder = builder.ssl_ca_certificate(CaCertificate::file(path));
+    }
+    if config.danger_accept_invalid_certs {
+        builder = builder.ssl_options(SslOption::DANGER_ACCEPT_INVALID_CERTS | SslOption::DANGER_ACCEPT_INVALID_HOSTS | SslOption::DANGER_ACCEPT_REVOKED_CERTS);
+    }
+    if let Some(proxy) = &config.proxy {
+        builder = builder.proxy(Some(proxy.uri.clone())).proxy_blacklist(DIRECT_HOSTS.iter().copied());
+        if let Some((user, password)) = &proxy.credentials {
+            builder = builder.proxy_authentication(Authentication::all()).proxy_credentials(Credentials::new(user.clone(), password.clone()));
+        }
+    }
+    builder.build().map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+/// Rebuilds the shared clients from `config` and keeps it for later rebuilds. Requests
+/// already in flight finish on the old clients.
+pub async fn apply(state: &AppState, config: NetworkConfig) -> Result<(), String> {
+    let client = build_client(&config)?;
+    let ollama_client = build_client(&config)?;
+    if config.danger_accept_invalid_certs {
+        log::warn!("TLS certificate verification is disabled for all outbound requests");
+    }
+    *state.http_client.write().await = client;
+    *state.ollama_client.write().await = ollama_client;
+    *state.network.write().await = config;
+    Ok(())
+}
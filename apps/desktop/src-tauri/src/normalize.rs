@@ -0,0 +1,32 @@
+use crate::error::AppError;
+use crate::{projects, AppState, FileEntry};
+use repo_prompt_core::normalize::{self, Normalization};
+use repo_prompt_core::tokens::PackStats;
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizeResult {
+    files: Vec<FileEntry>,
+    /// Estimated tokens the cleanups removed.
+    tokens_saved: usize,
+    stats: PackStats,
+}
+
+/// Applies the cleanups turned on in `options` (LF line endings, trimmed trailing
+/// whitespace, collapsed blank lines, expanded tabs, cut overlong lines) to `files`, or
+/// to those of the last load of `project`, ahead of packing.
+#[tauri::command]
+pub fn normalize_files(app: AppHandle, state: State<'_, AppState>, files: Option<Vec<FileEntry>>, project: Option<String>, options: Normalization) -> Result<NormalizeResult, AppError> {
+    let mut files = match (files, project) {
+        (Some(files), _) => files,
+        (None, Some(project)) => projects::loaded_files(&app, &state, &project)?,
+        (None, None) => return Err(AppError::InvalidInput("Give the files to normalize or a loaded project".to_string())),
+    };
+    let tokens = |files: &[FileEntry]| files.iter().map(|f| f.stats.tokens).sum::<usize>();
+    let before = tokens(&files);
+    normalize::normalize_files(&mut files, &options);
+    let tokens_saved = before.saturating_sub(tokens(&files));
+    Ok(NormalizeResult { stats: PackStats::of(&files), files, tokens_saved })
+}
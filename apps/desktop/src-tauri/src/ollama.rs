@@ -0,0 +1,711 @@
+use crate::error::AppError;
+use crate::{docker, log_status, retry, AppState};
+use isahc::config::Configurable;
+use isahc::{AsyncBody, Response};
+use repo_prompt_core::usage::Usage;
+use serde::{Deserialize, Serialize};
+use std::process::{Child, Command};
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+pub use repo_prompt_core::ollama::{host_url, normalize_url, DEFAULT_OLLAMA_URL};
+
+/// Addresses tried, after the user's own, when the configured one doesn't answer.
+const COMMON_ADDRESSES: &[&str] = &[DEFAULT_OLLAMA_URL, "http://host.docker.internal:11434", "http://172.17.0.1:11434"];
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Emitted when the Ollama process started by the app exits without being stopped.
+pub const EXITED_EVENT: &str = "ollama://exited";
+/// Emitted after the supervisor brought a crashed server back up.
+pub const RESTARTED_EVENT: &str = "ollama://restarted";
+/// A server that keeps crashing is left down after this many restarts within [`RESTART_WINDOW`].
+const MAX_RESTARTS: usize = 3;
+const RESTART_WINDOW: Duration = Duration::from_secs(300);
+/// How long `start_ollama` waits for a freshly spawned server to answer.
+pub const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OllamaExit {
+    /// Exit code, `None` when the process was killed by a signal.
+    code: Option<i32>,
+    /// Whether the supervisor is restarting the server.
+    restarting: bool,
+}
+
+/// Polls `/api/tags` at `base`, with backoff, until the API answers or `timeout` passes.
+/// Answering on `/api/tags` (not just accepting connections) means models can be listed.
+pub async fn wait_until_ready(state: &AppState, base: &str, timeout: Duration) -> Result<(), String> {
+    let deadline = Instant::now() + timeout;
+    let mut delay = Duration::from_millis(100);
+    let client = state.ollama_client.read().await.clone();
+    loop {
+        let ready = match isahc::Request::get(format!("{}/api/tags", base)).timeout(PROBE_TIMEOUT).body(()) {
+            Ok(request) => matches!(client.send_async(request).await, Ok(res) if res.status().is_success()),
+            Err(e) => return Err(e.to_string()),
+        };
+        if ready {
+            return Ok(());
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(format!("Ollama did not become ready at {} within {}s", base, timeout.as_secs()));
+        }
+        tokio::time::sleep(delay.min(deadline - now)).await;
+        delay = (delay * 2).min(Duration::from_secs(1));
+    }
+}
+
+/// Whether an Ollama server answers at `base`.
+pub async fn responds(state: &AppState, base: &str) -> bool {
+    let Ok(request) = isahc::Request::get(format!("{}/api/version", base)).timeout(PROBE_TIMEOUT).body(()) else { return false };
+    let client = state.ollama_client.read().await.clone();
+    matches!(client.send_async(request).await, Ok(res) if res.status().is_success())
+}
+
+fn candidates(preferred: &[String]) -> Vec<String> {
+    let mut list: Vec<String> = preferred.iter().map(|u| normalize_url(u)).collect();
+    if let Ok(host) = std::env::var("OLLAMA_HOST") {
+        if !host.trim().is_empty() {
+            list.push(host_url(&host));
+        }
+    }
+    list.extend(COMMON_ADDRESSES.iter().map(|u| u.to_string()));
+    let mut seen = std::collections::HashSet::new();
+    list.retain(|u| seen.insert(u.clone()));
+    list
+}
+
+/// Returns the first address (from `preferred`, `OLLAMA_HOST`, the common ones, then
+/// the published ports of running Ollama containers) where Ollama answers, and remembers
+/// it as the last known-good address.
+pub async fn discover(state: &AppState, preferred: &[String]) -> Option<String> {
+    let tried = candidates(preferred);
+    // In demo mode only this machine may be reached.
+    let allowed = |base: &str| state.policy.check_local_url(base).is_ok();
+    for base in tried.iter().filter(|b| allowed(b)) {
+        if responds(state, base).await {
+            *state.ollama_url.write().await = Some(base.clone());
+            return Some(base.clone());
+        }
+    }
+    for base in docker::running_container_urls().await {
+        if !tried.contains(&base) && allowed(&base) && responds(state, &base).await {
+            *state.ollama_url.write().await = Some(base.clone());
+            return Some(base);
+        }
+    }
+    None
+}
+
+/// Sends a request to Ollama at `url` through the shared retry layer (GETs are also
+/// retried on timeouts and 5xx responses). If the server still can't be reached
+/// (typically because it crashed and is being restarted) known addresses are probed once
+/// in case it came back elsewhere, before a readable error is returned.
+pub async fn send(state: &AppState, url: &str, method: &str, path: &str, body: Option<String>) -> Result<Response<AsyncBody>, String> {
+    let mut base = normalize_url(url);
+    let mut rediscovered = false;
+    let client = state.ollama_client.read().await.clone();
+    loop {
+        let make = || {
+            let builder = isahc::Request::builder().method(method).uri(format!("{}{}", base, path));
+            match &body {
+                Some(b) => builder.header("Content-Type", "application/json").body(AsyncBody::from(b.clone())),
+                None => builder.body(AsyncBody::empty()),
+            }
+        };
+        match retry::send(&client, "Ollama", method == "GET", make).await {
+            Ok(res) => return Ok(res),
+            Err(e) if e.is_network() && !rediscovered => {
+                rediscovered = true;
+                let last_good = state.ollama_url.read().await.clone();
+                let preferred: Vec<String> = std::iter::once(base.clone()).chain(last_good).collect();
+                match discover(state, &preferred).await {
+                    Some(found) => base = found,
+                    None => return Err(format!("Cannot reach Ollama at {}. Make sure it is running, or start it from the app. ({})", base, e)),
+                }
+            }
+            Err(e) if e.is_network() => {
+                return Err(format!("Cannot reach Ollama at {}. Make sure it is running, or start it from the app. ({})", base, e));
+            }
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+}
+
+pub async fn get(state: &AppState, url: &str, path: &str) -> Result<Response<AsyncBody>, String> {
+    send(state, url, "GET", path, None).await
+}
+
+pub async fn post_json(state: &AppState, url: &str, path: &str, body: &serde_json::Value) -> Result<Response<AsyncBody>, String> {
+    send(state, url, "POST", path, Some(body.to_string())).await
+}
+
+/// Per-chunk event of a streamed generation; the last one has `done` set and carries the
+/// eval counts and timings.
+pub const TOKEN_EVENT: &str = "ollama://token";
+
+/// Download progress of `ollama_pull_model`, one per status line Ollama reports.
+pub const PULL_EVENT: &str = "ollama://pull-progress";
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PullProgress {
+    model: String,
+    /// `pulling manifest`, `downloading`, `verifying sha256 digest`, ..., `success`.
+    status: String,
+    /// Layer being downloaded.
+    digest: Option<String>,
+    completed: Option<u64>,
+    total: Option<u64>,
+    /// 0.0 to 1.0 for the current layer, when sizes are known.
+    fraction: Option<f64>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerationStats {
+    prompt_eval_count: u64,
+    eval_count: u64,
+    load_ms: u64,
+    prompt_eval_ms: u64,
+    eval_ms: u64,
+    total_ms: u64,
+    tokens_per_sec: f64,
+}
+
+impl GenerationStats {
+    fn from_final_chunk(chunk: &serde_json::Value) -> Self {
+        let ms = |key: &str| chunk[key].as_u64().unwrap_or_default() / 1_000_000;
+        let eval_count = chunk["eval_count"].as_u64().unwrap_or_default();
+        let eval_ns = chunk["eval_duration"].as_u64().unwrap_or_default();
+        GenerationStats {
+            prompt_eval_count: chunk["prompt_eval_count"].as_u64().unwrap_or_default(),
+            eval_count,
+            load_ms: ms("load_duration"),
+            prompt_eval_ms: ms("prompt_eval_duration"),
+            eval_ms: ms("eval_duration"),
+            total_ms: ms("total_duration"),
+            tokens_per_sec: if eval_ns > 0 { eval_count as f64 / (eval_ns as f64 / 1e9) } else { 0.0 },
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenEvent {
+    /// Caller-chosen id, so concurrent generations can be told apart.
+    request_id: Option<String>,
+    token: String,
+    done: bool,
+    stats: Option<GenerationStats>,
+}
+
+/// A finished reply with the tokens it took.
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OllamaReply {
+    pub text: String,
+    /// Summed over continuations; `None` when Ollama didn't report eval counts.
+    pub usage: Option<Usage>,
+    /// Follow-up requests made because the reply hit the token limit.
+    pub continuations: u32,
+}
+
+impl OllamaReply {
+    fn add_usage(&mut self, more: Option<Usage>) {
+        match (self.usage.as_mut(), more) {
+            (Some(total), Some(more)) => total.add(&more),
+            (None, more) => self.usage = more,
+            _ => {}
+        }
+    }
+}
+
+/// Eval counts of a finished reply (or final stream chunk), added to the app's totals.
+pub fn count_usage(state: &AppState, model: &str, data: &serde_json::Value) -> Option<Usage> {
+    let usage = Usage::from_ollama(data);
+    state.usage.record("ollama", model, usage.as_ref());
+    usage
+}
+
+/// One turn of an `/api/chat` conversation.
+#[derive(Deserialize, Serialize, Clone)]
+pub struct ChatMessage {
+    /// `system`, `user` or `assistant`.
+    pub role: String,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub images: Option<Vec<String>>,
+}
+
+/// Posts `body` to `path` with streaming on and calls `on_chunk` for every NDJSON object
+/// of the response until it reports the stream is done.
+async fn post_ndjson(
+    state: &AppState,
+    url: &str,
+    path: &str,
+    body: &serde_json::Value,
+    mut on_chunk: impl FnMut(&serde_json::Value) -> bool,
+) -> Result<(), String> {
+    use futures_util::io::{AsyncBufReadExt, BufReader};
+    use futures_util::StreamExt;
+    use isahc::AsyncReadResponseExt;
+
+    let mut res = post_json(state, url, path, body).await?;
+    if !res.status().is_success() {
+        let status = res.status();
+        return Err(format!("Ollama error ({}): {}", status, res.text().await.unwrap_or_default()));
+    }
+
+    let mut lines = BufReader::new(res.into_body()).lines();
+    while let Some(line) = lines.next().await {
+        let line = line.map_err(|e| format!("Ollama stream interrupted: {}", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let chunk: serde_json::Value = serde_json::from_str(&line).map_err(|e| e.to_string())?;
+        if let Some(error) = chunk["error"].as_str() {
+            return Err(format!("Ollama error: {}", error));
+        }
+        if on_chunk(&chunk) {
+            return Ok(());
+        }
+    }
+    Err("Ollama stream ended before the operation finished".to_string())
+}
+
+/// Sends a streaming request to `path`, emitting a [`TOKEN_EVENT`] per chunk. `field`
+/// picks the text out of each chunk (`response` for `/api/generate`). Returns the full
+/// text, with the token counts of the final chunk, once it arrives.
+pub async fn stream_ndjson(
+    app: &AppHandle,
+    state: &AppState,
+    url: &str,
+    path: &str,
+    body: &serde_json::Value,
+    field: fn(&serde_json::Value) -> Option<&str>,
+    request_id: Option<String>,
+) -> Result<OllamaReply, String> {
+    let mut output = OllamaReply::default();
+    let model = body["model"].as_str().unwrap_or_default();
+    post_ndjson(state, url, path, body, |chunk| {
+        let token = field(chunk).unwrap_or_default().to_string();
+        output.text.push_str(&token);
+        let done = chunk["done"].as_bool().unwrap_or(false);
+        if done {
+            output.usage = count_usage(state, model, chunk);
+        }
+        let stats = done.then(|| GenerationStats::from_final_chunk(chunk));
+        let _ = app.emit(TOKEN_EVENT, TokenEvent { request_id: request_id.clone(), token, done, stats });
+        done
+    })
+    .await?;
+    Ok(output)
+}
+
+/// How `start_ollama` launches the server.
+#[derive(Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerOptions {
+    /// Path to the `ollama` executable; looked up on PATH when unset.
+    pub binary_path: Option<String>,
+    /// `OLLAMA_HOST` for the server, e.g. `127.0.0.1:11500`.
+    pub host: Option<String>,
+    /// `OLLAMA_MODELS`, the directory models are stored in.
+    pub models_dir: Option<String>,
+    /// Extra arguments after `serve`.
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
+/// Spawns `ollama serve` without a console window and records its PID as the managed
+/// server's.
+pub fn spawn_server(state: &AppState, options: &ServerOptions) -> std::io::Result<Child> {
+    let binary = options.binary_path.as_deref().map(str::trim).filter(|p| !p.is_empty()).unwrap_or("ollama");
+    let mut command = Command::new(binary);
+    command.arg("serve").args(&options.extra_args);
+    if let Some(host) = options.host.as_deref().filter(|h| !h.trim().is_empty()) {
+        command.env("OLLAMA_HOST", host.trim());
+    }
+    if let Some(dir) = options.models_dir.as_deref().filter(|d| !d.trim().is_empty()) {
+        command.env("OLLAMA_MODELS", dir.trim());
+    }
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    let child = command.spawn()?;
+    state.ollama_pid.store(child.id(), Ordering::SeqCst);
+    Ok(child)
+}
+
+/// Kills the server we spawned and the model runners it started, leaving any other
+/// Ollama on the machine alone. Returns the number of processes killed.
+pub fn kill_managed(state: &AppState) -> usize {
+    let pid = state.ollama_pid.swap(0, Ordering::SeqCst);
+    if pid == 0 {
+        return 0;
+    }
+    let pid = sysinfo::Pid::from_u32(pid);
+    let mut system = sysinfo::System::new();
+    system.refresh_processes_specifics(sysinfo::ProcessesToUpdate::All, true, sysinfo::ProcessRefreshKind::everything());
+    system
+        .processes()
+        .values()
+        .filter(|p| p.pid() == pid || p.parent() == Some(pid))
+        .filter(|p| p.kill())
+        .count()
+}
+
+/// Waits on the server process we started, on its own thread. When it exits while still
+/// marked as ours (i.e. not through `stop_ollama` or app exit) an [`EXITED_EVENT`] is
+/// emitted and, if auto-restart is enabled, the server is spawned again, up to
+/// [`MAX_RESTARTS`] times per [`RESTART_WINDOW`]. Requests in flight are retried by [`send`]
+/// once the server answers again.
+pub fn supervise(app: AppHandle, child: Child, options: ServerOptions) {
+    std::thread::spawn(move || {
+        let mut child = child;
+        let mut restarts: Vec<Instant> = Vec::new();
+        loop {
+            let code = child.wait().ok().and_then(|status| status.code());
+            let state = app.state::<AppState>();
+            if !state.we_started_ollama.load(Ordering::SeqCst) {
+                return;
+            }
+            state.ollama_pid.store(0, Ordering::SeqCst);
+
+            restarts.retain(|t| t.elapsed() < RESTART_WINDOW);
+            let restarting = state.ollama_auto_restart.load(Ordering::SeqCst) && restarts.len() < MAX_RESTARTS;
+            let _ = app.emit(EXITED_EVENT, OllamaExit { code, restarting });
+            let code_text = code.map(|c| c.to_string()).unwrap_or_else(|| "none".to_string());
+            if !restarting {
+                state.we_started_ollama.store(false, Ordering::SeqCst);
+                log_status(&app, format!("Ollama exited unexpectedly (code {})", code_text));
+                return;
+            }
+
+            log_status(&app, format!("Ollama exited unexpectedly (code {}), restarting", code_text));
+            std::thread::sleep(Duration::from_secs(1 << restarts.len()));
+            // `stop_ollama` or turning auto-restart off during the backoff wins.
+            if !state.we_started_ollama.load(Ordering::SeqCst) || !state.ollama_auto_restart.load(Ordering::SeqCst) {
+                state.we_started_ollama.store(false, Ordering::SeqCst);
+                return;
+            }
+            restarts.push(Instant::now());
+            match spawn_server(&state, &options) {
+                Ok(mut next) if !state.we_started_ollama.load(Ordering::SeqCst) => {
+                    // Stopped while spawning, too early for `stop_ollama` to see this process.
+                    kill_managed(&state);
+                    let _ = next.wait();
+                    return;
+                }
+                Ok(next) => {
+                    child = next;
+                    let _ = app.emit(RESTARTED_EVENT, restarts.len());
+                    log_status(&app, "Ollama restarted");
+                }
+                Err(e) => {
+                    state.we_started_ollama.store(false, Ordering::SeqCst);
+                    log_status(&app, format!("Failed to restart Ollama: {}", e));
+                    return;
+                }
+            }
+        }
+    });
+}
+
+/// Finds a running Ollama server, trying `candidates` first, then `OLLAMA_HOST`,
+/// 127.0.0.1:11434, the usual Docker host addresses and published container ports.
+/// Returns its base URL.
+#[tauri::command]
+pub async fn probe_ollama(state: State<'_, AppState>, candidates: Option<Vec<String>>) -> Result<String, AppError> {
+    discover(&state, &candidates.unwrap_or_default())
+        .await
+        .ok_or_else(|| AppError::Network("No Ollama server found. Start Ollama or enter its address in the settings.".to_string()))
+}
+
+/// Downloads `model` through `/api/pull`, emitting [`PULL_EVENT`] as layers download.
+/// Resolves once Ollama reports success, after which the model can be used.
+#[tauri::command]
+pub async fn ollama_pull_model(app: AppHandle, state: State<'_, AppState>, url: String, model: String) -> Result<String, AppError> {
+    state.policy.check_not_demo("Downloading Ollama models")?;
+    state.policy.check_local_url(&url)?;
+    let model = model.trim().to_string();
+    if model.is_empty() {
+        return Err(AppError::InvalidInput("No model name given".to_string()));
+    }
+    log_status(&app, format!("Pulling Ollama model {}", model));
+    let body = serde_json::json!({ "model": model, "stream": true });
+    let mut last_status = String::new();
+    let result = post_ndjson(&state, &url, "/api/pull", &body, |chunk| {
+        let status = chunk["status"].as_str().unwrap_or_default().to_string();
+        let completed = chunk["completed"].as_u64();
+        let total = chunk["total"].as_u64();
+        let fraction = match (completed, total) {
+            (Some(c), Some(t)) if t > 0 => Some(c as f64 / t as f64),
+            _ => None,
+        };
+        // Per-layer download lines repeat the same status; only log status changes.
+        if status != last_status {
+            log_status(&app, format!("{}: {}", model, status));
+            last_status = status.clone();
+        }
+        let done = status == "success";
+        let digest = chunk["digest"].as_str().map(|d| d.to_string());
+        let _ = app.emit(PULL_EVENT, PullProgress { model: model.clone(), status, digest, completed, total, fraction });
+        done
+    })
+    .await;
+    match result {
+        Ok(()) => Ok(format!("Model {} is ready", model)),
+        Err(e) => {
+            log_status(&app, format!("Failed to pull {}", model));
+            Err(e.into())
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelDetails {
+    model: String,
+    family: Option<String>,
+    parameter_size: Option<String>,
+    quantization: Option<String>,
+    /// Context length the model was trained with, the upper bound for `num_ctx`.
+    context_length: Option<u64>,
+    /// `num_ctx` set in the model's Modelfile, if any.
+    default_num_ctx: Option<u64>,
+    capabilities: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunningModel {
+    name: String,
+    size_bytes: u64,
+    vram_bytes: u64,
+    parameter_size: Option<String>,
+    quantization: Option<String>,
+    context_length: Option<u64>,
+    /// When Ollama will unload it if unused (RFC 3339).
+    expires_at: Option<String>,
+}
+
+async fn read_json(mut res: Response<AsyncBody>) -> Result<serde_json::Value, String> {
+    use isahc::AsyncReadResponseExt;
+    let status = res.status();
+    let text = res.text().await.map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        return Err(format!("Ollama error ({}): {}", status, text));
+    }
+    serde_json::from_str(&text).map_err(|e| e.to_string())
+}
+
+fn opt_string(value: &serde_json::Value) -> Option<String> {
+    value.as_str().filter(|s| !s.is_empty()).map(|s| s.to_string())
+}
+
+/// Context window, parameter size, quantization and capabilities of an installed model,
+/// from `/api/show`.
+#[tauri::command]
+pub async fn ollama_show_model(state: State<'_, AppState>, url: String, model: String) -> Result<ModelDetails, AppError> {
+    state.policy.check_local_url(&url)?;
+    let data = read_json(post_json(&state, &url, "/api/show", &serde_json::json!({ "model": model })).await?).await?;
+    let context_length = data["model_info"]
+        .as_object()
+        .and_then(|info| info.iter().find(|(k, _)| k.ends_with(".context_length")))
+        .and_then(|(_, v)| v.as_u64());
+    // `parameters` is the Modelfile's PARAMETER lines, one `name value` per line.
+    let default_num_ctx = data["parameters"]
+        .as_str()
+        .unwrap_or_default()
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("num_ctx").and_then(|v| v.trim().parse().ok()));
+    Ok(ModelDetails {
+        family: opt_string(&data["details"]["family"]),
+        parameter_size: opt_string(&data["details"]["parameter_size"]),
+        quantization: opt_string(&data["details"]["quantization_level"]),
+        context_length,
+        default_num_ctx,
+        capabilities: data["capabilities"]
+            .as_array()
+            .map(|c| c.iter().filter_map(|c| c.as_str().map(|s| s.to_string())).collect())
+            .unwrap_or_default(),
+        model,
+    })
+}
+
+/// Removes an installed model to free disk space.
+#[tauri::command]
+pub async fn ollama_delete_model(app: AppHandle, state: State<'_, AppState>, url: String, model: String) -> Result<String, AppError> {
+    use isahc::AsyncReadResponseExt;
+    state.policy.check_not_demo("Deleting Ollama models")?;
+    state.policy.check_local_url(&url)?;
+    let mut res = send(&state, &url, "DELETE", "/api/delete", Some(serde_json::json!({ "model": model }).to_string())).await?;
+    match res.status().as_u16() {
+        200 => {
+            log_status(&app, format!("Deleted Ollama model {}", model));
+            Ok(format!("Model {} deleted", model))
+        }
+        404 => Err(AppError::NotFound(format!("Model {} is not installed", model))),
+        status => Err(AppError::from_status(status, format!("Ollama error ({}): {}", status, res.text().await.unwrap_or_default()), None)),
+    }
+}
+
+/// Models currently loaded in memory, from `/api/ps`.
+#[tauri::command]
+pub async fn ollama_running_models(state: State<'_, AppState>, url: String) -> Result<Vec<RunningModel>, AppError> {
+    state.policy.check_local_url(&url)?;
+    let data = read_json(get(&state, &url, "/api/ps").await?).await?;
+    Ok(data["models"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|m| RunningModel {
+            name: m["name"].as_str().or(m["model"].as_str()).unwrap_or_default().to_string(),
+            size_bytes: m["size"].as_u64().unwrap_or_default(),
+            vram_bytes: m["size_vram"].as_u64().unwrap_or_default(),
+            parameter_size: opt_string(&m["details"]["parameter_size"]),
+            quantization: opt_string(&m["details"]["quantization_level"]),
+            context_length: m["context_length"].as_u64(),
+            expires_at: opt_string(&m["expires_at"]),
+        })
+        .collect())
+}
+
+/// Asks Ollama to release `model` from memory now (`keep_alive: 0`). Embedding-only
+/// models reject `/api/generate`, so `/api/embed` is tried for those.
+pub async fn unload(state: &AppState, url: &str, model: &str) -> Result<(), String> {
+    let generate = post_json(state, url, "/api/generate", &serde_json::json!({ "model": model, "keep_alive": 0 })).await?;
+    if generate.status().is_success() {
+        return Ok(());
+    }
+    read_json(post_json(state, url, "/api/embed", &serde_json::json!({ "model": model, "input": [], "keep_alive": 0 })).await?)
+        .await
+        .map(|_| ())
+}
+
+/// Unloads a model so its (V)RAM is freed, e.g. before switching back to a hosted provider.
+#[tauri::command]
+pub async fn ollama_unload_model(app: AppHandle, state: State<'_, AppState>, url: String, model: String) -> Result<String, AppError> {
+    state.policy.check_local_url(&url)?;
+    unload(&state, &url, &model).await?;
+    log_status(&app, format!("Unloaded Ollama model {}", model));
+    Ok(format!("Model {} unloaded", model))
+}
+
+/// Resolves once the Ollama API at `url` (default: the last known-good address, else
+/// 127.0.0.1:11434) answers, or fails after `timeout_ms` (default 30s). Returns the URL.
+#[tauri::command]
+pub async fn wait_for_ollama(state: State<'_, AppState>, url: Option<String>, timeout_ms: Option<u64>) -> Result<String, AppError> {
+    let base = match url.filter(|u| !u.trim().is_empty()) {
+        Some(u) => normalize_url(&u),
+        None => state.ollama_url.read().await.clone().unwrap_or_else(|| DEFAULT_OLLAMA_URL.to_string()),
+    };
+    let timeout = timeout_ms.map(Duration::from_millis).unwrap_or(STARTUP_TIMEOUT);
+    wait_until_ready(&state, &base, timeout).await?;
+    *state.ollama_url.write().await = Some(base.clone());
+    Ok(base)
+}
+
+/// Inputs sent per `/api/embed` request unless the caller asks otherwise.
+pub const DEFAULT_EMBED_BATCH: usize = 64;
+/// A request is also cut once its inputs reach this many characters.
+const MAX_EMBED_BATCH_CHARS: usize = 256 * 1024;
+
+/// Embeds `inputs` through `/api/embed`, several per request, in order. Batches hold at
+/// most `batch_size` inputs and [`MAX_EMBED_BATCH_CHARS`] characters. `progress` is
+/// called with the number of inputs embedded so far.
+pub async fn embed_many(
+    state: &AppState,
+    url: &str,
+    model: &str,
+    inputs: &[String],
+    batch_size: usize,
+    keep_alive: Option<&serde_json::Value>,
+    progress: impl Fn(usize),
+) -> Result<Vec<Vec<f32>>, String> {
+    let mut vectors = Vec::with_capacity(inputs.len());
+    let mut start = 0;
+    while start < inputs.len() {
+        let mut end = start;
+        let mut chars = 0;
+        while end < inputs.len() && end - start < batch_size.max(1) && (end == start || chars + inputs[end].len() <= MAX_EMBED_BATCH_CHARS) {
+            chars += inputs[end].len();
+            end += 1;
+        }
+        let mut body = serde_json::json!({ "model": model, "input": &inputs[start..end] });
+        if let Some(keep_alive) = keep_alive {
+            body["keep_alive"] = keep_alive.clone();
+        }
+        let data = read_json(post_json(state, url, "/api/embed", &body).await?).await?;
+        let batch: Vec<Vec<f32>> = serde_json::from_value(data["embeddings"].clone()).map_err(|_| "No embeddings field in response".to_string())?;
+        if batch.len() != end - start {
+            return Err(format!("Ollama returned {} embeddings for {} inputs", batch.len(), end - start));
+        }
+        vectors.extend(batch);
+        start = end;
+        progress(start);
+    }
+    Ok(vectors)
+}
+
+/// Non-streamed `/api/generate` of `prompt`; returns the response text. The caller
+/// checks the policy and redacts the prompt.
+pub async fn generate_text(state: &AppState, url: &str, model: &str, prompt: &str, num_ctx: Option<usize>) -> Result<String, String> {
+    let mut body = serde_json::json!({ "model": model, "prompt": prompt, "stream": false });
+    if let Some(ctx) = num_ctx {
+        body["options"] = serde_json::json!({ "num_ctx": ctx });
+    }
+    let _span = state.trace.span("llm", "ollama_generate").attr("model", model);
+    let data = read_json(post_json(state, url, "/api/generate", &body).await?).await?;
+    count_usage(state, model, &data);
+    Ok(data["response"].as_str().unwrap_or_default().to_string())
+}
+
+/// Non-streamed `/api/chat` of `messages`, continued while it stops at the token limit.
+/// The caller checks the policy and redacts the messages.
+pub async fn chat_text(state: &AppState, url: &str, model: &str, messages: serde_json::Value, num_ctx: Option<usize>) -> Result<OllamaReply, String> {
+    let mut body = serde_json::json!({ "model": model, "messages": messages, "stream": false });
+    if let Some(ctx) = num_ctx {
+        body["options"] = serde_json::json!({ "num_ctx": ctx });
+    }
+    let _span = state.trace.span("llm", "ollama_chat").attr("model", model);
+    let data = read_json(post_json(state, url, "/api/chat", &body).await?).await?;
+    let mut reply = OllamaReply { text: data["message"]["content"].as_str().unwrap_or_default().to_string(), usage: count_usage(state, model, &data), continuations: 0 };
+    continue_reply(state, url, body, &mut reply, opt_string(&data["done_reason"]), crate::continuation::DEFAULT_MAX_CONTINUATIONS).await?;
+    Ok(reply)
+}
+
+/// While a non-streamed reply stopped at the token limit (`done_reason: "length"`),
+/// asks for the rest through `/api/chat`, up to `max` times, and stitches it onto
+/// `reply`, counting the continuations and their tokens. `chat` is the `/api/chat` body
+/// (model, options, messages) of the conversation that produced `reply`.
+pub async fn continue_reply(
+    state: &AppState,
+    url: &str,
+    mut chat: serde_json::Value,
+    reply: &mut OllamaReply,
+    mut done_reason: Option<String>,
+    max: u32,
+) -> Result<(), String> {
+    let mut segment = reply.text.clone();
+    let model = chat["model"].as_str().unwrap_or_default().to_string();
+    chat["stream"] = serde_json::Value::Bool(false);
+    while done_reason.as_deref() == Some("length") && reply.continuations < max {
+        let Some(messages) = chat["messages"].as_array_mut() else { break };
+        messages.push(serde_json::json!({ "role": "assistant", "content": segment }));
+        messages.push(serde_json::json!({ "role": "user", "content": crate::continuation::CONTINUE_PROMPT }));
+        let data = read_json(post_json(state, url, "/api/chat", &chat).await?).await?;
+        segment = data["message"]["content"].as_str().unwrap_or_default().to_string();
+        crate::continuation::stitch(&mut reply.text, &segment);
+        reply.add_usage(count_usage(state, &model, &data));
+        done_reason = opt_string(&data["done_reason"]);
+        reply.continuations += 1;
+    }
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use crate::error::AppError;
+use crate::AppState;
+use isahc::config::Configurable;
+use isahc::HttpClient;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+const LOW_DISK_BYTES: u64 = 1_000_000_000;
+
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckResult {
+    id: String,
+    label: String,
+    status: CheckStatus,
+    detail: String,
+}
+
+impl CheckResult {
+    fn new(id: &str, label: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self { id: id.to_string(), label: label.to_string(), status, detail: detail.into() }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnboardingReport {
+    /// True when there are no errors (warnings don't block usage).
+    ready: bool,
+    checks: Vec<CheckResult>,
+}
+
+async fn probe(client: &HttpClient, url: &str) -> Result<u16, String> {
+    let request = isahc::Request::get(url)
+        .timeout(PROBE_TIMEOUT)
+        .header("User-Agent", "Tauri/Prompt-Generator")
+        .body(())
+        .map_err(|e| e.to_string())?;
+    client.send_async(request).await.map(|r| r.status().as_u16()).map_err(|e| e.to_string())
+}
+
+async fn check_endpoint(client: &HttpClient, id: &str, label: &str, url: &str) -> CheckResult {
+    // Any HTTP status means the host is reachable; only transport errors count as failures.
+    match probe(client, url).await {
+        Ok(code) => CheckResult::new(id, label, CheckStatus::Ok, format!("Reachable (HTTP {})", code)),
+        Err(e) => CheckResult::new(id, label, CheckStatus::Error, format!("Unreachable: {}. Check your network or proxy settings.", e)),
+    }
+}
+
+fn check_dir_writable(id: &str, label: &str, dir: tauri::Result<PathBuf>) -> CheckResult {
+    let dir = match dir {
+        Ok(d) => d,
+        Err(e) => return CheckResult::new(id, label, CheckStatus::Error, format!("Could not resolve directory: {}", e)),
+    };
+    let probe_file = dir.join(".write_test");
+    let result = std::fs::create_dir_all(&dir)
+        .and_then(|_| std::fs::write(&probe_file, b"ok"))
+        .and_then(|_| std::fs::remove_file(&probe_file));
+    match result {
+        Ok(_) => CheckResult::new(id, label, CheckStatus::Ok, dir.display().to_string()),
+        Err(e) => CheckResult::new(id, label, CheckStatus::Error, format!("{} is not writable: {}", dir.display(), e)),
+    }
+}
+
+fn check_disk_space(dir: &Path) -> CheckResult {
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    let disk = disks
+        .list()
+        .iter()
+        .filter(|d| dir.starts_with(d.mount_point()))
+        .max_by_key(|d| d.mount_point().as_os_str().len());
+    match disk {
+        Some(d) if d.available_space() < LOW_DISK_BYTES => CheckResult::new(
+            "disk_space",
+            "Free disk space",
+            CheckStatus::Warning,
+            format!("Only {} MB free on {}; caches and local models may not fit.", d.available_space() / 1_000_000, d.mount_point().display()),
+        ),
+        Some(d) => CheckResult::new(
+            "disk_space",
+            "Free disk space",
+            CheckStatus::Ok,
+            format!("{} GB free on {}", d.available_space() / 1_000_000_000, d.mount_point().display()),
+        ),
+        None => CheckResult::new("disk_space", "Free disk space", CheckStatus::Warning, "Could not determine the disk for the app data directory."),
+    }
+}
+
+/// Verifies network reachability, available providers, disk space and app directory
+/// permissions, returning a checklist the UI can walk the user through on first run.
+#[tauri::command]
+pub async fn run_onboarding_checks(app: AppHandle, state: State<'_, AppState>) -> Result<OnboardingReport, AppError> {
+    let client = state.http_client.read().await.clone();
+    let mut checks = Vec::new();
+
+    let (github, gemini) = tokio::join!(
+        check_endpoint(&client, "network_github", "GitHub API", "https://api.github.com"),
+        check_endpoint(&client, "network_gemini", "Gemini API", "https://generativelanguage.googleapis.com"),
+    );
+    checks.push(github);
+    checks.push(gemini);
+
+    let has_gemini_key = !state.gemini_api_key.read().await.is_empty();
+    checks.push(if has_gemini_key {
+        CheckResult::new("provider_gemini", "Gemini API key", CheckStatus::Ok, "Configured")
+    } else {
+        CheckResult::new("provider_gemini", "Gemini API key", CheckStatus::Warning, "Not set. Add a key in settings or set GEMINI_API_KEY.")
+    });
+
+    let ollama_process = crate::is_ollama_running().await;
+    let ollama_client = state.ollama_client.read().await.clone();
+    let ollama_api = probe(&ollama_client, "http://127.0.0.1:11434/api/tags").await;
+    checks.push(match (ollama_api, ollama_process) {
+        (Ok(200), _) => CheckResult::new("provider_ollama", "Ollama", CheckStatus::Ok, "Running at http://127.0.0.1:11434"),
+        (_, true) => CheckResult::new("provider_ollama", "Ollama", CheckStatus::Warning, "Process is running but the API at 127.0.0.1:11434 did not answer."),
+        _ => CheckResult::new("provider_ollama", "Ollama", CheckStatus::Warning, "Not detected. Install or start Ollama to use local models."),
+    });
+
+    let paths = app.path();
+    checks.push(check_dir_writable("dir_data", "App data directory", paths.app_data_dir()));
+    checks.push(check_dir_writable("dir_config", "App config directory", paths.app_config_dir()));
+    checks.push(check_dir_writable("dir_cache", "App cache directory", paths.app_cache_dir()));
+    if let Ok(dir) = paths.app_data_dir() {
+        checks.push(check_disk_space(&dir));
+    }
+
+    let ready = checks.iter().all(|c| c.status != CheckStatus::Error);
+    Ok(OnboardingReport { ready, checks })
+}
@@ -0,0 +1,19 @@
+use crate::error::AppError;
+use crate::FileEntry;
+use repo_prompt_core::outline::outline_source;
+
+/// Replaces each file's content with its outline. Files in languages without a
+/// bundled grammar are left out, so the caller can decide how to handle them.
+#[tauri::command]
+pub async fn generate_outline(files: Vec<FileEntry>) -> Result<Vec<FileEntry>, AppError> {
+    tokio::task::spawn_blocking(move || {
+        files
+            .into_iter()
+            .filter_map(|f| {
+                outline_source(&f.path, &f.content).map(|content| FileEntry::new(f.path, content))
+            })
+            .collect()
+    })
+    .await
+    .map_err(AppError::from)
+}
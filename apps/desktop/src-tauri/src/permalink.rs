@@ -0,0 +1,15 @@
+use crate::error::AppError;
+/// Builds the permalink for a citation (`path`, optional `[start, end]` lines) at a pinned
+/// commit, so findings can be pasted straight into PR or MR comments. `host` defaults to
+/// `github.com`; any host containing "gitlab" uses GitLab's URL scheme.
+#[tauri::command]
+pub fn make_permalink(
+    host: Option<String>,
+    owner: String,
+    repo: String,
+    commit_sha: String,
+    path: String,
+    line_range: Option<(u32, u32)>,
+) -> Result<String, AppError> {
+    repo_prompt_core::permalink::permalink(host.as_deref(), &owner, &repo, &commit_sha, &path, line_range).map_err(AppError::InvalidInput)
+}
@@ -0,0 +1,282 @@
+use crate::error::AppError;
+use crate::AppState;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
+
+/// Policy file as written by an administrator.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct PolicyFile {
+    /// Providers that may receive prompts (`gemini`, `ollama`, `openai`, ...). Absent means all.
+    allowed_providers: Option<Vec<String>>,
+    /// Directories nothing may be exported into.
+    forbidden_export_paths: Vec<PathBuf>,
+    redaction_rules: Vec<RedactionRule>,
+    /// Locks the app into demo mode (see [`OrgPolicy::is_demo`]).
+    demo_mode: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RedactionRule {
+    name: String,
+    /// Regular expression; every match is replaced before text leaves the app.
+    pattern: String,
+    replacement: Option<String>,
+}
+
+/// Organization policy loaded from a system-wide location at startup. It is enforced in
+/// the backend and can't be changed from the app's settings. A policy file that exists but
+/// can't be parsed blocks all providers and exports rather than being ignored.
+#[derive(Default)]
+pub struct OrgPolicy {
+    source: Option<PathBuf>,
+    allowed_providers: Option<Vec<String>>,
+    forbidden_export_paths: Vec<PathBuf>,
+    redactions: Vec<(String, Regex, String)>,
+    error: Option<String>,
+    demo: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicySummary {
+    /// Where the policy was loaded from; `None` when no policy is installed.
+    source: Option<String>,
+    allowed_providers: Option<Vec<String>>,
+    forbidden_export_paths: Vec<String>,
+    redaction_rules: Vec<String>,
+    error: Option<String>,
+    demo_mode: bool,
+    /// The bundled sample repository, the only one that can be scanned in demo mode.
+    demo_sample_path: Option<String>,
+}
+
+/// System-wide location administrators deploy the policy to.
+fn policy_path() -> PathBuf {
+    #[cfg(target_os = "windows")]
+    {
+        let base = std::env::var_os("ProgramData").map(PathBuf::from).unwrap_or_else(|| PathBuf::from(r"C:\ProgramData"));
+        base.join("RepoPromptGenerator").join("policy.json")
+    }
+    #[cfg(target_os = "macos")]
+    {
+        PathBuf::from("/Library/Application Support/RepoPromptGenerator/policy.json")
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        PathBuf::from("/etc/repo-prompt-generator/policy.json")
+    }
+}
+
+/// Absolute form of `path` with symlinks resolved as far as the path exists, so a
+/// not-yet-created export file is compared by its real parent directory.
+fn resolve(path: &Path) -> PathBuf {
+    let mut existing = path.to_path_buf();
+    let mut rest = Vec::new();
+    while !existing.exists() {
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent.to_path_buf();
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+    let mut resolved = existing.canonicalize().unwrap_or(existing);
+    resolved.extend(rest.iter().rev());
+    resolved
+}
+
+/// Demo mode requested on the command line (`--demo`) or through `REPO_PROMPT_DEMO=1`.
+fn demo_requested() -> bool {
+    std::env::args().any(|a| a == "--demo") || std::env::var("REPO_PROMPT_DEMO").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+/// The host part of `url`, without user info, port or IPv6 brackets.
+pub fn url_host(url: &str) -> &str {
+    let host = url.split("://").nth(1).unwrap_or(url).split(['/', '?']).next().unwrap_or_default();
+    let host = host.rsplit_once('@').map(|(_, h)| h).unwrap_or(host);
+    if host.starts_with('[') {
+        host.split(']').next().map(|h| &h[1..]).unwrap_or_default()
+    } else {
+        host.split(':').next().unwrap_or_default()
+    }
+}
+
+/// Where the sample repository bundled for demo mode is installed.
+pub fn demo_sample_dir(app: &AppHandle) -> Option<PathBuf> {
+    let dir = app.path().resource_dir().ok()?.join("demo").join("sample-repo");
+    dir.canonicalize().ok()
+}
+
+impl OrgPolicy {
+    pub fn load() -> Self {
+        let mut policy = OrgPolicy::load_file();
+        policy.demo |= demo_requested();
+        policy
+    }
+
+    fn load_file() -> Self {
+        let path = policy_path();
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return OrgPolicy::default(),
+            Err(e) => return OrgPolicy::failed(path, format!("Failed to read policy: {}", e)),
+        };
+        let file: PolicyFile = match serde_json::from_str(&text) {
+            Ok(file) => file,
+            Err(e) => return OrgPolicy::failed(path, format!("Invalid policy file: {}", e)),
+        };
+        let mut redactions = Vec::new();
+        for rule in file.redaction_rules {
+            match Regex::new(&rule.pattern) {
+                Ok(re) => {
+                    let replacement = rule.replacement.unwrap_or_else(|| format!("[REDACTED:{}]", rule.name));
+                    redactions.push((rule.name, re, replacement));
+                }
+                Err(e) => return OrgPolicy::failed(path, format!("Invalid pattern in redaction rule '{}': {}", rule.name, e)),
+            }
+        }
+        OrgPolicy {
+            source: Some(path),
+            allowed_providers: file.allowed_providers.map(|list| list.iter().map(|p| p.trim().to_lowercase()).collect()),
+            forbidden_export_paths: file.forbidden_export_paths.iter().map(|p| resolve(p)).collect(),
+            redactions,
+            error: None,
+            demo: file.demo_mode,
+        }
+    }
+
+    fn failed(path: PathBuf, error: String) -> Self {
+        log::error!("{}: {}", path.display(), error);
+        OrgPolicy { source: Some(path), error: Some(error), ..OrgPolicy::default() }
+    }
+
+    fn check_loaded(&self) -> Result<(), AppError> {
+        match &self.error {
+            Some(e) => Err(AppError::PolicyDenied(format!("Blocked by organization policy (the policy file could not be loaded: {})", e))),
+            None => Ok(()),
+        }
+    }
+
+    /// Demo mode, for kiosk machines: only the bundled sample repository can be scanned,
+    /// only the local provider used, and nothing read from or written to the file system,
+    /// the network or the key settings.
+    pub fn is_demo(&self) -> bool {
+        self.demo
+    }
+
+    /// Errors with "`action` is disabled in demo mode" when in demo mode.
+    pub fn check_not_demo(&self, action: &str) -> Result<(), AppError> {
+        if self.demo {
+            return Err(AppError::PolicyDenied(format!("{} is disabled in demo mode", action)));
+        }
+        Ok(())
+    }
+
+    /// In demo mode, errors unless `path` is inside the bundled sample repository.
+    pub fn check_scan_path(&self, app: &AppHandle, path: &Path) -> Result<(), AppError> {
+        if !self.demo {
+            return Ok(());
+        }
+        self.check_scan_path_within(demo_sample_dir(app).as_deref(), path)
+    }
+
+    /// [`OrgPolicy::check_scan_path`] against a known sample directory; without one,
+    /// nothing can be scanned in demo mode.
+    pub fn check_scan_path_within(&self, sample: Option<&Path>, path: &Path) -> Result<(), AppError> {
+        if !self.demo {
+            return Ok(());
+        }
+        match (sample, path.canonicalize()) {
+            (Some(sample), Ok(path)) if path.starts_with(sample) => Ok(()),
+            _ => Err(AppError::PolicyDenied("Only the bundled sample repository can be scanned in demo mode".to_string())),
+        }
+    }
+
+    /// In demo mode, errors unless `url` points at this machine.
+    pub fn check_local_url(&self, url: &str) -> Result<(), AppError> {
+        if !self.demo {
+            return Ok(());
+        }
+        if matches!(url_host(url), "localhost" | "127.0.0.1" | "::1") {
+            Ok(())
+        } else {
+            Err(AppError::PolicyDenied("Only local endpoints can be reached in demo mode".to_string()))
+        }
+    }
+
+    /// Errors unless prompts may be sent to `provider`.
+    pub fn check_provider(&self, provider: &str) -> Result<(), AppError> {
+        self.check_loaded()?;
+        if self.demo && provider != "ollama" {
+            return Err(AppError::PolicyDenied("Only the local provider is available in demo mode".to_string()));
+        }
+        match &self.allowed_providers {
+            Some(allowed) if !allowed.iter().any(|p| p == provider) => {
+                Err(AppError::PolicyDenied(format!("The {} provider is not allowed by your organization's policy", provider)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Errors if `path` lies under a forbidden export location.
+    pub fn check_export(&self, path: &Path) -> Result<(), AppError> {
+        self.check_loaded()?;
+        self.check_not_demo("Saving files")?;
+        let target = resolve(path);
+        let forbidden = |dir: &PathBuf| {
+            if cfg!(target_os = "windows") {
+                target.to_string_lossy().to_lowercase().starts_with(&dir.to_string_lossy().to_lowercase())
+            } else {
+                target.starts_with(dir)
+            }
+        };
+        match self.forbidden_export_paths.iter().find(|dir| forbidden(dir)) {
+            Some(dir) => Err(AppError::PolicyDenied(format!("Exporting to {} is not allowed by your organization's policy", dir.display()))),
+            None => Ok(()),
+        }
+    }
+
+    /// Applies the mandatory redaction rules.
+    pub fn redact(&self, text: &str) -> String {
+        let mut out = text.to_string();
+        for (_, re, replacement) in &self.redactions {
+            if re.is_match(&out) {
+                out = re.replace_all(&out, replacement.as_str()).into_owned();
+            }
+        }
+        out
+    }
+
+    /// Redacts every string inside a JSON request body.
+    pub fn redact_json(&self, value: &mut serde_json::Value) {
+        if self.redactions.is_empty() {
+            return;
+        }
+        match value {
+            serde_json::Value::String(s) => *s = self.redact(s),
+            serde_json::Value::Array(items) => items.iter_mut().for_each(|v| self.redact_json(v)),
+            serde_json::Value::Object(map) => map.values_mut().for_each(|v| self.redact_json(v)),
+            _ => {}
+        }
+    }
+}
+
+/// The policy in force, so the UI can hide disallowed providers and explain blocked actions.
+#[tauri::command]
+pub fn get_org_policy(app: AppHandle, state: State<'_, AppState>) -> PolicySummary {
+    let policy = &state.policy;
+    PolicySummary {
+        source: policy.source.as_ref().map(|p| p.display().to_string()),
+        allowed_providers: policy.allowed_providers.clone(),
+        forbidden_export_paths: policy.forbidden_export_paths.iter().map(|p| p.display().to_string()).collect(),
+        redaction_rules: policy.redactions.iter().map(|(name, _, _)| name.clone()).collect(),
+        error: policy.error.clone(),
+        demo_mode: policy.demo,
+        demo_sample_path: if policy.demo { demo_sample_dir(&app).map(|p| p.display().to_string()) } else { None },
+    }
+}
@@ -0,0 +1,110 @@
+use crate::error::AppError;
+use crate::{cache, github, log_status, normalize_subpath, repocache, select_source_files, AppState, FileEntry};
+use futures_util::stream::{self, StreamExt};
+use repo_prompt_core::ranking::ScoringRules;
+use serde::Serialize;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+/// Files prefetched after a fetch when the caller doesn't say how many.
+pub const DEFAULT_PREFETCH_FILES: usize = 20;
+/// Requests in flight at once; the foreground fetch uses up to 32.
+const PREFETCH_CONCURRENCY: usize = 2;
+/// Pause between batches, so prefetching never competes with what the user is waiting for.
+const BATCH_DELAY: Duration = Duration::from_millis(300);
+/// Prefetching stops while fewer requests than this are left in the hour's quota.
+const MIN_REMAINING_QUOTA: u32 = 200;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefetchPlan {
+    /// Files that will be fetched in the background, best ranked first.
+    queued: Vec<String>,
+    /// Ranked files that were already cached.
+    already_cached: usize,
+}
+
+/// Fetches `paths` at `commit_sha` into the repository file cache in the background, a
+/// few at a time. A newer prefetch (or `cancel_prefetch`) stops this one, and so does
+/// a low GitHub quota. Unauthenticated clients don't prefetch: 60 requests an hour
+/// leave nothing to spare.
+pub fn start(app: AppHandle, token: String, owner: String, repo: String, commit_sha: String, paths: Vec<String>) {
+    let state = app.state::<AppState>();
+    let generation = state.prefetch_generation.fetch_add(1, Ordering::SeqCst) + 1;
+    if paths.is_empty() || token.is_empty() {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        let gh = github::GithubClient::new(state.http_client.read().await.clone(), token)
+            .with_cache(cache::open_cache(&app, &state, "github").ok());
+        let _span = state.trace.span("fetch", "prefetch").attr("files", paths.len());
+        let mut fetched = 0;
+        for batch in paths.chunks(PREFETCH_CONCURRENCY) {
+            if state.prefetch_generation.load(Ordering::SeqCst) != generation {
+                break;
+            }
+            let files: Vec<FileEntry> = stream::iter(batch.iter().cloned())
+                .map(|path| {
+                    let (gh, owner, repo, commit_sha) = (&gh, &owner, &repo, &commit_sha);
+                    async move { gh.fetch_file_content(owner, repo, &path, commit_sha).await.map(|content| FileEntry::new(path, content)) }
+                })
+                .buffer_unordered(PREFETCH_CONCURRENCY)
+                .filter_map(|entry| async move { entry })
+                .collect()
+                .await;
+            fetched += files.len();
+            repocache::put_files(&app, &state, &owner, &repo, &commit_sha, &files);
+            if gh.rate_limit().remaining.is_some_and(|r| r < MIN_REMAINING_QUOTA) {
+                log_status(&app, "Background prefetch paused: GitHub quota is low");
+                break;
+            }
+            tokio::time::sleep(BATCH_DELAY).await;
+        }
+        if fetched > 0 {
+            log_status(&app, format!("Prefetched {} more files from {}/{}", fetched, owner, repo));
+        }
+    });
+}
+
+/// Queues the next `count` best-ranked source files after `selected` for background
+/// fetching, so expanding the selection (or `fetch_github_files`) finds them cached.
+/// `tree` is the snapshot's file list; `subpath` and `scoring` (default: the settings'
+/// rules) rank like the fetch did.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn prefetch_files(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    owner: String,
+    repo: String,
+    commit_sha: String,
+    tree: Vec<String>,
+    selected: Vec<String>,
+    subpath: Option<String>,
+    count: Option<usize>,
+    token: Option<String>,
+    scoring: Option<ScoringRules>,
+) -> Result<PrefetchPlan, AppError> {
+    state.policy.check_not_demo("GitHub access")?;
+    if !github::is_full_sha(&commit_sha) {
+        return Err(AppError::InvalidInput(format!("'{}' is not a full commit SHA", commit_sha)));
+    }
+    let prefix = normalize_subpath(subpath)?.map(|s| format!("{}/", s)).unwrap_or_default();
+    let rules = match scoring {
+        Some(rules) => rules,
+        None => state.scoring.read().await.clone(),
+    };
+    rules.validate()?;
+    let ranked = select_source_files(&tree, &prefix, &selected, count.unwrap_or(DEFAULT_PREFETCH_FILES).clamp(1, 200), &rules);
+    let (cached, queued) = repocache::cached_files(&app, &state, &owner, &repo, &commit_sha, ranked);
+    start(app.clone(), token.unwrap_or_default(), owner, repo, commit_sha, queued.clone());
+    Ok(PrefetchPlan { queued, already_cached: cached.len() })
+}
+
+/// Stops the running background prefetch after its current batch.
+#[tauri::command]
+pub fn cancel_prefetch(state: State<'_, AppState>) {
+    state.prefetch_generation.fetch_add(1, Ordering::SeqCst);
+}
@@ -0,0 +1,170 @@
+use crate::{logs, AppState};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Progress of an operation is emitted on `progress://{operation_id}`.
+pub const PROGRESS_EVENT_PREFIX: &str = "progress://";
+/// Updates closer together than this are only recorded, not emitted, except the last one
+/// of a stage.
+const MIN_EMIT_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum OperationState {
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationProgress {
+    pub operation_id: String,
+    /// `scan`, `github-fetch`, `index` or `generate`.
+    pub kind: String,
+    pub stage: String,
+    pub current: u64,
+    /// `None` while the amount of work isn't known yet.
+    pub total: Option<u64>,
+    pub message: String,
+    pub state: OperationState,
+    /// Unix milliseconds.
+    pub started_at: u64,
+    pub updated_at: u64,
+}
+
+/// Operations in flight, for [`list_operations`]. Finished ones are dropped.
+#[derive(Default)]
+pub struct ProgressTracker {
+    operations: Mutex<HashMap<String, OperationProgress>>,
+    counter: AtomicU64,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+/// Event names may only hold alphanumerics and `-/:_`.
+pub(crate) fn sanitize_id(id: &str) -> String {
+    id.chars().map(|c| if c.is_ascii_alphanumeric() || "-/:_".contains(c) { c } else { '_' }).collect()
+}
+
+impl ProgressTracker {
+    /// Registers an operation of `kind` and returns its reporter. Without a caller-chosen
+    /// `operation_id`, one like `scan-3` is made up; either way it's available from
+    /// [`Progress::id`].
+    pub fn start(&self, app: &AppHandle, kind: &str, operation_id: Option<String>) -> Progress {
+        let id = match operation_id.map(|id| sanitize_id(id.trim())).filter(|id| !id.is_empty()) {
+            Some(id) => id,
+            None => format!("{}-{}", kind, self.counter.fetch_add(1, Ordering::Relaxed) + 1),
+        };
+        let now = now_ms();
+        let entry = OperationProgress {
+            operation_id: id.clone(),
+            kind: kind.to_string(),
+            stage: "starting".to_string(),
+            current: 0,
+            total: None,
+            message: String::new(),
+            state: OperationState::Running,
+            started_at: now,
+            updated_at: now,
+        };
+        self.operations.lock().unwrap().insert(id.clone(), entry.clone());
+        let _ = app.emit(&format!("{}{}", PROGRESS_EVENT_PREFIX, id), &entry);
+        Progress { app: app.clone(), id, last_emit: Mutex::new(None), finished: false }
+    }
+}
+
+/// Reports the progress of one operation. Dropping it without [`Progress::finish`] (e.g.
+/// when the operation returns early with an error) reports it as failed; the error
+/// itself reaches the caller through the command's result.
+pub struct Progress {
+    app: AppHandle,
+    id: String,
+    last_emit: Mutex<Option<Instant>>,
+    finished: bool,
+}
+
+impl Progress {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Records the current stage; `current` and `total` count the stage's units (files,
+    /// chunks, ...).
+    pub fn update(&self, stage: &str, current: u64, total: Option<u64>, message: impl Into<String>) {
+        let final_step = total.is_some_and(|t| current >= t);
+        let mut last_emit = self.last_emit.lock().unwrap();
+        let entry = self.record(|p| {
+            let new_stage = p.stage != stage;
+            p.stage = stage.to_string();
+            p.current = current;
+            p.total = total;
+            p.message = message.into();
+            new_stage
+        });
+        let Some((entry, new_stage)) = entry else { return };
+        if new_stage || final_step || last_emit.map_or(true, |t| t.elapsed() >= MIN_EMIT_INTERVAL) {
+            *last_emit = Some(Instant::now());
+            self.emit(&entry);
+        }
+    }
+
+    /// Reports a stage without a unit count.
+    pub fn stage(&self, stage: &str, message: impl Into<String>) {
+        self.update(stage, 0, None, message);
+    }
+
+    pub fn finish(mut self, message: impl Into<String>) {
+        self.finished = true;
+        self.end(OperationState::Completed, message.into());
+    }
+
+    fn end(&self, state: OperationState, message: String) {
+        let Some(tracker) = self.app.try_state::<AppState>() else { return };
+        let entry = tracker.progress.operations.lock().unwrap().remove(&self.id);
+        if let Some(mut entry) = entry {
+            let duration = Duration::from_millis(now_ms().saturating_sub(entry.started_at));
+            logs::operation(&entry.kind, &self.id, state == OperationState::Completed, &message, duration);
+            entry.state = state;
+            entry.stage = if state == OperationState::Completed { "done" } else { "failed" }.to_string();
+            entry.message = message;
+            entry.updated_at = now_ms();
+            self.emit(&entry);
+        }
+    }
+
+    fn record<R>(&self, change: impl FnOnce(&mut OperationProgress) -> R) -> Option<(OperationProgress, R)> {
+        let state = self.app.try_state::<AppState>()?;
+        let mut operations = state.progress.operations.lock().unwrap();
+        let entry = operations.get_mut(&self.id)?;
+        let result = change(entry);
+        entry.updated_at = now_ms();
+        Some((entry.clone(), result))
+    }
+
+    fn emit(&self, entry: &OperationProgress) {
+        let _ = self.app.emit(&format!("{}{}", PROGRESS_EVENT_PREFIX, self.id), entry);
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.end(OperationState::Failed, "The operation did not complete".to_string());
+        }
+    }
+}
+
+/// Operations currently in flight, oldest first.
+#[tauri::command]
+pub fn list_operations(state: State<'_, AppState>) -> Vec<OperationProgress> {
+    let mut operations: Vec<OperationProgress> = state.progress.operations.lock().unwrap().values().cloned().collect();
+    operations.sort_by_key(|o| o.started_at);
+    operations
+}
@@ -0,0 +1,149 @@
+use crate::error::AppError;
+use crate::{cache, github, log_status, normalize_subpath, paths, AppState, FileEntry, DEFAULT_FETCH_CONCURRENCY};
+use repo_prompt_core::ignore::IgnoreRules;
+use repo_prompt_core::scan::read_directory_ignoring;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tauri::{AppHandle, State};
+
+const NAMESPACE: &str = "projects";
+
+/// Files of the last load of a project, kept so a single directory can be refreshed
+/// without loading the whole project again.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadedProject {
+    /// Local root directory, or `owner/repo` for GitHub.
+    root: String,
+    /// Branch or tag a GitHub project follows; refreshed directories are read at its
+    /// current head.
+    git_ref: Option<String>,
+    /// Commit the GitHub files were last read at.
+    commit_sha: Option<String>,
+    files: Vec<FileEntry>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubtreeRefresh {
+    /// Every file of the project after the refresh.
+    files: Vec<FileEntry>,
+    added: Vec<String>,
+    changed: Vec<String>,
+    removed: Vec<String>,
+    unchanged: usize,
+    commit_sha: Option<String>,
+}
+
+fn load(app: &AppHandle, state: &AppState, root: &str) -> Result<LoadedProject, String> {
+    let bytes = cache::open_cache(app, state, NAMESPACE)?
+        .get(root)
+        .ok_or_else(|| format!("{} has not been loaded yet; load the whole project first", root))?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("Cached project is corrupt: {}", e))
+}
+
+fn store(app: &AppHandle, state: &AppState, project: &LoadedProject) -> Result<(), String> {
+    let data = serde_json::to_vec(project).map_err(|e| e.to_string())?;
+    cache::open_cache(app, state, NAMESPACE)?.put(&project.root, &data).map(|_| ())
+}
+
+/// Files of the project loaded from `root` (a scanned directory or `owner/repo`).
+pub fn loaded_files(app: &AppHandle, state: &AppState, root: &str) -> Result<Vec<FileEntry>, String> {
+    load(app, state, root).map(|p| p.files)
+}
+
+/// Remembers the files of a project that was just loaded. Failing to cache only means
+/// the next refresh has to be a full load, so errors are logged and dropped.
+pub fn remember(app: &AppHandle, state: &AppState, root: String, git_ref: Option<String>, commit_sha: Option<String>, files: &[FileEntry]) {
+    let files = files.to_vec();
+    if let Err(e) = store(app, state, &LoadedProject { root, git_ref, commit_sha, files }) {
+        log::warn!("Failed to cache loaded project: {}", e);
+    }
+}
+
+/// Replaces the files for which `in_dir` holds with `fresh`, recording what changed.
+fn patch(project: &mut LoadedProject, in_dir: impl Fn(&str) -> bool, fresh: Vec<FileEntry>) -> SubtreeRefresh {
+    let mut old: HashMap<String, String> = HashMap::new();
+    project.files.retain_mut(|f| {
+        if in_dir(&f.path) {
+            old.insert(std::mem::take(&mut f.path), std::mem::take(&mut f.content));
+            false
+        } else {
+            true
+        }
+    });
+    let (mut added, mut changed, mut unchanged) = (Vec::new(), Vec::new(), 0);
+    for file in &fresh {
+        match old.remove(&file.path) {
+            None => added.push(file.path.clone()),
+            Some(content) if content != file.content => changed.push(file.path.clone()),
+            Some(_) => unchanged += 1,
+        }
+    }
+    let mut removed: Vec<String> = old.into_keys().collect();
+    removed.sort();
+    added.sort();
+    changed.sort();
+    project.files.extend(fresh);
+    SubtreeRefresh { files: Vec::new(), added, changed, removed, unchanged, commit_sha: project.commit_sha.clone() }
+}
+
+/// Rescans (local) or refetches (GitHub) only the directory `path` of an already loaded
+/// project and patches the cached file set, instead of loading everything again.
+/// `project` is the `path` given to the scan, or `owner/repo` for GitHub. For GitHub,
+/// the files previously loaded from that directory are read again at the current head
+/// of the followed ref, and files deleted there are dropped.
+#[tauri::command]
+pub async fn refresh_subtree(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    project: String,
+    path: String,
+    token: Option<String>,
+) -> Result<SubtreeRefresh, AppError> {
+    let dir = normalize_subpath(Some(path))?.ok_or_else(|| "Use a full load to refresh the whole project".to_string())?;
+    let mut loaded = load(&app, &state, &project)?;
+    let _span = state.trace.span("scan", "refresh_subtree").attr("dir", &dir);
+    let prefix = format!("{}/", dir);
+
+    let mut refresh = if loaded.git_ref.is_none() {
+        let root = Path::new(&loaded.root).join(&dir);
+        state.policy.check_scan_path(&app, &root)?;
+        // A deleted directory just drops what was loaded from it.
+        let fresh = if root.is_dir() {
+            log_status(&app, format!("Rescanning {}", root.display()));
+            let ignore = IgnoreRules::for_directory(Path::new(&loaded.root), &state.ignore_patterns.read().await);
+            let mut files = read_directory_ignoring(root.clone(), Path::new(&loaded.root), &ignore, |_, _| {}).await;
+            paths::make_relative(&mut files, Path::new(&loaded.root));
+            files
+        } else {
+            Vec::new()
+        };
+        patch(&mut loaded, |p| p.starts_with(&prefix), fresh)
+    } else {
+        state.policy.check_not_demo("GitHub access")?;
+        let (owner, repo) = loaded.root.split_once('/').ok_or_else(|| format!("Invalid project: {}", loaded.root))?;
+        let (owner, repo) = (owner.to_string(), repo.to_string());
+        let git_ref = loaded.git_ref.clone().unwrap_or_default();
+        let gh = github::GithubClient::new(state.http_client.read().await.clone(), token.unwrap_or_default())
+            .with_cache(cache::open_cache(&app, &state, "github").ok());
+        let commit_sha = gh.resolve_ref(&owner, &repo, &git_ref).await?;
+        log_status(&app, format!("Refetching {} from {}/{}@{}", dir, owner, repo, &commit_sha[..7.min(commit_sha.len())]));
+        let tree = gh.fetch_tree(&owner, &repo, &commit_sha, &prefix, |msg| log_status(&app, msg)).await?;
+        let wanted: Vec<String> = loaded.files.iter().filter(|f| f.path.starts_with(&prefix) && tree.contains(&f.path)).map(|f| f.path.clone()).collect();
+        let fresh = crate::fetch_files(&gh, &owner, &repo, &commit_sha, wanted, DEFAULT_FETCH_CONCURRENCY, |_| {}).await;
+        // Files that failed to download keep their previous content.
+        let fetched: HashSet<String> = fresh.iter().map(|f| f.path.clone()).collect();
+        loaded.commit_sha = Some(commit_sha);
+        patch(&mut loaded, |p| p.starts_with(&prefix) && (fetched.contains(p) || !tree.iter().any(|t| t == p)), fresh)
+    };
+
+    log_status(
+        &app,
+        format!("Refreshed {}: {} added, {} changed, {} removed", dir, refresh.added.len(), refresh.changed.len(), refresh.removed.len()),
+    );
+    store(&app, &state, &loaded)?;
+    refresh.files = loaded.files;
+    Ok(refresh)
+}
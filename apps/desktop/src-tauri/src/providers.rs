@@ -0,0 +1,101 @@
+use crate::error::AppError;
+use crate::{ollama, AppState};
+use isahc::prelude::*;
+use serde::Serialize;
+use tauri::State;
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderCapabilities {
+    provider: String,
+    streaming: bool,
+    json_mode: bool,
+    embeddings: bool,
+    vision: bool,
+    tools: bool,
+    /// Context window in tokens, when known for the provider/model.
+    max_context_tokens: Option<u64>,
+}
+
+fn static_capabilities(provider: &str, model: Option<&str>) -> Option<ProviderCapabilities> {
+    let model = model.unwrap_or_default().to_lowercase();
+    let caps = match provider {
+        "gemini" => ProviderCapabilities {
+            provider: provider.to_string(),
+            streaming: true,
+            json_mode: true,
+            embeddings: true,
+            vision: true,
+            tools: true,
+            max_context_tokens: Some(1_048_576),
+        },
+        "ollama" => ProviderCapabilities {
+            provider: provider.to_string(),
+            streaming: true,
+            json_mode: true,
+            embeddings: true,
+            vision: ["llava", "vision", "bakllava", "moondream", "minicpm-v"].iter().any(|m| model.contains(m)),
+            tools: false,
+            // Ollama's default window unless num_ctx is raised.
+            max_context_tokens: Some(4096),
+        },
+        "openai" | "custom" | "azure" | "lmstudio" => ProviderCapabilities {
+            provider: provider.to_string(),
+            streaming: true,
+            json_mode: true,
+            embeddings: true,
+            vision: ["gpt-4o", "gpt-4.1", "gpt-5", "vision", "-vl"].iter().any(|m| model.contains(m)),
+            tools: true,
+            max_context_tokens: if model.is_empty() { None } else { Some(128_000) },
+        },
+        _ => return None,
+    };
+    Some(caps)
+}
+
+/// Known context window for a provider/model, without asking the server.
+pub fn context_window(provider: &str, model: Option<&str>) -> Option<u64> {
+    static_capabilities(provider, model).and_then(|c| c.max_context_tokens)
+}
+
+/// Refines the static Ollama entry with `/api/show`, which reports the model's real
+/// capabilities and trained context length.
+async fn refine_ollama(state: &AppState, url: &str, model: &str, caps: &mut ProviderCapabilities) {
+    let body = serde_json::json!({ "model": model });
+    let Ok(mut res) = ollama::post_json(state, url, "/api/show", &body).await else { return };
+    if !res.status().is_success() {
+        return;
+    }
+    let Ok(data) = res.text().await.map(|t| serde_json::from_str::<serde_json::Value>(&t).unwrap_or_default()) else { return };
+
+    if let Some(list) = data["capabilities"].as_array() {
+        let has = |name: &str| list.iter().any(|c| c.as_str() == Some(name));
+        caps.vision = has("vision");
+        caps.tools = has("tools");
+        caps.embeddings = has("embedding") || !has("completion");
+    }
+    if let Some(info) = data["model_info"].as_object() {
+        if let Some(ctx) = info.iter().find(|(k, _)| k.ends_with(".context_length")).and_then(|(_, v)| v.as_u64()) {
+            caps.max_context_tokens = Some(ctx);
+        }
+    }
+}
+
+/// Reports what a provider (and optionally a specific model) supports so the UI can
+/// enable streaming, JSON mode, image attachments, etc. only where they work.
+#[tauri::command]
+pub async fn get_provider_capabilities(
+    state: State<'_, AppState>,
+    provider: String,
+    model: Option<String>,
+    url: Option<String>,
+) -> Result<ProviderCapabilities, AppError> {
+    let provider = provider.trim().to_lowercase();
+    let mut caps = static_capabilities(&provider, model.as_deref()).ok_or_else(|| format!("Unknown provider: {}", provider))?;
+    if provider == "ollama" {
+        if let (Some(url), Some(model)) = (url.as_deref(), model.as_deref()) {
+            refine_ollama(&state, url, model, &mut caps).await;
+        }
+    }
+    Ok(caps)
+}
@@ -0,0 +1,123 @@
+use crate::error::AppError;
+use crate::{log_status, AppState};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+
+const RECENT_FILE: &str = "recent_repos.json";
+/// Oldest unpinned entries are dropped past this many; pinned ones are always kept.
+const MAX_UNPINNED: usize = 30;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RepoKind {
+    Local,
+    Github,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentRepo {
+    kind: RepoKind,
+    /// Local path or `owner/repo`.
+    key: String,
+    /// Options of the last load (subpath, ref, file limit, ...), to repeat it as it was.
+    /// Tokens are never stored.
+    options: Value,
+    pinned: bool,
+    /// Unix seconds.
+    last_used: u64,
+    use_count: u32,
+}
+
+fn recent_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| format!("Failed to resolve data dir: {}", e))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+    Ok(dir.join(RECENT_FILE))
+}
+
+fn load_recent(app: &AppHandle) -> Result<Vec<RecentRepo>, String> {
+    match fs::read_to_string(recent_path(app)?) {
+        Ok(text) => serde_json::from_str(&text).map_err(|e| format!("Recent repositories file is corrupt: {}", e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to read recent repositories: {}", e)),
+    }
+}
+
+fn store_recent(app: &AppHandle, entries: &[RecentRepo]) -> Result<(), String> {
+    let path = recent_path(app)?;
+    let tmp = path.with_extension("json.tmp");
+    let text = serde_json::to_string(entries).map_err(|e| e.to_string())?;
+    fs::write(&tmp, text).map_err(|e| format!("Failed to save recent repositories: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to save recent repositories: {}", e))
+}
+
+/// Pinned first, then most recently used.
+fn sorted(mut entries: Vec<RecentRepo>) -> Vec<RecentRepo> {
+    entries.sort_by(|a, b| b.pinned.cmp(&a.pinned).then(b.last_used.cmp(&a.last_used)));
+    entries
+}
+
+/// Notes that a repository was loaded. Called after successful scans and fetches;
+/// failures only go to the status log, since the load itself succeeded.
+pub fn record(app: &AppHandle, kind: RepoKind, key: String, options: Value) {
+    let state = app.state::<AppState>();
+    let _guard = state.recent_lock.lock().unwrap();
+    let result = load_recent(app).and_then(|entries| {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+        let mut entries = sorted(entries);
+        match entries.iter_mut().find(|e| e.kind == kind && e.key == key) {
+            Some(entry) => {
+                entry.options = options;
+                entry.last_used = now;
+                entry.use_count += 1;
+            }
+            None => entries.push(RecentRepo { kind, key, options, pinned: false, last_used: now, use_count: 1 }),
+        }
+        let mut entries = sorted(entries);
+        let mut unpinned = 0;
+        entries.retain(|e| {
+            unpinned += usize::from(!e.pinned);
+            e.pinned || unpinned <= MAX_UNPINNED
+        });
+        store_recent(app, &entries)
+    });
+    if let Err(e) = result {
+        log_status(app, format!("Could not update recent repositories: {}", e));
+    }
+}
+
+/// Recently loaded local folders and GitHub repositories, pinned ones first.
+#[tauri::command]
+pub fn get_recent_repos(app: AppHandle, limit: Option<usize>) -> Result<Vec<RecentRepo>, AppError> {
+    let mut entries = sorted(load_recent(&app)?);
+    entries.truncate(limit.unwrap_or(usize::MAX));
+    Ok(entries)
+}
+
+/// Pins (or unpins) an entry so it stays at the top and survives `clear_history`.
+#[tauri::command]
+pub fn pin_repo(app: AppHandle, state: State<'_, AppState>, kind: RepoKind, key: String, pinned: bool) -> Result<Vec<RecentRepo>, AppError> {
+    let _guard = state.recent_lock.lock().unwrap();
+    let mut entries = load_recent(&app)?;
+    let entry = entries
+        .iter_mut()
+        .find(|e| e.kind == kind && e.key == key)
+        .ok_or_else(|| format!("{} is not in the recent repositories", key))?;
+    entry.pinned = pinned;
+    store_recent(&app, &entries)?;
+    Ok(sorted(entries))
+}
+
+/// Forgets the recent repositories, except pinned ones unless `include_pinned`.
+#[tauri::command]
+pub fn clear_history(app: AppHandle, state: State<'_, AppState>, include_pinned: Option<bool>) -> Result<Vec<RecentRepo>, AppError> {
+    let _guard = state.recent_lock.lock().unwrap();
+    let mut entries = load_recent(&app)?;
+    entries.retain(|e| e.pinned && !include_pinned.unwrap_or(false));
+    store_recent(&app, &entries)?;
+    Ok(sorted(entries))
+}
@@ -0,0 +1,252 @@
+use crate::error::AppError;
+use crate::cache::{self, DiskCache, GcReport};
+use crate::{github, AppState, FileEntry, GithubRepoData};
+use repo_prompt_core::ranking::ScoringRules;
+use serde::{Deserialize, Serialize};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::atomic::Ordering;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+
+/// Whole `fetch_github_repo` results.
+const SNAPSHOTS: &str = "repos";
+/// Single files read at a commit SHA; they never change, so they don't expire.
+const FILES: &str = "repo_files";
+/// Key of the list of snapshots in the [`SNAPSHOTS`] namespace.
+const INDEX_KEY: &str = "index";
+pub const DEFAULT_TTL_SECS: u64 = 3600;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotEntry {
+    key: String,
+    owner: String,
+    repo: String,
+    /// Requested branch, tag or SHA; `None` for the default branch.
+    git_ref: Option<String>,
+    commit_sha: String,
+    /// Unix seconds.
+    fetched_at: u64,
+    /// Fetched at a full SHA, so it can't go stale.
+    pinned: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedSnapshot {
+    #[serde(flatten)]
+    entry: SnapshotEntry,
+    age_secs: u64,
+    expired: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamespaceStats {
+    name: String,
+    entries: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    namespaces: Vec<NamespaceStats>,
+    blob_count: usize,
+    /// Compressed size of everything cached.
+    total_bytes: u64,
+    ttl_secs: u64,
+    snapshots: Vec<CachedSnapshot>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearReport {
+    snapshots_removed: usize,
+    gc: GcReport,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+fn digest(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn load_index(cache: &DiskCache) -> Vec<SnapshotEntry> {
+    cache.get(INDEX_KEY).and_then(|bytes| serde_json::from_slice(&bytes).ok()).unwrap_or_default()
+}
+
+fn store_index(cache: &DiskCache, index: &[SnapshotEntry]) -> Result<(), String> {
+    cache.put(INDEX_KEY, &serde_json::to_vec(index).map_err(|e| e.to_string())?).map(|_| ())
+}
+
+/// Everything that changes what `fetch_github_repo` returns, besides the repository's
+/// own history.
+#[allow(clippy::too_many_arguments)]
+pub fn snapshot_key(
+    owner: &str,
+    repo: &str,
+    git_ref: Option<&str>,
+    subpath: Option<&str>,
+    max_files: Option<u32>,
+    include_submodules: bool,
+    raw_dependencies: bool,
+    scoring: &ScoringRules,
+    authenticated: bool,
+    use_tarball: bool,
+    manifest_files: &[String],
+    ignore_patterns: &[String],
+) -> String {
+    // A token can see private repositories, and the tarball is read differently from the
+    // contents API, so neither result stands in for the other.
+    let mut extra = String::new();
+    if authenticated {
+        extra.push_str("|token");
+    }
+    if use_tarball {
+        extra.push_str("|tarball");
+    }
+    // Rules, manifests and ignore patterns other than the defaults pick other files.
+    if *scoring != ScoringRules::default() {
+        extra.push_str(&format!("|scoring {:x}", digest(scoring)));
+    }
+    if !manifest_files.is_empty() {
+        extra.push_str(&format!("|manifests {:x}", digest(&manifest_files)));
+    }
+    if !ignore_patterns.is_empty() {
+        extra.push_str(&format!("|ignore {:x}", digest(&ignore_patterns)));
+    }
+    format!(
+        "{}/{}@{}|{}|{}|{}{}{}",
+        owner.to_lowercase(),
+        repo.to_lowercase(),
+        git_ref.unwrap_or("HEAD"),
+        subpath.unwrap_or_default(),
+        max_files.map(|n| n.to_string()).unwrap_or_default(),
+        include_submodules,
+        if raw_dependencies { "|raw" } else { "" },
+        extra
+    )
+}
+
+/// A cached fetch result younger than the configured TTL (or pinned to a commit), with
+/// its age in seconds.
+pub fn get_snapshot(app: &AppHandle, state: &AppState, key: &str) -> Option<(GithubRepoData, u64)> {
+    let cache = cache::open_cache(app, state, SNAPSHOTS).ok()?;
+    let entry = load_index(&cache).into_iter().find(|e| e.key == key)?;
+    let age = now_secs().saturating_sub(entry.fetched_at);
+    if !entry.pinned && age > state.repo_cache_ttl_secs.load(Ordering::Relaxed) {
+        return None;
+    }
+    let data = serde_json::from_slice(&cache.get(key)?).ok()?;
+    Some((data, age))
+}
+
+pub fn put_snapshot(app: &AppHandle, state: &AppState, key: &str, git_ref: Option<&str>, data: &GithubRepoData) -> Result<(), String> {
+    let cache = cache::open_cache(app, state, SNAPSHOTS)?;
+    cache.put(key, &serde_json::to_vec(data).map_err(|e| e.to_string())?)?;
+    let _guard = state.snapshot_index_lock.lock().unwrap();
+    let mut index = load_index(&cache);
+    index.retain(|e| e.key != key);
+    index.push(SnapshotEntry {
+        key: key.to_string(),
+        owner: data.info.owner.clone(),
+        repo: data.info.repo.clone(),
+        git_ref: git_ref.map(str::to_string),
+        commit_sha: data.info.commit_sha.clone(),
+        fetched_at: now_secs(),
+        pinned: git_ref.is_some_and(github::is_full_sha),
+    });
+    store_index(&cache, &index)
+}
+
+fn file_key(owner: &str, repo: &str, commit_sha: &str, path: &str) -> String {
+    format!("{}/{}@{}:{}", owner.to_lowercase(), repo.to_lowercase(), commit_sha, path)
+}
+
+/// Splits `paths` into the files already cached at `commit_sha` and those to fetch.
+pub fn cached_files(app: &AppHandle, state: &AppState, owner: &str, repo: &str, commit_sha: &str, paths: Vec<String>) -> (Vec<FileEntry>, Vec<String>) {
+    let Ok(cache) = cache::open_cache(app, state, FILES) else { return (Vec::new(), paths) };
+    let mut hits = Vec::new();
+    let mut missing = Vec::new();
+    for path in paths {
+        match cache.get(&file_key(owner, repo, commit_sha, &path)).and_then(|b| String::from_utf8(b).ok()) {
+            Some(content) => hits.push(FileEntry::new(path, content)),
+            None => missing.push(path),
+        }
+    }
+    (hits, missing)
+}
+
+pub fn put_files(app: &AppHandle, state: &AppState, owner: &str, repo: &str, commit_sha: &str, files: &[FileEntry]) {
+    if let Ok(cache) = cache::open_cache(app, state, FILES) {
+        for f in files {
+            let _ = cache.put(&file_key(owner, repo, commit_sha, &f.path), f.content.as_bytes());
+        }
+    }
+}
+
+/// Size of the on-disk cache per namespace, and the cached repository snapshots.
+#[tauri::command]
+pub async fn get_cache_stats(app: AppHandle, state: State<'_, AppState>) -> Result<CacheStats, AppError> {
+    let ttl = state.repo_cache_ttl_secs.load(Ordering::Relaxed);
+    let snapshots = cache::open_cache(&app, &state, SNAPSHOTS)?;
+    tokio::task::spawn_blocking(move || {
+        let usage = cache::usage(&app)?;
+        let now = now_secs();
+        let mut snapshots: Vec<CachedSnapshot> = load_index(&snapshots)
+            .into_iter()
+            .map(|entry| {
+                let age_secs = now.saturating_sub(entry.fetched_at);
+                CachedSnapshot { expired: !entry.pinned && age_secs > ttl, age_secs, entry }
+            })
+            .collect();
+        snapshots.sort_by_key(|s| s.age_secs);
+        Ok(CacheStats {
+            namespaces: usage.namespaces.into_iter().map(|(name, entries)| NamespaceStats { name, entries }).collect(),
+            blob_count: usage.blob_count,
+            total_bytes: usage.blob_bytes,
+            ttl_secs: ttl,
+            snapshots,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Drops the cached snapshots of `owner/repo`, or with no repository given, every cached
+/// snapshot, file and ETag response from GitHub. Unreferenced blobs are deleted after.
+#[tauri::command]
+pub async fn clear_repo_cache(app: AppHandle, state: State<'_, AppState>, owner: Option<String>, repo: Option<String>) -> Result<ClearReport, AppError> {
+    let cache = cache::open_cache(&app, &state, SNAPSHOTS)?;
+    let level = state.cache_compression_level.load(Ordering::Relaxed);
+    tokio::task::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let _guard = state.snapshot_index_lock.lock().unwrap();
+        let mut index = load_index(&cache);
+        let before = index.len();
+        match (owner, repo) {
+            (Some(owner), Some(repo)) => {
+                let (removed, kept): (Vec<SnapshotEntry>, Vec<SnapshotEntry>) =
+                    index.into_iter().partition(|e| e.owner.eq_ignore_ascii_case(&owner) && e.repo.eq_ignore_ascii_case(&repo));
+                for entry in &removed {
+                    cache.remove(&entry.key);
+                }
+                index = kept;
+                store_index(&cache, &index)?;
+            }
+            (None, None) => {
+                index.clear();
+                for namespace in [SNAPSHOTS, FILES, "github"] {
+                    cache::clear_namespace(&app, namespace)?;
+                }
+            }
+            _ => return Err(AppError::InvalidInput("Give both owner and repo, or neither to clear every repository".to_string())),
+        }
+        Ok(ClearReport { snapshots_removed: before - index.len(), gc: cache::collect_garbage(&app, level)? })
+    })
+    .await?
+}
@@ -0,0 +1,10 @@
+use crate::error::AppError;
+use repo_prompt_core::repourl::RepoUrl;
+
+/// Takes apart a repository URL pasted from a browser or clone dialog (GitHub or GitLab,
+/// HTTPS or SSH, `/tree/`, `/blob/` and `.git` forms) into provider, owner, repo, ref and
+/// subpath, so users don't have to type owner and repo separately.
+#[tauri::command]
+pub fn parse_repo_url(input: String) -> Result<RepoUrl, AppError> {
+    repo_prompt_core::repourl::parse_repo_url(&input).map_err(AppError::InvalidInput)
+}
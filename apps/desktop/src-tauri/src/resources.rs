@@ -0,0 +1,230 @@
+use crate::error::AppError;
+use crate::{ollama, AppState};
+use isahc::AsyncReadResponseExt;
+use serde::Serialize;
+use std::process::Command;
+use sysinfo::System;
+use tauri::State;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+/// Memory a loaded model takes beyond its weights (KV cache at a default context, runtime
+/// buffers), as a share of the weights plus a fixed part.
+const OVERHEAD_RATIO: f64 = 0.2;
+const OVERHEAD_BYTES: u64 = 512 * 1024 * 1024;
+const GB: f64 = 1_073_741_824.0;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Gpu {
+    name: String,
+    vendor: String,
+    total_vram_bytes: Option<u64>,
+    free_vram_bytes: Option<u64>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Fit {
+    /// Fits in free GPU memory.
+    Gpu,
+    /// Fits in free RAM, or split between GPU and RAM; runs, but slower.
+    Partial,
+    /// Needs more than is free now, but less than is installed.
+    Tight,
+    /// Needs more memory than the machine has.
+    TooLarge,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelFit {
+    model: String,
+    /// Size of the model's weights.
+    size_bytes: u64,
+    /// Whether `size_bytes` was estimated from the parameter count and quantization
+    /// rather than read from the installed files.
+    estimated: bool,
+    /// Memory expected to be needed once loaded.
+    required_bytes: u64,
+    fit: Fit,
+    warning: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemResources {
+    total_memory_bytes: u64,
+    available_memory_bytes: u64,
+    cpu_cores: usize,
+    gpus: Vec<Gpu>,
+    /// GPU and CPU share the RAM (Apple Silicon), so RAM counts as GPU memory.
+    unified_memory: bool,
+    model_fit: Option<ModelFit>,
+}
+
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let mut command = Command::new(program);
+    command.args(args);
+    #[cfg(target_os = "windows")]
+    command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    let output = command.output().ok().filter(|o| o.status.success())?;
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn nvidia_gpus() -> Vec<Gpu> {
+    let Some(out) = run("nvidia-smi", &["--query-gpu=name,memory.total,memory.free", "--format=csv,noheader,nounits"]) else { return Vec::new() };
+    out.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let mib = |i: usize| fields.get(i).and_then(|v| v.parse::<u64>().ok()).map(|m| m * 1024 * 1024);
+            (!fields[0].is_empty()).then(|| Gpu { name: fields[0].to_string(), vendor: "NVIDIA".to_string(), total_vram_bytes: mib(1), free_vram_bytes: mib(2) })
+        })
+        .collect()
+}
+
+fn amd_gpus() -> Vec<Gpu> {
+    let Some(out) = run("rocm-smi", &["--showmeminfo", "vram", "--json"]) else { return Vec::new() };
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(&out) else { return Vec::new() };
+    let bytes = |card: &serde_json::Value, key: &str| card[key].as_str().and_then(|v| v.trim().parse::<u64>().ok());
+    json.as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(name, _)| name.starts_with("card"))
+        .map(|(name, card)| {
+            let total = bytes(card, "VRAM Total Memory (B)");
+            let used = bytes(card, "VRAM Total Used Memory (B)");
+            Gpu {
+                name: name.clone(),
+                vendor: "AMD".to_string(),
+                total_vram_bytes: total,
+                free_vram_bytes: total.zip(used).map(|(t, u)| t.saturating_sub(u)),
+            }
+        })
+        .collect()
+}
+
+fn detect_gpus() -> (Vec<Gpu>, bool) {
+    let unified = cfg!(all(target_os = "macos", target_arch = "aarch64"));
+    if unified {
+        return (vec![Gpu { name: "Apple Silicon GPU".to_string(), vendor: "Apple".to_string(), total_vram_bytes: None, free_vram_bytes: None }], true);
+    }
+    let mut gpus = nvidia_gpus();
+    gpus.extend(amd_gpus());
+    (gpus, false)
+}
+
+/// Bits per weight of an Ollama quantization level (`Q4_K_M`, `Q8_0`, `F16`, ...).
+fn bits_per_weight(quantization: &str) -> f64 {
+    let q = quantization.to_uppercase();
+    match q.as_str() {
+        "F32" => 32.0,
+        "F16" | "BF16" => 16.0,
+        _ => match q.strip_prefix('Q').or_else(|| q.strip_prefix("IQ")).and_then(|r| r.chars().next()).and_then(|c| c.to_digit(10)) {
+            // K-quants and block scales add roughly half a bit per weight.
+            Some(bits) => bits as f64 + 0.5,
+            None => 4.5,
+        },
+    }
+}
+
+/// Size of `model`'s weights: the installed size from `/api/tags`, else an estimate from
+/// the parameter count and quantization reported by `/api/show`.
+async fn model_size(state: &AppState, url: &str, model: &str) -> Result<(u64, bool), AppError> {
+    let mut res = ollama::get(state, url, "/api/tags").await?;
+    let tags: serde_json::Value = serde_json::from_str(&res.text().await?).unwrap_or_default();
+    let installed = tags["models"].as_array().into_iter().flatten().find(|m| m["name"].as_str() == Some(model) || m["model"].as_str() == Some(model));
+    if let Some(size) = installed.and_then(|m| m["size"].as_u64()).filter(|s| *s > 0) {
+        return Ok((size, false));
+    }
+
+    let mut res = ollama::post_json(state, url, "/api/show", &serde_json::json!({ "model": model })).await?;
+    let status = res.status().as_u16();
+    let text = res.text().await?;
+    if !(200..300).contains(&status) {
+        return Err(AppError::from_status(status, format!("Ollama error ({}): {}", status, text), None));
+    }
+    let data: serde_json::Value = serde_json::from_str(&text).map_err(|e| AppError::Provider { status: None, message: e.to_string() })?;
+    let params = data["model_info"]["general.parameter_count"]
+        .as_u64()
+        .ok_or_else(|| AppError::Provider { status: None, message: format!("Ollama did not report the size of {}", model) })?;
+    let bits = bits_per_weight(data["details"]["quantization_level"].as_str().unwrap_or_default());
+    Ok(((params as f64 * bits / 8.0) as u64, true))
+}
+
+fn assess(model: String, size_bytes: u64, estimated: bool, resources: &SystemResources) -> ModelFit {
+    let required = size_bytes + (size_bytes as f64 * OVERHEAD_RATIO) as u64 + OVERHEAD_BYTES;
+    let free_vram: u64 = if resources.unified_memory {
+        resources.available_memory_bytes
+    } else {
+        resources.gpus.iter().filter_map(|g| g.free_vram_bytes).max().unwrap_or(0)
+    };
+    let total_vram: u64 = if resources.unified_memory { 0 } else { resources.gpus.iter().filter_map(|g| g.total_vram_bytes).max().unwrap_or(0) };
+    let gb = |b: u64| b as f64 / GB;
+    let (fit, warning) = if required <= free_vram {
+        (Fit::Gpu, None)
+    } else if required <= free_vram + resources.available_memory_bytes && !resources.unified_memory {
+        let message = if free_vram > 0 {
+            format!("{} needs about {:.1} GB but only {:.1} GB of GPU memory is free; part of it will run on the CPU, which is much slower.", model, gb(required), gb(free_vram))
+        } else {
+            format!("{} needs about {:.1} GB and will run on the CPU, which is much slower than on a GPU.", model, gb(required))
+        };
+        (Fit::Partial, Some(message))
+    } else if required <= resources.total_memory_bytes + total_vram {
+        let free = if resources.unified_memory { resources.available_memory_bytes } else { free_vram + resources.available_memory_bytes };
+        (
+            Fit::Tight,
+            Some(format!(
+                "{} needs about {:.1} GB but only {:.1} GB is free. Close other applications or pick a smaller model, or loading may hang or swap.",
+                model,
+                gb(required),
+                gb(free)
+            )),
+        )
+    } else {
+        (
+            Fit::TooLarge,
+            Some(format!(
+                "{} needs about {:.1} GB, more than this machine's {:.1} GB of memory. Pick a smaller or more heavily quantized model.",
+                model,
+                gb(required),
+                gb(resources.total_memory_bytes + total_vram)
+            )),
+        )
+    };
+    ModelFit { model, size_bytes, estimated, required_bytes: required, fit, warning }
+}
+
+/// RAM, CPU cores and GPUs (with VRAM where `nvidia-smi` or `rocm-smi` report it; Apple
+/// Silicon shares RAM with the GPU). With `model`, also checks whether that Ollama model
+/// (at `url` or the configured server) will fit, and returns a warning when it likely
+/// won't, before the user waits on a load that swaps or hangs.
+#[tauri::command]
+pub async fn get_system_resources(state: State<'_, AppState>, model: Option<String>, url: Option<String>) -> Result<SystemResources, AppError> {
+    let (mut resources, gpus) = tokio::task::spawn_blocking(|| {
+        let mut system = System::new();
+        system.refresh_memory();
+        let resources = SystemResources {
+            total_memory_bytes: system.total_memory(),
+            available_memory_bytes: system.available_memory(),
+            cpu_cores: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            gpus: Vec::new(),
+            unified_memory: false,
+            model_fit: None,
+        };
+        (resources, detect_gpus())
+    })
+    .await?;
+    (resources.gpus, resources.unified_memory) = gpus;
+
+    if let Some(model) = model.filter(|m| !m.trim().is_empty()) {
+        let url = match url.filter(|u| !u.trim().is_empty()) {
+            Some(u) => ollama::normalize_url(&u),
+            None => state.ollama_url.read().await.clone().unwrap_or_else(|| ollama::DEFAULT_OLLAMA_URL.to_string()),
+        };
+        let (size, estimated) = model_size(&state, &url, &model).await?;
+        resources.model_fit = Some(assess(model, size, estimated, &resources));
+    }
+    Ok(resources)
+}
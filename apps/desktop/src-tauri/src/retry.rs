@@ -0,0 +1,80 @@
+use isahc::error::ErrorKind;
+use isahc::{AsyncBody, HttpClient, Request, Response};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+/// Attempts per request (the first one included) unless changed in the settings.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 4;
+pub const MAX_ATTEMPTS_LIMIT: u32 = 10;
+const BASE_DELAY: Duration = Duration::from_millis(500);
+const MAX_DELAY: Duration = Duration::from_secs(8);
+
+/// Process-wide, since requests are also made from places without the app state (the
+/// GitHub client).
+static MAX_ATTEMPTS: AtomicU32 = AtomicU32::new(DEFAULT_MAX_ATTEMPTS);
+
+pub fn set_max_attempts(attempts: u32) {
+    MAX_ATTEMPTS.store(attempts.clamp(1, MAX_ATTEMPTS_LIMIT), Ordering::Relaxed);
+}
+
+pub fn max_attempts() -> u32 {
+    MAX_ATTEMPTS.load(Ordering::Relaxed)
+}
+
+/// Whether `error` is worth another attempt. A failed connection or lookup never reached
+/// the server, so any request can be repeated; a timeout or reset may have, so only
+/// idempotent ones are.
+fn transient_error(error: &isahc::Error, idempotent: bool) -> bool {
+    match error.kind() {
+        ErrorKind::ConnectionFailed | ErrorKind::NameResolution => true,
+        ErrorKind::Timeout | ErrorKind::Io => idempotent,
+        _ => false,
+    }
+}
+
+/// Exponential backoff with jitter: a random delay between half and all of
+/// `BASE_DELAY * 2^attempt`, capped at `MAX_DELAY`.
+fn backoff(attempt: u32) -> Duration {
+    let full = BASE_DELAY.saturating_mul(1 << attempt.min(16)).min(MAX_DELAY);
+    let random = RandomState::new().build_hasher().finish();
+    full / 2 + full.mul_f64((random % 1000) as f64 / 2000.0)
+}
+
+/// Sends the request built by `make`, retrying transient failures with backoff up to
+/// the configured number of attempts. 5xx responses and timeouts are only retried when
+/// the caller says the request is `idempotent`. Each attempt is written to the operation
+/// log under `service`. After the last attempt the final response (even a 5xx) or error
+/// is returned.
+pub async fn send<B: Into<AsyncBody>>(
+    client: &HttpClient,
+    service: &str,
+    idempotent: bool,
+    make: impl Fn() -> Result<Request<B>, isahc::http::Error>,
+) -> Result<Response<AsyncBody>, isahc::Error> {
+    let attempts = max_attempts();
+    let mut attempt = 1;
+    loop {
+        let (parts, body) = make()?.into_parts();
+        let body: AsyncBody = body.into();
+        let (method, url, bytes_sent) = (parts.method.to_string(), parts.uri.to_string(), body.len());
+        let started = Instant::now();
+        let result = client.send_async(Request::from_parts(parts, body)).await;
+        let outcome = result.as_ref().map(|res| res.status().as_u16()).map_err(|e| e.to_string());
+        let bytes_received = result.as_ref().ok().and_then(|res| res.body().len());
+        crate::logs::request(service, &method, &url, outcome.as_ref().copied().map_err(String::as_str), started.elapsed(), bytes_sent, bytes_received, attempt);
+        let reason = match &result {
+            Ok(res) if idempotent && res.status().is_server_error() => res.status().to_string(),
+            Err(e) if transient_error(e, idempotent) => e.to_string(),
+            _ => return result,
+        };
+        if attempt >= attempts {
+            return result;
+        }
+        let delay = backoff(attempt - 1);
+        log::warn!("{} request failed ({}); retry {} of {} in {} ms", service, reason, attempt, attempts - 1, delay.as_millis());
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
@@ -0,0 +1,357 @@
+use crate::error::AppError;
+use crate::{cache, github, log_status, AppState, FileEntry, DEFAULT_FETCH_CONCURRENCY};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+/// One finding to post as an inline PR comment. Lines refer to the PR's head version of
+/// the file and must be part of the diff, otherwise GitHub rejects the whole review.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewComment {
+    path: String,
+    line: u32,
+    /// First line of a multi-line comment; `line` is then the last one.
+    start_line: Option<u32>,
+    body: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostedReview {
+    id: u64,
+    html_url: String,
+    comments: usize,
+}
+
+/// Posts the selected findings as a single review on a pull request. `event` is
+/// `COMMENT` (default), `REQUEST_CHANGES` or `APPROVE`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn post_review_comments(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    owner: String,
+    repo: String,
+    pr_number: u64,
+    token: String,
+    comments: Vec<ReviewComment>,
+    summary: Option<String>,
+    event: Option<String>,
+    commit_sha: Option<String>,
+) -> Result<PostedReview, AppError> {
+    if token.trim().is_empty() {
+        return Err(AppError::MissingCredentials("Posting review comments requires a GitHub token with pull request write access".to_string()));
+    }
+    let event = event.unwrap_or_else(|| "COMMENT".to_string()).to_uppercase();
+    if !["COMMENT", "REQUEST_CHANGES", "APPROVE"].contains(&event.as_str()) {
+        return Err(AppError::InvalidInput(format!("Unknown review event: {}", event)));
+    }
+    let summary = summary.unwrap_or_default();
+    if comments.is_empty() && summary.trim().is_empty() {
+        return Err(AppError::InvalidInput("Nothing to post: select at least one finding or write a summary".to_string()));
+    }
+
+    let inline: Vec<serde_json::Value> = comments
+        .iter()
+        .map(|c| {
+            let mut comment = serde_json::json!({ "path": c.path, "line": c.line, "side": "RIGHT", "body": c.body });
+            if let Some(start) = c.start_line.filter(|s| *s < c.line) {
+                comment["start_line"] = start.into();
+                comment["start_side"] = "RIGHT".into();
+            }
+            comment
+        })
+        .collect();
+    let mut body = serde_json::json!({ "event": event, "body": summary, "comments": inline });
+    // Anchors the comments to the analysed commit; if the PR moved on, GitHub maps them.
+    if let Some(sha) = commit_sha.filter(|s| github::is_full_sha(s)) {
+        body["commit_id"] = sha.into();
+    }
+
+    state.policy.check_not_demo("GitHub access")?;
+    let gh = github::GithubClient::new(state.http_client.read().await.clone(), token);
+    log_status(&app, format!("Posting {} review comments to {}/{}#{}", comments.len(), owner, repo, pr_number));
+    let res = gh
+        .post_json(&format!("/repos/{}/{}/pulls/{}/reviews", owner, repo, pr_number), &body)
+        .await
+        .map_err(|e| match e {
+            AppError::InvalidInput(message) if message.contains("(422") => {
+                AppError::InvalidInput(format!("{} (every commented line must be part of the PR diff)", message))
+            }
+            e => e,
+        })?;
+
+    Ok(PostedReview {
+        id: res["id"].as_u64().unwrap_or_default(),
+        html_url: res["html_url"].as_str().unwrap_or_default().to_string(),
+        comments: comments.len(),
+    })
+}
+
+/// GitHub lists at most 3000 changed files per pull request, 100 per page.
+const MAX_PR_FILE_PAGES: u32 = 30;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedFile {
+    pub(crate) path: String,
+    /// `added`, `modified`, `removed`, `renamed`, ...
+    pub(crate) status: String,
+    pub(crate) additions: u64,
+    pub(crate) deletions: u64,
+    pub(crate) previous_path: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullRequestData {
+    number: u64,
+    title: String,
+    description: String,
+    author: String,
+    base_ref: String,
+    head_ref: String,
+    /// Head commit; pass it to `post_review_comments` to anchor findings.
+    head_sha: String,
+    html_url: String,
+    changed_files: Vec<ChangedFile>,
+    /// Unified diff assembled from the per-file patches.
+    diff: String,
+    /// Current (head) contents of the changed files that still exist.
+    source_files: Vec<FileEntry>,
+    /// Everything above packed into one review context block.
+    packed: String,
+}
+
+/// Appends the changed files from a `files` listing (PR files or compare API) and their
+/// patches as a unified diff.
+fn collect_changes(items: &[serde_json::Value], changed_files: &mut Vec<ChangedFile>, diff: &mut String) {
+    for item in items {
+        let path = item["filename"].as_str().unwrap_or_default().to_string();
+        let previous_path = item["previous_filename"].as_str().map(|s| s.to_string());
+        let old_path = previous_path.as_deref().unwrap_or(&path);
+        diff.push_str(&format!("diff --git a/{} b/{}\n--- a/{}\n+++ b/{}\n", old_path, path, old_path, path));
+        match item["patch"].as_str() {
+            Some(patch) => {
+                diff.push_str(patch);
+                diff.push('\n');
+            }
+            // GitHub omits patches for binary and very large diffs.
+            None => diff.push_str("(diff not available)\n"),
+        }
+        changed_files.push(ChangedFile {
+            path,
+            status: item["status"].as_str().unwrap_or_default().to_string(),
+            additions: item["additions"].as_u64().unwrap_or_default(),
+            deletions: item["deletions"].as_u64().unwrap_or_default(),
+            previous_path,
+        });
+    }
+}
+
+/// Fetches the contents at `sha` of the changed files that still exist (up to `max_files`,
+/// default 50), in the order they were listed.
+async fn fetch_touched_files(
+    gh: &github::GithubClient,
+    owner: &str,
+    repo: &str,
+    sha: &str,
+    changed_files: &[ChangedFile],
+    max_files: Option<usize>,
+) -> Vec<FileEntry> {
+    use futures_util::stream::{self, StreamExt};
+
+    let limit = max_files.unwrap_or(50).clamp(1, 300);
+    let to_fetch: Vec<String> = changed_files.iter().filter(|f| f.status != "removed").take(limit).map(|f| f.path.clone()).collect();
+    let mut files: Vec<FileEntry> = stream::iter(to_fetch)
+        .map(|path| async move { gh.fetch_file_content(owner, repo, &path, sha).await.map(|content| FileEntry::new(path, content)) })
+        .buffer_unordered(DEFAULT_FETCH_CONCURRENCY)
+        .filter_map(|entry| async move { entry })
+        .collect()
+        .await;
+    let order: Vec<&str> = changed_files.iter().map(|f| f.path.as_str()).collect();
+    files.sort_by_key(|f| order.iter().position(|p| *p == f.path));
+    files
+}
+
+fn pack_pr_review(pr: &PullRequestData) -> String {
+    let mut out = format!("# Pull request #{}: {}\n\nAuthor: {}\nMerging {} into {}\n\n", pr.number, pr.title, pr.author, pr.head_ref, pr.base_ref);
+    if !pr.description.trim().is_empty() {
+        out.push_str(&format!("## Description\n\n{}\n\n", pr.description.trim()));
+    }
+    out.push_str("## Changed files\n\n");
+    for f in &pr.changed_files {
+        out.push_str(&format!("- {} ({}, +{} -{})\n", f.path, f.status, f.additions, f.deletions));
+    }
+    out.push_str(&format!("\n## Diff\n\n```diff\n{}```\n\n## Changed files after the PR\n", pr.diff));
+    for f in &pr.source_files {
+        out.push_str(&format!("\n--- {} ---\n{}\n", f.path, f.content));
+    }
+    out
+}
+
+/// Fetches a pull request for a review prompt: title and description, the unified diff,
+/// and the head contents of the changed files (up to `max_files`, default 50).
+#[tauri::command]
+pub async fn fetch_github_pr(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    owner: String,
+    repo: String,
+    number: u64,
+    token: Option<String>,
+    max_files: Option<usize>,
+) -> Result<PullRequestData, AppError> {
+    state.policy.check_not_demo("GitHub access")?;
+    let gh = github::GithubClient::new(state.http_client.read().await.clone(), token.unwrap_or_default())
+        .with_cache(cache::open_cache(&app, &state, "github").ok());
+
+    log_status(&app, format!("Fetching pull request {}/{}#{}", owner, repo, number));
+    let pr = gh.get_json(&format!("/repos/{}/{}/pulls/{}", owner, repo, number)).await?;
+    let head_sha = pr["head"]["sha"].as_str().ok_or("Pull request has no head commit")?.to_string();
+
+    let mut changed_files = Vec::new();
+    let mut diff = String::new();
+    for page in 1..=MAX_PR_FILE_PAGES {
+        let json = gh.get_json(&format!("/repos/{}/{}/pulls/{}/files?per_page=100&page={}", owner, repo, number, page)).await?;
+        let items = json.as_array().cloned().unwrap_or_default();
+        collect_changes(&items, &mut changed_files, &mut diff);
+        if items.len() < 100 {
+            break;
+        }
+    }
+
+    // Head commits of fork PRs are also reachable through the base repository.
+    log_status(&app, format!("Fetching changed files at {}", &head_sha[..7.min(head_sha.len())]));
+    let source_files = fetch_touched_files(&gh, &owner, &repo, &head_sha, &changed_files, max_files).await;
+
+    let mut data = PullRequestData {
+        number,
+        title: pr["title"].as_str().unwrap_or_default().to_string(),
+        description: pr["body"].as_str().unwrap_or_default().to_string(),
+        author: pr["user"]["login"].as_str().unwrap_or_default().to_string(),
+        base_ref: pr["base"]["ref"].as_str().unwrap_or_default().to_string(),
+        head_ref: pr["head"]["ref"].as_str().unwrap_or_default().to_string(),
+        head_sha,
+        html_url: pr["html_url"].as_str().unwrap_or_default().to_string(),
+        changed_files,
+        diff,
+        source_files,
+        packed: String::new(),
+    };
+    data.packed = pack_pr_review(&data);
+    log_status(&app, format!("Pull request #{} ready: {} files changed", number, data.changed_files.len()));
+    Ok(data)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareCommit {
+    sha: String,
+    /// First line of the commit message.
+    message: String,
+    author: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareData {
+    base: String,
+    head: String,
+    base_sha: String,
+    head_sha: String,
+    /// `ahead`, `behind`, `diverged` or `identical`.
+    status: String,
+    ahead_by: u64,
+    behind_by: u64,
+    commits: Vec<CompareCommit>,
+    changed_files: Vec<ChangedFile>,
+    diff: String,
+    /// Head contents of the touched files.
+    source_files: Vec<FileEntry>,
+    /// Only the diff and the touched files, packed for a "what changed" prompt.
+    packed: String,
+}
+
+fn pack_compare(c: &CompareData) -> String {
+    let mut out = format!("# Changes from {} to {}\n\n{} commits, {} files changed\n\n## Commits\n\n", c.base, c.head, c.ahead_by, c.changed_files.len());
+    for commit in &c.commits {
+        out.push_str(&format!("- {} {} ({})\n", &commit.sha[..7.min(commit.sha.len())], commit.message, commit.author));
+    }
+    out.push_str(&format!("\n## Diff\n\n```diff\n{}```\n\n## Touched files at {}\n", c.diff, c.head));
+    for f in &c.source_files {
+        out.push_str(&format!("\n--- {} ---\n{}\n", f.path, f.content));
+    }
+    out
+}
+
+/// Compares two refs (tags, branches or SHAs) with the compare API and returns the
+/// commits, changed files, unified diff and the touched files at `head`, e.g. to explain
+/// what changed between v1.2 and v1.3.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn fetch_github_compare(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    owner: String,
+    repo: String,
+    base: String,
+    head: String,
+    token: Option<String>,
+    max_files: Option<usize>,
+) -> Result<CompareData, AppError> {
+    let (base, head) = (base.trim().to_string(), head.trim().to_string());
+    github::validate_ref(&base).map_err(AppError::InvalidInput)?;
+    github::validate_ref(&head).map_err(AppError::InvalidInput)?;
+    state.policy.check_not_demo("GitHub access")?;
+    let gh = github::GithubClient::new(state.http_client.read().await.clone(), token.unwrap_or_default())
+        .with_cache(cache::open_cache(&app, &state, "github").ok());
+
+    log_status(&app, format!("Comparing {}...{} in {}/{}", base, head, owner, repo));
+    let json = gh
+        .get_json(&format!("/repos/{}/{}/compare/{}...{}", owner, repo, urlencoding::encode(&base), urlencoding::encode(&head)))
+        .await?;
+
+    // The compare API lists at most 300 files and 250 commits.
+    let mut changed_files = Vec::new();
+    let mut diff = String::new();
+    collect_changes(json["files"].as_array().map(|a| a.as_slice()).unwrap_or_default(), &mut changed_files, &mut diff);
+    let commits = json["commits"]
+        .as_array()
+        .map(|list| {
+            list.iter()
+                .map(|c| CompareCommit {
+                    sha: c["sha"].as_str().unwrap_or_default().to_string(),
+                    message: c["commit"]["message"].as_str().unwrap_or_default().lines().next().unwrap_or_default().to_string(),
+                    author: c["commit"]["author"]["name"].as_str().unwrap_or_default().to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let head_sha = json["commits"]
+        .as_array()
+        .and_then(|c| c.last())
+        .and_then(|c| c["sha"].as_str())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| head.clone());
+
+    log_status(&app, format!("Fetching {} touched files", changed_files.len()));
+    let source_files = fetch_touched_files(&gh, &owner, &repo, &head_sha, &changed_files, max_files).await;
+
+    let mut data = CompareData {
+        base_sha: json["merge_base_commit"]["sha"].as_str().unwrap_or_default().to_string(),
+        head_sha,
+        status: json["status"].as_str().unwrap_or_default().to_string(),
+        ahead_by: json["ahead_by"].as_u64().unwrap_or_default(),
+        behind_by: json["behind_by"].as_u64().unwrap_or_default(),
+        base,
+        head,
+        commits,
+        changed_files,
+        diff,
+        source_files,
+        packed: String::new(),
+    };
+    data.packed = pack_compare(&data);
+    Ok(data)
+}
@@ -0,0 +1,94 @@
+use crate::error::AppError;
+use crate::{projects, scan_local_repository, usage, AppState, FileEntry};
+use serde::Serialize;
+use std::collections::HashSet;
+use tauri::{AppHandle, Emitter, State};
+
+/// Batches of scanned files, tagged with the operation ID.
+pub const BATCH_EVENT: &str = "scan://batch";
+/// Content per batch when the caller doesn't say; a file larger than this goes alone.
+const DEFAULT_BATCH_BYTES: usize = 1024 * 1024;
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanBatch {
+    operation_id: String,
+    /// 0-based; batches are emitted in order.
+    index: usize,
+    /// Number of batches of the scan.
+    total: usize,
+    files: Vec<FileEntry>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestEntry {
+    path: String,
+    bytes: usize,
+    /// Index of the batch that carried the content.
+    batch: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanManifest {
+    operation_id: String,
+    files: Vec<ManifestEntry>,
+    total_bytes: usize,
+    batches: usize,
+}
+
+/// Splits `files` into batches of about `max_bytes` of content each.
+fn split_batches(files: Vec<FileEntry>, max_bytes: usize) -> Vec<Vec<FileEntry>> {
+    let mut batches: Vec<Vec<FileEntry>> = Vec::new();
+    let mut size = 0;
+    for file in files {
+        if batches.is_empty() || (size + file.content.len() > max_bytes && size > 0) {
+            batches.push(Vec::new());
+            size = 0;
+        }
+        size += file.content.len();
+        batches.last_mut().unwrap().push(file);
+    }
+    batches
+}
+
+/// Scans like `scan_local_repository`, but sends the file contents as `scan://batch`
+/// events of about `batch_bytes` (default 1 MB) each instead of in the reply, so a big
+/// repository doesn't go through one IPC message. Listen for the events (filtering on
+/// `operation_id`) before calling; the reply is only the manifest, once every batch was
+/// emitted. Contents can also be read later with `read_project_files`.
+#[tauri::command]
+pub async fn scan_local_repository_streamed(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    subpath: Option<String>,
+    operation_id: Option<String>,
+    batch_bytes: Option<usize>,
+) -> Result<ScanManifest, AppError> {
+    let operation_id = operation_id.filter(|id| !id.trim().is_empty()).unwrap_or_else(|| format!("scan-{}", usage::now_ms()));
+    let files = scan_local_repository(app.clone(), state, path, subpath, Some(operation_id.clone())).await?;
+    let batches = split_batches(files, batch_bytes.unwrap_or(DEFAULT_BATCH_BYTES).max(1));
+    let total = batches.len();
+    let mut manifest = ScanManifest { operation_id: operation_id.clone(), files: Vec::new(), total_bytes: 0, batches: total };
+    for (index, files) in batches.into_iter().enumerate() {
+        for file in &files {
+            manifest.total_bytes += file.content.len();
+            manifest.files.push(ManifestEntry { path: file.path.clone(), bytes: file.content.len(), batch: index });
+        }
+        let _ = app.emit(BATCH_EVENT, ScanBatch { operation_id: operation_id.clone(), index, total, files });
+        // Lets the event loop deliver the batch before the next one is serialized.
+        tokio::task::yield_now().await;
+    }
+    Ok(manifest)
+}
+
+/// Contents of `paths` from the last load of `project` (the scanned directory or
+/// `owner/repo`), for reading files of a streamed scan on demand. Paths that weren't
+/// loaded are left out.
+#[tauri::command]
+pub fn read_project_files(app: AppHandle, state: State<'_, AppState>, project: String, paths: Vec<String>) -> Result<Vec<FileEntry>, AppError> {
+    let wanted: HashSet<&str> = paths.iter().map(String::as_str).collect();
+    Ok(projects::loaded_files(&app, &state, &project)?.into_iter().filter(|f| wanted.contains(f.path.as_str())).collect())
+}
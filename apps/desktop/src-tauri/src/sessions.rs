@@ -0,0 +1,211 @@
+use crate::conversation::{self, Turn};
+use crate::error::AppError;
+use crate::usage::{self, ModelUsage};
+use crate::{gemini, log_status, ollama, providers, retry, AppState};
+use isahc::prelude::*;
+use repo_prompt_core::continuation;
+use repo_prompt_core::usage::Usage;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+const DEFAULT_GEMINI_MODEL: &str = "gemini-3-flash-preview";
+const DEFAULT_OPENAI_URL: &str = "https://api.openai.com/v1";
+
+/// A conversation kept by the backend: the packed repository goes in once, at creation,
+/// and every message is sent with the history before it.
+#[derive(Clone)]
+struct ChatSession {
+    provider: String,
+    model: String,
+    /// Ollama server or OpenAI-compatible base URL.
+    url: String,
+    /// Bearer token for OpenAI-compatible servers; never sent back to the frontend.
+    api_key: String,
+    system_prompt: String,
+    context: String,
+    context_window: Option<u64>,
+    turns: Vec<Turn>,
+    /// Unix milliseconds.
+    created_at: u64,
+    usage: ModelUsage,
+}
+
+/// Open chat sessions by ID. They live as long as the app does.
+#[derive(Default)]
+pub struct SessionStore {
+    sessions: Mutex<HashMap<String, ChatSession>>,
+    counter: AtomicU64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    session_id: String,
+    provider: String,
+    model: String,
+    context_window: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionReply {
+    reply: String,
+    /// Turns in the session after this exchange.
+    turns: usize,
+    /// Oldest turns left out of this request so it fit the model's window; they stay in
+    /// the history.
+    dropped_turns: usize,
+    context_truncated: bool,
+    /// Tokens this message took, when the provider reported them.
+    usage: Option<Usage>,
+}
+
+impl SessionStore {
+    fn get(&self, id: &str) -> Result<ChatSession, AppError> {
+        self.sessions.lock().unwrap().get(id).cloned().ok_or_else(|| AppError::NotFound(format!("No chat session {}", id)))
+    }
+
+    /// When the session started and the tokens its messages took so far.
+    pub fn usage(&self, id: &str) -> Result<(u64, ModelUsage), AppError> {
+        let session = self.get(id)?;
+        Ok((session.created_at, session.usage))
+    }
+}
+
+/// POSTs `messages` to an OpenAI-compatible `/chat/completions` and returns the reply
+/// with the tokens it took.
+async fn openai_chat(state: &AppState, session: &ChatSession, messages: serde_json::Value) -> Result<(String, Option<Usage>), AppError> {
+    let body = serde_json::json!({ "model": session.model, "messages": messages }).to_string();
+    let url = format!("{}/chat/completions", session.url.trim_end_matches('/'));
+    let make = || {
+        let mut request = isahc::Request::builder().method("POST").uri(&url).header("Content-Type", "application/json");
+        if !session.api_key.is_empty() {
+            request = request.header("Authorization", format!("Bearer {}", session.api_key));
+        }
+        request.body(body.clone())
+    };
+    let client = state.http_client.read().await.clone();
+    let _span = state.trace.span("llm", "openai_chat").attr("model", &session.model);
+    let mut response = retry::send(&client, "OpenAI-compatible server", true, make).await?;
+    let status = response.status().as_u16();
+    let text = response.text().await?;
+    if !(200..300).contains(&status) {
+        return Err(AppError::from_status(status, format!("Chat request failed ({}): {}", status, text), None));
+    }
+    let data: serde_json::Value = serde_json::from_str(&text).map_err(|e| AppError::Provider { status: None, message: e.to_string() })?;
+    let usage = Usage::from_openai(&data);
+    state.usage.record(&session.provider, &session.model, usage.as_ref());
+    let reply = data["choices"][0]["message"]["content"]
+        .as_str()
+        .ok_or_else(|| AppError::Provider { status: None, message: "The reply had no message content".to_string() })?;
+    Ok((reply.to_string(), usage))
+}
+
+/// Starts a chat session about a packed repository. `provider` is `gemini`, `ollama`
+/// (at `url` or the configured server) or `openai`/`custom` (at `url`, by default
+/// api.openai.com, with `api_key`). `context` (e.g. the packed repository) and
+/// `system_prompt` are kept with the session and sent with every message, so follow-up
+/// questions need only the question. `context_window` overrides the model's known window.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn create_session(
+    state: State<'_, AppState>,
+    provider: String,
+    model: Option<String>,
+    url: Option<String>,
+    api_key: Option<String>,
+    system_prompt: Option<String>,
+    context: Option<String>,
+    context_window: Option<u64>,
+) -> Result<SessionInfo, AppError> {
+    let provider = provider.trim().to_lowercase();
+    state.policy.check_provider(&provider)?;
+    let model = model.filter(|m| !m.trim().is_empty());
+    let (model, url) = match provider.as_str() {
+        "gemini" => (model.unwrap_or_else(|| DEFAULT_GEMINI_MODEL.to_string()), String::new()),
+        "ollama" => {
+            let url = match url.filter(|u| !u.trim().is_empty()) {
+                Some(u) => ollama::normalize_url(&u),
+                None => state.ollama_url.read().await.clone().unwrap_or_else(|| ollama::DEFAULT_OLLAMA_URL.to_string()),
+            };
+            (model.ok_or_else(|| AppError::InvalidInput("Choose an Ollama model first".to_string()))?, url)
+        }
+        "openai" | "custom" => {
+            let url = url.filter(|u| !u.trim().is_empty()).unwrap_or_else(|| DEFAULT_OPENAI_URL.to_string());
+            (model.ok_or_else(|| AppError::InvalidInput("Choose a model first".to_string()))?, url)
+        }
+        other => return Err(AppError::InvalidInput(format!("Chat sessions are not supported for provider: {}", other))),
+    };
+    let context_window = context_window.or_else(|| providers::context_window(&provider, Some(&model)));
+    let session = ChatSession {
+        provider: provider.clone(),
+        model: model.clone(),
+        url,
+        api_key: api_key.unwrap_or_default(),
+        system_prompt: state.policy.redact(system_prompt.as_deref().unwrap_or_default()),
+        context: state.policy.redact(context.as_deref().unwrap_or_default()),
+        context_window,
+        turns: Vec::new(),
+        created_at: usage::now_ms(),
+        usage: ModelUsage::new(&provider, &model),
+    };
+    let session_id = format!("session-{}", state.sessions.counter.fetch_add(1, Ordering::Relaxed) + 1);
+    state.sessions.sessions.lock().unwrap().insert(session_id.clone(), session);
+    Ok(SessionInfo { session_id, provider, model, context_window })
+}
+
+/// Sends `content` as the next user message of a session and returns the reply. The
+/// history is replayed in the provider's format, dropping its oldest turns from the
+/// request when the model's window is too small. The exchange is only recorded once the
+/// reply arrives, so a failed message can simply be sent again.
+#[tauri::command]
+pub async fn send_message(app: AppHandle, state: State<'_, AppState>, session_id: String, content: String) -> Result<SessionReply, AppError> {
+    if content.trim().is_empty() {
+        return Err(AppError::InvalidInput("The message is empty".to_string()));
+    }
+    let session = state.sessions.get(&session_id)?;
+    state.policy.check_provider(&session.provider)?;
+    let user_turn = Turn { role: "user".to_string(), content: state.policy.redact(&content) };
+
+    let mut context = session.context.clone();
+    let mut turns = session.turns.clone();
+    turns.push(user_turn.clone());
+    let (payload, dropped_turns, context_truncated) =
+        conversation::render(&session.provider, &session.system_prompt, &mut context, &mut turns, session.context_window)?;
+
+    log_status(&app, format!("Sending message {} of {} to {} ({})", session.turns.len() / 2 + 1, session_id, session.provider, session.model));
+    let (reply, usage) = match session.provider.as_str() {
+        "gemini" => {
+            let reply = gemini::generate_complete(&state, &session.model, &payload, continuation::DEFAULT_MAX_CONTINUATIONS).await?;
+            (reply.text, reply.usage)
+        }
+        "ollama" => {
+            let reply = ollama::chat_text(&state, &session.url, &session.model, payload["messages"].clone(), None).await?;
+            (reply.text, reply.usage)
+        }
+        _ => openai_chat(&state, &session, payload["messages"].clone()).await?,
+    };
+
+    let mut sessions = state.sessions.sessions.lock().unwrap();
+    // Deleted while the reply was on its way.
+    let stored = sessions.get_mut(&session_id).ok_or_else(|| AppError::NotFound(format!("No chat session {}", session_id)))?;
+    stored.turns.push(user_turn);
+    stored.turns.push(Turn { role: "assistant".to_string(), content: reply.clone() });
+    stored.usage.add(usage.as_ref());
+    Ok(SessionReply { reply, turns: stored.turns.len(), dropped_turns, context_truncated, usage })
+}
+
+/// The messages of a session so far, oldest first.
+#[tauri::command]
+pub fn get_history(state: State<'_, AppState>, session_id: String) -> Result<Vec<Turn>, AppError> {
+    Ok(state.sessions.get(&session_id)?.turns)
+}
+
+/// Ends a session and forgets its history. Returns whether it existed.
+#[tauri::command]
+pub fn delete_session(state: State<'_, AppState>, session_id: String) -> bool {
+    state.sessions.sessions.lock().unwrap().remove(&session_id).is_some()
+}
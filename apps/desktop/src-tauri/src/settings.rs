@@ -0,0 +1,305 @@
+use crate::error::AppError;
+use crate::{embedcache, log_status, network, ollama, repocache, retry, set_app_config, AppState};
+use repo_prompt_core::ranking::ScoringRules;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Manager, State};
+
+const SETTINGS_FILE: &str = "settings.json";
+/// Bumped whenever the layout changes; add the upgrade step to [`MIGRATIONS`].
+const CURRENT_VERSION: u32 = 1;
+/// Step `i` upgrades a version `i` document to version `i + 1`.
+const MIGRATIONS: &[fn(&mut Value)] = &[from_flat];
+const OUTPUT_FORMATS: &[&str] = &["markdown", "xml", "plain"];
+
+/// Preferences kept by the backend. API keys are not part of it: they stay in the
+/// environment or are entered per session.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub version: u32,
+    pub provider: ProviderSettings,
+    pub ollama: OllamaSettings,
+    pub network: NetworkSettings,
+    pub scan: ScanSettings,
+    pub output: OutputSettings,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProviderSettings {
+    /// `gemini` or `ollama`.
+    pub default_provider: String,
+    pub gemini_model: Option<String>,
+    pub ollama_model: Option<String>,
+    pub temperature: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OllamaSettings {
+    pub url: Option<String>,
+    /// Start the local server when the app opens.
+    pub auto_start: bool,
+    pub auto_restart: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NetworkSettings {
+    /// `host:port` or a `http://`, `https://` or `socks5://` URL, optionally with
+    /// `user:password@` for proxies that require a login.
+    pub proxy: Option<String>,
+    /// PEM bundle to trust instead of the system's CA store (corporate TLS inspection).
+    pub ca_certificate_file: Option<String>,
+    /// Turns off TLS certificate verification for every request. Unsafe; prefer
+    /// `ca_certificate_file`.
+    pub danger_accept_invalid_certs: bool,
+    /// How long fetched repositories are reused before being fetched again.
+    pub repo_cache_ttl_secs: u64,
+    /// Tries per request, the first included, before a transient failure is reported.
+    pub max_attempts: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScanSettings {
+    pub include_patterns: Vec<String>,
+    /// Paths never scanned or fetched, in gitignore syntax. A repository's own
+    /// `.repopromptignore` is applied after them and can include paths again with `!`.
+    pub exclude_patterns: Vec<String>,
+    pub max_file_size_kb: Option<u64>,
+    pub respect_gitignore: bool,
+    /// More dependency manifests to include, by file name (`BUILD.bazel`) or extension
+    /// (`*.nimble`), besides the built-in ones.
+    pub manifest_files: Vec<String>,
+    /// How source files are ranked when only some are fetched or fit the budget.
+    pub scoring: ScoringRules,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OutputSettings {
+    /// `markdown`, `xml` or `plain`.
+    pub format: String,
+    pub include_tree: bool,
+    pub token_budget: Option<usize>,
+    pub cache_compression_level: Option<i32>,
+    /// Size limit of the embedding cache in megabytes; least recently used vectors go first.
+    pub embedding_cache_max_mb: Option<u64>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            version: CURRENT_VERSION,
+            provider: ProviderSettings::default(),
+            ollama: OllamaSettings::default(),
+            network: NetworkSettings::default(),
+            scan: ScanSettings::default(),
+            output: OutputSettings::default(),
+        }
+    }
+}
+
+impl Default for ProviderSettings {
+    fn default() -> Self {
+        ProviderSettings { default_provider: "gemini".to_string(), gemini_model: None, ollama_model: None, temperature: None }
+    }
+}
+
+impl Default for OllamaSettings {
+    fn default() -> Self {
+        OllamaSettings { url: None, auto_start: false, auto_restart: true }
+    }
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        NetworkSettings {
+            proxy: None,
+            ca_certificate_file: None,
+            danger_accept_invalid_certs: false,
+            repo_cache_ttl_secs: repocache::DEFAULT_TTL_SECS,
+            max_attempts: retry::DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+}
+
+impl Default for ScanSettings {
+    fn default() -> Self {
+        ScanSettings { include_patterns: Vec::new(), exclude_patterns: Vec::new(), max_file_size_kb: None, respect_gitignore: true, manifest_files: Vec::new(), scoring: ScoringRules::default() }
+    }
+}
+
+impl Default for OutputSettings {
+    fn default() -> Self {
+        OutputSettings { format: "markdown".to_string(), include_tree: true, token_budget: None, cache_compression_level: None, embedding_cache_max_mb: None }
+    }
+}
+
+/// Version 0: the flat object the webview kept in local storage and may hand over
+/// on first launch (`provider`, `ollamaUrl`, `proxy`, `outputFormat`, ...).
+fn from_flat(doc: &mut Value) {
+    let Some(flat) = doc.as_object_mut().map(std::mem::take) else { return };
+    let get = |key: &str| flat.get(key).cloned().unwrap_or(Value::Null);
+    *doc = serde_json::json!({
+        "provider": {
+            "defaultProvider": get("provider"),
+            "geminiModel": get("geminiModel"),
+            "ollamaModel": get("ollamaModel"),
+            "temperature": get("temperature"),
+        },
+        "ollama": { "url": get("ollamaUrl"), "autoStart": get("autoStartOllama") },
+        "network": { "proxy": get("proxy") },
+        "scan": { "includePatterns": get("includePatterns"), "excludePatterns": get("excludePatterns") },
+        "output": { "format": get("outputFormat") },
+    });
+    // Keys missing from the old object come through as null; the defaults fill them in.
+    strip_nulls(doc);
+}
+
+fn strip_nulls(value: &mut Value) {
+    if let Some(map) = value.as_object_mut() {
+        map.retain(|_, v| !v.is_null());
+        map.values_mut().for_each(strip_nulls);
+    }
+}
+
+/// Upgrades a stored document to [`CURRENT_VERSION`]. Documents from a newer version
+/// are read as they are; unknown fields are ignored.
+fn migrate(mut doc: Value) -> Value {
+    let version = doc["version"].as_u64().unwrap_or(0) as usize;
+    for step in MIGRATIONS.iter().skip(version) {
+        step(&mut doc);
+    }
+    if let Some(map) = doc.as_object_mut() {
+        map.insert("version".to_string(), CURRENT_VERSION.into());
+    }
+    doc
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| format!("Failed to resolve config dir: {}", e))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    Ok(dir.join(SETTINGS_FILE))
+}
+
+fn read(app: &AppHandle) -> Result<Settings, String> {
+    let text = match fs::read_to_string(settings_path(app)?) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Settings::default()),
+        Err(e) => return Err(format!("Failed to read settings: {}", e)),
+    };
+    let doc: Value = serde_json::from_str(&text).map_err(|e| format!("Settings file is corrupt: {}", e))?;
+    serde_json::from_value(migrate(doc)).map_err(|e| format!("Settings file is corrupt: {}", e))
+}
+
+/// Writes to a temporary file first so a crash mid-write can't lose the settings.
+fn write(app: &AppHandle, settings: &Settings) -> Result<(), String> {
+    let path = settings_path(app)?;
+    let tmp = path.with_extension("json.tmp");
+    let text = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(&tmp, text).map_err(|e| format!("Failed to save settings: {}", e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to save settings: {}", e))
+}
+
+fn validate(mut settings: Settings) -> Result<Settings, String> {
+    settings.version = CURRENT_VERSION;
+    settings.provider.default_provider = settings.provider.default_provider.trim().to_lowercase();
+    settings.output.format = settings.output.format.trim().to_lowercase();
+    if !OUTPUT_FORMATS.contains(&settings.output.format.as_str()) {
+        return Err(format!("Unknown output format: {} (expected one of {})", settings.output.format, OUTPUT_FORMATS.join(", ")));
+    }
+    if !(1..=retry::MAX_ATTEMPTS_LIMIT).contains(&settings.network.max_attempts) {
+        return Err(format!("Attempts per request must be between 1 and {}, got {}", retry::MAX_ATTEMPTS_LIMIT, settings.network.max_attempts));
+    }
+    if let Some(t) = settings.provider.temperature.filter(|t| !(0.0..=2.0).contains(t)) {
+        return Err(format!("Temperature must be between 0 and 2, got {}", t));
+    }
+    settings.scan.manifest_files = settings.scan.manifest_files.iter().map(|f| f.trim().to_string()).filter(|f| !f.is_empty()).collect();
+    // A name, or `*` and a suffix; a bare `*` would make every file a manifest.
+    let invalid = |f: &&String| {
+        let rest = f.strip_prefix('*').unwrap_or(f);
+        f.contains('/') || rest.is_empty() || rest.contains('*')
+    };
+    if let Some(f) = settings.scan.manifest_files.iter().find(invalid) {
+        return Err(format!("Manifest files are matched by file name or *.extension, got {}", f));
+    }
+    settings.scan.scoring.validate()?;
+    settings.ollama.url = settings.ollama.url.filter(|u| !u.trim().is_empty()).map(|u| ollama::normalize_url(&u));
+    settings.network.proxy = settings.network.proxy.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    if let Some(proxy) = &settings.network.proxy {
+        network::Proxy::parse(proxy)?;
+    }
+    settings.network.ca_certificate_file = settings.network.ca_certificate_file.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    if let Some(path) = &settings.network.ca_certificate_file {
+        network::check_ca_file(path)?;
+    }
+    Ok(settings)
+}
+
+/// Hands the backend-relevant settings to the running app. The network options and
+/// compression level go through `set_app_config`, which demo mode refuses, so they're
+/// skipped there.
+async fn apply(state: State<'_, AppState>, settings: &Settings) -> Result<(), String> {
+    if let Some(url) = &settings.ollama.url {
+        *state.ollama_url.write().await = Some(url.clone());
+    }
+    state.ollama_auto_restart.store(settings.ollama.auto_restart, Ordering::SeqCst);
+    state.repo_cache_ttl_secs.store(settings.network.repo_cache_ttl_secs, Ordering::Relaxed);
+    let embedding_cache_max_bytes = settings.output.embedding_cache_max_mb.map_or(embedcache::DEFAULT_MAX_BYTES, |mb| mb * 1024 * 1024);
+    state.embeddings.max_bytes.store(embedding_cache_max_bytes, Ordering::Relaxed);
+    *state.manifest_files.write().await = settings.scan.manifest_files.clone();
+    *state.ignore_patterns.write().await = settings.scan.exclude_patterns.clone();
+    *state.scoring.write().await = settings.scan.scoring.clone();
+    retry::set_max_attempts(settings.network.max_attempts);
+    if !state.policy.is_demo() {
+        let network = &settings.network;
+        set_app_config(
+            state,
+            None,
+            Some(network.proxy.clone().unwrap_or_default()),
+            settings.output.cache_compression_level,
+            Some(network.ca_certificate_file.clone().unwrap_or_default()),
+            Some(network.danger_accept_invalid_certs),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Loads the saved settings at startup and applies them.
+pub async fn restore(app: AppHandle) {
+    let result = match read(&app) {
+        Ok(settings) => apply(app.state::<AppState>(), &settings).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        log_status(&app, format!("Saved settings were not applied: {}", e));
+    }
+}
+
+/// Saved settings, upgraded to the current layout; defaults when none were saved yet.
+#[tauri::command]
+pub fn load_settings(app: AppHandle) -> Result<Settings, AppError> {
+    Ok(read(&app)?)
+}
+
+/// Validates, stores and applies the settings, returning them as saved. Older layouts
+/// (including the webview's flat object, which has no `version`) are upgraded first.
+#[tauri::command]
+pub async fn save_settings(app: AppHandle, state: State<'_, AppState>, settings: Value) -> Result<Settings, AppError> {
+    let settings = serde_json::from_value(migrate(settings)).map_err(|e| AppError::InvalidInput(format!("Invalid settings: {}", e)))?;
+    let settings = validate(settings).map_err(AppError::InvalidInput)?;
+    write(&app, &settings)?;
+    apply(state, &settings).await?;
+    log_status(&app, "Settings saved");
+    if settings.network.danger_accept_invalid_certs {
+        log_status(&app, "Warning: TLS certificate verification is turned off; connections can be intercepted");
+    }
+    Ok(settings)
+}
@@ -0,0 +1,244 @@
+use crate::error::AppError;
+use crate::FileEntry;
+use repo_prompt_core::tokens::PackStats;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("rs", "Rust"),
+    ("ts", "TypeScript"), ("tsx", "TypeScript"), ("mts", "TypeScript"), ("cts", "TypeScript"),
+    ("js", "JavaScript"), ("jsx", "JavaScript"), ("mjs", "JavaScript"), ("cjs", "JavaScript"),
+    ("py", "Python"), ("pyi", "Python"),
+    ("go", "Go"),
+    ("java", "Java"), ("kt", "Kotlin"), ("kts", "Kotlin"), ("scala", "Scala"),
+    ("c", "C"), ("h", "C"),
+    ("cpp", "C++"), ("cc", "C++"), ("cxx", "C++"), ("hpp", "C++"), ("hh", "C++"), ("hxx", "C++"),
+    ("cs", "C#"), ("fs", "F#"),
+    ("swift", "Swift"), ("m", "Objective-C"), ("mm", "Objective-C"),
+    ("rb", "Ruby"), ("php", "PHP"), ("pl", "Perl"), ("lua", "Lua"),
+    ("ex", "Elixir"), ("exs", "Elixir"), ("erl", "Erlang"), ("hs", "Haskell"), ("clj", "Clojure"),
+    ("dart", "Dart"), ("zig", "Zig"), ("nim", "Nim"), ("r", "R"), ("jl", "Julia"),
+    ("sh", "Shell"), ("bash", "Shell"), ("zsh", "Shell"), ("fish", "Shell"), ("ps1", "PowerShell"),
+    ("sql", "SQL"),
+    ("html", "HTML"), ("htm", "HTML"), ("css", "CSS"), ("scss", "SCSS"), ("sass", "SCSS"), ("less", "Less"),
+    ("vue", "Vue"), ("svelte", "Svelte"),
+    ("md", "Markdown"), ("mdx", "Markdown"), ("rst", "reStructuredText"),
+    ("json", "JSON"), ("yaml", "YAML"), ("yml", "YAML"), ("toml", "TOML"), ("xml", "XML"),
+    ("proto", "Protocol Buffers"), ("graphql", "GraphQL"), ("tf", "HCL"),
+];
+
+const FILE_NAMES: &[(&str, &str)] = &[
+    ("dockerfile", "Dockerfile"),
+    ("makefile", "Makefile"),
+    ("gnumakefile", "Makefile"),
+    ("cmakelists.txt", "CMake"),
+    ("rakefile", "Ruby"),
+    ("gemfile", "Ruby"),
+    ("justfile", "Just"),
+];
+
+const INTERPRETERS: &[(&str, &str)] = &[
+    ("python", "Python"),
+    ("node", "JavaScript"),
+    ("deno", "TypeScript"),
+    ("bash", "Shell"),
+    ("sh", "Shell"),
+    ("zsh", "Shell"),
+    ("ruby", "Ruby"),
+    ("perl", "Perl"),
+    ("php", "PHP"),
+    ("lua", "Lua"),
+];
+
+fn from_shebang(content: &str) -> Option<&'static str> {
+    let first = content.lines().next()?.strip_prefix("#!")?;
+    let mut parts = first.split_whitespace();
+    let mut program = parts.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = parts.find(|p| !p.starts_with('-'))?;
+    }
+    INTERPRETERS
+        .iter()
+        .find(|(name, _)| program.starts_with(name))
+        .map(|(_, lang)| *lang)
+}
+
+/// Classifies a file by extension, well-known file name, or shebang line.
+pub fn detect_language(path: &str, content: &str) -> Option<&'static str> {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path).to_lowercase();
+    if let Some((_, lang)) = FILE_NAMES.iter().find(|(n, _)| *n == name) {
+        return Some(lang);
+    }
+    if name.starts_with("dockerfile") {
+        return Some("Dockerfile");
+    }
+    if let Some((_, ext)) = name.rsplit_once('.') {
+        if let Some((_, lang)) = EXTENSIONS.iter().find(|(e, _)| *e == ext) {
+            return Some(lang);
+        }
+    }
+    from_shebang(content)
+}
+
+/// Directories whose contents are third-party or build output, as linguist treats them.
+const VENDORED_DIRS: &[&str] = &[
+    "node_modules/", "vendor/", "vendors/", "third_party/", "third-party/", "thirdparty/", "external/",
+    "bower_components/", "dist/", "build/", "out/", "target/", ".venv/", "venv/", "site-packages/", "Pods/",
+];
+
+/// Data and prose formats that linguist leaves out of the language bar.
+const NON_PROGRAMMING: &[&str] = &["Markdown", "reStructuredText", "JSON", "YAML", "TOML", "XML"];
+
+/// Whether a file is vendored or generated and should not count towards the breakdown.
+pub fn is_vendored_or_generated(path: &str, content: &str) -> bool {
+    let normalized = format!("/{}", path.replace('\\', "/"));
+    if VENDORED_DIRS.iter().any(|d| normalized.contains(&format!("/{}", d))) {
+        return true;
+    }
+    let name = normalized.rsplit('/').next().unwrap_or_default().to_lowercase();
+    if name.ends_with(".min.js") || name.ends_with(".min.css") || name.ends_with(".lock") || name.ends_with(".map")
+        || name == "package-lock.json" || name == "pnpm-lock.yaml" || name.ends_with(".pb.go") || name.ends_with("_pb2.py")
+    {
+        return true;
+    }
+    content
+        .lines()
+        .take(5)
+        .any(|l| l.contains("@generated") || l.contains("DO NOT EDIT") || l.contains("Code generated by") || l.contains("auto-generated"))
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageShare {
+    pub language: String,
+    pub bytes: u64,
+    /// Share of all counted bytes, 0-100.
+    pub percent: f64,
+}
+
+/// Linguist-style language bar: percent by bytes, vendored/generated files and data
+/// formats excluded.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageBreakdown {
+    pub languages: Vec<LanguageShare>,
+    /// e.g. "Languages: Rust 62.1%, TypeScript 30.4%, Other 7.5%", for the prompt header.
+    pub summary: String,
+}
+
+/// Builds the breakdown from per-language byte counts (e.g. GitHub's `/languages`).
+pub fn breakdown_from_bytes(bytes: HashMap<String, u64>) -> LanguageBreakdown {
+    let total: u64 = bytes.values().sum();
+    let mut languages: Vec<LanguageShare> = bytes
+        .into_iter()
+        .filter(|(_, b)| *b > 0)
+        .map(|(language, bytes)| LanguageShare { percent: bytes as f64 * 100.0 / total as f64, language, bytes })
+        .collect();
+    languages.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.language.cmp(&b.language)));
+
+    let mut parts: Vec<String> = languages.iter().take(5).map(|l| format!("{} {:.1}%", l.language, l.percent)).collect();
+    let rest: f64 = languages.iter().skip(5).map(|l| l.percent).sum();
+    if rest > 0.0 {
+        parts.push(format!("Other {:.1}%", rest));
+    }
+    let summary = if parts.is_empty() { String::new() } else { format!("Languages: {}", parts.join(", ")) };
+    LanguageBreakdown { languages, summary }
+}
+
+/// Length of the directory prefix shared by all paths, so that absolute paths from a local
+/// scan (e.g. `/home/me/build/project/...`) are judged relative to the project root.
+fn common_dir_len(files: &[FileEntry]) -> usize {
+    let Some(first) = files.first() else { return 0 };
+    let mut len = first.path.rfind(['/', '\\']).map(|i| i + 1).unwrap_or(0);
+    for file in &files[1..] {
+        while len > 0 && file.path.get(..len) != Some(&first.path[..len]) {
+            len = first.path[..len - 1].rfind(['/', '\\']).map(|i| i + 1).unwrap_or(0);
+        }
+    }
+    len
+}
+
+pub fn language_breakdown(files: &[FileEntry]) -> LanguageBreakdown {
+    let root = common_dir_len(files);
+    let mut bytes: HashMap<String, u64> = HashMap::new();
+    for file in files.iter().filter(|f| !is_vendored_or_generated(&f.path[root..], &f.content)) {
+        match detect_language(&file.path, &file.content) {
+            Some(lang) if !NON_PROGRAMMING.contains(&lang) => *bytes.entry(lang.to_string()).or_default() += file.content.len() as u64,
+            _ => {}
+        }
+    }
+    breakdown_from_bytes(bytes)
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageStats {
+    pub language: String,
+    pub files: usize,
+    pub lines: usize,
+    pub bytes: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoStats {
+    pub languages: Vec<LanguageStats>,
+    pub total_files: usize,
+    pub total_lines: usize,
+    pub total_bytes: u64,
+    pub total_words: usize,
+    pub total_tokens: usize,
+    /// The largest files by tokens.
+    pub largest: Vec<String>,
+    /// One-line summary suitable for prepending to a prompt.
+    pub summary: String,
+    pub breakdown: LanguageBreakdown,
+}
+
+pub fn compute_stats(files: &[FileEntry]) -> RepoStats {
+    let mut by_lang: HashMap<&'static str, LanguageStats> = HashMap::new();
+    for file in files {
+        let lang = detect_language(&file.path, &file.content).unwrap_or("Other");
+        let entry = by_lang.entry(lang).or_insert_with(|| LanguageStats {
+            language: lang.to_string(),
+            files: 0,
+            lines: 0,
+            bytes: 0,
+        });
+        entry.files += 1;
+        entry.lines += file.content.lines().count();
+        entry.bytes += file.content.len() as u64;
+    }
+
+    let mut languages: Vec<LanguageStats> = by_lang.into_values().collect();
+    languages.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.language.cmp(&b.language)));
+
+    let total_files = languages.iter().map(|l| l.files).sum();
+    let total_lines = languages.iter().map(|l| l.lines).sum();
+    let total_bytes: u64 = languages.iter().map(|l| l.bytes).sum();
+
+    let summary = languages
+        .iter()
+        .map(|l| format!("{} ({} files, {} lines)", l.language, l.files, l.lines))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let pack = PackStats::of(files);
+    RepoStats {
+        breakdown: language_breakdown(files),
+        total_words: pack.words,
+        total_tokens: pack.tokens,
+        largest: pack.largest,
+        summary: format!("{} files, {} lines: {}", total_files, total_lines, summary),
+        languages,
+        total_files,
+        total_lines,
+        total_bytes,
+    }
+}
+
+/// Per-language file, line, and byte totals for files from a local scan or GitHub fetch.
+#[tauri::command]
+pub async fn repo_stats(files: Vec<FileEntry>) -> Result<RepoStats, AppError> {
+    Ok(tokio::task::spawn_blocking(move || compute_stats(&files)).await?)
+}
@@ -0,0 +1,38 @@
+use crate::AppState;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Event carrying one human-readable status line per emission.
+pub const STATUS_EVENT: &str = "status://log";
+const MAX_LINES: usize = 500;
+
+/// Recent status lines, kept so a screen reader view opened mid-operation can catch up.
+#[derive(Default)]
+pub struct StatusLog {
+    lines: Mutex<VecDeque<String>>,
+}
+
+impl StatusLog {
+    fn push(&self, line: String) {
+        let mut lines = self.lines.lock().unwrap();
+        if lines.len() == MAX_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+}
+
+/// Records a plain-text status update and emits it on [`STATUS_EVENT`].
+pub fn log_status(app: &AppHandle, message: impl Into<String>) {
+    let message = message.into();
+    if let Some(state) = app.try_state::<AppState>() {
+        state.status_log.push(message.clone());
+    }
+    let _ = app.emit(STATUS_EVENT, message);
+}
+
+#[tauri::command]
+pub fn get_status_log(state: State<'_, AppState>) -> Vec<String> {
+    state.status_log.lines.lock().unwrap().iter().cloned().collect()
+}
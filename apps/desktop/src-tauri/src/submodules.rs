@@ -0,0 +1,133 @@
+use crate::{fetch_files, github, select_source_files, FileEntry};
+use repo_prompt_core::ranking::ScoringRules;
+use serde::{Deserialize, Serialize};
+
+/// A submodule declared in `.gitmodules`, with the commit the parent tree pins it to.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmoduleInfo {
+    path: String,
+    url: String,
+    /// Pinned commit from the parent's tree; `None` when it couldn't be resolved.
+    commit_sha: Option<String>,
+    /// `owner/repo` when the submodule lives on GitHub and can be fetched.
+    github_repo: Option<String>,
+    /// Number of files fetched from the submodule.
+    pub files_fetched: usize,
+    error: Option<String>,
+}
+
+/// Reads `path` and `url` of every `[submodule "..."]` section.
+fn parse_gitmodules(content: &str) -> Vec<(String, String)> {
+    let mut modules = Vec::new();
+    let mut current: Option<(Option<String>, Option<String>)> = None;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            if let Some((Some(path), Some(url))) = current.take() {
+                modules.push((path, url));
+            }
+            if line.starts_with("[submodule") {
+                current = Some((None, None));
+            }
+        } else if let (Some(entry), Some((key, value))) = (current.as_mut(), line.split_once('=')) {
+            match key.trim() {
+                "path" => entry.0 = Some(value.trim().trim_matches('"').trim_end_matches('/').to_string()),
+                "url" => entry.1 = Some(value.trim().trim_matches('"').to_string()),
+                _ => {}
+            }
+        }
+    }
+    if let Some((Some(path), Some(url))) = current {
+        modules.push((path, url));
+    }
+    modules
+}
+
+/// Maps a submodule URL to a GitHub `(owner, repo)`. Handles HTTPS, `git@github.com:` and
+/// `ssh://` URLs, and URLs relative to the parent repository (`../other.git`).
+fn github_repo_from_url(url: &str, parent_owner: &str, parent_repo: &str) -> Option<(String, String)> {
+    let path = if url.starts_with("../") || url.starts_with("./") {
+        let mut parts = vec![parent_owner.to_string(), parent_repo.to_string()];
+        for seg in url.split('/') {
+            match seg {
+                "." | "" => {}
+                ".." => {
+                    parts.pop()?;
+                }
+                s => parts.push(s.to_string()),
+            }
+        }
+        parts.join("/")
+    } else {
+        let rest = url
+            .strip_prefix("git@github.com:")
+            .or_else(|| url.split_once("github.com/").map(|(_, r)| r))?;
+        rest.to_string()
+    };
+    let mut segs = path.trim_end_matches('/').trim_end_matches(".git").split('/').filter(|s| !s.is_empty());
+    let (owner, repo) = (segs.next()?, segs.next()?);
+    Some((owner.to_string(), repo.to_string()))
+}
+
+/// Pinned commit of the gitlink at `path`, via the contents API (works in tarball mode
+/// too, where the archive carries no gitlink entries).
+async fn pinned_commit(gh: &github::GithubClient, owner: &str, repo: &str, path: &str, git_ref: &str) -> Option<String> {
+    let json = gh
+        .get_json(&format!("/repos/{}/{}/contents/{}?ref={}", owner, repo, github::encode_path(path), urlencoding::encode(git_ref)))
+        .await
+        .ok()?;
+    if json["type"] != "submodule" {
+        return None;
+    }
+    json["sha"].as_str().map(|s| s.to_string())
+}
+
+/// Resolves the submodules declared in `gitmodules` that lie under `prefix`, and when
+/// `budget_per_module` is set fetches that many top-scored files from each, returned
+/// (by `rules`) with paths prefixed by the submodule path.
+#[allow(clippy::too_many_arguments)]
+pub async fn resolve_submodules(
+    gh: &github::GithubClient,
+    owner: &str,
+    repo: &str,
+    git_ref: &str,
+    gitmodules: &str,
+    prefix: &str,
+    budget_per_module: Option<usize>,
+    concurrency: usize,
+    rules: &ScoringRules,
+) -> (Vec<SubmoduleInfo>, Vec<String>, Vec<FileEntry>) {
+    let mut infos = Vec::new();
+    let mut tree = Vec::new();
+    let mut files = Vec::new();
+
+    for (path, url) in parse_gitmodules(gitmodules).into_iter().filter(|(p, _)| format!("{}/", p).starts_with(prefix)) {
+        let commit_sha = pinned_commit(gh, owner, repo, &path, git_ref).await;
+        let target = github_repo_from_url(&url, owner, repo);
+        let mut info = SubmoduleInfo {
+            github_repo: target.as_ref().map(|(o, r)| format!("{}/{}", o, r)),
+            path,
+            url,
+            commit_sha,
+            files_fetched: 0,
+            error: None,
+        };
+
+        if let (Some(budget), Some((sub_owner, sub_repo)), Some(sha)) = (budget_per_module, &target, &info.commit_sha) {
+            match gh.fetch_tree(sub_owner, sub_repo, sha, "", |_| {}).await {
+                Ok(paths) => {
+                    let selected = select_source_files(&paths, "", &[], budget, rules);
+                    let fetched = fetch_files(gh, sub_owner, sub_repo, sha, selected, concurrency, |_| {}).await;
+                    info.files_fetched = fetched.len();
+                    tree.extend(paths.iter().map(|p| format!("{}/{}", info.path, p)));
+                    files.extend(fetched.into_iter().map(|f| FileEntry { path: format!("{}/{}", info.path, f.path), ..f }));
+                }
+                Err(e) => info.error = Some(e.into()),
+            }
+        } else if budget_per_module.is_some() && target.is_none() {
+            info.error = Some("Only submodules hosted on GitHub can be fetched".to_string());
+        }
+        infos.push(info);
+    }
+    (infos, tree, files)
+}
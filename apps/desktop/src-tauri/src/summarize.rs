@@ -0,0 +1,183 @@
+use crate::error::AppError;
+use crate::chunking::chunk_file;
+use crate::tokens::estimate_tokens;
+use crate::llm::Llm;
+use crate::{log_status, AppState, FileEntry};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
+
+/// One per file summarized (or failed), and one per merge round.
+pub const PROGRESS_EVENT: &str = "summarize://progress";
+
+const DEFAULT_BUDGET: usize = 8_000;
+/// Files are cut to about this many tokens (at a declaration boundary where possible)
+/// before being summarized, so the map prompt fits [`MAP_CONTEXT`].
+const MAX_FILE_TOKENS: usize = 6_000;
+/// `num_ctx` for Ollama map and reduce calls.
+const MAP_CONTEXT: usize = 8192;
+/// Summaries merged in one reduce call, in tokens.
+const REDUCE_INPUT_TOKENS: usize = 5_000;
+const MAX_REDUCE_ROUNDS: usize = 3;
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SummaryProgress {
+    request_id: Option<String>,
+    /// `map` while files are summarized, `reduce` while summaries are merged.
+    stage: &'static str,
+    path: Option<String>,
+    done: usize,
+    total: usize,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSummary {
+    path: String,
+    summary: String,
+    tokens: usize,
+    /// Only the start of the file was summarized.
+    clipped: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepositorySummary {
+    /// Condensed context ready to include in a prompt.
+    packed: String,
+    total_tokens: usize,
+    files: Vec<FileSummary>,
+    /// Files whose summary failed, with the error.
+    failed: Vec<(String, String)>,
+    /// Merge rounds needed to fit the budget; 0 when the file summaries fit as they are.
+    reduce_rounds: usize,
+}
+
+fn clip(text: &str, chars: usize) -> (&str, bool) {
+    match text.char_indices().nth(chars) {
+        Some((i, _)) => (&text[..i], true),
+        None => (text, false),
+    }
+}
+
+fn map_prompt(file: &FileEntry, words: usize) -> (String, bool) {
+    let mut chunks = chunk_file(file, MAX_FILE_TOKENS, 0);
+    let clipped = chunks.len() > 1;
+    let content = if chunks.is_empty() { String::new() } else { chunks.swap_remove(0).content };
+    let prompt = format!(
+        "Summarize this file for a developer who will ask questions about the repository it belongs to. \
+         Cover its purpose, the main types and functions and what it depends on. Use at most {} words and \
+         reply with the summary only.{}\n\nFile: {}\n```\n{}\n```",
+        words,
+        if clipped { " Only the start of the file is shown." } else { "" },
+        file.path,
+        content
+    );
+    (prompt, clipped)
+}
+
+fn reduce_prompt(sections: &[String], target_tokens: usize) -> String {
+    format!(
+        "Below are summaries of files from one repository. Merge them into a single condensed overview of \
+         at most {} words. Keep the file paths for anything you mention, group related files, and drop details \
+         that don't help understand the code base. Reply with the overview only.\n\n{}",
+        target_tokens * 3 / 4,
+        sections.join("\n\n")
+    )
+}
+
+/// Splits sections into groups of about [`REDUCE_INPUT_TOKENS`].
+fn group(sections: Vec<String>) -> Vec<Vec<String>> {
+    let mut groups: Vec<Vec<String>> = Vec::new();
+    let mut size = 0;
+    for section in sections {
+        let tokens = estimate_tokens(&section);
+        match groups.last_mut() {
+            Some(last) if size + tokens <= REDUCE_INPUT_TOKENS => last.push(section),
+            _ => {
+                groups.push(vec![section]);
+                size = 0;
+            }
+        }
+        size += tokens;
+    }
+    groups
+}
+
+/// Map-reduce summary of a repository too big for the model's context: each file is
+/// summarized on its own (map), then the summaries are merged in groups until they fit
+/// `budget_tokens` (reduce). `provider` is `ollama` (default, at `url`) or `gemini`.
+/// Emits `summarize://progress` per file and per merge round, tagged with `request_id`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn summarize_repository(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    files: Vec<FileEntry>,
+    provider: Option<String>,
+    model: Option<String>,
+    url: Option<String>,
+    budget_tokens: Option<usize>,
+    request_id: Option<String>,
+) -> Result<RepositorySummary, AppError> {
+    let llm = Llm::resolve(&state, "Summarizing", provider, model, url, MAP_CONTEXT).await?;
+    let budget = budget_tokens.unwrap_or(DEFAULT_BUDGET).max(500);
+    let files: Vec<FileEntry> = files.into_iter().filter(|f| !f.content.trim().is_empty()).collect();
+    if files.is_empty() {
+        return Err(AppError::InvalidInput("No files to summarize".to_string()));
+    }
+    let _span = state.trace.span("llm", "summarize_repository").attr("files", files.len());
+
+    let progress = |stage, path: Option<String>, done, total, error: Option<String>| {
+        let _ = app.emit(PROGRESS_EVENT, SummaryProgress { request_id: request_id.clone(), stage, path, done, total, error });
+    };
+
+    // Map. Calls go one at a time: a local model serves them serially anyway, and this
+    // keeps the rate of hosted calls modest.
+    let words = (budget * 3 / 4 / files.len()).clamp(40, 200);
+    let mut summaries = Vec::new();
+    let mut failed = Vec::new();
+    log_status(&app, format!("Summarizing {} files with {}", files.len(), llm.model));
+    for (i, file) in files.iter().enumerate() {
+        let (prompt, clipped) = map_prompt(file, words);
+        match llm.complete(&state, &prompt).await {
+            Ok(summary) => {
+                let summary = summary.trim().to_string();
+                progress("map", Some(file.path.clone()), i + 1, files.len(), None);
+                summaries.push(FileSummary { path: file.path.clone(), tokens: estimate_tokens(&summary), summary, clipped });
+            }
+            Err(e) => {
+                progress("map", Some(file.path.clone()), i + 1, files.len(), Some(e.clone()));
+                failed.push((file.path.clone(), e));
+            }
+        }
+    }
+    if summaries.is_empty() {
+        let first = failed.first().map(|(_, e)| e.clone()).unwrap_or_default();
+        return Err(AppError::Provider { status: None, message: format!("No file could be summarized: {}", first) });
+    }
+
+    // Reduce.
+    let mut sections: Vec<String> = summaries.iter().map(|s| format!("### {}\n{}", s.path, s.summary)).collect();
+    let mut rounds = 0;
+    while estimate_tokens(&sections.join("\n\n")) > budget && rounds < MAX_REDUCE_ROUNDS {
+        rounds += 1;
+        let groups = group(sections);
+        let target = (budget / groups.len()).max(100);
+        log_status(&app, format!("Merging summaries (round {}, {} groups)", rounds, groups.len()));
+        let mut merged = Vec::with_capacity(groups.len());
+        for (i, g) in groups.iter().enumerate() {
+            merged.push(llm.complete(&state, &reduce_prompt(g, target)).await?.trim().to_string());
+            progress("reduce", None, i + 1, groups.len(), None);
+        }
+        sections = merged;
+    }
+    let mut packed = format!("## Repository summary\n{}\n", sections.join("\n\n"));
+    if estimate_tokens(&packed) > budget {
+        packed = format!("{}\n[... summary truncated to fit the budget ...]\n", clip(&packed, budget * 4).0);
+    }
+
+    log_status(&app, format!("Summary ready: {} files, {} failed", summaries.len(), failed.len()));
+    Ok(RepositorySummary { total_tokens: estimate_tokens(&packed), packed, files: summaries, failed, reduce_rounds: rounds })
+}
@@ -0,0 +1,339 @@
+use crate::error::AppError;
+use crate::tokens::estimate_tokens;
+use crate::{projects, AppState, FileEntry};
+use repo_prompt_core::outline::language_for_path;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use tauri::{AppHandle, State};
+use tree_sitter::{Node, Parser};
+
+/// Node kinds that define a named symbol, across the bundled grammars.
+const DEFINITION_KINDS: &[&str] = &[
+    // Rust
+    "function_item",
+    "function_signature_item",
+    "struct_item",
+    "enum_item",
+    "union_item",
+    "trait_item",
+    "type_item",
+    "const_item",
+    "static_item",
+    "macro_definition",
+    // Python
+    "function_definition",
+    "class_definition",
+    // JavaScript / TypeScript
+    "function_declaration",
+    "generator_function_declaration",
+    "class_declaration",
+    "abstract_class_declaration",
+    "method_definition",
+    "interface_declaration",
+    "type_alias_declaration",
+    "enum_declaration",
+    // Go
+    "method_declaration",
+    "type_spec",
+];
+
+/// Callers whose body is longer than this are shown as their signature and the lines
+/// that mention the symbol.
+const MAX_CALLER_LINES: usize = 40;
+const MAX_DEFINITIONS: usize = 5;
+const MAX_CALLERS: usize = 10;
+const MAX_CALLEES: usize = 15;
+/// Callee names defined more often than this (`new`, `get`, ...) can't be resolved by
+/// name and are left out.
+const MAX_CALLEE_CANDIDATES: usize = 3;
+
+/// A named definition and the names it calls.
+pub struct Definition {
+    pub name: String,
+    /// Enclosing type, impl, class or trait, if any.
+    pub container: Option<String>,
+    pub kind: String,
+    pub path: String,
+    /// 1-based, inclusive; includes leading doc comments and attributes.
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Text up to the body, or the whole definition when it has no body.
+    pub signature: String,
+    pub calls: BTreeSet<String>,
+}
+
+impl Definition {
+    fn display_name(&self) -> String {
+        match &self.container {
+            Some(c) => format!("{}::{}", c, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Matches `name`, or `Container::name` / `Container.name`.
+    fn matches(&self, query: &str) -> bool {
+        let (container, name) = match query.rsplit_once("::").or_else(|| query.rsplit_once('.')) {
+            Some((c, n)) => (Some(c.rsplit("::").next().unwrap_or(c)), n),
+            None => (None, query),
+        };
+        self.name == name && container.map_or(true, |c| self.container.as_deref() == Some(c))
+    }
+}
+
+/// Name of the function a call expression invokes: the last segment of paths, fields
+/// and member accesses.
+fn callee_name<'a>(node: Node, src: &'a str) -> Option<&'a str> {
+    let field = match node.kind() {
+        "identifier" | "field_identifier" | "property_identifier" | "type_identifier" => {
+            return node.utf8_text(src.as_bytes()).ok()
+        }
+        "field_expression" | "selector_expression" => "field",
+        "member_expression" => "property",
+        "scoped_identifier" => "name",
+        "attribute" => "attribute",
+        "generic_function" => "function",
+        _ => return None,
+    };
+    callee_name(node.child_by_field_name(field)?, src)
+}
+
+/// Name of the type an `impl`, class or trait introduces, for its members.
+fn container_name(node: &Node, src: &str) -> Option<String> {
+    let named = match node.kind() {
+        "impl_item" => node.child_by_field_name("type"),
+        "trait_item" | "class_definition" | "class_declaration" | "abstract_class_declaration" => node.child_by_field_name("name"),
+        _ => None,
+    }?;
+    let text = named.utf8_text(src.as_bytes()).ok()?;
+    // `Foo<T>` is looked up as `Foo`.
+    Some(text.split('<').next().unwrap_or(text).trim().to_string())
+}
+
+/// First line of `node` including the doc comments and attributes right above it.
+fn leading_start_row(node: &Node) -> usize {
+    let mut row = node.start_position().row;
+    let mut prev = node.prev_named_sibling();
+    while let Some(p) = prev {
+        let trivia = p.kind().contains("comment") || matches!(p.kind(), "attribute_item" | "decorator");
+        if !trivia || p.end_position().row + 1 < row {
+            break;
+        }
+        row = p.start_position().row;
+        prev = p.prev_named_sibling();
+    }
+    row
+}
+
+struct Indexer<'a> {
+    path: &'a str,
+    src: &'a str,
+    definitions: Vec<Definition>,
+}
+
+impl Indexer<'_> {
+    fn definition(&self, node: &Node, container: &Option<String>) -> Option<Definition> {
+        let name_node = match node.kind() {
+            // `const handler = async (req) => { ... }`
+            "variable_declarator" => match node.child_by_field_name("value")?.kind() {
+                "arrow_function" | "function_expression" | "function" => node.child_by_field_name("name"),
+                _ => None,
+            },
+            kind if DEFINITION_KINDS.contains(&kind) => node.child_by_field_name("name"),
+            _ => None,
+        }?;
+        let body = node.child_by_field_name("body").or_else(|| node.child_by_field_name("value")?.child_by_field_name("body"));
+        let body_start = body.map_or(node.end_byte(), |b| b.start_byte());
+        Some(Definition {
+            name: name_node.utf8_text(self.src.as_bytes()).ok()?.to_string(),
+            container: container.clone(),
+            kind: node.kind().to_string(),
+            path: self.path.to_string(),
+            start_line: leading_start_row(node) + 1,
+            end_line: node.end_position().row + 1,
+            signature: self.src[node.start_byte()..body_start].trim_end().to_string(),
+            calls: BTreeSet::new(),
+        })
+    }
+
+    fn visit(&mut self, node: Node, container: &Option<String>, current: Option<usize>) {
+        let mut current = current;
+        if let Some(def) = self.definition(&node, container) {
+            self.definitions.push(def);
+            current = Some(self.definitions.len() - 1);
+        }
+        if let Some(i) = current {
+            let function = match node.kind() {
+                "call_expression" | "call" => node.child_by_field_name("function"),
+                "new_expression" => node.child_by_field_name("constructor"),
+                _ => None,
+            };
+            if let Some(name) = function.and_then(|f| callee_name(f, self.src)) {
+                self.definitions[i].calls.insert(name.to_string());
+            }
+        }
+        let container = container_name(&node, self.src).or_else(|| container.clone());
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.visit(child, &container, current);
+        }
+    }
+}
+
+/// Definitions in one file, with the names each one calls. Empty when no grammar is
+/// bundled for the file type.
+pub fn index_file(file: &FileEntry) -> Vec<Definition> {
+    let Some(language) = language_for_path(&file.path) else { return Vec::new() };
+    let mut parser = Parser::new();
+    if parser.set_language(&language).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(&file.content, None) else { return Vec::new() };
+    let mut indexer = Indexer { path: &file.path, src: &file.content, definitions: Vec::new() };
+    indexer.visit(tree.root_node(), &None, None);
+    indexer.definitions
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolSnippet {
+    path: String,
+    name: String,
+    kind: String,
+    start_line: usize,
+    end_line: usize,
+    content: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolContext {
+    symbol: String,
+    definitions: Vec<SymbolSnippet>,
+    /// Definitions that call the symbol, whole when short.
+    callers: Vec<SymbolSnippet>,
+    /// Signatures of the project's definitions the symbol calls.
+    callees: Vec<SymbolSnippet>,
+    /// The bundle, ready to include in a prompt.
+    packed: String,
+    tokens: usize,
+}
+
+fn snippet(def: &Definition, content: String) -> SymbolSnippet {
+    SymbolSnippet {
+        path: def.path.clone(),
+        name: def.display_name(),
+        kind: def.kind.clone(),
+        start_line: def.start_line,
+        end_line: def.end_line,
+        content,
+    }
+}
+
+fn extension(path: &str) -> &str {
+    path.rsplit('.').next().unwrap_or_default()
+}
+
+fn source_lines<'a>(files: &'a [FileEntry], def: &Definition) -> Vec<&'a str> {
+    files
+        .iter()
+        .find(|f| f.path == def.path)
+        .map(|f| f.content.lines().skip(def.start_line - 1).take(def.end_line + 1 - def.start_line).collect())
+        .unwrap_or_default()
+}
+
+/// A long caller cut down to its signature and the lines that mention `name`.
+fn call_sites(lines: &[&str], def: &Definition, name: &str) -> String {
+    let mut out = def.signature.clone();
+    let mut last = None;
+    for (i, line) in lines.iter().enumerate().filter(|(_, l)| l.contains(name)) {
+        if last.map_or(true, |l: usize| i > l + 1) {
+            out.push_str("\n    ...");
+        }
+        out.push('\n');
+        out.push_str(line);
+        last = Some(i);
+    }
+    out.push_str("\n    ...");
+    out
+}
+
+fn build_context(files: &[FileEntry], symbol: &str) -> Result<SymbolContext, String> {
+    let index: Vec<Definition> = files.iter().flat_map(index_file).collect();
+    let targets: Vec<&Definition> = index.iter().filter(|d| d.matches(symbol)).take(MAX_DEFINITIONS).collect();
+    let Some(first) = targets.first() else {
+        return Err(format!("No definition of `{}` found in the project", symbol));
+    };
+    let name = first.name.clone();
+    let is_target = |d: &Definition| targets.iter().any(|t| std::ptr::eq(*t, d));
+
+    let definitions: Vec<SymbolSnippet> = targets.iter().map(|d| snippet(d, source_lines(files, d).join("\n"))).collect();
+
+    let callers: Vec<SymbolSnippet> = index
+        .iter()
+        .filter(|d| !is_target(d) && d.calls.contains(&name))
+        .take(MAX_CALLERS)
+        .map(|d| {
+            let lines = source_lines(files, d);
+            let content = if lines.len() <= MAX_CALLER_LINES { lines.join("\n") } else { call_sites(&lines, d, &name) };
+            snippet(d, content)
+        })
+        .collect();
+
+    let called: BTreeSet<&String> = targets.iter().flat_map(|t| &t.calls).collect();
+    let mut callees = Vec::new();
+    for callee in called {
+        let mut candidates: Vec<&Definition> = index.iter().filter(|d| &d.name == callee && !is_target(d)).collect();
+        // A call resolves to a definition in the same language when there is one.
+        if candidates.iter().any(|d| extension(&d.path) == extension(&first.path)) {
+            candidates.retain(|d| extension(&d.path) == extension(&first.path));
+        }
+        if candidates.len() <= MAX_CALLEE_CANDIDATES {
+            callees.extend(candidates.into_iter().map(|d| snippet(d, d.signature.clone())));
+        }
+    }
+    callees.truncate(MAX_CALLEES);
+
+    let mut packed = format!("## Context for `{}`\n", symbol);
+    for (title, snippets) in [("Definition", &definitions), ("Calls", &callees), ("Called by", &callers)] {
+        if snippets.is_empty() {
+            continue;
+        }
+        packed.push_str(&format!("\n### {}\n", title));
+        for s in snippets {
+            packed.push_str(&format!("\n--- {} ({}:{}-{}) ---\n{}\n", s.name, s.path, s.start_line, s.end_line, s.content));
+        }
+    }
+    Ok(SymbolContext {
+        symbol: symbol.to_string(),
+        tokens: estimate_tokens(&packed),
+        packed,
+        definitions,
+        callers,
+        callees,
+    })
+}
+
+/// Minimal context for one function, method or type: its definition, the signatures of
+/// the project functions it calls, and the functions that call it. `name` may be
+/// qualified (`Type::method` or `Class.method`). Calls are matched by name, so callers
+/// of a same-named function elsewhere can show up. Reads `files` when given, else the
+/// project's last loaded files.
+#[tauri::command]
+pub async fn get_symbol_context(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    project: String,
+    name: String,
+    files: Option<Vec<FileEntry>>,
+) -> Result<SymbolContext, AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("Enter a symbol name".to_string()));
+    }
+    let files = match files {
+        Some(files) => files,
+        None => projects::loaded_files(&app, &state, &project)?,
+    };
+    let _span = state.trace.span("scan", "get_symbol_context").attr("files", files.len());
+    Ok(tokio::task::spawn_blocking(move || build_context(&files, &name)).await??)
+}
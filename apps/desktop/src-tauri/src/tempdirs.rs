@@ -0,0 +1,134 @@
+use crate::error::AppError;
+use crate::AppState;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::State;
+
+const SESSION_PREFIX: &str = "session-";
+
+/// Owns every temporary directory the app creates. Each run gets its own
+/// `session-<pid>` directory, removed on exit; sessions left behind by a crashed run are
+/// swept the next time the app starts.
+#[derive(Default)]
+pub struct TempDirManager {
+    root: Mutex<Option<PathBuf>>,
+    counter: AtomicU64,
+}
+
+/// A temporary directory that is deleted when dropped, so failed operations clean up
+/// after themselves.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+fn dir_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+fn session_pid(path: &Path) -> Option<u32> {
+    path.file_name()?.to_str()?.strip_prefix(SESSION_PREFIX)?.parse().ok()
+}
+
+impl TempDirManager {
+    /// Sets the temp root (under the app cache dir) and removes orphaned sessions.
+    pub fn init(&self, root: PathBuf) {
+        let _ = fs::create_dir_all(&root);
+        *self.root.lock().unwrap() = Some(root);
+        self.remove_orphans();
+    }
+
+    fn session_dir(&self) -> Result<PathBuf, String> {
+        let root = self.root.lock().unwrap().clone().ok_or("Temp directory manager is not initialized")?;
+        Ok(root.join(format!("{}{}", SESSION_PREFIX, std::process::id())))
+    }
+
+    /// Creates a fresh directory inside this session's temp area.
+    pub fn create(&self, prefix: &str) -> Result<TempDir, String> {
+        let n = self.counter.fetch_add(1, Ordering::Relaxed);
+        let path = self.session_dir()?.join(format!("{}-{}", prefix, n));
+        fs::create_dir_all(&path).map_err(|e| format!("Failed to create temp dir: {}", e))?;
+        Ok(TempDir { path })
+    }
+
+    /// Removes this session's temp area. Called on app exit.
+    pub fn cleanup_session(&self) {
+        if let Ok(dir) = self.session_dir() {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+
+    /// Deletes session directories whose owning process is no longer running.
+    fn remove_orphans(&self) -> u64 {
+        let Some(root) = self.root.lock().unwrap().clone() else { return 0 };
+        let Ok(entries) = fs::read_dir(&root) else { return 0 };
+
+        let mut system = System::new();
+        system.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::nothing());
+        let own_pid = std::process::id();
+        let own_name = system.process(Pid::from_u32(own_pid)).map(|p| p.name().to_os_string());
+
+        let mut freed = 0;
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let Some(pid) = session_pid(&path) else { continue };
+            if pid == own_pid {
+                continue;
+            }
+            // A live process with the same PID but a different name means the PID was reused.
+            let alive = system.process(Pid::from_u32(pid)).is_some_and(|p| Some(p.name().to_os_string()) == own_name);
+            if !alive {
+                freed += dir_size(&path);
+                let _ = fs::remove_dir_all(&path);
+            }
+        }
+        freed
+    }
+
+    fn usage(&self) -> TempUsage {
+        let Some(root) = self.root.lock().unwrap().clone() else { return TempUsage::default() };
+        let sessions = fs::read_dir(&root)
+            .map(|entries| entries.filter_map(|e| e.ok()).filter(|e| session_pid(&e.path()).is_some()).count())
+            .unwrap_or(0);
+        TempUsage { sessions, bytes: dir_size(&root) }
+    }
+}
+
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TempUsage {
+    sessions: usize,
+    bytes: u64,
+}
+
+#[tauri::command]
+pub async fn get_temp_usage(state: State<'_, AppState>) -> Result<TempUsage, AppError> {
+    Ok(state.temp_dirs.usage())
+}
+
+/// Removes temp data left behind by crashed runs. Returns the number of bytes freed.
+#[tauri::command]
+pub async fn clear_orphaned_temp_dirs(state: State<'_, AppState>) -> Result<u64, AppError> {
+    Ok(state.temp_dirs.remove_orphans())
+}
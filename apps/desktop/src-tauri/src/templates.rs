@@ -0,0 +1,400 @@
+use crate::error::AppError;
+use crate::export::write_atomic;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+const TEMPLATES_FILE: &str = "prompt_templates.json";
+
+/// Templates shipped with the app: id, name, description, content. Saving one under the
+/// same id overrides it; deleting the override brings it back.
+const BUILTIN_TEMPLATES: &[(&str, &str, &str, &str)] = &[
+    (
+        "architecture-review",
+        "Architecture review",
+        "Assess the structure of the codebase and suggest improvements.",
+        "You are a senior software architect reviewing a codebase.\n\n\
+{{#user_goal}}Focus: {{user_goal}}\n\n{{/user_goal}}\
+## Project structure\n```\n{{tree}}\n```\n\n\
+{{#readme}}## README\n{{readme}}\n\n{{/readme}}\
+## Source files\n{{files}}\n\n\
+Describe the architecture: the main components, how they depend on each other and how data flows between them. \
+Then point out coupling, layering violations, duplicated responsibilities and missing abstractions, \
+and suggest concrete, prioritized improvements with the files they touch.\n",
+    ),
+    (
+        "onboarding-doc",
+        "Onboarding document",
+        "Write a guide for a developer new to the project.",
+        "Write an onboarding document for a developer joining this project.\n\n\
+{{#user_goal}}They will start by working on: {{user_goal}}\n\n{{/user_goal}}\
+## Project structure\n```\n{{tree}}\n```\n\n\
+{{#readme}}## README\n{{readme}}\n\n{{/readme}}\
+## Source files\n{{files}}\n\n\
+Cover what the project does, how to build, run and test it, how the code is organized, \
+the key concepts and where they live, and the conventions to follow. \
+End with a short reading order of the files to start with.\n",
+    ),
+    (
+        "bug-hunt",
+        "Bug hunt",
+        "Look for bugs, edge cases and risky code.",
+        "You are reviewing this code for bugs.\n\n\
+{{#user_goal}}Symptom or area to investigate: {{user_goal}}\n\n{{/user_goal}}\
+## Project structure\n```\n{{tree}}\n```\n\n\
+{{#readme}}## README\n{{readme}}\n\n{{/readme}}\
+## Source files\n{{files}}\n\n\
+List likely bugs: logic errors, unhandled errors and edge cases, race conditions, resource leaks and security issues. \
+For each, give the file and function, explain how it fails, rate its severity and propose a fix.\n",
+    ),
+];
+
+/// A reusable prompt skeleton, rendered with [`render_template`].
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptTemplate {
+    id: String,
+    name: String,
+    #[serde(default)]
+    description: String,
+    content: String,
+    /// Unix seconds; 0 for a built-in.
+    updated_at: u64,
+    /// Shipped with the app (possibly overridden by a saved copy).
+    #[serde(skip_deserializing)]
+    builtin: bool,
+    /// Variables the template references, in order of first use.
+    #[serde(skip_deserializing)]
+    variables: Vec<String>,
+}
+
+/// A problem found while checking a template against its variables. `kind` is one of
+/// `unterminatedTag`, `unclosedSection`, `unexpectedClose`, `unknownVariable`,
+/// `unusedVariable` or `emptySection`.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateIssue {
+    kind: &'static str,
+    name: String,
+    line: usize,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderedTemplate {
+    /// `None` when there are errors (any structural error, or unknown variables in strict mode).
+    output: Option<String>,
+    errors: Vec<TemplateIssue>,
+    warnings: Vec<TemplateIssue>,
+}
+
+enum Node {
+    Text(String),
+    Var { name: String, line: usize },
+    Section { name: String, inverted: bool, line: usize, children: Vec<Node> },
+}
+
+fn issue(kind: &'static str, name: &str, line: usize, message: String) -> TemplateIssue {
+    TemplateIssue { kind, name: name.to_string(), line, message }
+}
+
+/// Parses `{{var}}`, `{{#section}}...{{/section}}` and inverted `{{^section}}` tags.
+fn parse(template: &str) -> Result<Vec<Node>, TemplateIssue> {
+    // Stack of open sections: (name, inverted, line, nodes collected so far).
+    let mut stack: Vec<(String, bool, usize, Vec<Node>)> = vec![(String::new(), false, 0, Vec::new())];
+    let mut rest = template;
+    let mut line = 1;
+
+    while let Some(start) = rest.find("{{") {
+        let (text, after) = rest.split_at(start);
+        line += text.matches('\n').count();
+        if !text.is_empty() {
+            stack.last_mut().unwrap().3.push(Node::Text(text.to_string()));
+        }
+        let end = after.find("}}").ok_or_else(|| issue("unterminatedTag", "", line, format!("Tag opened on line {} is never closed", line)))?;
+        let tag = after[2..end].trim();
+        rest = &after[end + 2..];
+
+        if let Some(name) = tag.strip_prefix('#').or_else(|| tag.strip_prefix('^')) {
+            stack.push((name.trim().to_string(), tag.starts_with('^'), line, Vec::new()));
+        } else if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim();
+            if stack.len() == 1 || stack.last().unwrap().0 != name {
+                return Err(issue("unexpectedClose", name, line, format!("Closing tag {{{{/{}}}}} on line {} has no matching opening tag", name, line)));
+            }
+            let (name, inverted, open_line, children) = stack.pop().unwrap();
+            stack.last_mut().unwrap().3.push(Node::Section { name, inverted, line: open_line, children });
+        } else {
+            stack.last_mut().unwrap().3.push(Node::Var { name: tag.to_string(), line });
+        }
+        line += after[..end].matches('\n').count();
+    }
+    if !rest.is_empty() {
+        stack.last_mut().unwrap().3.push(Node::Text(rest.to_string()));
+    }
+    if stack.len() > 1 {
+        let (name, _, open_line, _) = stack.pop().unwrap();
+        return Err(issue("unclosedSection", &name, open_line, format!("Section '{}' opened on line {} is never closed", name, open_line)));
+    }
+    Ok(stack.pop().unwrap().3)
+}
+
+/// Variables referenced by `nodes`, in order of first use.
+fn referenced(nodes: &[Node], names: &mut Vec<String>) {
+    for node in nodes {
+        let (name, children) = match node {
+            Node::Text(_) => continue,
+            Node::Var { name, .. } => (name, &[][..]),
+            Node::Section { name, children, .. } => (name, &children[..]),
+        };
+        if !names.contains(name) {
+            names.push(name.clone());
+        }
+        referenced(children, names);
+    }
+}
+
+fn templates_path(app: &AppHandle) -> Result<PathBuf, AppError> {
+    let dir = app.path().app_config_dir().map_err(|e| AppError::Io(format!("Failed to resolve config dir: {}", e)))?;
+    Ok(dir.join(TEMPLATES_FILE))
+}
+
+/// The saved templates, without the built-ins.
+fn load_saved(app: &AppHandle) -> Result<Vec<PromptTemplate>, AppError> {
+    match fs::read_to_string(templates_path(app)?) {
+        Ok(text) => serde_json::from_str(&text).map_err(|e| AppError::Io(format!("Templates file is corrupt: {}", e))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(AppError::Io(format!("Failed to read templates: {}", e))),
+    }
+}
+
+fn store_saved(app: &AppHandle, templates: &[PromptTemplate]) -> Result<(), AppError> {
+    let text = serde_json::to_string_pretty(templates).map_err(|e| AppError::Internal(e.to_string()))?;
+    write_atomic(&templates_path(app)?, None, text.as_bytes())
+}
+
+fn is_builtin(id: &str) -> bool {
+    BUILTIN_TEMPLATES.iter().any(|(builtin, ..)| *builtin == id)
+}
+
+/// Fills in the fields derived from the id and content.
+fn described(mut template: PromptTemplate) -> PromptTemplate {
+    template.builtin = is_builtin(&template.id);
+    template.variables.clear();
+    if let Ok(nodes) = parse(&template.content) {
+        referenced(&nodes, &mut template.variables);
+    }
+    template
+}
+
+/// Built-ins first (or the saved copies overriding them), then the saved templates by name.
+fn all_templates(app: &AppHandle) -> Result<Vec<PromptTemplate>, AppError> {
+    let mut saved = load_saved(app)?;
+    let mut templates = Vec::with_capacity(BUILTIN_TEMPLATES.len() + saved.len());
+    for (id, name, description, content) in BUILTIN_TEMPLATES {
+        let template = match saved.iter().position(|t| t.id == *id) {
+            Some(i) => saved.remove(i),
+            None => PromptTemplate {
+                id: id.to_string(),
+                name: name.to_string(),
+                description: description.to_string(),
+                content: content.to_string(),
+                updated_at: 0,
+                builtin: true,
+                variables: Vec::new(),
+            },
+        };
+        templates.push(template);
+    }
+    saved.sort_by_key(|t| t.name.to_lowercase());
+    templates.extend(saved);
+    Ok(templates.into_iter().map(described).collect())
+}
+
+/// Id derived from the name, made unique among `existing`.
+fn new_id(name: &str, existing: &[PromptTemplate]) -> String {
+    let slug: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let base = if slug.is_empty() { "template".to_string() } else { slug };
+    let mut id = base.clone();
+    let mut n = 2;
+    while is_builtin(&id) || existing.iter().any(|t| t.id == id) {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+    id
+}
+
+/// The built-in templates and the saved ones. Common variables are `tree`, `readme`,
+/// `files` and `user_goal`; each template lists those it uses.
+#[tauri::command]
+pub fn list_templates(app: AppHandle) -> Result<Vec<PromptTemplate>, AppError> {
+    all_templates(&app)
+}
+
+/// Creates a template, or replaces the one with `id`. Saving under a built-in's id
+/// overrides it. The content must parse; unknown variables are only checked on render.
+#[tauri::command]
+pub fn save_template(app: AppHandle, id: Option<String>, name: String, description: Option<String>, content: String) -> Result<PromptTemplate, AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("A template needs a name".to_string()));
+    }
+    if let Err(e) = parse(&content) {
+        return Err(AppError::InvalidInput(e.message));
+    }
+    let mut saved = load_saved(&app)?;
+    let template = PromptTemplate {
+        id: match id {
+            Some(id) if is_builtin(&id) || saved.iter().any(|t| t.id == id) => id,
+            Some(id) => return Err(AppError::NotFound(format!("Template not found: {}", id))),
+            None => new_id(&name, &saved),
+        },
+        name,
+        description: description.unwrap_or_default().trim().to_string(),
+        content,
+        updated_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default(),
+        builtin: false,
+        variables: Vec::new(),
+    };
+    match saved.iter_mut().find(|t| t.id == template.id) {
+        Some(existing) => *existing = template.clone(),
+        None => saved.push(template.clone()),
+    }
+    store_saved(&app, &saved)?;
+    Ok(described(template))
+}
+
+/// Deletes a saved template. For an overridden built-in this restores the shipped one.
+#[tauri::command]
+pub fn delete_template(app: AppHandle, id: String) -> Result<(), AppError> {
+    let mut saved = load_saved(&app)?;
+    let before = saved.len();
+    saved.retain(|t| t.id != id);
+    if saved.len() == before {
+        return Err(match is_builtin(&id) {
+            true => AppError::InvalidInput(format!("Built-in template '{}' can't be deleted", id)),
+            false => AppError::NotFound(format!("Template not found: {}", id)),
+        });
+    }
+    store_saved(&app, &saved)
+}
+
+fn is_truthy(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => false,
+        Some(Value::Bool(b)) => *b,
+        Some(Value::String(s)) => !s.trim().is_empty(),
+        Some(Value::Array(a)) => !a.is_empty(),
+        Some(Value::Object(o)) => !o.is_empty(),
+        Some(Value::Number(_)) => true,
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        Value::Array(items) => items.iter().map(display).collect::<Vec<_>>().join("\n"),
+        other => other.to_string(),
+    }
+}
+
+fn check(nodes: &[Node], vars: &HashMap<String, Value>, used: &mut BTreeSet<String>, unknown: &mut Vec<TemplateIssue>, empty: &mut Vec<TemplateIssue>) {
+    for node in nodes {
+        match node {
+            Node::Text(_) => {}
+            Node::Var { name, line } => {
+                used.insert(name.clone());
+                if !vars.contains_key(name) {
+                    unknown.push(issue("unknownVariable", name, *line, format!("Variable '{}' on line {} is not defined", name, line)));
+                }
+            }
+            Node::Section { name, inverted, line, children } => {
+                used.insert(name.clone());
+                if !vars.contains_key(name) {
+                    unknown.push(issue("unknownVariable", name, *line, format!("Section variable '{}' on line {} is not defined", name, line)));
+                } else if !inverted && !is_truthy(vars.get(name)) {
+                    empty.push(issue("emptySection", name, *line, format!("Section '{}' on line {} will be omitted because its value is empty", name, line)));
+                }
+                check(children, vars, used, unknown, empty);
+            }
+        }
+    }
+}
+
+fn render(nodes: &[Node], vars: &HashMap<String, Value>, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var { name, .. } => out.push_str(&vars.get(name).map(display).unwrap_or_default()),
+            Node::Section { name, inverted, children, .. } => {
+                if is_truthy(vars.get(name)) != *inverted {
+                    render(children, vars, out);
+                }
+            }
+        }
+    }
+}
+
+/// Renders `template`, or the library template `template_id`. Every referenced
+/// variable is checked before rendering; in `strict` mode (the default) an undefined
+/// variable is an error and nothing is rendered, so empty placeholders never reach the
+/// model. Unused variables and sections that will render empty are reported as warnings.
+#[tauri::command]
+pub fn render_template(
+    app: AppHandle,
+    template: Option<String>,
+    template_id: Option<String>,
+    variables: HashMap<String, Value>,
+    strict: Option<bool>,
+) -> Result<RenderedTemplate, AppError> {
+    let template = match (template, template_id) {
+        (Some(template), _) => template,
+        (None, Some(id)) => all_templates(&app)?.into_iter().find(|t| t.id == id).ok_or_else(|| AppError::NotFound(format!("Template not found: {}", id)))?.content,
+        (None, None) => return Err(AppError::InvalidInput("Pass a template or a template id".to_string())),
+    };
+    Ok(render_text(&template, &variables, strict.unwrap_or(true)))
+}
+
+fn render_text(template: &str, variables: &HashMap<String, Value>, strict: bool) -> RenderedTemplate {
+    let nodes = match parse(template) {
+        Ok(nodes) => nodes,
+        Err(e) => return RenderedTemplate { output: None, errors: vec![e], warnings: Vec::new() },
+    };
+
+    let mut used = BTreeSet::new();
+    let mut unknown = Vec::new();
+    let mut warnings = Vec::new();
+    check(&nodes, variables, &mut used, &mut unknown, &mut warnings);
+    let mut unused: Vec<&String> = variables.keys().filter(|k| !used.contains(*k)).collect();
+    unused.sort();
+    for name in unused {
+        warnings.push(issue("unusedVariable", name, 0, format!("Variable '{}' is provided but never used", name)));
+    }
+
+    let errors = if strict {
+        unknown
+    } else {
+        warnings.extend(unknown);
+        Vec::new()
+    };
+    let output = errors.is_empty().then(|| {
+        let mut out = String::with_capacity(template.len());
+        render(&nodes, variables, &mut out);
+        out
+    });
+    RenderedTemplate { output, errors, warnings }
+}
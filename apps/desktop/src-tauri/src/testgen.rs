@@ -0,0 +1,335 @@
+use crate::error::AppError;
+use crate::chunking::chunk_file;
+use crate::llm::Llm;
+use crate::symbols::{index_file, Definition};
+use crate::tokens::estimate_tokens;
+use crate::{log_status, normalize_subpath, paths, projects, AppState, FileEntry};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use tauri::{AppHandle, State};
+
+/// The file under test is cut to about this many tokens.
+const TARGET_TOKENS: usize = 6_000;
+/// Existing tests shown as examples of the project's conventions.
+const MAX_EXAMPLES: usize = 2;
+const EXAMPLE_TOKENS: usize = 1_200;
+/// Signatures of functions the file calls from elsewhere in the project.
+const MAX_RELATED: usize = 25;
+/// `num_ctx` for Ollama; the prompt carries the file, examples and signatures.
+const TEST_CONTEXT: usize = 16_384;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestProposal {
+    /// File the tests are for.
+    path: String,
+    /// Where the project's conventions put the tests, relative to the project root.
+    test_path: String,
+    /// A file already exists at `test_path`.
+    exists: bool,
+    framework: String,
+    test_command: Option<String>,
+    /// Existing tests given to the model as examples.
+    examples: Vec<String>,
+    prompt_tokens: usize,
+    /// Generated test file.
+    content: String,
+}
+
+/// Test framework and file layout the project uses for one language.
+struct Convention {
+    framework: String,
+    test_command: Option<String>,
+    test_path: String,
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn parent(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+/// Whether `path` is inside `dir`, comparing whole path segments.
+fn is_within(path: &str, dir: &str) -> bool {
+    dir.is_empty() || path.strip_prefix(dir).is_some_and(|rest| rest.starts_with('/'))
+}
+
+fn join(dir: &str, name: &str) -> String {
+    if dir.is_empty() { name.to_string() } else { format!("{}/{}", dir, name) }
+}
+
+fn extension(path: &str) -> &str {
+    file_name(path).rsplit_once('.').map_or("", |(_, ext)| ext)
+}
+
+/// File name without its last extension.
+fn stem(path: &str) -> &str {
+    let name = file_name(path);
+    name.rsplit_once('.').map_or(name, |(stem, _)| stem)
+}
+
+fn family(ext: &str) -> &str {
+    match ext {
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => "js",
+        "py" | "pyi" => "py",
+        other => other,
+    }
+}
+
+fn same_family(a: &str, b: &str) -> bool {
+    family(a) == family(b)
+}
+
+fn is_test_path(path: &str) -> bool {
+    let name = file_name(path);
+    path.split('/').any(|dir| matches!(dir, "test" | "tests" | "__tests__" | "spec"))
+        || name.starts_with("test_")
+        || name.contains(".test.")
+        || name.contains(".spec.")
+        || name.ends_with("_test.go")
+        || name.ends_with("_test.py")
+}
+
+/// The manifest named `name` closest above `path`.
+fn nearest<'a>(files: &'a [FileEntry], path: &str, name: &str) -> Option<&'a FileEntry> {
+    files
+        .iter()
+        .filter(|f| file_name(&f.path) == name && is_within(path, parent(&f.path)))
+        .max_by_key(|f| f.path.len())
+}
+
+/// Most common value, ties going to the first seen.
+fn most_common<'a>(values: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for v in values {
+        match counts.iter_mut().find(|(c, _)| *c == v) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((v, 1)),
+        }
+    }
+    counts.iter().rev().max_by_key(|(_, n)| *n).map(|(v, _)| *v)
+}
+
+fn detect_convention(files: &[FileEntry], target: &str, tests: &[&FileEntry]) -> Result<Convention, String> {
+    let ext = extension(target);
+    let (dir, stem) = (parent(target), stem(target));
+    let convention = match ext {
+        "rs" => {
+            let crate_dir = nearest(files, target, "Cargo.toml").map_or("", |f| parent(&f.path));
+            // `src/foo/mod.rs` and `src/lib.rs` are named after their directory or crate.
+            let name = match (stem, file_name(dir)) {
+                ("mod" | "lib" | "main", "src" | "") => Some(file_name(crate_dir)).filter(|n| !n.is_empty()).unwrap_or("integration"),
+                ("mod" | "lib" | "main", dir_name) => dir_name,
+                _ => stem,
+            };
+            Convention {
+                framework: "Rust built-in test harness".to_string(),
+                test_command: Some("cargo test".to_string()),
+                test_path: join(&join(crate_dir, "tests"), &format!("{}.rs", name.replace('-', "_"))),
+            }
+        }
+        "py" => {
+            let mentions_pytest = files.iter().any(|f| {
+                matches!(file_name(&f.path), "conftest.py" | "pytest.ini")
+                    || (matches!(file_name(&f.path), "pyproject.toml" | "setup.cfg" | "tox.ini" | "requirements-dev.txt" | "requirements.txt")
+                        && f.content.contains("pytest"))
+            });
+            let tests_dir = most_common(tests.iter().map(|t| parent(&t.path))).unwrap_or("tests");
+            Convention {
+                framework: if mentions_pytest { "pytest" } else { "unittest" }.to_string(),
+                test_command: Some(if mentions_pytest { "pytest" } else { "python -m unittest" }.to_string()),
+                test_path: join(tests_dir, &format!("test_{}.py", stem)),
+            }
+        }
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => {
+            let package: serde_json::Value = nearest(files, target, "package.json")
+                .and_then(|f| serde_json::from_str(&f.content).ok())
+                .unwrap_or_default();
+            let has_dep = |name: &str| ["dependencies", "devDependencies"].iter().any(|k| package[k].get(name).is_some());
+            let framework = ["vitest", "jest", "mocha", "ava"].into_iter().find(|f| has_dep(f)).unwrap_or("node:test");
+            let test_command = match framework {
+                _ if package["scripts"]["test"].is_string() => "npm test".to_string(),
+                "vitest" => "npx vitest run".to_string(),
+                "node:test" => "node --test".to_string(),
+                f => format!("npx {}", f),
+            };
+            let suffix = if tests.iter().filter(|t| t.path.contains(".spec.")).count() > tests.len() / 2 { "spec" } else { "test" };
+            let name = format!("{}.{}.{}", stem, suffix, ext);
+            let test_path = if tests.iter().any(|t| t.path.contains("/__tests__/") || t.path.starts_with("__tests__/")) {
+                join(&join(dir, "__tests__"), &name)
+            } else {
+                match most_common(tests.iter().map(|t| parent(&t.path)).filter(|d| d.split('/').any(|p| p == "test" || p == "tests"))) {
+                    Some(tests_dir) => join(tests_dir, &name),
+                    None => join(dir, &name),
+                }
+            };
+            Convention { framework: framework.to_string(), test_command: Some(test_command), test_path }
+        }
+        "go" => Convention {
+            framework: "Go testing package".to_string(),
+            test_command: Some("go test ./...".to_string()),
+            test_path: join(dir, &format!("{}_test.go", stem)),
+        },
+        _ => return Err(format!("Generating tests is not supported for .{} files", ext)),
+    };
+    Ok(convention)
+}
+
+/// Existing tests in the target's language, those nearest to it first.
+fn example_tests<'a>(tests: &[&'a FileEntry], target: &str) -> Vec<&'a FileEntry> {
+    let shared = |path: &str| path.split('/').zip(target.split('/')).take_while(|(a, b)| a == b).count();
+    let mut examples: Vec<&FileEntry> = tests.to_vec();
+    examples.sort_by_key(|t| std::cmp::Reverse(shared(&t.path)));
+    examples.truncate(MAX_EXAMPLES);
+    examples
+}
+
+/// Signatures of what the target calls from other files, so the model knows their shape.
+fn related_signatures(files: &[FileEntry], target: &FileEntry) -> Vec<String> {
+    let calls: BTreeSet<String> = index_file(target).into_iter().flat_map(|d| d.calls).collect();
+    if calls.is_empty() {
+        return Vec::new();
+    }
+    let mut by_name: HashMap<String, Vec<Definition>> = HashMap::new();
+    for file in files.iter().filter(|f| f.path != target.path && !is_test_path(&f.path) && same_family(extension(&f.path), extension(&target.path))) {
+        for def in index_file(file).into_iter().filter(|d| calls.contains(&d.name)) {
+            by_name.entry(def.name.clone()).or_default().push(def);
+        }
+    }
+    let mut related: Vec<String> = calls
+        .iter()
+        .filter_map(|name| by_name.get(name))
+        // Names defined in many places (`new`, `get`) can't be told apart.
+        .filter(|defs| defs.len() <= 2)
+        .flatten()
+        .map(|d| format!("// {}:{}\n{}", d.path, d.start_line, d.signature))
+        .collect();
+    related.truncate(MAX_RELATED);
+    related
+}
+
+fn first_chunk(file: &FileEntry, max_tokens: usize) -> (String, bool) {
+    let mut chunks = chunk_file(file, max_tokens, 0);
+    let clipped = chunks.len() > 1;
+    (if chunks.is_empty() { String::new() } else { chunks.swap_remove(0).content }, clipped)
+}
+
+fn build_prompt(target: &FileEntry, convention: &Convention, examples: &[&FileEntry], related: &[String]) -> String {
+    let (source, clipped) = first_chunk(target, TARGET_TOKENS);
+    let mut prompt = format!(
+        "Write unit tests for the file below using {}. The tests will be saved as `{}`, so import the code under test \
+         relative to that location. Cover the main behaviour, edge cases and error paths of the public functions, \
+         follow the conventions of the existing tests, and don't test private details that aren't reachable from \
+         there. Reply with the complete test file in a single code block and nothing else.",
+        convention.framework, convention.test_path
+    );
+    if extension(&target.path) == "rs" {
+        prompt.push_str(" This is an integration test, so only the crate's public API is available.");
+    }
+    prompt.push_str(&format!("\n\n### File under test: {}\n```\n{}\n```\n", target.path, source));
+    if clipped {
+        prompt.push_str("(Only the start of the file is shown.)\n");
+    }
+    if !related.is_empty() {
+        prompt.push_str(&format!("\n### Signatures of project code it calls\n```\n{}\n```\n", related.join("\n\n")));
+    }
+    for example in examples {
+        let (content, _) = first_chunk(example, EXAMPLE_TOKENS);
+        prompt.push_str(&format!("\n### Existing test: {}\n```\n{}\n```\n", example.path, content));
+    }
+    prompt
+}
+
+/// The first fenced code block of a reply, or the whole reply when it has none.
+fn extract_code(reply: &str) -> String {
+    let Some(open) = reply.find("```") else { return reply.trim().to_string() };
+    let after = &reply[open + 3..];
+    let body = after.split_once('\n').map_or("", |(_, rest)| rest);
+    let code = body.find("```").map_or(body, |close| &body[..close]);
+    format!("{}\n", code.trim_end())
+}
+
+/// Generates a test file for `path`: detects the project's test framework and layout,
+/// shows the model the file, the signatures of what it calls and existing tests as
+/// examples, and returns the proposed file and where it belongs. Nothing is written;
+/// pass the proposal to `write_generated_test` to save it. Reads `files` when given,
+/// else the project's last loaded files.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn generate_tests_for(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    project: String,
+    path: String,
+    files: Option<Vec<FileEntry>>,
+    provider: Option<String>,
+    model: Option<String>,
+    url: Option<String>,
+) -> Result<TestProposal, AppError> {
+    let llm = Llm::resolve(&state, "Generating tests", provider, model, url, TEST_CONTEXT).await?;
+    let files = match files {
+        Some(files) => files,
+        None => projects::loaded_files(&app, &state, &project)?,
+    };
+    let path = paths::prompt_path(&path, Some(Path::new(&project)));
+    let _span = state.trace.span("llm", "generate_tests_for").attr("path", &path);
+
+    let target_path = path.clone();
+    let (prompt, convention, examples, exists) = tokio::task::spawn_blocking(move || {
+        let target = files.iter().find(|f| f.path == path).ok_or_else(|| format!("{} is not part of the loaded project", path))?;
+        let tests: Vec<&FileEntry> =
+            files.iter().filter(|f| is_test_path(&f.path) && same_family(extension(&f.path), extension(&target.path))).collect();
+        let convention = detect_convention(&files, &target.path, &tests)?;
+        let examples = example_tests(&tests, &target.path);
+        let prompt = build_prompt(target, &convention, &examples, &related_signatures(&files, target));
+        let exists = files.iter().any(|f| f.path == convention.test_path);
+        Ok::<_, String>((prompt, convention, examples.iter().map(|e| e.path.clone()).collect::<Vec<_>>(), exists))
+    })
+    .await
+    .map_err(|e| format!("Test generation task failed: {}", e))??;
+
+    log_status(&app, format!("Generating {} tests with {}", convention.framework, llm.model));
+    let reply = llm.complete(&state, &prompt).await?;
+    Ok(TestProposal {
+        path: target_path,
+        test_path: convention.test_path,
+        exists,
+        framework: convention.framework,
+        test_command: convention.test_command,
+        examples,
+        prompt_tokens: estimate_tokens(&prompt),
+        content: extract_code(&reply),
+    })
+}
+
+/// Writes a generated test file into a local project. `test_path` is relative to the
+/// project root; an existing file is only replaced with `overwrite`.
+#[tauri::command]
+pub async fn write_generated_test(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    project: String,
+    test_path: String,
+    content: String,
+    overwrite: Option<bool>,
+) -> Result<String, AppError> {
+    let root = Path::new(&project);
+    if !root.is_dir() {
+        return Err(AppError::InvalidInput("Tests can only be written into a local project".to_string()));
+    }
+    let relative = normalize_subpath(Some(test_path.clone()))?.ok_or_else(|| format!("Invalid test path: {}", test_path))?;
+    let target = root.join(&relative);
+    state.policy.check_export(&target)?;
+    if target.exists() && !overwrite.unwrap_or(false) {
+        return Err(AppError::InvalidInput(format!("{} already exists", relative)));
+    }
+    if let Some(dir) = target.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    std::fs::write(&target, content).map_err(|e| format!("Failed to write {}: {}", relative, e))?;
+    log_status(&app, format!("Wrote tests to {}", relative));
+    Ok(target.to_string_lossy().to_string())
+}
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub use repo_prompt_core::tokens::estimate_tokens;
+
+/// One piece of the assembled prompt: `kind` is `tree`, `readme`, `dependencies`, `file`,
+/// `template` or `block`, `label` is what the UI shows (the path, for files).
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptSection {
+    id: String,
+    kind: String,
+    label: String,
+    content: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionTokens {
+    id: String,
+    kind: String,
+    label: String,
+    chars: usize,
+    tokens: usize,
+    /// Fraction of the whole prompt, 0.0 to 1.0.
+    share: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBreakdown {
+    total_tokens: usize,
+    /// Per section, largest first, for the heatmap/treemap.
+    sections: Vec<SectionTokens>,
+    /// Totals per section kind.
+    by_kind: BTreeMap<String, usize>,
+    /// `total_tokens` as a fraction of `budget`, when a budget was given.
+    budget_used: Option<f64>,
+}
+
+/// Breaks the prompt's token usage down by section so users can see where the budget goes.
+#[tauri::command]
+pub fn prompt_token_breakdown(sections: Vec<PromptSection>, budget: Option<usize>) -> TokenBreakdown {
+    let mut counted: Vec<SectionTokens> = sections
+        .into_iter()
+        .map(|s| SectionTokens {
+            chars: s.content.chars().count(),
+            tokens: estimate_tokens(&s.content),
+            id: s.id,
+            kind: s.kind,
+            label: s.label,
+            share: 0.0,
+        })
+        .collect();
+
+    let total_tokens: usize = counted.iter().map(|s| s.tokens).sum();
+    let mut by_kind = BTreeMap::new();
+    for s in &mut counted {
+        s.share = if total_tokens == 0 { 0.0 } else { s.tokens as f64 / total_tokens as f64 };
+        *by_kind.entry(s.kind.clone()).or_insert(0) += s.tokens;
+    }
+    counted.sort_by_key(|s| std::cmp::Reverse(s.tokens));
+
+    TokenBreakdown {
+        total_tokens,
+        sections: counted,
+        by_kind,
+        budget_used: budget.filter(|b| *b > 0).map(|b| total_tokens as f64 / b as f64),
+    }
+}
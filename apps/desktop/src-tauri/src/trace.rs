@@ -0,0 +1,217 @@
+use crate::error::AppError;
+use crate::AppState;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+/// Oldest spans are dropped past this many, so a long session can't grow without bound.
+const MAX_SPANS: usize = 100_000;
+
+struct SpanRecord {
+    name: String,
+    category: &'static str,
+    /// Microseconds since the recorder was created.
+    start_us: u64,
+    duration_us: u64,
+    thread: u64,
+    attributes: Vec<(String, String)>,
+}
+
+/// In-memory span recorder for the scan, fetch, assemble and LLM stages. Nothing is
+/// recorded until tracing is enabled, and nothing leaves the machine: spans are only
+/// written to a file on export.
+pub struct TraceRecorder {
+    enabled: AtomicBool,
+    origin: Instant,
+    /// Wall-clock time matching `origin`, for formats that want absolute timestamps.
+    origin_unix_ns: u128,
+    spans: Mutex<Vec<SpanRecord>>,
+}
+
+impl Default for TraceRecorder {
+    fn default() -> Self {
+        TraceRecorder {
+            enabled: AtomicBool::new(false),
+            origin: Instant::now(),
+            origin_unix_ns: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default(),
+            spans: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+/// An open span; recorded when ended or dropped.
+pub struct Span<'a> {
+    recorder: &'a TraceRecorder,
+    name: String,
+    category: &'static str,
+    start: Instant,
+    attributes: Vec<(String, String)>,
+}
+
+impl Span<'_> {
+    pub fn attr(mut self, key: &str, value: impl ToString) -> Self {
+        self.attributes.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn end(self) {}
+}
+
+impl Drop for Span<'_> {
+    fn drop(&mut self) {
+        if !self.recorder.enabled.load(Ordering::Relaxed) {
+            return;
+        }
+        let start_us = self.start.duration_since(self.recorder.origin).as_micros() as u64;
+        self.recorder.push(SpanRecord {
+            name: std::mem::take(&mut self.name),
+            category: self.category,
+            start_us,
+            duration_us: self.start.elapsed().as_micros() as u64,
+            thread: thread_id(),
+            attributes: std::mem::take(&mut self.attributes),
+        });
+    }
+}
+
+/// Stable numeric id for the current thread; Chrome's viewer lays spans out per thread.
+fn thread_id() -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    std::thread::current().id().hash(&mut hasher);
+    hasher.finish() % 100_000
+}
+
+impl TraceRecorder {
+    /// Opens a span in `category` (`scan`, `fetch`, `assemble`, `llm`).
+    pub fn span(&self, category: &'static str, name: impl Into<String>) -> Span<'_> {
+        Span { recorder: self, name: name.into(), category, start: Instant::now(), attributes: Vec::new() }
+    }
+
+    fn push(&self, span: SpanRecord) {
+        let mut spans = self.spans.lock().unwrap();
+        if spans.len() >= MAX_SPANS {
+            spans.drain(..MAX_SPANS / 10);
+        }
+        spans.push(span);
+    }
+
+    /// Chrome trace event format, loadable in `chrome://tracing` or Perfetto.
+    fn chrome_trace(&self) -> serde_json::Value {
+        let pid = std::process::id();
+        let events: Vec<serde_json::Value> = self
+            .spans
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|s| {
+                let args: serde_json::Map<String, serde_json::Value> =
+                    s.attributes.iter().map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone()))).collect();
+                serde_json::json!({
+                    "name": s.name,
+                    "cat": s.category,
+                    "ph": "X",
+                    "ts": s.start_us,
+                    "dur": s.duration_us,
+                    "pid": pid,
+                    "tid": s.thread,
+                    "args": args,
+                })
+            })
+            .collect();
+        serde_json::json!({ "traceEvents": events, "displayTimeUnit": "ms" })
+    }
+
+    /// OTLP/JSON (`ExportTraceServiceRequest`), with every span in one trace.
+    fn otlp_trace(&self) -> serde_json::Value {
+        let trace_id = &blake3::hash(&self.origin_unix_ns.to_le_bytes()).to_hex()[..32];
+        let spans: Vec<serde_json::Value> = self
+            .spans
+            .lock()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let start_ns = self.origin_unix_ns + s.start_us as u128 * 1000;
+                let mut attributes = vec![serde_json::json!({ "key": "category", "value": { "stringValue": s.category } })];
+                attributes.extend(s.attributes.iter().map(|(k, v)| serde_json::json!({ "key": k, "value": { "stringValue": v } })));
+                serde_json::json!({
+                    "traceId": trace_id,
+                    "spanId": format!("{:016x}", i + 1),
+                    "name": s.name,
+                    "kind": 1,
+                    "startTimeUnixNano": start_ns.to_string(),
+                    "endTimeUnixNano": (start_ns + s.duration_us as u128 * 1000).to_string(),
+                    "attributes": attributes,
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "resourceSpans": [{
+                "resource": { "attributes": [{ "key": "service.name", "value": { "stringValue": "repo-prompt-generator" } }] },
+                "scopeSpans": [{ "scope": { "name": "repo-prompt-generator" }, "spans": spans }],
+            }]
+        })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceExport {
+    path: String,
+    span_count: usize,
+}
+
+/// Turns span recording on or off. Turning it on starts a fresh trace.
+#[tauri::command]
+pub fn set_tracing(state: State<'_, AppState>, enabled: bool) {
+    if enabled && !state.trace.enabled.load(Ordering::Relaxed) {
+        state.trace.spans.lock().unwrap().clear();
+    }
+    state.trace.enabled.store(enabled, Ordering::Relaxed);
+}
+
+/// Records a span measured by the frontend (e.g. prompt assembly), given as milliseconds
+/// since it started and its duration.
+#[tauri::command]
+pub fn record_span(state: State<'_, AppState>, name: String, category: Option<String>, started_ms_ago: f64, duration_ms: f64) {
+    if !state.trace.enabled.load(Ordering::Relaxed) {
+        return;
+    }
+    let category = match category.as_deref() {
+        Some("scan") => "scan",
+        Some("fetch") => "fetch",
+        Some("llm") => "llm",
+        _ => "assemble",
+    };
+    let elapsed_us = state.trace.origin.elapsed().as_micros() as u64;
+    state.trace.push(SpanRecord {
+        name,
+        category,
+        start_us: elapsed_us.saturating_sub((started_ms_ago.max(0.0) * 1000.0) as u64),
+        duration_us: (duration_ms.max(0.0) * 1000.0) as u64,
+        thread: 0,
+        attributes: vec![("source".to_string(), "frontend".to_string())],
+    });
+}
+
+/// Writes the recorded spans to `path`, as a Chrome trace (default) or OTLP JSON
+/// (`format: "otlp"`).
+#[tauri::command]
+pub fn export_trace(state: State<'_, AppState>, path: String, format: Option<String>) -> Result<TraceExport, AppError> {
+    state.policy.check_export(std::path::Path::new(&path))?;
+    let span_count = state.trace.spans.lock().unwrap().len();
+    if span_count == 0 {
+        return Err(AppError::NotFound("No spans recorded. Enable tracing and run a scan first.".to_string()));
+    }
+    let json = match format.as_deref() {
+        None | Some("chrome") => state.trace.chrome_trace(),
+        Some("otlp") => state.trace.otlp_trace(),
+        Some(other) => return Err(AppError::InvalidInput(format!("Unknown trace format: {}", other))),
+    };
+    let text = serde_json::to_string(&json).map_err(|e| e.to_string())?;
+    std::fs::write(&path, text).map_err(|e| format!("Failed to write trace: {}", e))?;
+    Ok(TraceExport { path, span_count })
+}
@@ -0,0 +1,42 @@
+use crate::error::AppError;
+use repo_prompt_core::tokens::{estimate_tokens, FileStats};
+use repo_prompt_core::tree::{self, TreeEntry, TreeOptions};
+use serde::Deserialize;
+
+/// A path from a GitHub tree, or a file from a scan with its size.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum TreeInput {
+    Path(String),
+    #[serde(rename_all = "camelCase")]
+    File {
+        path: String,
+        bytes: Option<u64>,
+        tokens: Option<usize>,
+        /// As serialized with a `FileEntry`.
+        stats: Option<FileStats>,
+        content: Option<String>,
+    },
+}
+
+impl From<TreeInput> for TreeEntry {
+    fn from(input: TreeInput) -> Self {
+        match input {
+            TreeInput::Path(path) => TreeEntry { path, bytes: None, tokens: None },
+            TreeInput::File { path, bytes, tokens, stats, content } => TreeEntry {
+                path,
+                bytes: bytes.or(stats.map(|s| s.bytes as u64)).or(content.as_ref().map(|c| c.len() as u64)),
+                tokens: tokens.or(stats.map(|s| s.tokens)).or(content.as_deref().map(estimate_tokens)),
+            },
+        }
+    }
+}
+
+/// Renders paths (a GitHub tree) or scanned files as a `├──`/`└──` tree, or a nested
+/// Markdown list with `style: "markdown"`. Files and directories can be annotated with
+/// their size and tokens, and `maxDepth`/`maxEntries` shorten the tree of a large repository.
+#[tauri::command]
+pub fn render_tree(paths: Vec<TreeInput>, options: Option<TreeOptions>) -> Result<String, AppError> {
+    let entries: Vec<TreeEntry> = paths.into_iter().map(TreeEntry::from).collect();
+    Ok(tree::render(&entries, &options.unwrap_or_default()))
+}
@@ -0,0 +1,108 @@
+use crate::error::AppError;
+use crate::AppState;
+use repo_prompt_core::usage::{estimate_cost, Usage};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelUsage {
+    provider: String,
+    model: String,
+    calls: u64,
+    usage: Usage,
+    /// `None` when the model's price isn't known.
+    estimated_cost_usd: Option<f64>,
+}
+
+impl ModelUsage {
+    pub fn new(provider: &str, model: &str) -> Self {
+        ModelUsage { provider: provider.to_string(), model: model.to_string(), calls: 0, usage: Usage::default(), estimated_cost_usd: None }
+    }
+
+    pub fn add(&mut self, usage: Option<&Usage>) {
+        self.calls += 1;
+        if let Some(usage) = usage {
+            self.usage.add(usage);
+        }
+        self.estimated_cost_usd = estimate_cost(&self.provider, &self.model, &self.usage);
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageStats {
+    /// Unix milliseconds the totals count from.
+    since: u64,
+    calls: u64,
+    usage: Usage,
+    /// Sum over the models with a known price.
+    estimated_cost_usd: f64,
+    /// Calls to models without a known price, left out of the cost.
+    unpriced_calls: u64,
+    models: Vec<ModelUsage>,
+}
+
+impl UsageStats {
+    pub fn new(since: u64, mut models: Vec<ModelUsage>) -> Self {
+        models.sort_by(|a, b| b.usage.total_tokens.cmp(&a.usage.total_tokens).then_with(|| a.model.cmp(&b.model)));
+        let mut usage = Usage::default();
+        for m in &models {
+            usage.add(&m.usage);
+        }
+        UsageStats {
+            since,
+            calls: models.iter().map(|m| m.calls).sum(),
+            usage,
+            estimated_cost_usd: models.iter().filter_map(|m| m.estimated_cost_usd).sum(),
+            unpriced_calls: models.iter().filter(|m| m.estimated_cost_usd.is_none()).map(|m| m.calls).sum(),
+            models,
+        }
+    }
+}
+
+pub fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+/// Tokens spent on model calls since the app started (or the last reset), per model.
+pub struct UsageTracker {
+    models: Mutex<HashMap<(String, String), ModelUsage>>,
+    since: Mutex<u64>,
+}
+
+impl Default for UsageTracker {
+    fn default() -> Self {
+        UsageTracker { models: Mutex::new(HashMap::new()), since: Mutex::new(now_ms()) }
+    }
+}
+
+impl UsageTracker {
+    /// Counts one call to `model`; `usage` is `None` when the provider didn't report it.
+    pub fn record(&self, provider: &str, model: &str, usage: Option<&Usage>) {
+        let mut models = self.models.lock().unwrap();
+        models.entry((provider.to_string(), model.to_string())).or_insert_with(|| ModelUsage::new(provider, model)).add(usage);
+    }
+}
+
+/// Tokens used and their estimated cost, per model: for the chat session `session_id`
+/// when given, otherwise for every call since the app started. `reset` starts the app
+/// totals over after reading them.
+#[tauri::command]
+pub fn get_usage_stats(state: State<'_, AppState>, session_id: Option<String>, reset: Option<bool>) -> Result<UsageStats, AppError> {
+    if let Some(id) = session_id {
+        let (since, usage) = state.sessions.usage(&id)?;
+        return Ok(UsageStats::new(since, vec![usage]));
+    }
+    let mut models = state.usage.models.lock().unwrap();
+    let mut since = state.usage.since.lock().unwrap();
+    let stats = UsageStats::new(*since, models.values().cloned().collect());
+    if reset.unwrap_or(false) {
+        models.clear();
+        *since = now_ms();
+    }
+    Ok(stats)
+}
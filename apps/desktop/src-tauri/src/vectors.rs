@@ -0,0 +1,303 @@
+use crate::error::AppError;
+use crate::chunking::{chunk_file, Chunk};
+use crate::progress::Progress;
+use crate::{cache, embedcache, gemini, log_status, ollama, paths, projects, AppState, FileEntry};
+use repo_prompt_core::embeddings::{normalize, rank_by_similarity};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::{AppHandle, State};
+
+const NAMESPACE: &str = "vectors";
+const CHUNK_TOKENS: usize = 384;
+/// Repeated at the start of the next chunk, so code split at a boundary is still found
+/// with its surroundings.
+const OVERLAP_TOKENS: usize = 24;
+const DEFAULT_TOP_K: usize = 8;
+const DEFAULT_MAX_FILES: usize = 10;
+const SNIPPETS_PER_FILE: usize = 3;
+/// Snippets are cut to about this many characters.
+const SNIPPET_CHARS: usize = 400;
+const DEFAULT_OLLAMA_MODEL: &str = "nomic-embed-text";
+
+#[derive(Serialize, Deserialize)]
+struct IndexedChunk {
+    path: String,
+    start_line: usize,
+    end_line: usize,
+    /// blake3 of the embedded text; unchanged chunks keep their vector on reindex.
+    hash: String,
+    content: String,
+    /// Normalized to unit length, so the dot product is the cosine similarity.
+    vector: Vec<f32>,
+}
+
+/// Embedded chunks of one project, stored in the `vectors` cache namespace.
+#[derive(Serialize, Deserialize)]
+struct VectorIndex {
+    provider: String,
+    model: String,
+    /// Ollama address the index was built with.
+    url: Option<String>,
+    chunks: Vec<IndexedChunk>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexReport {
+    files: usize,
+    chunks: usize,
+    /// Chunks whose vector was reused from the previous index or the embedding cache.
+    reused: usize,
+    embedded: usize,
+    dimensions: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexHit {
+    path: String,
+    start_line: usize,
+    end_line: usize,
+    content: String,
+    /// Cosine similarity to the query, -1.0 to 1.0.
+    score: f32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snippet {
+    start_line: usize,
+    end_line: usize,
+    score: f32,
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileMatch {
+    path: String,
+    /// Score of the file's best chunk.
+    score: f32,
+    /// Best chunks first.
+    snippets: Vec<Snippet>,
+}
+
+/// Start of a chunk, cut at a line boundary where possible.
+fn snippet_text(content: &str) -> String {
+    let Some((cut, _)) = content.char_indices().nth(SNIPPET_CHARS) else { return content.to_string() };
+    let cut = content[..cut].rfind('\n').filter(|&i| i > 0).unwrap_or(cut);
+    format!("{}\n…", &content[..cut])
+}
+
+/// Text sent for embedding; the path is included since it says a lot about the code.
+fn embed_text(chunk: &Chunk) -> String {
+    format!("{}\n{}", chunk.path, chunk.content)
+}
+
+/// Embeds `inputs` with the index's provider. `query` selects Gemini's query task type.
+/// Document embedding is reported to `progress` when given.
+async fn embed(app: &AppHandle, state: &AppState, index: &VectorIndex, inputs: &[String], query: bool, progress: Option<&Progress>) -> Result<Vec<Vec<f32>>, String> {
+    state.policy.check_provider(&index.provider)?;
+    if inputs.is_empty() {
+        return Ok(Vec::new());
+    }
+    let inputs: Vec<String> = inputs.iter().map(|i| state.policy.redact(i)).collect();
+    let total = inputs.len();
+    let report = |done: usize| {
+        if !query {
+            log_status(app, format!("Embedded {} of {} chunks", done, total));
+        }
+        if let Some(progress) = progress {
+            progress.update("embedding", done as u64, Some(total as u64), format!("Embedded {} of {} chunks", done, total));
+        }
+    };
+    let vectors = match index.provider.as_str() {
+        "ollama" => {
+            let url = index.url.as_deref().unwrap_or(ollama::DEFAULT_OLLAMA_URL);
+            ollama::embed_many(state, url, &index.model, &inputs, ollama::DEFAULT_EMBED_BATCH, None, report).await?
+        }
+        "gemini" => gemini::embed_many(state, &index.model, &inputs, if query { "RETRIEVAL_QUERY" } else { "RETRIEVAL_DOCUMENT" }, report).await?,
+        other => return Err(format!("Embeddings are not supported for provider: {}", other)),
+    };
+    Ok(vectors.into_iter().map(normalize).collect())
+}
+
+fn load_index(app: &AppHandle, state: &AppState, project: &str) -> Option<VectorIndex> {
+    let bytes = cache::open_cache(app, state, NAMESPACE).ok()?.get(project)?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Embedding provider, model and Ollama address for an index, defaulting to those of
+/// `existing` and then to a local Ollama with [`DEFAULT_OLLAMA_MODEL`].
+async fn settings(state: &AppState, existing: Option<&VectorIndex>, provider: Option<String>, model: Option<String>, url: Option<String>) -> (String, String, Option<String>) {
+    let provider = match provider.filter(|p| !p.trim().is_empty()) {
+        Some(p) => p.trim().to_lowercase(),
+        None => existing.map(|i| i.provider.clone()).unwrap_or_else(|| "ollama".to_string()),
+    };
+    let same_provider = existing.filter(|i| i.provider == provider);
+    let model = model
+        .filter(|m| !m.trim().is_empty())
+        .or_else(|| same_provider.map(|i| i.model.clone()))
+        .unwrap_or_else(|| if provider == "gemini" { gemini::DEFAULT_EMBED_MODEL } else { DEFAULT_OLLAMA_MODEL }.to_string());
+    let url = match (provider.as_str(), url.filter(|u| !u.trim().is_empty())) {
+        ("ollama", Some(u)) => Some(ollama::normalize_url(&u)),
+        ("ollama", None) => match same_provider.and_then(|i| i.url.clone()) {
+            Some(u) => Some(u),
+            None => Some(state.ollama_url.read().await.clone().unwrap_or_else(|| ollama::DEFAULT_OLLAMA_URL.to_string())),
+        },
+        _ => None,
+    };
+    (provider, model, url)
+}
+
+/// Indexes `files` for `project`, reusing the vectors of unchanged chunks, and stores it.
+async fn build_index(
+    app: &AppHandle,
+    state: &AppState,
+    project: &str,
+    files: &[FileEntry],
+    (provider, model, url): (String, String, Option<String>),
+    previous: Option<VectorIndex>,
+    progress: Option<&Progress>,
+) -> Result<(VectorIndex, IndexReport), String> {
+    let _span = state.trace.span("llm", "index_repository").attr("files", files.len());
+    let chunks: Vec<Chunk> = files
+        .iter()
+        .flat_map(|f| chunk_file(f, CHUNK_TOKENS, OVERLAP_TOKENS))
+        .map(|c| Chunk { path: paths::prompt_path(&c.path, None), ..c })
+        .collect();
+    let hashes: Vec<String> = chunks.iter().map(|c| blake3::hash(embed_text(c).as_bytes()).to_hex().to_string()).collect();
+
+    // Vectors from the previous index are only comparable when built by the same model.
+    let mut known: HashMap<String, Vec<f32>> = match previous {
+        Some(old) if old.provider == provider && old.model == model => old.chunks.into_iter().map(|c| (c.hash, c.vector)).collect(),
+        _ => HashMap::new(),
+    };
+    let mut index = VectorIndex { provider, model, url, chunks: Vec::with_capacity(chunks.len()) };
+    let mut missing: Vec<usize> = (0..chunks.len()).filter(|&i| !known.contains_key(&hashes[i])).collect();
+    // Chunks the same model embedded before, for this project or any other.
+    let store = embedcache::EmbeddingCache::open(app, state, &index.provider, &index.model).ok();
+    if let Some(store) = store.as_ref().filter(|_| !missing.is_empty()) {
+        known.extend(store.get_many(missing.iter().map(|&i| &hashes[i])));
+        missing.retain(|&i| !known.contains_key(&hashes[i]));
+    }
+    if !missing.is_empty() {
+        log_status(app, format!("Indexing {}: {} chunks, {} to embed", project, chunks.len(), missing.len()));
+    }
+    let inputs: Vec<String> = missing.iter().map(|&i| embed_text(&chunks[i])).collect();
+    let fresh: Vec<(String, Vec<f32>)> = missing.iter().map(|&i| hashes[i].clone()).zip(embed(app, state, &index, &inputs, false, progress).await?).collect();
+    if let Some(store) = &store {
+        match store.put_many(&fresh) {
+            Ok(0) => {}
+            Ok(evicted) => log_status(app, format!("Evicted {} least recently used embeddings to stay within the cache limit", evicted)),
+            Err(e) => log_status(app, format!("Embeddings were not cached: {}", e)),
+        }
+    }
+    known.extend(fresh);
+
+    for (chunk, hash) in chunks.into_iter().zip(hashes) {
+        let Some(vector) = known.get(&hash).cloned() else { continue };
+        index.chunks.push(IndexedChunk { path: chunk.path, start_line: chunk.start_line, end_line: chunk.end_line, hash, content: chunk.content, vector });
+    }
+    let dimensions = index.chunks.first().map(|c| c.vector.len()).unwrap_or_default();
+    let data = serde_json::to_vec(&index).map_err(|e| e.to_string())?;
+    cache::open_cache(app, state, NAMESPACE)?.put(project, &data)?;
+
+    let report = IndexReport { files: files.len(), chunks: index.chunks.len(), reused: index.chunks.len() - inputs.len(), embedded: inputs.len(), dimensions };
+    Ok((index, report))
+}
+
+/// Chunks of `index` by similarity to `query`, best first.
+async fn rank<'a>(app: &AppHandle, state: &AppState, index: &'a VectorIndex, query: String) -> Result<Vec<(f32, &'a IndexedChunk)>, String> {
+    let query = embed(app, state, index, &[query], true, None).await?.pop().unwrap_or_default();
+    if index.chunks.first().is_some_and(|c| c.vector.len() != query.len()) {
+        return Err("The query embedding doesn't match the index; reindex the project".to_string());
+    }
+    Ok(rank_by_similarity(&query, &index.chunks, |c| &c.vector))
+}
+
+/// Chunks and embeds a project's files into a persistent vector index for retrieval.
+/// `files` defaults to the files of the last load of `project` (a scanned directory or
+/// `owner/repo`). `provider` is `ollama` (at `url`) or `gemini`, by default the one the
+/// project was last indexed with, else Ollama. Reindexing only embeds chunks that
+/// changed since the last run with the same model.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn index_repository(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    project: String,
+    files: Option<Vec<FileEntry>>,
+    provider: Option<String>,
+    model: Option<String>,
+    url: Option<String>,
+    operation_id: Option<String>,
+) -> Result<IndexReport, AppError> {
+    let files = match files {
+        Some(files) => files,
+        None => projects::loaded_files(&app, &state, &project)?,
+    };
+    let previous = load_index(&app, &state, &project);
+    let settings = settings(&state, previous.as_ref(), provider, model, url).await;
+    let progress = state.progress.start(&app, "index", operation_id);
+    progress.stage("chunking", format!("Chunking {} files", files.len()));
+    let (_, report) = build_index(&app, &state, &project, &files, settings, previous, Some(&progress)).await?;
+    log_status(&app, format!("Indexed {} chunks from {} files", report.chunks, report.files));
+    progress.finish(format!("Indexed {} chunks from {} files", report.chunks, report.files));
+    Ok(report)
+}
+
+/// Returns the `top_k` (default 8) chunks of `project`'s index most similar to `query`,
+/// best first.
+#[tauri::command]
+pub async fn query_index(app: AppHandle, state: State<'_, AppState>, project: String, query: String, top_k: Option<usize>) -> Result<Vec<IndexHit>, AppError> {
+    let index = load_index(&app, &state, &project).ok_or_else(|| format!("{} has not been indexed yet", project))?;
+    let _span = state.trace.span("llm", "query_index").attr("chunks", index.chunks.len());
+    Ok(rank(&app, &state, &index, query)
+        .await?
+        .into_iter()
+        .take(top_k.unwrap_or(DEFAULT_TOP_K).max(1))
+        .map(|(score, c)| IndexHit { path: c.path.clone(), start_line: c.start_line, end_line: c.end_line, content: c.content.clone(), score })
+        .collect())
+}
+
+/// Finds the files of a scanned directory or `owner/repo` most relevant to `query`,
+/// with the best-matching snippets of each. The project's index is brought up to date
+/// with its loaded files first (only changed chunks are embedded); a project that was
+/// indexed but isn't loaded is searched as indexed. Provider settings default to those
+/// the index was built with.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn semantic_search(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    project: String,
+    query: String,
+    max_files: Option<usize>,
+    provider: Option<String>,
+    model: Option<String>,
+    url: Option<String>,
+) -> Result<Vec<FileMatch>, AppError> {
+    let _span = state.trace.span("llm", "semantic_search");
+    let existing = load_index(&app, &state, &project);
+    let index = match projects::loaded_files(&app, &state, &project) {
+        Ok(files) => {
+            let settings = settings(&state, existing.as_ref(), provider, model, url).await;
+            build_index(&app, &state, &project, &files, settings, existing, None).await?.0
+        }
+        Err(e) => existing.ok_or(e)?,
+    };
+
+    let mut matches: Vec<FileMatch> = Vec::new();
+    for (score, chunk) in rank(&app, &state, &index, query).await? {
+        let snippet = Snippet { start_line: chunk.start_line, end_line: chunk.end_line, score, text: snippet_text(&chunk.content) };
+        match matches.iter_mut().find(|m| m.path == chunk.path) {
+            Some(m) if m.snippets.len() < SNIPPETS_PER_FILE => m.snippets.push(snippet),
+            Some(_) => {}
+            // Chunks come best first, so a file's first chunk sets its score.
+            None => matches.push(FileMatch { path: chunk.path.clone(), score, snippets: vec![snippet] }),
+        }
+    }
+    matches.truncate(max_files.unwrap_or(DEFAULT_MAX_FILES).max(1));
+    Ok(matches)
+}
@@ -0,0 +1,206 @@
+use crate::error::AppError;
+use crate::llm::Llm;
+use crate::{log_status, normalize_subpath, paths, AppState};
+use notify::{EventKind, RecursiveMode, Watcher};
+use repo_prompt_core::ignore::IgnoreRules;
+use repo_prompt_core::normalize::Normalization;
+use repo_prompt_core::pack::{self, PackFormat, PackOptions};
+use repo_prompt_core::scan::{is_skipped_name, read_directory_ignoring};
+use repo_prompt_core::tokens::PackStats;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::mpsc;
+
+/// A fresh pack of a watched repository.
+pub const PACK_EVENT: &str = "watch://pack";
+const DEFAULT_DEBOUNCE_MS: u64 = 500;
+/// `num_ctx` for an Ollama analysis, which gets the whole pack.
+const ANALYSIS_CONTEXT: usize = 32_768;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchAnalysis {
+    /// Sent ahead of the pack, e.g. "Review the latest changes".
+    instructions: String,
+    /// `ollama` (default) or `gemini`.
+    provider: Option<String>,
+    model: Option<String>,
+    url: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchOptions {
+    /// `md` (default), `xml` or `txt`.
+    format: Option<String>,
+    /// Token budget of the pack; without one every file is packed.
+    budget: Option<usize>,
+    #[serde(default)]
+    keep_duplicates: bool,
+    #[serde(default)]
+    keep_vendored: bool,
+    #[serde(default)]
+    normalization: Normalization,
+    /// Quiet time after the last change before re-packing, in milliseconds.
+    debounce_ms: Option<u64>,
+    /// Model run on every fresh pack.
+    analysis: Option<WatchAnalysis>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchedPack {
+    watch_id: String,
+    /// 0 for the pack made when the watch starts, then one more per re-pack.
+    revision: u64,
+    /// Paths whose changes led to this pack, relative to the repository root.
+    changed: Vec<String>,
+    prompt: String,
+    stats: PackStats,
+    /// Files left out to fit the budget.
+    omitted: Vec<String>,
+    /// The analysis model's reply, when one is configured and answered.
+    analysis: Option<String>,
+    analysis_error: Option<String>,
+}
+
+/// Watches running, by ID. Dropping a watcher ends its re-pack loop.
+#[derive(Default)]
+pub struct Watches {
+    active: Mutex<HashMap<String, notify::RecommendedWatcher>>,
+    counter: AtomicU64,
+}
+
+/// What one watch packs, and how.
+struct Packer {
+    id: String,
+    root: PathBuf,
+    subpath: Option<String>,
+    name: String,
+    options: PackOptions,
+    analysis: Option<(Llm, String)>,
+}
+
+impl Packer {
+    /// `path` relative to the root, when a change there can change the pack.
+    fn relevant(&self, path: &Path, ignore: &IgnoreRules) -> Option<String> {
+        let relative = path.strip_prefix(&self.root).ok()?.to_string_lossy().replace('\\', "/");
+        let in_scope = self.subpath.as_ref().map_or(true, |sub| relative.starts_with(&format!("{}/", sub)));
+        let skipped = relative.split('/').any(is_skipped_name) || ignore.is_ignored(&relative, path.is_dir());
+        (in_scope && !skipped && !relative.is_empty()).then_some(relative)
+    }
+
+    async fn publish(&self, app: &AppHandle, state: &AppState, revision: u64, changed: Vec<String>) {
+        let ignore = IgnoreRules::for_directory(&self.root, &state.ignore_patterns.read().await);
+        let dir = self.subpath.as_ref().map_or_else(|| self.root.clone(), |sub| self.root.join(sub));
+        let mut files = read_directory_ignoring(dir, &self.root, &ignore, |_, _| {}).await;
+        paths::make_relative(&mut files, &self.root);
+        let pack = pack::assemble(self.name.clone(), None, files, self.subpath.as_deref(), &self.options);
+        let prompt = state.policy.redact(&pack::render(&pack, self.options.format));
+
+        let (analysis, analysis_error) = match &self.analysis {
+            Some((llm, instructions)) => match llm.complete(state, &format!("{}\n\n{}", instructions, prompt)).await {
+                Ok(reply) => (Some(reply), None),
+                Err(e) => (None, Some(e)),
+            },
+            None => (None, None),
+        };
+        log_status(app, format!("Re-packed {} ({} files)", self.name, pack.files.len()));
+        let _ = app.emit(
+            PACK_EVENT,
+            WatchedPack { watch_id: self.id.clone(), revision, changed, stats: PackStats::of(&pack.files), omitted: pack.omitted, prompt, analysis, analysis_error },
+        );
+    }
+}
+
+/// Watches the local repository at `path` (or its `subpath`) and re-packs it whenever
+/// files in it change, once changes have been quiet for `debounceMs` (default 500).
+/// Every pack, the first one right away, is emitted as a `watch://pack` event with the
+/// prompt and the changed paths. With `analysis`, the model's reply to the instructions
+/// plus the pack comes with it. Returns the watch ID for `stop_watch`.
+#[tauri::command]
+pub async fn start_watch(app: AppHandle, state: State<'_, AppState>, path: String, subpath: Option<String>, options: Option<WatchOptions>) -> Result<String, AppError> {
+    let options = options.unwrap_or_default();
+    let subpath = normalize_subpath(subpath)?;
+    let root = PathBuf::from(&path);
+    let dir = subpath.as_ref().map_or_else(|| root.clone(), |sub| root.join(sub));
+    if !dir.is_dir() {
+        return Err(AppError::NotFound(format!("Directory not found: {}", dir.display())));
+    }
+    state.policy.check_scan_path(&app, &dir)?;
+    // Events name paths under the directory as it was watched; canonical paths keep them
+    // comparable with the root.
+    let (root, dir) = (root.canonicalize()?, dir.canonicalize()?);
+    let format = PackFormat::parse(options.format.as_deref().unwrap_or("md"))?;
+    let analysis = match options.analysis {
+        Some(a) => Some((Llm::resolve(&state, "Watch analysis", a.provider, a.model, a.url, ANALYSIS_CONTEXT).await?, a.instructions)),
+        None => None,
+    };
+
+    let id = format!("watch-{}", state.watches.counter.fetch_add(1, Ordering::Relaxed) + 1);
+    let (tx, mut rx) = mpsc::unbounded_channel::<Vec<PathBuf>>();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        match event {
+            Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+                let _ = tx.send(event.paths);
+            }
+            _ => {}
+        }
+    })
+    .map_err(|e| AppError::Io(format!("Failed to start watching {}: {}", dir.display(), e)))?;
+    watcher.watch(&dir, RecursiveMode::Recursive).map_err(|e| AppError::Io(format!("Failed to watch {}: {}", dir.display(), e)))?;
+
+    let packer = Packer {
+        id: id.clone(),
+        name: root.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| path.clone()),
+        root,
+        subpath,
+        options: PackOptions {
+            format,
+            budget: options.budget,
+            keep_duplicates: options.keep_duplicates,
+            keep_vendored: options.keep_vendored,
+            scoring: state.scoring.read().await.clone(),
+            normalization: options.normalization,
+        },
+        analysis,
+    };
+    let debounce = Duration::from_millis(options.debounce_ms.unwrap_or(DEFAULT_DEBOUNCE_MS).clamp(50, 60_000));
+    state.watches.active.lock().unwrap().insert(id.clone(), watcher);
+    log_status(&app, format!("Watching {} for changes", path));
+
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        packer.publish(&app, &state, 0, Vec::new()).await;
+        let mut revision = 0;
+        // Ends once the watcher is dropped by `stop_watch`.
+        while let Some(paths) = rx.recv().await {
+            let mut changed: BTreeSet<PathBuf> = paths.into_iter().collect();
+            while let Ok(Some(more)) = tokio::time::timeout(debounce, rx.recv()).await {
+                changed.extend(more);
+            }
+            let ignore = IgnoreRules::for_directory(&packer.root, &state.ignore_patterns.read().await);
+            let changed: Vec<String> = changed.iter().filter_map(|p| packer.relevant(p, &ignore)).collect();
+            if !changed.is_empty() {
+                revision += 1;
+                packer.publish(&app, &state, revision, changed).await;
+            }
+        }
+    });
+    Ok(id)
+}
+
+/// Stops a watch started by `start_watch`; false when it isn't running.
+#[tauri::command]
+pub fn stop_watch(app: AppHandle, state: State<'_, AppState>, watch_id: String) -> Result<bool, AppError> {
+    let stopped = state.watches.active.lock().unwrap().remove(&watch_id).is_some();
+    if stopped {
+        log_status(&app, format!("Stopped {}", watch_id));
+    }
+    Ok(stopped)
+}
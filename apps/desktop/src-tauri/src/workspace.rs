@@ -0,0 +1,301 @@
+use crate::error::AppError;
+use crate::{cache, github, log_status, paths, AppState};
+use repo_prompt_core::dedup::{self, DuplicateGroup, VendoredFile};
+use repo_prompt_core::ignore::{IgnoreRules, IGNORE_FILE};
+use repo_prompt_core::normalize::{self, Normalization};
+use repo_prompt_core::pack::{self, Pack, PackFormat};
+use repo_prompt_core::ranking::ScoringRules;
+use repo_prompt_core::repourl::{parse_repo_url, Provider};
+use repo_prompt_core::scan::{normalize_subpath, read_directory_ignoring};
+use repo_prompt_core::tokens::PackStats;
+use repo_prompt_core::FileEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSource {
+    /// A local directory, or a GitHub repository as `owner/repo` or a URL.
+    source: String,
+    /// Directory the source's files are listed under; defaults to its directory or
+    /// repository name.
+    name: Option<String>,
+    /// Branch, tag or commit of a repository; otherwise the URL's, or the default branch.
+    git_ref: Option<String>,
+    subpath: Option<String>,
+    /// Share of the budget relative to the other sources (default 1).
+    weight: Option<f64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceOptions {
+    /// `md` (default), `xml` or `txt`.
+    format: Option<String>,
+    /// Token budget of the whole pack, shared by the sources; without one every file is packed.
+    budget: Option<usize>,
+    #[serde(default)]
+    keep_duplicates: bool,
+    #[serde(default)]
+    keep_vendored: bool,
+    #[serde(default)]
+    normalization: Normalization,
+    /// Decides which files of each source make its share; the settings' rules by default.
+    scoring: Option<ScoringRules>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSourceReport {
+    name: String,
+    source: String,
+    /// Commit a repository was packed at; `None` for a local directory.
+    commit_sha: Option<String>,
+    files: usize,
+    /// Files left out to fit the source's share of the budget.
+    omitted: usize,
+    tokens: usize,
+    /// The source's share of the budget, when there is one.
+    budget: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspacePack {
+    prompt: String,
+    sources: Vec<WorkspaceSourceReport>,
+    stats: PackStats,
+    /// Files left out to fit the budget, with their source's prefix.
+    omitted: Vec<String>,
+}
+
+/// One source, read and namespaced.
+struct Loaded {
+    name: String,
+    source: String,
+    commit_sha: Option<String>,
+    weight: f64,
+    files: Vec<FileEntry>,
+}
+
+/// `base`, or `base-2`, `base-3`, ... when another source already took it.
+fn unique_name(base: &str, taken: &mut HashSet<String>) -> String {
+    let mut name = base.to_string();
+    let mut n = 1;
+    while !taken.insert(name.clone()) {
+        n += 1;
+        name = format!("{}-{}", base, n);
+    }
+    name
+}
+
+/// Splits `budget` across sources in proportion to `weights`. A source needing less than
+/// its share gets what it needs, and the rest is split again among the others.
+fn split_budget(budget: usize, needs: &[usize], weights: &[f64]) -> Vec<usize> {
+    let mut shares = vec![0; needs.len()];
+    let mut open: Vec<usize> = (0..needs.len()).collect();
+    let mut left = budget;
+    while !open.is_empty() {
+        let total: f64 = open.iter().map(|&i| weights[i]).sum();
+        let share = |i: usize| (left as f64 * weights[i] / total) as usize;
+        let (satisfied, rest): (Vec<usize>, Vec<usize>) = open.iter().partition(|&&i| needs[i] <= share(i));
+        if satisfied.is_empty() {
+            for &i in &rest {
+                shares[i] = share(i);
+            }
+            break;
+        }
+        for &i in &satisfied {
+            shares[i] = needs[i];
+            left -= needs[i];
+        }
+        open = rest;
+    }
+    shares
+}
+
+async fn load_local(app: &AppHandle, state: &AppState, root: &Path, subpath: Option<&str>) -> Result<Vec<FileEntry>, AppError> {
+    let dir = match subpath {
+        Some(sub) => root.join(sub),
+        None => root.to_path_buf(),
+    };
+    if !dir.is_dir() {
+        return Err(AppError::NotFound(format!("Directory not found: {}", dir.display())));
+    }
+    state.policy.check_scan_path(app, &dir)?;
+    let ignore = IgnoreRules::for_directory(root, &state.ignore_patterns.read().await);
+    let mut files = read_directory_ignoring(dir, root, &ignore, |_, _| {}).await;
+    paths::make_relative(&mut files, root);
+    Ok(files)
+}
+
+/// The repository's files at `git_ref` (its default branch when `None`), from one
+/// tarball download. Returns the commit they were taken at.
+async fn load_github(
+    state: &AppState,
+    gh: &github::GithubClient,
+    owner: &str,
+    repo: &str,
+    git_ref: Option<String>,
+    subpath: Option<&str>,
+) -> Result<(String, Vec<FileEntry>), AppError> {
+    let git_ref = match git_ref {
+        Some(r) => r,
+        None => gh.get_json(&format!("/repos/{}/{}", owner, repo)).await?["default_branch"].as_str().unwrap_or("main").to_string(),
+    };
+    let sha = gh.resolve_ref(owner, repo, &git_ref).await?;
+    let work_dir = state.temp_dirs.create("workspace")?;
+    let tarball = gh.fetch_tarball(owner, repo, &sha, work_dir.path()).await?;
+    let ignore = IgnoreRules::layered(&state.ignore_patterns.read().await, tarball.files.get(IGNORE_FILE).map(String::as_str));
+    let prefix = subpath.map(|s| format!("{}/", s)).unwrap_or_default();
+    let files = tarball
+        .files
+        .into_iter()
+        .filter(|(path, _)| path.starts_with(&prefix) && !ignore.is_ignored(path, false))
+        .map(|(path, content)| FileEntry::new(path, content))
+        .collect();
+    Ok((sha, files))
+}
+
+/// Packs several local directories and GitHub repositories into one prompt. Each
+/// source's files are listed under its name (`api/src/main.rs`, `web/src/app.ts`), and
+/// the budget is shared by weight: what a source doesn't need goes to the others, and
+/// each source keeps its highest-scoring files within its share. Vendored files and
+/// duplicates are left out per source unless the options keep them. Progress is
+/// reported on `progress://{operation_id}`.
+#[tauri::command]
+pub async fn pack_workspace(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    sources: Vec<WorkspaceSource>,
+    options: Option<WorkspaceOptions>,
+    token: Option<String>,
+    operation_id: Option<String>,
+) -> Result<WorkspacePack, AppError> {
+    if sources.is_empty() {
+        return Err(AppError::InvalidInput("A workspace needs at least one source".to_string()));
+    }
+    if sources.iter().any(|s| s.weight.is_some_and(|w| !w.is_finite() || w <= 0.0)) {
+        return Err(AppError::InvalidInput("Source weights must be positive".to_string()));
+    }
+    let options = options.unwrap_or_default();
+    let format = PackFormat::parse(options.format.as_deref().unwrap_or("md"))?;
+    let scoring = match options.scoring {
+        Some(rules) => rules,
+        None => state.scoring.read().await.clone(),
+    };
+    scoring.validate().map_err(AppError::InvalidInput)?;
+
+    let progress = state.progress.start(&app, "workspace", operation_id);
+    let span = state.trace.span("pack", "workspace").attr("sources", sources.len());
+    let mut gh = None;
+    let mut taken = HashSet::new();
+    let total = sources.len();
+    let mut loaded = Vec::with_capacity(total);
+    for (i, source) in sources.into_iter().enumerate() {
+        let subpath = normalize_subpath(source.subpath.clone())?;
+        let label = source.source.trim().to_string();
+        log_status(&app, format!("Reading {} ({} of {})", label, i + 1, total));
+        progress.update("reading", i as u64, Some(total as u64), format!("Reading {}", label));
+        let local = PathBuf::from(&label);
+        let (default_name, commit_sha, mut files) = if local.is_dir() {
+            let name = local.canonicalize().ok().and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string())).unwrap_or_else(|| label.clone());
+            (name, None, load_local(&app, &state, &local, subpath.as_deref()).await?)
+        } else {
+            let url = parse_repo_url(&label).map_err(|_| AppError::NotFound(format!("Neither a directory nor a repository: {}", label)))?;
+            if url.provider == Provider::Gitlab {
+                return Err(AppError::InvalidInput(format!("Only GitHub repositories can be packed into a workspace: {}", label)));
+            }
+            if gh.is_none() {
+                state.policy.check_not_demo("GitHub access")?;
+                gh = Some(
+                    github::GithubClient::new(state.http_client.read().await.clone(), token.clone().unwrap_or_default())
+                        .with_cache(cache::open_cache(&app, &state, "github").ok()),
+                );
+            }
+            let gh = gh.as_ref().expect("client created above");
+            let subpath = subpath.clone().or(url.subpath);
+            let (sha, files) = load_github(&state, gh, &url.owner, &url.repo, source.git_ref.or(url.git_ref), subpath.as_deref()).await?;
+            (url.repo, Some(sha), files)
+        };
+
+        let name = source.name.as_deref().map(|n| n.trim_matches('/')).filter(|n| !n.is_empty()).unwrap_or(&default_name).to_string();
+        let name = unique_name(&name, &mut taken);
+        // Tarballs list files they don't keep the content of (binary or too large).
+        files.retain(|f| !f.content.is_empty());
+        for file in &mut files {
+            file.path = format!("{}/{}", name, file.path);
+        }
+        loaded.push(Loaded { name, source: label, commit_sha, weight: source.weight.unwrap_or(1.0), files });
+    }
+
+    progress.stage("packing", "Packing the workspace");
+    let mut tree = Vec::new();
+    let mut duplicates: Vec<DuplicateGroup> = Vec::new();
+    let mut vendored: Vec<VendoredFile> = Vec::new();
+    for source in &mut loaded {
+        let mut files = std::mem::take(&mut source.files);
+        normalize::normalize_files(&mut files, &options.normalization);
+        tree.extend(files.iter().map(|f| f.path.clone()));
+        if !options.keep_vendored {
+            let (kept, left_out) = dedup::split_vendored(files);
+            vendored.extend(left_out);
+            files = kept;
+        }
+        if !options.keep_duplicates {
+            let (kept, groups) = dedup::dedup(files);
+            duplicates.extend(groups);
+            files = kept;
+        }
+        source.files = files;
+    }
+    tree.sort();
+
+    let names: Vec<&str> = loaded.iter().map(|s| s.name.as_str()).collect();
+    let refs: Vec<String> = loaded.iter().filter_map(|s| s.commit_sha.as_ref().map(|sha| format!("{} at {}", s.name, &sha[..sha.len().min(7)]))).collect();
+    let mut pack = Pack {
+        name: format!("Workspace: {}", names.join(", ")),
+        git_ref: (!refs.is_empty()).then(|| refs.join(", ")),
+        tree,
+        readme: None,
+        files: Vec::new(),
+        omitted: Vec::new(),
+        duplicates,
+        vendored,
+    };
+
+    let shares = options.budget.map(|budget| {
+        let available = budget.saturating_sub(pack::header_tokens(&pack, format));
+        let needs: Vec<usize> = loaded.iter().map(|s| s.files.iter().map(pack::file_tokens).sum()).collect();
+        let weights: Vec<f64> = loaded.iter().map(|s| s.weight).collect();
+        split_budget(available, &needs, &weights)
+    });
+    let mut reports = Vec::with_capacity(loaded.len());
+    for (i, source) in loaded.into_iter().enumerate() {
+        let share = shares.as_ref().map(|s| s[i]);
+        let (files, omitted) = match share {
+            Some(share) => pack::fit_budget(source.files, share, 0, &scoring),
+            None => (source.files, Vec::new()),
+        };
+        reports.push(WorkspaceSourceReport {
+            name: source.name,
+            source: source.source,
+            commit_sha: source.commit_sha,
+            files: files.len(),
+            omitted: omitted.len(),
+            tokens: files.iter().map(pack::file_tokens).sum(),
+            budget: share,
+        });
+        pack.files.extend(files);
+        pack.omitted.extend(omitted);
+    }
+
+    let prompt = state.policy.redact(&pack::render(&pack, format));
+    let stats = PackStats::of(&pack.files);
+    span.attr("files", stats.files).end();
+    log_status(&app, format!("Workspace packed: {} files from {} sources", stats.files, reports.len()));
+    progress.finish(format!("{} files from {} sources", stats.files, reports.len()));
+    Ok(WorkspacePack { prompt, sources: reports, stats, omitted: pack.omitted })
+}
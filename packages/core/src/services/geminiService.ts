@@ -1,10 +1,37 @@
 /// <reference types="vite/client" />
 import { GoogleGenAI, Type } from "@google/genai";
 import { RepoData } from "./githubService";
+import type { TokenUsage } from "./ollamaService";
 import { buildCodeDependencyGraph } from "../utils/codeGraph";
 import { isTauri, tauriInvoke } from "../utils/tauriAdapter.ts";
 import { safeJsonParse } from "../utils/jsonUtils.ts";
 
+/** What the desktop backend's `call_gemini_secure` returns; the reply text is `text`. */
+export interface GeminiReply {
+  text: string;
+  finishReason: string | null;
+  /** The model stopped at the output token limit; the text is incomplete. */
+  truncated: boolean;
+  /** The body wasn't valid JSON and the text was recovered from what could be read. */
+  recovered: boolean;
+  usage: TokenUsage | null;
+  /** Follow-up requests made because the reply hit the output token limit. */
+  continuations: number;
+}
+
+/** `options` of the desktop backend's `call_gemini_secure` and `call_gemini_json`. */
+export interface GeminiGenerationOptions {
+  temperature?: number;
+  topP?: number;
+  topK?: number;
+  maxOutputTokens?: number;
+  /** At most 5. */
+  stopSequences?: string[];
+  systemInstruction?: string;
+  /** e.g. `{ category: "DANGEROUS_CONTENT", threshold: "BLOCK_ONLY_HIGH" }`. */
+  safetySettings?: { category: string; threshold: string }[];
+}
+
 export function buildPromptText(
   repoData: RepoData,
   taskInstruction: string,
@@ -124,15 +151,18 @@ Return ONLY a valid JSON object with the following structure, nothing else. Do n
 
   try {
     if (isTauri()) {
-      const responseText = await tauriInvoke<string>("call_gemini_secure", {
+      // The backend parses and checks the reply against the schema.
+      const { value: parsed } = await tauriInvoke<{ value: { optimizedQuery?: string; intent?: string } }>("call_gemini_json", {
         prompt,
         model: "gemini-3-flash-preview",
-      });
-      
-      const cleanText = responseText.trim();
-      const parsed = safeJsonParse<{optimizedQuery?: string, intent?: string}>(cleanText, {
-        optimizedQuery: query,
-        intent: "GENERAL",
+        response_schema: {
+          type: "OBJECT",
+          properties: {
+            optimizedQuery: { type: "STRING" },
+            intent: { type: "STRING", enum: ["BUG_HUNT", "ARCHITECTURE", "UI_UX", "DATA", "GENERAL"] },
+          },
+          required: ["optimizedQuery", "intent"],
+        },
       });
 
       return {
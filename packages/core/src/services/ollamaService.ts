@@ -2,6 +2,20 @@ import { RepoData } from "./githubService";
 import { isTauri, tauriInvoke } from "../utils/tauriAdapter.ts";
 import { safeJsonParse } from "../utils/jsonUtils";
 
+/** Token counts the backend reports for a model call. */
+export interface TokenUsage {
+  promptTokens: number;
+  outputTokens: number;
+  totalTokens: number;
+}
+
+/** What the desktop backend's `ollama_generate` and `ollama_chat` return. */
+export interface OllamaReply {
+  text: string;
+  usage: TokenUsage | null;
+  continuations: number;
+}
+
 export async function checkOllamaConnection(url: string): Promise<boolean> {
   try {
     if (isTauri()) {
@@ -45,7 +59,7 @@ export async function summarize_with_ollama(
 
   try {
     if (isTauri()) {
-      return await tauriInvoke<string>("ollama_generate", {
+      const reply = await tauriInvoke<OllamaReply>("ollama_generate", {
         url,
         model,
         prompt,
@@ -53,6 +67,7 @@ export async function summarize_with_ollama(
         numPredict,
         temperature,
       });
+      return reply.text;
     }
 
     const res = await fetch(`${url}/api/generate`, {
@@ -110,7 +125,7 @@ ${query}
 
   try {
     if (isTauri()) {
-      const text = await tauriInvoke<string>("ollama_generate", {
+      const { text } = await tauriInvoke<OllamaReply>("ollama_generate", {
         url,
         model,
         prompt,
@@ -170,7 +185,7 @@ export async function generate_final_prompt_with_ollama(
 ): Promise<string> {
   try {
     if (isTauri()) {
-      return await tauriInvoke<string>("ollama_generate", {
+      const reply = await tauriInvoke<OllamaReply>("ollama_generate", {
         url,
         model,
         prompt,
@@ -178,6 +193,7 @@ export async function generate_final_prompt_with_ollama(
         numPredict,
         temperature,
       });
+      return reply.text;
     }
 
     const res = await fetch(`${url}/api/generate`, {
@@ -8,6 +8,7 @@ export async function fetchOpenAICompatibleModels(
   try {
     if (isTauri()) {
       const response = await tauriInvoke<any>("ai_network_request", {
+        provider: "openai",
         method: "GET",
         url: `${baseURL}/models`,
         headers: {
@@ -121,6 +122,7 @@ export async function generate_final_prompt_with_openai_compatible(
 ): Promise<string> {
   if (isTauri()) {
     const response = await tauriInvoke<any>("ai_network_request", {
+      provider: "openai",
       method: "POST",
       url: `${baseURL}/chat/completions`,
       headers: {
@@ -243,6 +245,7 @@ Return your response in the following JSON format:
 
   if (isTauri()) {
     const response = await tauriInvoke<any>("ai_network_request", {
+      provider: "openai",
       method: "POST",
       url: `${baseURL}/chat/completions`,
       headers: {
@@ -9,8 +9,13 @@ export interface RagChunk {
   score?: number;
 }
 
+export type EmbeddingEngine = "ollama" | "lmstudio" | "llamacpp" | "gemini";
+
+const GEMINI_EMBED_MODEL = "text-embedding-004";
+
 /**
- * Fetches an embedding for a piece of text from Ollama.
+ * Fetches an embedding for a piece of text from Ollama, an OpenAI-compatible server or
+ * Gemini (which ignores `ollamaUrl`).
  * Uses local caching to avoid redundant API calls.
  */
 export async function getEmbedding(
@@ -18,7 +23,7 @@ export async function getEmbedding(
   ollamaUrl: string,
   model: string,
   repoUrl: string,
-  engine: "ollama" | "lmstudio" | "llamacpp" = "ollama"
+  engine: EmbeddingEngine = "ollama"
 ): Promise<number[]> {
   // Try to get from cache first
   const cached = await EmbeddingCacheService.getEmbedding(text, model, repoUrl);
@@ -26,7 +31,9 @@ export async function getEmbedding(
 
   let embedding: number[];
 
-  if (isTauri()) {
+  if (engine === "gemini") {
+    embedding = await getGeminiEmbedding(text, model || GEMINI_EMBED_MODEL);
+  } else if (isTauri()) {
     if (engine === "ollama") {
       embedding = await tauriInvoke<number[]>("ollama_embed", {
         url: ollamaUrl,
@@ -35,6 +42,7 @@ export async function getEmbedding(
       });
     } else {
       const response = await tauriInvoke<any>("ai_network_request", {
+        provider: "openai",
         method: "POST",
         url: `${ollamaUrl}/embeddings`,
         headers: {
@@ -102,6 +110,39 @@ export async function getEmbedding(
   return embedding;
 }
 
+/**
+ * Embeds text with Gemini: through the backend (which holds the key and waits out rate
+ * limits) in the desktop app, or the REST API with the build-time key on the web.
+ */
+async function getGeminiEmbedding(text: string, model: string): Promise<number[]> {
+  if (isTauri()) {
+    const [embedding] = await tauriInvoke<number[][]>("gemini_embed", { inputs: [text], model });
+    return embedding;
+  }
+  const apiKey = (typeof process !== 'undefined' ? process.env.GEMINI_API_KEY : undefined) || import.meta.env.VITE_GEMINI_API_KEY || "";
+  if (!apiKey) {
+    throw new Error("Gemini embeddings need an API key (GEMINI_API_KEY).");
+  }
+  const res = await fetch(`https://generativelanguage.googleapis.com/v1beta/models/${model}:embedContent`, {
+    method: "POST",
+    headers: { "Content-Type": "application/json", "x-goog-api-key": apiKey },
+    body: JSON.stringify({
+      model: `models/${model}`,
+      content: { parts: [{ text }] },
+      taskType: "RETRIEVAL_DOCUMENT",
+    }),
+  });
+  if (!res.ok) {
+    const errText = await res.text();
+    throw new Error(`Embedding failed (${res.status}): ${errText}`);
+  }
+  const data = await res.json();
+  if (!data.embedding || !data.embedding.values) {
+    throw new Error("Invalid embedding response format");
+  }
+  return data.embedding.values;
+}
+
 /**
  * Calculates cosine similarity between two vectors.
  */
@@ -146,7 +187,7 @@ export async function performRAG(
   searchStrategy: number = 0.5, // 0 = Pure Vector, 1 = Pure BM25
   chunkSize: number = 30,
   onProgress?: (msg: string) => void,
-  engine: "ollama" | "lmstudio" | "llamacpp" = "ollama"
+  engine: EmbeddingEngine = "ollama"
 ): Promise<{ path: string; content: string }[]> {
   const RAG_SYSTEM_INSTRUCTION = `
 Analyze the provided code snippets. When determining relevance:
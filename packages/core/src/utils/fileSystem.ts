@@ -12,7 +12,8 @@ export const saveMarkdownFile = async (
         filters: [{ name: "Markdown", extensions: ["md"] }],
       });
       if (filePath) {
-        await tauriInvoke("save_text_file", { path: filePath, content });
+        // The save dialog has already asked before replacing an existing file.
+        await tauriInvoke("save_text_file", { path: filePath, content, overwrite: true });
       }
     } catch (e) {
       console.error("Failed to save file in Tauri:", e);
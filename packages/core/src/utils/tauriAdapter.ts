@@ -4,9 +4,45 @@ export function isTauri(): boolean {
   return typeof window !== "undefined" && (window as any).__TAURI_INTERNALS__ !== undefined;
 }
 
+export type ErrorCategory =
+  | "auth"
+  | "network"
+  | "rate-limit"
+  | "not-found"
+  | "io"
+  | "invalid"
+  | "policy"
+  | "provider"
+  | "cancelled"
+  | "internal";
+
+/** Error rejected by a Tauri command, as serialized by the backend's `AppError`. */
+export class TauriCommandError extends Error {
+  code: string;
+  category: ErrorCategory;
+  retryAfterSecs: number | null;
+  retryable: boolean;
+
+  constructor(payload: { code: string; category: ErrorCategory; message: string; retryAfterSecs?: number | null; retryable?: boolean }) {
+    super(payload.message);
+    this.name = "TauriCommandError";
+    this.code = payload.code;
+    this.category = payload.category;
+    this.retryAfterSecs = payload.retryAfterSecs ?? null;
+    this.retryable = payload.retryable ?? false;
+  }
+}
+
 export async function tauriInvoke<T>(cmd: string, args?: Record<string, unknown>): Promise<T> {
   if (!isTauri()) {
     throw new Error("Not running in Tauri");
   }
-  return invoke<T>(cmd, args);
+  try {
+    return await invoke<T>(cmd, args);
+  } catch (e) {
+    if (e && typeof e === "object" && "code" in e && "message" in e) {
+      throw new TauriCommandError(e as any);
+    }
+    throw e;
+  }
 }
@@ -1244,11 +1244,20 @@ pause`;
     }
   };
 
-  const handleCopy = () => {
+  const handleCopy = async () => {
     if (prompt) {
-      navigator.clipboard.writeText(prompt);
-      setCopied(true);
-      setTimeout(() => setCopied(false), 2000);
+      try {
+        // The webview clipboard can truncate or hang on multi-megabyte prompts.
+        if (isTauri()) {
+          await tauriInvoke("copy_to_clipboard", { content: prompt });
+        } else {
+          await navigator.clipboard.writeText(prompt);
+        }
+        setCopied(true);
+        setTimeout(() => setCopied(false), 2000);
+      } catch (err: any) {
+        setError(err.message || "Failed to copy to the clipboard.");
+      }
     }
   };
 